@@ -10,22 +10,28 @@ use jsonrpsee::core::{Error as JsonRpseeError, RpcResult};
 use jsonrpsee::types::SubscriptionResult;
 use jsonrpsee::SubscriptionSink;
 use massa_consensus_exports::ConsensusChannels;
+use massa_execution_exports::{event_matches_filter, ExecutionChannels};
+use massa_models::api::EventFilter;
 use massa_models::version::Version;
 use massa_protocol_exports::ProtocolSenders;
 use serde::Serialize;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
 use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::{Stream, StreamExt};
 
 impl API<ApiV2> {
     /// generate a new massa API
     pub fn new(
         consensus_channels: ConsensusChannels,
         protocol_senders: ProtocolSenders,
+        execution_channels: ExecutionChannels,
         api_settings: APIConfig,
         version: Version,
     ) -> Self {
         API(ApiV2 {
             consensus_channels,
             protocol_senders,
+            execution_channels,
             api_settings,
             version,
         })
@@ -50,6 +56,19 @@ impl MassaApiServer for API<ApiV2> {
         Ok(self.0.version)
     }
 
+    fn subscribe_blockclique_changes(&self, sink: SubscriptionSink) -> SubscriptionResult {
+        broadcast_via_ws(
+            self.0.consensus_channels.blockclique_change_sender.clone(),
+            sink,
+        );
+        Ok(())
+    }
+
+    fn subscribe_final_blocks(&self, sink: SubscriptionSink) -> SubscriptionResult {
+        broadcast_via_ws(self.0.execution_channels.final_block_sender.clone(), sink);
+        Ok(())
+    }
+
     fn subscribe_new_blocks(&self, sink: SubscriptionSink) -> SubscriptionResult {
         broadcast_via_ws(self.0.consensus_channels.block_sender.clone(), sink);
         Ok(())
@@ -69,14 +88,43 @@ impl MassaApiServer for API<ApiV2> {
         broadcast_via_ws(self.0.protocol_senders.operation_sender.clone(), sink);
         Ok(())
     }
+
+    fn subscribe_sc_events(
+        &self,
+        sink: SubscriptionSink,
+        filter: EventFilter,
+    ) -> SubscriptionResult {
+        // events are pushed as they are emitted: the ordering, cursor and limit do not apply
+        let filter = EventFilter {
+            order: Default::default(),
+            cursor: None,
+            limit: None,
+            ..filter
+        };
+        let rx = BroadcastStream::new(self.0.execution_channels.sc_event_sender.subscribe())
+            .filter(move |item| match item {
+                Ok(event) => event_matches_filter(event, &filter),
+                Err(_) => true,
+            });
+        pipe_via_ws(rx, sink);
+        Ok(())
+    }
 }
 
 /// Brodcast the stream(sender) content via a WebSocket
 fn broadcast_via_ws<T: Serialize + Send + Clone + 'static>(
     sender: tokio::sync::broadcast::Sender<T>,
-    mut sink: SubscriptionSink,
+    sink: SubscriptionSink,
 ) {
-    let rx = BroadcastStream::new(sender.subscribe());
+    pipe_via_ws(BroadcastStream::new(sender.subscribe()), sink);
+}
+
+/// Pipe the content of a stream received from a broadcast channel via a WebSocket
+fn pipe_via_ws<T, S>(rx: S, mut sink: SubscriptionSink)
+where
+    T: Serialize + Send + 'static,
+    S: Stream<Item = Result<T, BroadcastStreamRecvError>> + Unpin + Send + 'static,
+{
     tokio::spawn(async move {
         match sink.pipe_from_try_stream(rx).await {
             SubscriptionClosed::Success => {
@@ -2,6 +2,7 @@
 //! Json RPC API for a massa-node
 use jsonrpsee::core::RpcResult;
 use jsonrpsee::proc_macros::rpc;
+use massa_models::api::EventFilter;
 use massa_models::version::Version;
 
 /// Exposed API methods
@@ -11,6 +12,22 @@ pub trait MassaApi {
     #[method(name = "get_version")]
     async fn get_version(&self) -> RpcResult<Version>;
 
+    /// Switches of the blockclique to a different set of candidate blocks.
+    #[subscription(
+        name = "subscribe_blockclique_changes" => "blockclique_changes",
+        unsubscribe = "unsubscribe_blockclique_changes",
+        item = BlockcliqueChange
+    )]
+    fn subscribe_blockclique_changes(&self);
+
+    /// Blocks that became final, with the final state hash obtained after their execution.
+    #[subscription(
+        name = "subscribe_final_blocks" => "final_blocks",
+        unsubscribe = "unsubscribe_final_blocks",
+        item = FinalBlock
+    )]
+    fn subscribe_final_blocks(&self);
+
     /// New produced block.
     #[subscription(
 		name = "subscribe_new_blocks" => "new_blocks",
@@ -42,4 +59,12 @@ pub trait MassaApi {
 		item = Operation
 	)]
     fn subscribe_new_operations(&self);
+
+    /// New SC output events matching the filter, candidate and final.
+    #[subscription(
+		name = "subscribe_sc_events" => "sc_events",
+		unsubscribe = "unsubscribe_sc_events",
+		item = SCOutputEvent
+	)]
+    fn subscribe_sc_events(&self, filter: EventFilter);
 }
@@ -66,4 +66,18 @@ pub struct APIConfig {
     pub t0: MassaTime,
     /// periods per cycle
     pub periods_per_cycle: u64,
+    /// maximum gas of a read-only execution, upper bound of gas estimations
+    pub max_read_only_gas: u64,
+    /// safety margin added to the estimated gas, in percent
+    pub gas_estimation_margin_percent: u64,
+    /// maximum number of gas estimations per request
+    pub max_gas_estimations_per_request: u64,
+    /// maximum number of read-only executions run by a gas estimation
+    pub max_gas_estimation_iterations: u64,
+    /// maximum number of datastore keys returned per view by `get_datastore_keys`
+    pub max_datastore_keys_per_request: u64,
+    /// maximum number of events returned by `get_filtered_sc_output_event`
+    pub max_events_per_request: u64,
+    /// maximum number of operations returned by `get_address_history`
+    pub max_address_history_per_request: u64,
 }
@@ -8,6 +8,7 @@ use jsonrpsee::{
 
 use massa_consensus_exports::error::ConsensusError;
 use massa_execution_exports::ExecutionError;
+use massa_final_state::FinalStateError;
 use massa_hash::MassaHashError;
 use massa_models::error::ModelsError;
 use massa_network_exports::NetworkError;
@@ -38,6 +39,8 @@ pub enum ApiError {
     TimeError(#[from] TimeError),
     /// Wallet error: {0}
     WalletError(#[from] WalletError),
+    /// Final state error: {0}
+    FinalStateError(#[from] FinalStateError),
     /// Not found
     NotFound,
     /// Inconsistency error: {0}
@@ -75,6 +78,7 @@ impl From<ApiError> for JsonRpseeError {
             ApiError::MissingCommandSender(_) => -32017,
             ApiError::MissingConfig(_) => -32018,
             ApiError::WrongAPI => -32019,
+            ApiError::FinalStateError(_) => -32020,
         };
 
         CallError::Custom(ErrorObject::owned(code, err.to_string(), None::<()>)).into()
@@ -10,22 +10,31 @@ use jsonrpsee::core::{Error as JsonRpseeError, RpcResult};
 use jsonrpsee::proc_macros::rpc;
 use jsonrpsee::server::{AllowHosts, ServerBuilder, ServerHandle};
 use jsonrpsee::RpcModule;
+use massa_async_pool::{AsyncMessageFilter, AsyncMessageId, AsyncMessageInfo};
 use massa_consensus_exports::{ConsensusChannels, ConsensusController};
-use massa_execution_exports::ExecutionController;
+use massa_execution_exports::{ExecutionChannels, ExecutionController};
+use massa_final_state::FinalState;
+use massa_ledger_exports::LedgerEntryProof;
 use massa_models::api::{
-    AddressInfo, BlockInfo, BlockSummary, DatastoreEntryInput, DatastoreEntryOutput,
-    EndorsementInfo, EventFilter, NodeStatus, OperationInfo, OperationInput,
-    ReadOnlyBytecodeExecution, ReadOnlyCall, TimeInterval,
+    AddressActivity, AddressHistoryInput, AddressInfo, AddressStorageInfo, AddressesAtSlotInput,
+    BlockInfo, BlockSummary, DatastoreEntriesAtSlotInput, DatastoreEntryInput,
+    DatastoreEntryOutput, DatastoreKeysInput, DatastoreKeysOutput, EndorsementInfo, EventFilter,
+    FinalStateIntegrityReport, GasEstimationInput, GraphExportInput, GraphExportOutput,
+    LedgerEntryProofInput, NodeStatus, OperationInfo, OperationInput, ReadOnlyBytecodeExecution,
+    ReadOnlyCall, TimeInterval,
 };
 use massa_models::clique::Clique;
 use massa_models::composite::PubkeySig;
-use massa_models::execution::ExecuteReadOnlyResponse;
+use massa_models::execution::{
+    DryRunOperationResponse, ExecuteReadOnlyResponse, ExecutionTrace, GasEstimation,
+};
 use massa_models::node::NodeId;
 use massa_models::operation::OperationId;
 use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::PreHashSet;
 use massa_models::{
     address::Address,
+    amount::Amount,
     block::{Block, BlockId},
     endorsement::EndorsementId,
     slot::Slot,
@@ -40,6 +49,7 @@ use massa_wallet::Wallet;
 use parking_lot::RwLock;
 use serde_json::Value;
 use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
 use std::sync::Arc;
 use tower_http::cors::{Any, CorsLayer};
 
@@ -84,6 +94,8 @@ pub struct Public {
 pub struct Private {
     /// link to the network component
     pub network_command_sender: NetworkCommandSender,
+    /// link to the consensus component
+    pub consensus_controller: Box<dyn ConsensusController>,
     /// link to the execution component
     pub execution_controller: Box<dyn ExecutionController>,
     /// API settings
@@ -92,6 +104,8 @@ pub struct Private {
     pub stop_node_channel: mpsc::Sender<()>,
     /// User wallet
     pub node_wallet: Arc<RwLock<Wallet>>,
+    /// final state
+    pub final_state: Arc<RwLock<FinalState>>,
 }
 
 /// API v2 content
@@ -100,6 +114,8 @@ pub struct ApiV2 {
     pub consensus_channels: ConsensusChannels,
     /// link(channels) to the protocol component
     pub protocol_senders: ProtocolSenders,
+    /// link(channels) to the execution component
+    pub execution_channels: ExecutionChannels,
     /// API settings
     pub api_settings: APIConfig,
     /// node version
@@ -232,6 +248,18 @@ pub trait MassaRpc {
         arg: Vec<ReadOnlyCall>,
     ) -> RpcResult<Vec<ExecuteReadOnlyResponse>>;
 
+    /// Execute signed operations on top of the current candidate state, without committing their effects.
+    /// The operations are checked and executed exactly as if they were included in a block.
+    #[method(name = "dry_run_operations")]
+    async fn dry_run_operations(
+        &self,
+        arg: Vec<OperationInput>,
+    ) -> RpcResult<Vec<DryRunOperationResponse>>;
+
+    /// Estimate the gas to set as `max_gas` for ExecuteSC and CallSC operations.
+    #[method(name = "estimate_gas")]
+    async fn estimate_gas(&self, arg: Vec<GasEstimationInput>) -> RpcResult<Vec<GasEstimation>>;
+
     /// Remove a vector of addresses used to stake.
     /// No confirmation to expect.
     #[method(name = "remove_staking_addresses")]
@@ -296,6 +324,33 @@ pub trait MassaRpc {
     #[method(name = "node_remove_from_bootstrap_blacklist")]
     async fn node_remove_from_bootstrap_blacklist(&self, arg: Vec<IpAddr>) -> RpcResult<()>;
 
+    /// Export a snapshot of the final state to the given file.
+    /// Returns the final slot at which the snapshot was taken.
+    #[method(name = "node_export_final_state_snapshot")]
+    async fn node_export_final_state_snapshot(&self, arg: PathBuf) -> RpcResult<Slot>;
+
+    /// Recompute every final state component hash from scratch and compare it to the stored one.
+    #[method(name = "node_check_final_state_integrity")]
+    async fn node_check_final_state_integrity(&self) -> RpcResult<FinalStateIntegrityReport>;
+
+    /// Export the blocks of a time window of the consensus graph with their parent links,
+    /// status, fitness and clique membership, in Graphviz DOT or as a JSON graph.
+    #[method(name = "node_export_graph")]
+    async fn node_export_graph(&self, arg: GraphExportInput) -> RpcResult<GraphExportOutput>;
+
+    /// Get the call trees recorded for the given operations.
+    /// Requires call tracing to be enabled in the node execution settings.
+    #[method(name = "trace_operation")]
+    async fn trace_operation(
+        &self,
+        arg: Vec<OperationId>,
+    ) -> RpcResult<Vec<Option<ExecutionTrace>>>;
+
+    /// Get the call trees recorded for the operations and asynchronous messages executed at a slot.
+    /// Requires call tracing to be enabled in the node execution settings.
+    #[method(name = "trace_slot")]
+    async fn trace_slot(&self, arg: Slot) -> RpcResult<Vec<ExecutionTrace>>;
+
     /// Unban given IP address(es).
     /// No confirmation to expect.
     #[method(name = "node_unban_by_ip")]
@@ -347,10 +402,55 @@ pub trait MassaRpc {
         arg: Vec<DatastoreEntryInput>,
     ) -> RpcResult<Vec<DatastoreEntryOutput>>;
 
+    /// Get a page of the final and candidate datastore keys of an address,
+    /// optionally filtered by prefix.
+    #[method(name = "get_datastore_keys")]
+    async fn get_datastore_keys(&self, arg: DatastoreKeysInput) -> RpcResult<DatastoreKeysOutput>;
+
+    /// Get the final balances of addresses at the output of a past final slot.
+    /// Only available on archival nodes.
+    #[method(name = "get_balances_at")]
+    async fn get_balances_at(&self, arg: AddressesAtSlotInput) -> RpcResult<Vec<Option<Amount>>>;
+
+    /// Get final datastore entries at the output of a past final slot.
+    /// Only available on archival nodes.
+    #[method(name = "get_datastore_entries_at")]
+    async fn get_datastore_entries_at(
+        &self,
+        arg: DatastoreEntriesAtSlotInput,
+    ) -> RpcResult<Vec<Option<Vec<u8>>>>;
+
+    /// Get the final datastore keys of addresses at the output of a past final slot.
+    /// Only available on archival nodes.
+    #[method(name = "get_datastore_keys_at")]
+    async fn get_datastore_keys_at(
+        &self,
+        arg: AddressesAtSlotInput,
+    ) -> RpcResult<Vec<Option<Vec<Vec<u8>>>>>;
+
+    /// Get proofs of final ledger entries against the final state hash.
+    #[method(name = "get_ledger_entry_proofs")]
+    async fn get_ledger_entry_proofs(
+        &self,
+        arg: Vec<LedgerEntryProofInput>,
+    ) -> RpcResult<Vec<LedgerEntryProof>>;
+
     /// Get addresses.
     #[method(name = "get_addresses")]
     async fn get_addresses(&self, arg: Vec<Address>) -> RpcResult<Vec<AddressInfo>>;
 
+    /// Get the storage used by addresses and the coins locked to pay for it.
+    #[method(name = "get_addresses_storage")]
+    async fn get_addresses_storage(&self, arg: Vec<Address>) -> RpcResult<Vec<AddressStorageInfo>>;
+
+    /// Get the final operations involving an address, most recent first.
+    /// Requires the address index to be enabled on the node.
+    #[method(name = "get_address_history")]
+    async fn get_address_history(
+        &self,
+        arg: AddressHistoryInput,
+    ) -> RpcResult<Vec<AddressActivity>>;
+
     /// Adds operations to pool. Returns operations that were ok and sent to pool.
     #[method(name = "send_operations")]
     async fn send_operations(&self, arg: Vec<OperationInput>) -> RpcResult<Vec<OperationId>>;
@@ -365,6 +465,21 @@ pub trait MassaRpc {
     async fn get_filtered_sc_output_event(&self, arg: EventFilter)
         -> RpcResult<Vec<SCOutputEvent>>;
 
+    /// Get the messages of the final asynchronous pool, by decreasing priority, optionally filtered by:
+    /// * sender address
+    /// * destination address
+    /// * handler function name
+    /// * slot within their validity range
+    /// * trigger state
+    #[method(name = "get_async_messages")]
+    async fn get_async_messages(&self, arg: AsyncMessageFilter)
+        -> RpcResult<Vec<AsyncMessageInfo>>;
+
+    /// Get a message of the final asynchronous pool,
+    /// or whether it was executed, expired or dropped in a recent final slot.
+    #[method(name = "get_async_message")]
+    async fn get_async_message(&self, arg: AsyncMessageId) -> RpcResult<Option<AsyncMessageInfo>>;
+
     /// Get OpenRPC specification.
     #[method(name = "rpc.discover")]
     async fn get_openrpc_spec(&self) -> RpcResult<Value>;
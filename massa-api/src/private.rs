@@ -7,24 +7,37 @@ use crate::{MassaRpcServer, Private, RpcServer, StopHandle, Value, API};
 use async_trait::async_trait;
 use itertools::Itertools;
 use jsonrpsee::core::{Error as JsonRpseeError, RpcResult};
+use massa_async_pool::{AsyncMessageFilter, AsyncMessageId, AsyncMessageInfo};
+use massa_consensus_exports::ConsensusController;
 use massa_execution_exports::ExecutionController;
+use massa_final_state::{FinalState, FinalStateSnapshotWriter};
+use massa_ledger_exports::LedgerEntryProof;
 use massa_models::api::{
-    AddressInfo, BlockInfo, BlockSummary, DatastoreEntryInput, DatastoreEntryOutput,
-    EndorsementInfo, EventFilter, ListType, NodeStatus, OperationInfo, OperationInput,
+    AddressActivity, AddressHistoryInput, AddressInfo, AddressStorageInfo, AddressesAtSlotInput,
+    BlockInfo, BlockSummary, DatastoreEntriesAtSlotInput, DatastoreEntryInput,
+    DatastoreEntryOutput, DatastoreKeysInput, DatastoreKeysOutput, EndorsementInfo, EventFilter,
+    FinalStateIntegrityReport, GasEstimationInput, GraphExportInput, GraphExportOutput,
+    LedgerEntryProofInput, ListType, NodeStatus, OperationInfo, OperationInput,
     ReadOnlyBytecodeExecution, ReadOnlyCall, ScrudOperation, TimeInterval,
 };
 use massa_models::clique::Clique;
 use massa_models::composite::PubkeySig;
-use massa_models::execution::ExecuteReadOnlyResponse;
+use massa_models::execution::{
+    DryRunOperationResponse, ExecuteReadOnlyResponse, ExecutionTrace, GasEstimation,
+};
+use massa_models::graph_export::GraphExportFormat;
 use massa_models::node::NodeId;
 use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::PreHashSet;
+use massa_models::timeslots::time_range_to_slot_range;
 use massa_models::{
     address::Address,
+    amount::Amount,
     block::{Block, BlockId},
     endorsement::EndorsementId,
     operation::OperationId,
     slot::Slot,
+    streaming_step::StreamingStep,
 };
 use massa_network_exports::NetworkCommandSender;
 use massa_signature::KeyPair;
@@ -32,29 +45,35 @@ use massa_wallet::Wallet;
 
 use parking_lot::RwLock;
 use std::collections::BTreeSet;
-use std::fs::{remove_file, OpenOptions};
+use std::fs::{remove_file, File, OpenOptions};
+use std::io::BufWriter;
 use std::net::{IpAddr, SocketAddr};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::Arc;
 use tokio::sync::mpsc;
+use tracing::warn;
 
 impl API<Private> {
     /// generate a new private API
     pub fn new(
         network_command_sender: NetworkCommandSender,
+        consensus_controller: Box<dyn ConsensusController>,
         execution_controller: Box<dyn ExecutionController>,
         api_settings: APIConfig,
         node_wallet: Arc<RwLock<Wallet>>,
+        final_state: Arc<RwLock<FinalState>>,
     ) -> (Self, mpsc::Receiver<()>) {
         let (stop_node_channel, rx) = mpsc::channel(1);
         (
             API(Private {
                 network_command_sender,
+                consensus_controller,
                 execution_controller,
                 api_settings,
                 stop_node_channel,
                 node_wallet,
+                final_state,
             }),
             rx,
         )
@@ -119,6 +138,17 @@ impl MassaRpcServer for API<Private> {
         crate::wrong_api::<_>()
     }
 
+    async fn dry_run_operations(
+        &self,
+        _: Vec<OperationInput>,
+    ) -> RpcResult<Vec<DryRunOperationResponse>> {
+        crate::wrong_api::<Vec<DryRunOperationResponse>>()
+    }
+
+    async fn estimate_gas(&self, _: Vec<GasEstimationInput>) -> RpcResult<Vec<GasEstimation>> {
+        crate::wrong_api::<Vec<GasEstimation>>()
+    }
+
     async fn remove_staking_addresses(&self, addresses: Vec<Address>) -> RpcResult<()> {
         let node_wallet = self.0.node_wallet.clone();
         let mut w_wallet = node_wallet.write();
@@ -204,10 +234,47 @@ impl MassaRpcServer for API<Private> {
         crate::wrong_api()
     }
 
+    async fn get_datastore_keys(&self, _: DatastoreKeysInput) -> RpcResult<DatastoreKeysOutput> {
+        crate::wrong_api::<DatastoreKeysOutput>()
+    }
+
+    async fn get_balances_at(&self, _: AddressesAtSlotInput) -> RpcResult<Vec<Option<Amount>>> {
+        crate::wrong_api::<Vec<Option<Amount>>>()
+    }
+
+    async fn get_datastore_entries_at(
+        &self,
+        _: DatastoreEntriesAtSlotInput,
+    ) -> RpcResult<Vec<Option<Vec<u8>>>> {
+        crate::wrong_api::<Vec<Option<Vec<u8>>>>()
+    }
+
+    async fn get_datastore_keys_at(
+        &self,
+        _: AddressesAtSlotInput,
+    ) -> RpcResult<Vec<Option<Vec<Vec<u8>>>>> {
+        crate::wrong_api::<Vec<Option<Vec<Vec<u8>>>>>()
+    }
+
+    async fn get_ledger_entry_proofs(
+        &self,
+        _: Vec<LedgerEntryProofInput>,
+    ) -> RpcResult<Vec<LedgerEntryProof>> {
+        crate::wrong_api()
+    }
+
     async fn get_addresses(&self, _: Vec<Address>) -> RpcResult<Vec<AddressInfo>> {
         crate::wrong_api::<Vec<AddressInfo>>()
     }
 
+    async fn get_addresses_storage(&self, _: Vec<Address>) -> RpcResult<Vec<AddressStorageInfo>> {
+        crate::wrong_api::<Vec<AddressStorageInfo>>()
+    }
+
+    async fn get_address_history(&self, _: AddressHistoryInput) -> RpcResult<Vec<AddressActivity>> {
+        crate::wrong_api::<Vec<AddressActivity>>()
+    }
+
     async fn send_operations(&self, _: Vec<OperationInput>) -> RpcResult<Vec<OperationId>> {
         crate::wrong_api::<Vec<OperationId>>()
     }
@@ -216,6 +283,14 @@ impl MassaRpcServer for API<Private> {
         crate::wrong_api::<Vec<SCOutputEvent>>()
     }
 
+    async fn get_async_messages(&self, _: AsyncMessageFilter) -> RpcResult<Vec<AsyncMessageInfo>> {
+        crate::wrong_api::<Vec<AsyncMessageInfo>>()
+    }
+
+    async fn get_async_message(&self, _: AsyncMessageId) -> RpcResult<Option<AsyncMessageInfo>> {
+        crate::wrong_api::<Option<AsyncMessageInfo>>()
+    }
+
     async fn node_peers_whitelist(&self) -> RpcResult<Vec<IpAddr>> {
         let network_command_sender = self.0.network_command_sender.clone();
         match network_command_sender.get_peers().await {
@@ -300,11 +375,116 @@ impl MassaRpcServer for API<Private> {
         )
     }
 
+    async fn node_export_final_state_snapshot(&self, path: PathBuf) -> RpcResult<Slot> {
+        let final_state = self.0.final_state.clone();
+        let slot =
+            tokio::task::spawn_blocking(move || export_final_state_snapshot(&final_state, &path))
+                .await
+                .map_err(|err| ApiError::InternalServerError(err.to_string()))??;
+        Ok(slot)
+    }
+
+    async fn node_check_final_state_integrity(&self) -> RpcResult<FinalStateIntegrityReport> {
+        let final_state = self.0.final_state.clone();
+        let report = tokio::task::spawn_blocking(move || {
+            // only hold the final state while the ledger snapshot is taken
+            let integrity_check = final_state.read().start_integrity_check();
+            integrity_check.complete()
+        })
+        .await
+        .map_err(|err| ApiError::InternalServerError(err.to_string()))?;
+        if !report.is_consistent() {
+            warn!("final state integrity check failed: {}", report);
+        }
+        Ok(report)
+    }
+
+    async fn node_export_graph(&self, input: GraphExportInput) -> RpcResult<GraphExportOutput> {
+        let api_settings = &self.0.api_settings;
+        let (start_slot, end_slot) = time_range_to_slot_range(
+            api_settings.thread_count,
+            api_settings.t0,
+            api_settings.genesis_timestamp,
+            input.start,
+            input.end,
+        )
+        .map_err(ApiError::ModelsError)?;
+        let graph = self
+            .0
+            .consensus_controller
+            .get_block_graph_status(start_slot, end_slot)
+            .map_err(ApiError::ConsensusError)?
+            .to_graph_export();
+        Ok(match input.format {
+            GraphExportFormat::Dot => GraphExportOutput::Dot(graph.to_dot()),
+            GraphExportFormat::Json => GraphExportOutput::Json(graph),
+        })
+    }
+
+    async fn trace_operation(
+        &self,
+        ops: Vec<OperationId>,
+    ) -> RpcResult<Vec<Option<ExecutionTrace>>> {
+        if ops.len() as u64 > self.0.api_settings.max_arguments {
+            return Err(ApiError::BadRequest("too many arguments".into()).into());
+        }
+        Ok(self.0.execution_controller.get_operation_call_traces(&ops))
+    }
+
+    async fn trace_slot(&self, slot: Slot) -> RpcResult<Vec<ExecutionTrace>> {
+        match self.0.execution_controller.get_slot_call_traces(slot) {
+            Some(traces) => Ok(traces),
+            None => Err(ApiError::NotFound.into()),
+        }
+    }
+
     async fn get_openrpc_spec(&self) -> RpcResult<Value> {
         crate::wrong_api::<Value>()
     }
 }
 
+/// Write a snapshot of the final state to `path`.
+///
+/// The final state lock is taken again for each ledger part and released in between,
+/// so that slots keep being finalized during the export.
+/// The ledger changes they bring are added at the end of the snapshot.
+fn export_final_state_snapshot(
+    final_state: &RwLock<FinalState>,
+    path: &Path,
+) -> Result<Slot, ApiError> {
+    let file = File::create(path).map_err(|e| {
+        ApiError::InternalServerError(format!(
+            "failed to create the final state snapshot file: {}",
+            e
+        ))
+    })?;
+    let mut writer =
+        FinalStateSnapshotWriter::new(BufWriter::new(file)).map_err(ApiError::FinalStateError)?;
+    let ledger_start_slot = final_state.read().slot;
+    let mut cursor = StreamingStep::Started;
+    loop {
+        let (part, new_cursor) = final_state
+            .read()
+            .get_snapshot_ledger_part(cursor)
+            .map_err(ApiError::FinalStateError)?;
+        if part.is_empty() {
+            break;
+        }
+        writer
+            .write_ledger_part(&part)
+            .map_err(ApiError::FinalStateError)?;
+        cursor = new_cursor;
+    }
+    let snapshot = final_state
+        .read()
+        .create_snapshot_tail(ledger_start_slot)
+        .map_err(ApiError::FinalStateError)?;
+    writer
+        .finish(&snapshot)
+        .map_err(ApiError::FinalStateError)?;
+    Ok(snapshot.slot)
+}
+
 /// Run Search, Create, Read, Update, Delete operation on bootstrap list of IP(s)
 fn run_scrud_operation(
     bootstrap_list_file: PathBuf,
@@ -6,14 +6,19 @@ use crate::error::ApiError;
 use crate::{MassaRpcServer, Public, RpcServer, StopHandle, Value, API};
 use async_trait::async_trait;
 use jsonrpsee::core::{Error as JsonRpseeError, RpcResult};
+use massa_async_pool::{AsyncMessageFilter, AsyncMessageId, AsyncMessageInfo};
 use massa_consensus_exports::block_status::DiscardReason;
 use massa_consensus_exports::ConsensusController;
 use massa_execution_exports::{
     ExecutionController, ExecutionStackElement, ReadOnlyExecutionRequest, ReadOnlyExecutionTarget,
 };
+use massa_final_state::StateChangesSerializer;
+use massa_ledger_exports::LedgerEntryProof;
 use massa_models::api::{
-    BlockGraphStatus, DatastoreEntryInput, DatastoreEntryOutput, OperationInput,
-    ReadOnlyBytecodeExecution, ReadOnlyCall, SlotAmount,
+    AddressesAtSlotInput, BlockGraphStatus, DatastoreEntriesAtSlotInput, DatastoreEntryInput,
+    DatastoreEntryOutput, DatastoreKeysInput, DatastoreKeysOutput, FinalStateIntegrityReport,
+    GasEstimationInput, GasEstimationTarget, GraphExportInput, GraphExportOutput,
+    LedgerEntryProofInput, OperationInput, ReadOnlyBytecodeExecution, ReadOnlyCall, SlotAmount,
 };
 use massa_models::execution::ReadOnlyResult;
 use massa_models::operation::OperationDeserializer;
@@ -24,22 +29,24 @@ use massa_models::{
 };
 use massa_pos_exports::SelectorController;
 use massa_protocol_exports::ProtocolCommandSender;
-use massa_serialization::{DeserializeError, Deserializer};
+use massa_serialization::{DeserializeError, Deserializer, Serializer};
 
 use itertools::{izip, Itertools};
-use massa_models::datastore::DatastoreDeserializer;
+use massa_models::datastore::{Datastore, DatastoreDeserializer};
 use massa_models::{
     address::Address,
+    amount::Amount,
     api::{
-        AddressInfo, BlockInfo, BlockInfoContent, BlockSummary, EndorsementInfo, EventFilter,
-        NodeStatus, OperationInfo, TimeInterval,
+        AddressActivity, AddressHistoryInput, AddressInfo, AddressStorageInfo, BlockInfo,
+        BlockInfoContent, BlockSummary, EndorsementInfo, EventFilter, NodeStatus, OperationInfo,
+        TimeInterval,
     },
     block::BlockId,
     clique::Clique,
     composite::PubkeySig,
     config::CompactConfig,
     endorsement::EndorsementId,
-    execution::ExecuteReadOnlyResponse,
+    execution::{DryRunOperationResponse, ExecuteReadOnlyResponse, ExecutionTrace, GasEstimation},
     node::NodeId,
     operation::OperationId,
     output_event::SCOutputEvent,
@@ -55,6 +62,7 @@ use massa_storage::Storage;
 use massa_time::MassaTime;
 use std::collections::BTreeMap;
 use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
 
 impl API<Public> {
     /// generate a new public API
@@ -127,6 +135,7 @@ impl MassaRpcServer for API<Public> {
             address,
             bytecode,
             operation_datastore,
+            state_overrides,
         } in reqs
         {
             let address = address.unwrap_or_else(|| {
@@ -134,26 +143,9 @@ impl MassaRpcServer for API<Public> {
                 Address::from_public_key(&KeyPair::generate().get_public_key())
             });
 
-            let op_datastore = match operation_datastore {
-                Some(v) => {
-                    let deserializer = DatastoreDeserializer::new(
-                        self.0.api_settings.max_op_datastore_entry_count,
-                        self.0.api_settings.max_op_datastore_key_length,
-                        self.0.api_settings.max_op_datastore_value_length,
-                    );
-                    match deserializer.deserialize::<DeserializeError>(&v) {
-                        Ok((_, deserialized)) => Some(deserialized),
-                        Err(e) => {
-                            return Err(ApiError::InconsistencyError(format!(
-                                "Operation datastore error: {}",
-                                e
-                            ))
-                            .into())
-                        }
-                    }
-                }
-                None => None,
-            };
+            let op_datastore = operation_datastore
+                .map(|v| deserialize_operation_datastore(&self.0.api_settings, &v))
+                .transpose()?;
 
             // TODO:
             // * set a maximum gas value for read-only executions to prevent attacks
@@ -170,6 +162,7 @@ impl MassaRpcServer for API<Public> {
                     owned_addresses: vec![address],
                     operation_datastore: op_datastore,
                 }],
+                state_overrides,
             };
 
             // run
@@ -185,6 +178,7 @@ impl MassaRpcServer for API<Public> {
                     |res| ReadOnlyResult::Ok(res.call_result.clone()),
                 ),
                 gas_cost: result.as_ref().map_or_else(|_| 0, |v| v.gas_cost),
+                trace: result.as_ref().map_or_else(|_| None, |v| v.trace.clone()),
                 output_events: result
                     .map_or_else(|_| Default::default(), |mut v| v.out.events.take()),
             };
@@ -211,6 +205,7 @@ impl MassaRpcServer for API<Public> {
             target_function,
             parameter,
             caller_address,
+            state_overrides,
         } in reqs
         {
             let caller_address = caller_address.unwrap_or_else(|| {
@@ -245,6 +240,7 @@ impl MassaRpcServer for API<Public> {
                         operation_datastore: None, // should always be None
                     },
                 ],
+                state_overrides,
             };
 
             // run
@@ -260,6 +256,7 @@ impl MassaRpcServer for API<Public> {
                     |res| ReadOnlyResult::Ok(res.call_result.clone()),
                 ),
                 gas_cost: result.as_ref().map_or_else(|_| 0, |v| v.gas_cost),
+                trace: result.as_ref().map_or_else(|_| None, |v| v.trace.clone()),
                 output_events: result
                     .map_or_else(|_| Default::default(), |mut v| v.out.events.take()),
             };
@@ -271,6 +268,148 @@ impl MassaRpcServer for API<Public> {
         Ok(res)
     }
 
+    async fn dry_run_operations(
+        &self,
+        ops: Vec<OperationInput>,
+    ) -> RpcResult<Vec<DryRunOperationResponse>> {
+        if ops.len() as u64 > self.0.api_settings.max_arguments {
+            return Err(ApiError::BadRequest("too many arguments".into()).into());
+        }
+
+        let state_changes_serializer = StateChangesSerializer::new();
+        let mut res: Vec<DryRunOperationResponse> = Vec::with_capacity(ops.len());
+        for operation in deserialize_operations(&self.0.api_settings, ops)? {
+            let operation_id = operation.id;
+
+            // translate request
+            let req = ReadOnlyExecutionRequest {
+                max_gas: operation.get_gas_usage(),
+                call_stack: vec![],
+                target: ReadOnlyExecutionTarget::Operation(operation),
+                state_overrides: vec![],
+            };
+
+            // run
+            let result = self.0.execution_controller.execute_readonly_request(req);
+
+            // map result
+            let result = match result {
+                Ok(mut output) => {
+                    let mut state_changes = Vec::new();
+                    state_changes_serializer
+                        .serialize(&output.out.state_changes, &mut state_changes)
+                        .map_err(|err| {
+                            ApiError::InternalServerError(format!(
+                                "could not serialize state changes: {}",
+                                err
+                            ))
+                        })?;
+                    DryRunOperationResponse {
+                        executed_at: Some(output.out.slot),
+                        error: None,
+                        receipt: output.out.receipts.remove(&operation_id),
+                        output_events: output.out.events.take(),
+                        state_changes,
+                        trace: output.trace,
+                    }
+                }
+                Err(err) => DryRunOperationResponse {
+                    executed_at: None,
+                    error: Some(err.to_string()),
+                    receipt: None,
+                    output_events: Default::default(),
+                    state_changes: Vec::new(),
+                    trace: None,
+                },
+            };
+
+            res.push(result);
+        }
+
+        // return result
+        Ok(res)
+    }
+
+    async fn estimate_gas(&self, reqs: Vec<GasEstimationInput>) -> RpcResult<Vec<GasEstimation>> {
+        if reqs.len() as u64 > self.0.api_settings.max_gas_estimations_per_request {
+            return Err(ApiError::BadRequest("too many arguments".into()).into());
+        }
+
+        let max_read_only_gas = self.0.api_settings.max_read_only_gas;
+        let mut res: Vec<GasEstimation> = Vec::with_capacity(reqs.len());
+        for GasEstimationInput {
+            caller_address,
+            target,
+        } in reqs
+        {
+            let caller_address = caller_address.unwrap_or_else(|| {
+                // if no addr provided, use a random one
+                Address::from_public_key(&KeyPair::generate().get_public_key())
+            });
+
+            // translate the estimated execution into a read-only one
+            let (target, call_stack) = match target {
+                GasEstimationTarget::ExecuteSC {
+                    bytecode,
+                    operation_datastore,
+                } => (
+                    ReadOnlyExecutionTarget::BytecodeExecution(bytecode),
+                    vec![ExecutionStackElement {
+                        address: caller_address,
+                        coins: Default::default(),
+                        owned_addresses: vec![caller_address],
+                        operation_datastore: operation_datastore
+                            .map(|v| deserialize_operation_datastore(&self.0.api_settings, &v))
+                            .transpose()?,
+                    }],
+                ),
+                GasEstimationTarget::CallSC {
+                    target_address,
+                    target_function,
+                    parameter,
+                } => (
+                    ReadOnlyExecutionTarget::FunctionCall {
+                        target_func: target_function,
+                        target_addr: target_address,
+                        parameter,
+                    },
+                    vec![
+                        ExecutionStackElement {
+                            address: caller_address,
+                            coins: Default::default(),
+                            owned_addresses: vec![caller_address],
+                            operation_datastore: None,
+                        },
+                        ExecutionStackElement {
+                            address: target_address,
+                            coins: Default::default(),
+                            owned_addresses: vec![target_address],
+                            operation_datastore: None,
+                        },
+                    ],
+                ),
+            };
+            res.push(estimate_min_gas(
+                |max_gas| {
+                    self.0
+                        .execution_controller
+                        .execute_readonly_request(ReadOnlyExecutionRequest {
+                            max_gas,
+                            call_stack: call_stack.clone(),
+                            target: target.clone(),
+                            state_overrides: vec![],
+                        })
+                        .map(|output| output.gas_cost)
+                },
+                max_read_only_gas,
+                self.0.api_settings.max_gas_estimation_iterations,
+                self.0.api_settings.gas_estimation_margin_percent,
+            ));
+        }
+
+        Ok(res)
+    }
+
     async fn remove_staking_addresses(&self, _: Vec<Address>) -> RpcResult<()> {
         crate::wrong_api::<()>()
     }
@@ -479,21 +618,26 @@ impl MassaRpcServer for API<Public> {
                 .collect()
         };
 
+        // get the execution receipts of the operations
+        let receipts = self.0.execution_controller.get_operation_receipts(&ops);
+
         // gather all values into a vector of OperationInfo instances
         let mut res: Vec<OperationInfo> = Vec::with_capacity(ops.len());
         let zipped_iterator = izip!(
             ops.into_iter(),
             storage_info.into_iter(),
             in_pool.into_iter(),
-            is_final.into_iter()
+            is_final.into_iter(),
+            receipts.into_iter()
         );
-        for (id, (operation, in_blocks), in_pool, is_final) in zipped_iterator {
+        for (id, (operation, in_blocks), in_pool, is_final, receipt) in zipped_iterator {
             res.push(OperationInfo {
                 id,
                 operation,
                 in_pool,
                 is_final,
                 in_blocks: in_blocks.into_iter().collect(),
+                receipt,
             });
         }
 
@@ -720,6 +864,98 @@ impl MassaRpcServer for API<Public> {
             .collect())
     }
 
+    async fn get_datastore_keys(&self, arg: DatastoreKeysInput) -> RpcResult<DatastoreKeysOutput> {
+        let max_limit = self.0.api_settings.max_datastore_keys_per_request as usize;
+        let limit = match arg.limit {
+            Some(limit) if limit > max_limit => {
+                return Err(ApiError::BadRequest("limit is too large".into()).into())
+            }
+            Some(limit) => limit,
+            None => max_limit,
+        };
+        let (final_keys, candidate_keys) = self
+            .0
+            .execution_controller
+            .get_final_and_candidate_datastore_keys_page(
+                &arg.address,
+                &arg.prefix,
+                arg.start_after.as_deref(),
+                limit,
+            );
+        Ok(DatastoreKeysOutput {
+            address: arg.address,
+            final_keys: final_keys.into_iter().collect(),
+            candidate_keys: candidate_keys.into_iter().collect(),
+        })
+    }
+
+    async fn get_balances_at(&self, arg: AddressesAtSlotInput) -> RpcResult<Vec<Option<Amount>>> {
+        if arg.addresses.len() as u64 > self.0.api_settings.max_arguments {
+            return Err(ApiError::BadRequest("too many arguments".into()).into());
+        }
+        self.0
+            .execution_controller
+            .get_final_balances_at(&arg.addresses, arg.slot)
+            .map_err(|e| ApiError::ExecutionError(e).into())
+    }
+
+    async fn get_datastore_entries_at(
+        &self,
+        arg: DatastoreEntriesAtSlotInput,
+    ) -> RpcResult<Vec<Option<Vec<u8>>>> {
+        if arg.entries.len() as u64 > self.0.api_settings.max_arguments {
+            return Err(ApiError::BadRequest("too many arguments".into()).into());
+        }
+        self.0
+            .execution_controller
+            .get_final_data_entries_at(
+                arg.entries
+                    .into_iter()
+                    .map(|input| (input.address, input.key))
+                    .collect(),
+                arg.slot,
+            )
+            .map_err(|e| ApiError::ExecutionError(e).into())
+    }
+
+    async fn get_datastore_keys_at(
+        &self,
+        arg: AddressesAtSlotInput,
+    ) -> RpcResult<Vec<Option<Vec<Vec<u8>>>>> {
+        if arg.addresses.len() as u64 > self.0.api_settings.max_arguments {
+            return Err(ApiError::BadRequest("too many arguments".into()).into());
+        }
+        match self
+            .0
+            .execution_controller
+            .get_final_datastore_keys_at(&arg.addresses, arg.slot)
+        {
+            Ok(keys) => Ok(keys
+                .into_iter()
+                .map(|keys| keys.map(|keys| keys.into_iter().collect()))
+                .collect()),
+            Err(e) => Err(ApiError::ExecutionError(e).into()),
+        }
+    }
+
+    async fn get_ledger_entry_proofs(
+        &self,
+        entries: Vec<LedgerEntryProofInput>,
+    ) -> RpcResult<Vec<LedgerEntryProof>> {
+        if entries.len() as u64 > self.0.api_settings.max_arguments {
+            return Err(ApiError::BadRequest("too many arguments".into()).into());
+        }
+        self.0
+            .execution_controller
+            .get_final_ledger_entry_proofs(
+                entries
+                    .into_iter()
+                    .map(|input| (input.address, input.datastore_key))
+                    .collect(),
+            )
+            .map_err(|e| ApiError::ExecutionError(e).into())
+    }
+
     async fn get_addresses(&self, addresses: Vec<Address>) -> RpcResult<Vec<AddressInfo>> {
         // get info from storage about which blocks the addresses have created
         let created_blocks: Vec<PreHashSet<BlockId>> = {
@@ -853,6 +1089,48 @@ impl MassaRpcServer for API<Public> {
         Ok(res)
     }
 
+    async fn get_addresses_storage(
+        &self,
+        addresses: Vec<Address>,
+    ) -> RpcResult<Vec<AddressStorageInfo>> {
+        if addresses.len() as u64 > self.0.api_settings.max_arguments {
+            return Err(ApiError::BadRequest("too many arguments".into()).into());
+        }
+        let storage_reports = self
+            .0
+            .execution_controller
+            .get_addresses_storage(&addresses);
+        Ok(addresses
+            .into_iter()
+            .zip(storage_reports)
+            .map(
+                |(address, (final_storage, candidate_storage))| AddressStorageInfo {
+                    address,
+                    final_storage,
+                    candidate_storage,
+                },
+            )
+            .collect())
+    }
+
+    async fn get_address_history(
+        &self,
+        input: AddressHistoryInput,
+    ) -> RpcResult<Vec<AddressActivity>> {
+        let max_limit = self.0.api_settings.max_address_history_per_request as usize;
+        let limit = match input.limit {
+            Some(limit) if limit > max_limit => {
+                return Err(ApiError::BadRequest("limit is too large".into()).into())
+            }
+            Some(limit) => limit,
+            None => max_limit,
+        };
+        self.0
+            .execution_controller
+            .get_address_history(&input.address, input.cursor, limit)
+            .map_err(|e| ApiError::ExecutionError(e).into())
+    }
+
     async fn send_operations(&self, ops: Vec<OperationInput>) -> RpcResult<Vec<OperationId>> {
         let mut cmd_sender = self.0.pool_command_sender.clone();
         let mut protocol_sender = self.0.protocol_command_sender.clone();
@@ -862,46 +1140,7 @@ impl MassaRpcServer for API<Public> {
         if ops.len() as u64 > api_cfg.max_arguments {
             return Err(ApiError::BadRequest("too many arguments".into()).into());
         }
-        let operation_deserializer = WrappedDeserializer::new(OperationDeserializer::new(
-            api_cfg.max_datastore_value_length,
-            api_cfg.max_function_name_length,
-            api_cfg.max_parameter_size,
-            api_cfg.max_op_datastore_entry_count,
-            api_cfg.max_op_datastore_key_length,
-            api_cfg.max_op_datastore_value_length,
-        ));
-        let verified_ops = ops
-            .into_iter()
-            .map(|op_input| {
-                let mut op_serialized = Vec::new();
-                op_serialized.extend(op_input.signature.to_bytes());
-                op_serialized.extend(op_input.creator_public_key.to_bytes());
-                op_serialized.extend(op_input.serialized_content);
-                let (rest, op): (&[u8], WrappedOperation) = operation_deserializer
-                    .deserialize::<DeserializeError>(&op_serialized)
-                    .map_err(|err| {
-                        ApiError::ModelsError(ModelsError::DeserializeError(err.to_string()))
-                    })?;
-                if rest.is_empty() {
-                    Ok(op)
-                } else {
-                    Err(ApiError::ModelsError(ModelsError::DeserializeError(
-                        "There is data left after operation deserialization".to_owned(),
-                    ))
-                    .into())
-                }
-            })
-            .map(|op| match op {
-                Ok(operation) => {
-                    let _verify_signature = match operation.verify_signature() {
-                        Ok(()) => (),
-                        Err(e) => return Err(ApiError::ModelsError(e).into()),
-                    };
-                    Ok(operation)
-                }
-                Err(e) => Err(e),
-            })
-            .collect::<RpcResult<Vec<WrappedOperation>>>()?;
+        let verified_ops = deserialize_operations(&api_cfg, ops)?;
         to_send.store_operations(verified_ops.clone());
         let ids: Vec<OperationId> = verified_ops.iter().map(|op| op.id).collect();
         cmd_sender.add_operations(to_send.clone());
@@ -923,8 +1162,16 @@ impl MassaRpcServer for API<Public> {
     /// * operation id
     async fn get_filtered_sc_output_event(
         &self,
-        filter: EventFilter,
+        mut filter: EventFilter,
     ) -> RpcResult<Vec<SCOutputEvent>> {
+        let max_limit = self.0.api_settings.max_events_per_request as usize;
+        match filter.limit {
+            Some(limit) if limit > max_limit => {
+                return Err(ApiError::BadRequest("limit is too large".into()).into())
+            }
+            Some(_) => {}
+            None => filter.limit = Some(max_limit),
+        }
         let events = self
             .0
             .execution_controller
@@ -934,6 +1181,17 @@ impl MassaRpcServer for API<Public> {
         Ok(events)
     }
 
+    async fn get_async_messages(
+        &self,
+        filter: AsyncMessageFilter,
+    ) -> RpcResult<Vec<AsyncMessageInfo>> {
+        Ok(self.0.execution_controller.get_async_messages(&filter))
+    }
+
+    async fn get_async_message(&self, id: AsyncMessageId) -> RpcResult<Option<AsyncMessageInfo>> {
+        Ok(self.0.execution_controller.get_async_message(&id))
+    }
+
     async fn node_peers_whitelist(&self) -> RpcResult<Vec<IpAddr>> {
         crate::wrong_api::<Vec<IpAddr>>()
     }
@@ -974,6 +1232,26 @@ impl MassaRpcServer for API<Public> {
         crate::wrong_api::<()>()
     }
 
+    async fn node_export_final_state_snapshot(&self, _: PathBuf) -> RpcResult<Slot> {
+        crate::wrong_api::<Slot>()
+    }
+
+    async fn node_check_final_state_integrity(&self) -> RpcResult<FinalStateIntegrityReport> {
+        crate::wrong_api::<FinalStateIntegrityReport>()
+    }
+
+    async fn node_export_graph(&self, _: GraphExportInput) -> RpcResult<GraphExportOutput> {
+        crate::wrong_api::<GraphExportOutput>()
+    }
+
+    async fn trace_operation(&self, _: Vec<OperationId>) -> RpcResult<Vec<Option<ExecutionTrace>>> {
+        crate::wrong_api::<Vec<Option<ExecutionTrace>>>()
+    }
+
+    async fn trace_slot(&self, _: Slot) -> RpcResult<Vec<ExecutionTrace>> {
+        crate::wrong_api::<Vec<ExecutionTrace>>()
+    }
+
     async fn get_openrpc_spec(&self) -> RpcResult<Value> {
         let openrpc_spec_path = self.0.api_settings.openrpc_spec_path.clone();
         let openrpc: RpcResult<Value> = std::fs::read_to_string(openrpc_spec_path)
@@ -997,3 +1275,231 @@ impl MassaRpcServer for API<Public> {
         openrpc
     }
 }
+
+/// Search the minimal `max_gas` with which an execution succeeds
+///
+/// # Arguments
+/// * `execute`: runs the execution with the given `max_gas`, returning the gas it used if it succeeded
+/// * `max_read_only_gas`: upper bound of the estimation, the execution has to succeed with it
+/// * `max_iterations`: maximum number of executions run, including the one with the maximal gas
+/// * `margin_percent`: safety margin added to the minimal gas, in percent
+fn estimate_min_gas<E: std::fmt::Display>(
+    mut execute: impl FnMut(u64) -> Result<u64, E>,
+    max_read_only_gas: u64,
+    max_iterations: u64,
+    margin_percent: u64,
+) -> GasEstimation {
+    // the execution has to succeed with the maximal gas,
+    // and cannot succeed with less gas than it used
+    let gas_used = match execute(max_read_only_gas) {
+        Ok(gas_used) => gas_used,
+        Err(err) => {
+            return GasEstimation::Error(format!(
+                "execution failed with the maximal gas {}: {}",
+                max_read_only_gas, err
+            ))
+        }
+    };
+
+    // search the minimal gas with which the execution succeeds,
+    // most executions succeed with exactly the gas they use.
+    // The number of executions is bounded, including the one with the maximal gas:
+    // when it is reached, `high` is the smallest gas found to succeed.
+    let (mut low, mut high) = (gas_used.min(max_read_only_gas), max_read_only_gas);
+    let mut remaining_runs = max_iterations.saturating_sub(1);
+    if remaining_runs > 0 {
+        remaining_runs -= 1;
+        if execute(low).is_ok() {
+            high = low;
+        }
+    }
+    while low < high && remaining_runs > 0 {
+        remaining_runs -= 1;
+        let mid = low + (high - low) / 2;
+        if execute(mid).is_ok() {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
+    }
+
+    let margin = high.saturating_mul(margin_percent) / 100;
+    GasEstimation::Ok {
+        min_gas: high,
+        max_gas: high.saturating_add(margin).min(max_read_only_gas),
+    }
+}
+
+/// Deserialize operations received through the API and verify their signatures
+fn deserialize_operations(
+    api_cfg: &APIConfig,
+    ops: Vec<OperationInput>,
+) -> RpcResult<Vec<WrappedOperation>> {
+    let operation_deserializer = WrappedDeserializer::new(OperationDeserializer::new(
+        api_cfg.max_datastore_value_length,
+        api_cfg.max_function_name_length,
+        api_cfg.max_parameter_size,
+        api_cfg.max_op_datastore_entry_count,
+        api_cfg.max_op_datastore_key_length,
+        api_cfg.max_op_datastore_value_length,
+    ));
+    ops.into_iter()
+        .map(|op_input| {
+            let mut op_serialized = Vec::new();
+            op_serialized.extend(op_input.signature.to_bytes());
+            op_serialized.extend(op_input.creator_public_key.to_bytes());
+            op_serialized.extend(op_input.serialized_content);
+            let (rest, op): (&[u8], WrappedOperation) = operation_deserializer
+                .deserialize::<DeserializeError>(&op_serialized)
+                .map_err(|err| {
+                    ApiError::ModelsError(ModelsError::DeserializeError(err.to_string()))
+                })?;
+            if rest.is_empty() {
+                Ok(op)
+            } else {
+                Err(ApiError::ModelsError(ModelsError::DeserializeError(
+                    "There is data left after operation deserialization".to_owned(),
+                ))
+                .into())
+            }
+        })
+        .map(|op| match op {
+            Ok(operation) => {
+                let _verify_signature = match operation.verify_signature() {
+                    Ok(()) => (),
+                    Err(e) => return Err(ApiError::ModelsError(e).into()),
+                };
+                Ok(operation)
+            }
+            Err(e) => Err(e),
+        })
+        .collect::<RpcResult<Vec<WrappedOperation>>>()
+}
+
+/// Deserialize an operation datastore received through the API
+fn deserialize_operation_datastore(api_cfg: &APIConfig, bytes: &[u8]) -> RpcResult<Datastore> {
+    let deserializer = DatastoreDeserializer::new(
+        api_cfg.max_op_datastore_entry_count,
+        api_cfg.max_op_datastore_key_length,
+        api_cfg.max_op_datastore_value_length,
+    );
+    match deserializer.deserialize::<DeserializeError>(bytes) {
+        Ok((_, deserialized)) => Ok(deserialized),
+        Err(e) => {
+            Err(ApiError::InconsistencyError(format!("Operation datastore error: {}", e)).into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::estimate_min_gas;
+    use massa_models::execution::GasEstimation;
+
+    const MAX_READ_ONLY_GAS: u64 = 1_000_000;
+
+    /// Execution succeeding from `required_gas` on, and reporting `used_gas`
+    /// (less than required when part of the gas is reserved and given back)
+    fn execution(
+        required_gas: u64,
+        used_gas: u64,
+        runs: &mut Vec<u64>,
+    ) -> impl FnMut(u64) -> Result<u64, String> + '_ {
+        move |max_gas| {
+            runs.push(max_gas);
+            if max_gas >= required_gas {
+                Ok(used_gas)
+            } else {
+                Err(format!("out of gas with {}", max_gas))
+            }
+        }
+    }
+
+    #[test]
+    fn estimate_gas_converges_to_the_minimal_gas() {
+        // the execution succeeds with the gas it used: two executions are enough
+        let mut runs = Vec::new();
+        let estimation = estimate_min_gas(
+            execution(12_345, 12_345, &mut runs),
+            MAX_READ_ONLY_GAS,
+            64,
+            10,
+        );
+        assert!(matches!(
+            estimation,
+            GasEstimation::Ok {
+                min_gas: 12_345,
+                max_gas: 13_579
+            }
+        ));
+        assert_eq!(runs, vec![MAX_READ_ONLY_GAS, 12_345]);
+
+        // the execution needs more gas than it used: binary search above the used gas
+        let mut runs = Vec::new();
+        let estimation = estimate_min_gas(
+            execution(12_345, 10_000, &mut runs),
+            MAX_READ_ONLY_GAS,
+            64,
+            0,
+        );
+        assert!(matches!(
+            estimation,
+            GasEstimation::Ok {
+                min_gas: 12_345,
+                max_gas: 12_345
+            }
+        ));
+        assert!(runs.iter().all(|gas| *gas >= 10_000));
+        // logarithmic in the searched range
+        assert!(runs.len() <= 2 + 20);
+    }
+
+    #[test]
+    fn estimate_gas_fails_with_the_maximal_gas() {
+        let mut runs = Vec::new();
+        let estimation = estimate_min_gas(
+            execution(MAX_READ_ONLY_GAS + 1, 0, &mut runs),
+            MAX_READ_ONLY_GAS,
+            64,
+            10,
+        );
+        assert!(matches!(estimation, GasEstimation::Error(_)));
+        // no search without a successful execution
+        assert_eq!(runs, vec![MAX_READ_ONLY_GAS]);
+    }
+
+    #[test]
+    fn estimate_gas_respects_its_upper_bounds() {
+        // the margin does not go above the maximal gas
+        let mut runs = Vec::new();
+        let estimation = estimate_min_gas(
+            execution(990_000, 990_000, &mut runs),
+            MAX_READ_ONLY_GAS,
+            64,
+            10,
+        );
+        assert!(matches!(
+            estimation,
+            GasEstimation::Ok {
+                min_gas: 990_000,
+                max_gas: MAX_READ_ONLY_GAS
+            }
+        ));
+        assert!(runs.iter().all(|gas| *gas <= MAX_READ_ONLY_GAS));
+
+        // the number of executions is bounded, the estimation is a gas that succeeded
+        let mut runs = Vec::new();
+        let estimation = estimate_min_gas(
+            execution(12_345, 10_000, &mut runs),
+            MAX_READ_ONLY_GAS,
+            4,
+            0,
+        );
+        assert_eq!(runs.len(), 4);
+        let GasEstimation::Ok { min_gas, .. } = estimation else {
+            panic!("the estimation failed");
+        };
+        assert!(min_gas >= 12_345);
+        assert!(runs.contains(&min_gas));
+    }
+}
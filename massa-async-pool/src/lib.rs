@@ -100,9 +100,11 @@ pub use changes::{
 pub use config::AsyncPoolConfig;
 pub use message::{
     AsyncMessage, AsyncMessageDeserializer, AsyncMessageId, AsyncMessageIdDeserializer,
-    AsyncMessageIdSerializer, AsyncMessageSerializer, AsyncMessageTrigger,
+    AsyncMessageIdSerializer, AsyncMessageInfo, AsyncMessageSerializer, AsyncMessageStatus,
+    AsyncMessageTrigger, AsyncMessageTriggerDeserializer, AsyncMessageTriggerSerializer,
+    ASYNC_MESSAGE_TRIGGER_ARGUMENT_V1,
 };
-pub use pool::{AsyncPool, AsyncPoolDeserializer, AsyncPoolSerializer};
+pub use pool::{AsyncMessageFilter, AsyncPool, AsyncPoolDeserializer, AsyncPoolSerializer};
 
 #[cfg(test)]
 mod tests;
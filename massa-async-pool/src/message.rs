@@ -3,6 +3,7 @@
 //! This file defines the structure representing an asynchronous message
 
 use massa_hash::Hash;
+use massa_ledger_exports::LedgerChanges;
 use massa_models::address::{AddressDeserializer, AddressSerializer};
 use massa_models::amount::{AmountDeserializer, AmountSerializer};
 use massa_models::slot::{SlotDeserializer, SlotSerializer};
@@ -14,13 +15,14 @@ use massa_models::{
 };
 use massa_serialization::{
     Deserializer, OptionDeserializer, OptionSerializer, SerializeError, Serializer,
-    U64VarIntDeserializer, U64VarIntSerializer,
+    U32VarIntDeserializer, U32VarIntSerializer, U64VarIntDeserializer, U64VarIntSerializer,
 };
 use nom::error::{context, ContextError, ParseError};
-use nom::multi::length_data;
+use nom::multi::{length_count, length_data};
 use nom::sequence::tuple;
 use nom::{IResult, Parser};
 use num::rational::Ratio;
+use num_enum::{IntoPrimitive, TryFromPrimitive};
 use serde::{Deserialize, Serialize};
 use std::ops::Bound::{Excluded, Included};
 
@@ -167,27 +169,227 @@ impl Deserializer<AsyncMessageId> for AsyncMessageIdDeserializer {
     }
 }
 
-/// Structure defining a trigger for an asynchronous message
+/// Maximum nesting depth of the `All` and `Any` conditions of a trigger
+const MAX_TRIGGER_DEPTH: u32 = 4;
+
+/// Maximum number of conditions combined by an `All` or `Any` trigger
+const MAX_TRIGGER_CONDITIONS: u64 = 16;
+
+/// Tag of a message without trigger, in the serialization of the trigger field of a message
+const TRIGGER_NONE_TAG: u8 = b'0';
+
+/// Tag of a `LedgerChange` trigger in the legacy format, in the serialization of the trigger field of a message
+///
+/// The legacy format is the one of the triggers sent before the activation of the other triggers,
+/// so that the serialization and the hash of those messages are unchanged.
+const TRIGGER_LEGACY_TAG: u8 = b'1';
+
+/// Tag of a trigger in the versioned format (see `AsyncMessageTriggerSerializer`),
+/// in the serialization of the trigger field of a message
+const TRIGGER_VERSIONED_TAG: u8 = b'2';
+
+/// Version of the trigger argument of the `send_message` ABI giving a trigger in the versioned format.
+///
+/// It is given in place of the address of the filter, with the trigger serialized by `AsyncMessageTriggerSerializer`
+/// in place of the datastore key, which is then not bounded by the datastore key length.
+/// It is not a valid address, so that it can't be mistaken for the address of a legacy filter.
+pub const ASYNC_MESSAGE_TRIGGER_ARGUMENT_V1: &str = "trigger_v1";
+
+/// Condition on the ledger changes of a slot that makes an asynchronous message executable once met
+///
+/// Only `LedgerChange` triggers can be sent before the network upgrade
+/// activating the other ones (see `ExecutionConfig::async_message_triggers_activation_period`).
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
-pub struct AsyncMessageTrigger {
-    /// Filter on the address
-    pub address: Address,
+#[serde(from = "AsyncMessageTriggerJson", into = "AsyncMessageTriggerJson")]
+pub enum AsyncMessageTrigger {
+    /// Any change to the ledger entry of an address,
+    /// or only to one of its datastore entries if a key is given
+    LedgerChange {
+        /// Filter on the address
+        address: Address,
+        /// Filter on the datastore key
+        datastore_key: Option<Vec<u8>>,
+    },
+    /// Any change to a datastore entry of an address whose key starts with a prefix
+    DatastorePrefixChange {
+        /// Filter on the address
+        address: Address,
+        /// Prefix of the datastore keys
+        prefix: Vec<u8>,
+    },
+    /// The balance of an address is set to a value greater than or equal to a threshold
+    BalanceAbove {
+        /// Filter on the address
+        address: Address,
+        /// Balance threshold (included)
+        threshold: Amount,
+    },
+    /// The balance of an address is set to a value strictly lower than a threshold
+    BalanceBelow {
+        /// Filter on the address
+        address: Address,
+        /// Balance threshold (excluded)
+        threshold: Amount,
+    },
+    /// The bytecode of an address is changed
+    BytecodeChange {
+        /// Filter on the address
+        address: Address,
+    },
+    /// All the conditions are met by the changes of the same slot
+    All(Vec<AsyncMessageTrigger>),
+    /// At least one of the conditions is met
+    Any(Vec<AsyncMessageTrigger>),
+}
+
+impl AsyncMessageTrigger {
+    /// Checks whether the condition of the trigger is met by the ledger changes of a slot
+    pub fn is_triggered(&self, ledger_changes: &LedgerChanges) -> bool {
+        match self {
+            AsyncMessageTrigger::LedgerChange {
+                address,
+                datastore_key,
+            } => ledger_changes.has_changes(address, datastore_key.clone()),
+            AsyncMessageTrigger::DatastorePrefixChange { address, prefix } => {
+                ledger_changes.has_datastore_prefix_changes(address, prefix)
+            }
+            AsyncMessageTrigger::BalanceAbove { address, threshold } => ledger_changes
+                .get_balance_change(address)
+                .map_or(false, |balance| balance >= *threshold),
+            AsyncMessageTrigger::BalanceBelow { address, threshold } => ledger_changes
+                .get_balance_change(address)
+                .map_or(false, |balance| balance < *threshold),
+            AsyncMessageTrigger::BytecodeChange { address } => {
+                ledger_changes.has_bytecode_change(address)
+            }
+            AsyncMessageTrigger::All(conditions) => conditions
+                .iter()
+                .all(|condition| condition.is_triggered(ledger_changes)),
+            AsyncMessageTrigger::Any(conditions) => conditions
+                .iter()
+                .any(|condition| condition.is_triggered(ledger_changes)),
+        }
+    }
+}
 
-    /// Filter on the datastore key
-    pub datastore_key: Option<Vec<u8>>,
+/// JSON representation of a trigger,
+/// keeping the legacy object with an `address` and an optional `datastore_key` for `LedgerChange` triggers
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum AsyncMessageTriggerJson {
+    LedgerChange {
+        address: Address,
+        datastore_key: Option<Vec<u8>>,
+    },
+    Tagged(TaggedAsyncMessageTrigger),
 }
 
-/// Serializer for a trigger for an asynchronous message
-struct AsyncMessageTriggerSerializer {
+/// JSON representation of the triggers activated by the network upgrade,
+/// tagged with their name
+#[derive(Serialize, Deserialize)]
+enum TaggedAsyncMessageTrigger {
+    DatastorePrefixChange { address: Address, prefix: Vec<u8> },
+    BalanceAbove { address: Address, threshold: Amount },
+    BalanceBelow { address: Address, threshold: Amount },
+    BytecodeChange { address: Address },
+    All(Vec<AsyncMessageTrigger>),
+    Any(Vec<AsyncMessageTrigger>),
+}
+
+impl From<AsyncMessageTrigger> for AsyncMessageTriggerJson {
+    fn from(trigger: AsyncMessageTrigger) -> Self {
+        let tagged = match trigger {
+            AsyncMessageTrigger::LedgerChange {
+                address,
+                datastore_key,
+            } => {
+                return AsyncMessageTriggerJson::LedgerChange {
+                    address,
+                    datastore_key,
+                }
+            }
+            AsyncMessageTrigger::DatastorePrefixChange { address, prefix } => {
+                TaggedAsyncMessageTrigger::DatastorePrefixChange { address, prefix }
+            }
+            AsyncMessageTrigger::BalanceAbove { address, threshold } => {
+                TaggedAsyncMessageTrigger::BalanceAbove { address, threshold }
+            }
+            AsyncMessageTrigger::BalanceBelow { address, threshold } => {
+                TaggedAsyncMessageTrigger::BalanceBelow { address, threshold }
+            }
+            AsyncMessageTrigger::BytecodeChange { address } => {
+                TaggedAsyncMessageTrigger::BytecodeChange { address }
+            }
+            AsyncMessageTrigger::All(conditions) => TaggedAsyncMessageTrigger::All(conditions),
+            AsyncMessageTrigger::Any(conditions) => TaggedAsyncMessageTrigger::Any(conditions),
+        };
+        AsyncMessageTriggerJson::Tagged(tagged)
+    }
+}
+
+impl From<AsyncMessageTriggerJson> for AsyncMessageTrigger {
+    fn from(json: AsyncMessageTriggerJson) -> Self {
+        match json {
+            AsyncMessageTriggerJson::LedgerChange {
+                address,
+                datastore_key,
+            } => AsyncMessageTrigger::LedgerChange {
+                address,
+                datastore_key,
+            },
+            AsyncMessageTriggerJson::Tagged(tagged) => match tagged {
+                TaggedAsyncMessageTrigger::DatastorePrefixChange { address, prefix } => {
+                    AsyncMessageTrigger::DatastorePrefixChange { address, prefix }
+                }
+                TaggedAsyncMessageTrigger::BalanceAbove { address, threshold } => {
+                    AsyncMessageTrigger::BalanceAbove { address, threshold }
+                }
+                TaggedAsyncMessageTrigger::BalanceBelow { address, threshold } => {
+                    AsyncMessageTrigger::BalanceBelow { address, threshold }
+                }
+                TaggedAsyncMessageTrigger::BytecodeChange { address } => {
+                    AsyncMessageTrigger::BytecodeChange { address }
+                }
+                TaggedAsyncMessageTrigger::All(conditions) => AsyncMessageTrigger::All(conditions),
+                TaggedAsyncMessageTrigger::Any(conditions) => AsyncMessageTrigger::Any(conditions),
+            },
+        }
+    }
+}
+
+#[derive(IntoPrimitive, Debug, Eq, PartialEq, TryFromPrimitive)]
+#[repr(u32)]
+enum AsyncMessageTriggerId {
+    LedgerChange = 0,
+    DatastorePrefixChange = 1,
+    BalanceAbove = 2,
+    BalanceBelow = 3,
+    BytecodeChange = 4,
+    All = 5,
+    Any = 6,
+}
+
+/// Serializer for a trigger for an asynchronous message, in the versioned format:
+/// the id of the kind of trigger followed by its fields
+pub struct AsyncMessageTriggerSerializer {
+    id_serializer: U32VarIntSerializer,
     address_serializer: AddressSerializer,
     key_serializer: OptionSerializer<Vec<u8>, VecU8Serializer>,
+    prefix_serializer: VecU8Serializer,
+    amount_serializer: AmountSerializer,
+    u64_serializer: U64VarIntSerializer,
 }
 
 impl AsyncMessageTriggerSerializer {
+    /// Creates a new `AsyncMessageTriggerSerializer`
     pub fn new() -> Self {
         Self {
+            id_serializer: U32VarIntSerializer::new(),
             address_serializer: AddressSerializer::new(),
             key_serializer: OptionSerializer::new(VecU8Serializer::new()),
+            prefix_serializer: VecU8Serializer::new(),
+            amount_serializer: AmountSerializer::new(),
+            u64_serializer: U64VarIntSerializer::new(),
         }
     }
 }
@@ -198,29 +400,204 @@ impl Serializer<AsyncMessageTrigger> for AsyncMessageTriggerSerializer {
         value: &AsyncMessageTrigger,
         buffer: &mut Vec<u8>,
     ) -> Result<(), SerializeError> {
-        self.address_serializer.serialize(&value.address, buffer)?;
-        self.key_serializer
-            .serialize(&value.datastore_key, buffer)?;
+        match value {
+            AsyncMessageTrigger::LedgerChange {
+                address,
+                datastore_key,
+            } => {
+                self.id_serializer
+                    .serialize(&u32::from(AsyncMessageTriggerId::LedgerChange), buffer)?;
+                self.address_serializer.serialize(address, buffer)?;
+                self.key_serializer.serialize(datastore_key, buffer)?;
+            }
+            AsyncMessageTrigger::DatastorePrefixChange { address, prefix } => {
+                self.id_serializer.serialize(
+                    &u32::from(AsyncMessageTriggerId::DatastorePrefixChange),
+                    buffer,
+                )?;
+                self.address_serializer.serialize(address, buffer)?;
+                self.prefix_serializer.serialize(prefix, buffer)?;
+            }
+            AsyncMessageTrigger::BalanceAbove { address, threshold } => {
+                self.id_serializer
+                    .serialize(&u32::from(AsyncMessageTriggerId::BalanceAbove), buffer)?;
+                self.address_serializer.serialize(address, buffer)?;
+                self.amount_serializer.serialize(threshold, buffer)?;
+            }
+            AsyncMessageTrigger::BalanceBelow { address, threshold } => {
+                self.id_serializer
+                    .serialize(&u32::from(AsyncMessageTriggerId::BalanceBelow), buffer)?;
+                self.address_serializer.serialize(address, buffer)?;
+                self.amount_serializer.serialize(threshold, buffer)?;
+            }
+            AsyncMessageTrigger::BytecodeChange { address } => {
+                self.id_serializer
+                    .serialize(&u32::from(AsyncMessageTriggerId::BytecodeChange), buffer)?;
+                self.address_serializer.serialize(address, buffer)?;
+            }
+            AsyncMessageTrigger::All(conditions) | AsyncMessageTrigger::Any(conditions) => {
+                let id = match value {
+                    AsyncMessageTrigger::All(_) => AsyncMessageTriggerId::All,
+                    _ => AsyncMessageTriggerId::Any,
+                };
+                self.id_serializer.serialize(&u32::from(id), buffer)?;
+                let count: u64 = conditions.len().try_into().map_err(|_| {
+                    SerializeError::GeneralError(
+                        "could not convert trigger condition count to u64".into(),
+                    )
+                })?;
+                self.u64_serializer.serialize(&count, buffer)?;
+                for condition in conditions {
+                    self.serialize(condition, buffer)?;
+                }
+            }
+        }
         Ok(())
     }
 }
 
-/// Deserializer for a trigger for an asynchronous message
-struct AsyncMessageTriggerDeserializer {
+impl Default for AsyncMessageTriggerSerializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Deserializer for a trigger for an asynchronous message, in the versioned format
+pub struct AsyncMessageTriggerDeserializer {
+    id_deserializer: U32VarIntDeserializer,
     address_deserializer: AddressDeserializer,
-    key_serializer: OptionDeserializer<Vec<u8>, VecU8Deserializer>,
+    key_deserializer: OptionDeserializer<Vec<u8>, VecU8Deserializer>,
+    prefix_deserializer: VecU8Deserializer,
+    amount_deserializer: AmountDeserializer,
+    condition_count_deserializer: U64VarIntDeserializer,
 }
 
 impl AsyncMessageTriggerDeserializer {
+    /// Creates a new `AsyncMessageTriggerDeserializer`
+    ///
+    /// # Arguments
+    /// * `max_key_length`: maximum length of the datastore keys and prefixes of the triggers
     pub fn new(max_key_length: u32) -> Self {
         Self {
+            id_deserializer: U32VarIntDeserializer::new(Included(0), Included(u32::MAX)),
             address_deserializer: AddressDeserializer::new(),
-            key_serializer: OptionDeserializer::new(VecU8Deserializer::new(
+            key_deserializer: OptionDeserializer::new(VecU8Deserializer::new(
                 Included(0),
                 Excluded(max_key_length as u64),
             )),
+            prefix_deserializer: VecU8Deserializer::new(
+                Included(0),
+                Excluded(max_key_length as u64),
+            ),
+            amount_deserializer: AmountDeserializer::new(
+                Included(Amount::MIN),
+                Included(Amount::MAX),
+            ),
+            condition_count_deserializer: U64VarIntDeserializer::new(
+                Included(1),
+                Included(MAX_TRIGGER_CONDITIONS),
+            ),
         }
     }
+
+    /// Deserializes a trigger nested in `depth` combined conditions
+    fn deserialize_with_depth<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+        depth: u32,
+    ) -> IResult<&'a [u8], AsyncMessageTrigger, E> {
+        context("Failed AsyncMessageTrigger deserialization", |buffer| {
+            let (input, id) = self.id_deserializer.deserialize(buffer)?;
+            let id = AsyncMessageTriggerId::try_from(id).map_err(|_| {
+                nom::Err::Error(ParseError::from_error_kind(
+                    buffer,
+                    nom::error::ErrorKind::Eof,
+                ))
+            })?;
+            match id {
+                AsyncMessageTriggerId::LedgerChange => tuple((
+                    context("Failed address deserialization", |input| {
+                        self.address_deserializer.deserialize(input)
+                    }),
+                    context("Failed datastore_key deserialization", |input| {
+                        self.key_deserializer.deserialize(input)
+                    }),
+                ))
+                .map(
+                    |(address, datastore_key)| AsyncMessageTrigger::LedgerChange {
+                        address,
+                        datastore_key,
+                    },
+                )
+                .parse(input),
+                AsyncMessageTriggerId::DatastorePrefixChange => tuple((
+                    context("Failed address deserialization", |input| {
+                        self.address_deserializer.deserialize(input)
+                    }),
+                    context("Failed prefix deserialization", |input| {
+                        self.prefix_deserializer.deserialize(input)
+                    }),
+                ))
+                .map(
+                    |(address, prefix)| AsyncMessageTrigger::DatastorePrefixChange {
+                        address,
+                        prefix,
+                    },
+                )
+                .parse(input),
+                AsyncMessageTriggerId::BalanceAbove | AsyncMessageTriggerId::BalanceBelow => {
+                    tuple((
+                        context("Failed address deserialization", |input| {
+                            self.address_deserializer.deserialize(input)
+                        }),
+                        context("Failed threshold deserialization", |input| {
+                            self.amount_deserializer.deserialize(input)
+                        }),
+                    ))
+                    .map(|(address, threshold)| {
+                        if id == AsyncMessageTriggerId::BalanceAbove {
+                            AsyncMessageTrigger::BalanceAbove { address, threshold }
+                        } else {
+                            AsyncMessageTrigger::BalanceBelow { address, threshold }
+                        }
+                    })
+                    .parse(input)
+                }
+                AsyncMessageTriggerId::BytecodeChange => {
+                    context("Failed address deserialization", |input| {
+                        self.address_deserializer.deserialize(input)
+                    })
+                    .map(|address| AsyncMessageTrigger::BytecodeChange { address })
+                    .parse(input)
+                }
+                AsyncMessageTriggerId::All | AsyncMessageTriggerId::Any => {
+                    if depth >= MAX_TRIGGER_DEPTH {
+                        return Err(nom::Err::Error(ParseError::from_error_kind(
+                            buffer,
+                            nom::error::ErrorKind::TooLarge,
+                        )));
+                    }
+                    context(
+                        "Failed conditions deserialization",
+                        length_count(
+                            context("Failed condition count deserialization", |input| {
+                                self.condition_count_deserializer.deserialize(input)
+                            }),
+                            |input| self.deserialize_with_depth(input, depth + 1),
+                        ),
+                    )
+                    .map(|conditions| {
+                        if id == AsyncMessageTriggerId::All {
+                            AsyncMessageTrigger::All(conditions)
+                        } else {
+                            AsyncMessageTrigger::Any(conditions)
+                        }
+                    })
+                    .parse(input)
+                }
+            }
+        })(buffer)
+    }
 }
 
 impl Deserializer<AsyncMessageTrigger> for AsyncMessageTriggerDeserializer {
@@ -228,22 +605,106 @@ impl Deserializer<AsyncMessageTrigger> for AsyncMessageTriggerDeserializer {
         &self,
         buffer: &'a [u8],
     ) -> IResult<&'a [u8], AsyncMessageTrigger, E> {
-        context(
-            "Failed AsyncMessageTrigger deserialization",
-            tuple((
+        self.deserialize_with_depth(buffer, 0)
+    }
+}
+
+/// Serializer for the optional trigger of an asynchronous message
+///
+/// `LedgerChange` triggers are serialized in the legacy format, as they were before the network upgrade
+/// activating the other triggers, and the other triggers in the versioned format.
+struct AsyncMessageTriggerFieldSerializer {
+    address_serializer: AddressSerializer,
+    key_serializer: OptionSerializer<Vec<u8>, VecU8Serializer>,
+    trigger_serializer: AsyncMessageTriggerSerializer,
+}
+
+impl AsyncMessageTriggerFieldSerializer {
+    fn new() -> Self {
+        Self {
+            address_serializer: AddressSerializer::new(),
+            key_serializer: OptionSerializer::new(VecU8Serializer::new()),
+            trigger_serializer: AsyncMessageTriggerSerializer::new(),
+        }
+    }
+}
+
+impl Serializer<Option<AsyncMessageTrigger>> for AsyncMessageTriggerFieldSerializer {
+    fn serialize(
+        &self,
+        value: &Option<AsyncMessageTrigger>,
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), SerializeError> {
+        match value {
+            None => buffer.push(TRIGGER_NONE_TAG),
+            Some(AsyncMessageTrigger::LedgerChange {
+                address,
+                datastore_key,
+            }) => {
+                buffer.push(TRIGGER_LEGACY_TAG);
+                self.address_serializer.serialize(address, buffer)?;
+                self.key_serializer.serialize(datastore_key, buffer)?;
+            }
+            Some(trigger) => {
+                buffer.push(TRIGGER_VERSIONED_TAG);
+                self.trigger_serializer.serialize(trigger, buffer)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Deserializer for the optional trigger of an asynchronous message
+struct AsyncMessageTriggerFieldDeserializer {
+    address_deserializer: AddressDeserializer,
+    key_deserializer: OptionDeserializer<Vec<u8>, VecU8Deserializer>,
+    trigger_deserializer: AsyncMessageTriggerDeserializer,
+}
+
+impl AsyncMessageTriggerFieldDeserializer {
+    fn new(max_key_length: u32) -> Self {
+        Self {
+            address_deserializer: AddressDeserializer::new(),
+            key_deserializer: OptionDeserializer::new(VecU8Deserializer::new(
+                Included(0),
+                Excluded(max_key_length as u64),
+            )),
+            trigger_deserializer: AsyncMessageTriggerDeserializer::new(max_key_length),
+        }
+    }
+}
+
+impl Deserializer<Option<AsyncMessageTrigger>> for AsyncMessageTriggerFieldDeserializer {
+    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], Option<AsyncMessageTrigger>, E> {
+        match buffer.first() {
+            Some(&TRIGGER_NONE_TAG) => Ok((&buffer[1..], None)),
+            Some(&TRIGGER_LEGACY_TAG) => tuple((
                 context("Failed address deserialization", |input| {
                     self.address_deserializer.deserialize(input)
                 }),
                 context("Failed datastore_key deserialization", |input| {
-                    self.key_serializer.deserialize(input)
+                    self.key_deserializer.deserialize(input)
                 }),
-            )),
-        )
-        .map(|(address, datastore_key)| AsyncMessageTrigger {
-            address,
-            datastore_key,
-        })
-        .parse(buffer)
+            ))
+            .map(|(address, datastore_key)| {
+                Some(AsyncMessageTrigger::LedgerChange {
+                    address,
+                    datastore_key,
+                })
+            })
+            .parse(&buffer[1..]),
+            Some(&TRIGGER_VERSIONED_TAG) => self
+                .trigger_deserializer
+                .deserialize(&buffer[1..])
+                .map(|(rest, trigger)| (rest, Some(trigger))),
+            _ => Err(nom::Err::Error(ParseError::from_error_kind(
+                buffer,
+                nom::error::ErrorKind::Tag,
+            ))),
+        }
     }
 }
 
@@ -362,12 +823,48 @@ impl AsyncMessage {
     }
 }
 
+/// What happened to an asynchronous message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AsyncMessageStatus {
+    /// The message is waiting in the final pool
+    Pending,
+    /// The message was executed at a final slot
+    Executed {
+        /// slot at which the message was executed
+        slot: Slot,
+        /// false if the execution failed and the coins of the message were reimbursed
+        success: bool,
+    },
+    /// The message was removed from the pool at a final slot because its validity period ended
+    Expired {
+        /// slot at which the message was removed
+        slot: Slot,
+    },
+    /// The message was removed from the pool at a final slot because the pool was full
+    /// and the message had a lower priority than the other ones
+    Dropped {
+        /// slot at which the message was removed
+        slot: Slot,
+    },
+}
+
+/// An asynchronous message along with its status
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AsyncMessageInfo {
+    /// Identifier of the message
+    pub id: AsyncMessageId,
+    /// The message, if it is still in the final pool
+    pub message: Option<AsyncMessage>,
+    /// Status of the message
+    pub status: AsyncMessageStatus,
+}
+
 pub struct AsyncMessageSerializer {
     slot_serializer: SlotSerializer,
     amount_serializer: AmountSerializer,
     u64_serializer: U64VarIntSerializer,
     vec_u8_serializer: VecU8Serializer,
-    trigger_serializer: OptionSerializer<AsyncMessageTrigger, AsyncMessageTriggerSerializer>,
+    trigger_serializer: AsyncMessageTriggerFieldSerializer,
 }
 
 impl AsyncMessageSerializer {
@@ -377,7 +874,7 @@ impl AsyncMessageSerializer {
             amount_serializer: AmountSerializer::new(),
             u64_serializer: U64VarIntSerializer::new(),
             vec_u8_serializer: VecU8Serializer::new(),
-            trigger_serializer: OptionSerializer::new(AsyncMessageTriggerSerializer::new()),
+            trigger_serializer: AsyncMessageTriggerFieldSerializer::new(),
         }
     }
 }
@@ -408,7 +905,7 @@ impl Serializer<AsyncMessage> for AsyncMessageSerializer {
     ///     Slot::new(2, 0),
     ///     Slot::new(3, 0),
     ///     vec![1, 2, 3, 4],
-    ///     Some(AsyncMessageTrigger {
+    ///     Some(AsyncMessageTrigger::LedgerChange {
     ///         address: Address::from_str("A12dG5xP1RDEB5ocdHkymNVvvSJmUL9BgHwCksDowqmGWxfpm93x").unwrap(),
     ///         datastore_key: Some(vec![1, 2, 3, 4])
     ///     })
@@ -456,7 +953,7 @@ pub struct AsyncMessageDeserializer {
     max_gas_deserializer: U64VarIntDeserializer,
     data_deserializer: VecU8Deserializer,
     address_deserializer: AddressDeserializer,
-    trigger_deserializer: OptionDeserializer<AsyncMessageTrigger, AsyncMessageTriggerDeserializer>,
+    trigger_deserializer: AsyncMessageTriggerFieldDeserializer,
 }
 
 impl AsyncMessageDeserializer {
@@ -480,9 +977,7 @@ impl AsyncMessageDeserializer {
                 Included(max_async_message_data),
             ),
             address_deserializer: AddressDeserializer::new(),
-            trigger_deserializer: OptionDeserializer::new(AsyncMessageTriggerDeserializer::new(
-                max_key_length,
-            )),
+            trigger_deserializer: AsyncMessageTriggerFieldDeserializer::new(max_key_length),
         }
     }
 }
@@ -507,7 +1002,7 @@ impl Deserializer<AsyncMessage> for AsyncMessageDeserializer {
     ///     Slot::new(2, 0),
     ///     Slot::new(3, 0),
     ///     vec![1, 2, 3, 4],
-    ///     Some(AsyncMessageTrigger {
+    ///     Some(AsyncMessageTrigger::LedgerChange {
     ///        address: Address::from_str("A12dG5xP1RDEB5ocdHkymNVvvSJmUL9BgHwCksDowqmGWxfpm93x").unwrap(),
     ///        datastore_key: Some(vec![1, 2, 3, 4]),
     ///     })
@@ -618,18 +1113,22 @@ impl Deserializer<AsyncMessage> for AsyncMessageDeserializer {
 
 #[cfg(test)]
 mod tests {
+    use massa_ledger_exports::LedgerChanges;
     use massa_serialization::{DeserializeError, Deserializer, Serializer};
 
     use crate::{AsyncMessage, AsyncMessageDeserializer, AsyncMessageSerializer};
     use massa_models::{
-        address::Address,
+        address::{Address, AddressSerializer},
         amount::Amount,
         config::{MAX_ASYNC_MESSAGE_DATA, MAX_DATASTORE_KEY_LENGTH, THREAD_COUNT},
         slot::Slot,
     };
     use std::str::FromStr;
 
-    use super::AsyncMessageTrigger;
+    use super::{
+        AsyncMessageTrigger, AsyncMessageTriggerDeserializer, AsyncMessageTriggerSerializer,
+        MAX_TRIGGER_DEPTH, TRIGGER_LEGACY_TAG, TRIGGER_VERSIONED_TAG,
+    };
 
     #[test]
     fn bad_serialization_version() {
@@ -645,7 +1144,7 @@ mod tests {
             Slot::new(2, 0),
             Slot::new(3, 0),
             vec![1, 2, 3, 4],
-            Some(AsyncMessageTrigger {
+            Some(AsyncMessageTrigger::LedgerChange {
                 address: Address::from_str("A12htxRWiEm8jDJpJptr6cwEhWNcCSFWstN1MLSa96DDkVM9Y42G")
                     .unwrap(),
                 datastore_key: None,
@@ -666,4 +1165,192 @@ mod tests {
             .deserialize::<DeserializeError>(&serialized)
             .unwrap_err();
     }
+
+    #[test]
+    fn combined_trigger_serialization() {
+        let address =
+            Address::from_str("A12htxRWiEm8jDJpJptr6cwEhWNcCSFWstN1MLSa96DDkVM9Y42G").unwrap();
+        let trigger = AsyncMessageTrigger::All(vec![
+            AsyncMessageTrigger::BalanceAbove {
+                address,
+                threshold: Amount::from_str("10").unwrap(),
+            },
+            AsyncMessageTrigger::Any(vec![
+                AsyncMessageTrigger::DatastorePrefixChange {
+                    address,
+                    prefix: b"orders/".to_vec(),
+                },
+                AsyncMessageTrigger::BytecodeChange { address },
+            ]),
+        ]);
+        let serializer = AsyncMessageTriggerSerializer::new();
+        let deserializer = AsyncMessageTriggerDeserializer::new(MAX_DATASTORE_KEY_LENGTH as u32);
+        let mut serialized = Vec::new();
+        serializer.serialize(&trigger, &mut serialized).unwrap();
+        let (rest, deserialized) = deserializer
+            .deserialize::<DeserializeError>(&serialized)
+            .unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(deserialized, trigger);
+
+        // triggers nested deeper than the limit are rejected
+        let mut nested = AsyncMessageTrigger::BytecodeChange { address };
+        for _ in 0..=MAX_TRIGGER_DEPTH {
+            nested = AsyncMessageTrigger::Any(vec![nested]);
+        }
+        let mut serialized = Vec::new();
+        serializer.serialize(&nested, &mut serialized).unwrap();
+        deserializer
+            .deserialize::<DeserializeError>(&serialized)
+            .unwrap_err();
+    }
+
+    #[test]
+    fn trigger_field_formats() {
+        let address =
+            Address::from_str("A12htxRWiEm8jDJpJptr6cwEhWNcCSFWstN1MLSa96DDkVM9Y42G").unwrap();
+        let new_message = |trigger| {
+            AsyncMessage::new_with_hash(
+                Slot::new(1, 2),
+                0,
+                address,
+                address,
+                String::from("test"),
+                10000000,
+                Amount::from_str("1").unwrap(),
+                Amount::from_str("1").unwrap(),
+                Slot::new(2, 0),
+                Slot::new(3, 0),
+                vec![1, 2, 3, 4],
+                Some(trigger),
+            )
+        };
+        let message_serializer = AsyncMessageSerializer::new();
+        let message_deserializer = AsyncMessageDeserializer::new(
+            THREAD_COUNT,
+            MAX_ASYNC_MESSAGE_DATA,
+            MAX_DATASTORE_KEY_LENGTH as u32,
+        );
+
+        // a ledger change trigger is serialized as before the network upgrade
+        let legacy_message = new_message(AsyncMessageTrigger::LedgerChange {
+            address,
+            datastore_key: Some(vec![5, 6]),
+        });
+        let mut serialized = Vec::new();
+        message_serializer
+            .serialize(&legacy_message, &mut serialized)
+            .unwrap();
+        let mut legacy_trigger = vec![TRIGGER_LEGACY_TAG];
+        AddressSerializer::new()
+            .serialize(&address, &mut legacy_trigger)
+            .unwrap();
+        legacy_trigger.extend([b'1', 2, 5, 6]);
+        assert!(serialized.ends_with(&legacy_trigger));
+        let (rest, deserialized) = message_deserializer
+            .deserialize::<DeserializeError>(&serialized)
+            .unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(deserialized, legacy_message);
+
+        // the other triggers are serialized in the versioned format
+        let trigger = AsyncMessageTrigger::Any(vec![
+            AsyncMessageTrigger::LedgerChange {
+                address,
+                datastore_key: None,
+            },
+            AsyncMessageTrigger::BytecodeChange { address },
+        ]);
+        let versioned_message = new_message(trigger.clone());
+        let mut serialized = Vec::new();
+        message_serializer
+            .serialize(&versioned_message, &mut serialized)
+            .unwrap();
+        let mut versioned_trigger = vec![TRIGGER_VERSIONED_TAG];
+        AsyncMessageTriggerSerializer::new()
+            .serialize(&trigger, &mut versioned_trigger)
+            .unwrap();
+        assert!(serialized.ends_with(&versioned_trigger));
+        let (rest, deserialized) = message_deserializer
+            .deserialize::<DeserializeError>(&serialized)
+            .unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(deserialized, versioned_message);
+        assert_eq!(deserialized.hash, versioned_message.hash);
+
+        // unknown trigger formats are rejected
+        let trigger_tag_index = serialized.len() - versioned_trigger.len();
+        serialized[trigger_tag_index] = b'3';
+        message_deserializer
+            .deserialize::<DeserializeError>(&serialized)
+            .unwrap_err();
+    }
+
+    #[test]
+    fn trigger_json() {
+        let address =
+            Address::from_str("A12htxRWiEm8jDJpJptr6cwEhWNcCSFWstN1MLSa96DDkVM9Y42G").unwrap();
+
+        // a ledger change trigger keeps the JSON object it had before the network upgrade
+        let legacy_trigger = AsyncMessageTrigger::LedgerChange {
+            address,
+            datastore_key: None,
+        };
+        let json = serde_json::to_value(&legacy_trigger).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "address": address.to_string(), "datastore_key": null })
+        );
+        assert_eq!(
+            serde_json::from_value::<AsyncMessageTrigger>(json).unwrap(),
+            legacy_trigger
+        );
+
+        // the other triggers are tagged with their name
+        let trigger = AsyncMessageTrigger::All(vec![
+            legacy_trigger,
+            AsyncMessageTrigger::BytecodeChange { address },
+        ]);
+        let json = serde_json::to_value(&trigger).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "All": [
+                { "address": address.to_string(), "datastore_key": null },
+                { "BytecodeChange": { "address": address.to_string() } },
+            ] })
+        );
+        assert_eq!(
+            serde_json::from_value::<AsyncMessageTrigger>(json).unwrap(),
+            trigger
+        );
+    }
+
+    #[test]
+    fn combined_trigger_evaluation() {
+        let address =
+            Address::from_str("A12htxRWiEm8jDJpJptr6cwEhWNcCSFWstN1MLSa96DDkVM9Y42G").unwrap();
+        let trigger = AsyncMessageTrigger::All(vec![
+            AsyncMessageTrigger::BalanceBelow {
+                address,
+                threshold: Amount::from_str("10").unwrap(),
+            },
+            AsyncMessageTrigger::Any(vec![
+                AsyncMessageTrigger::DatastorePrefixChange {
+                    address,
+                    prefix: b"orders/".to_vec(),
+                },
+                AsyncMessageTrigger::BytecodeChange { address },
+            ]),
+        ]);
+
+        let mut ledger_changes = LedgerChanges::default();
+        ledger_changes.set_balance(address, Amount::from_str("5").unwrap());
+        assert!(!trigger.is_triggered(&ledger_changes));
+        ledger_changes.set_data_entry(address, b"other".to_vec(), vec![1]);
+        assert!(!trigger.is_triggered(&ledger_changes));
+        ledger_changes.set_data_entry(address, b"orders/1".to_vec(), vec![1]);
+        assert!(trigger.is_triggered(&ledger_changes));
+        ledger_changes.set_balance(address, Amount::from_str("10").unwrap());
+        assert!(!trigger.is_triggered(&ledger_changes));
+    }
 }
@@ -7,11 +7,11 @@ use crate::{
     config::AsyncPoolConfig,
     message::{AsyncMessage, AsyncMessageId},
     AsyncMessageDeserializer, AsyncMessageIdDeserializer, AsyncMessageIdSerializer,
-    AsyncMessageSerializer, AsyncMessageTrigger,
+    AsyncMessageSerializer,
 };
 use massa_hash::{Hash, HASH_SIZE_BYTES};
 use massa_ledger_exports::LedgerChanges;
-use massa_models::{slot::Slot, streaming_step::StreamingStep};
+use massa_models::{address::Address, slot::Slot, streaming_step::StreamingStep};
 use massa_serialization::{
     Deserializer, SerializeError, Serializer, U64VarIntDeserializer, U64VarIntSerializer,
 };
@@ -21,6 +21,7 @@ use nom::{
     sequence::tuple,
     IResult, Parser,
 };
+use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::ops::Bound::{Excluded, Included, Unbounded};
 
@@ -35,12 +36,58 @@ pub struct AsyncPool {
     config: AsyncPoolConfig,
 
     /// Messages sorted by decreasing ID (decreasing priority)
-    pub(crate) messages: BTreeMap<AsyncMessageId, AsyncMessage>,
+    pub messages: BTreeMap<AsyncMessageId, AsyncMessage>,
 
     /// Hash of the asynchronous pool
     pub hash: Hash,
 }
 
+/// Filter used when listing the messages of an asynchronous pool
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct AsyncMessageFilter {
+    /// optional sender address
+    pub sender: Option<Address>,
+    /// optional destination address
+    pub destination: Option<Address>,
+    /// optional handler function name
+    pub handler: Option<String>,
+    /// optional slot that must be within the validity range of the messages
+    pub valid_at: Option<Slot>,
+    /// optional trigger state
+    ///
+    /// Some(true) means messages that can be executed
+    /// Some(false) means messages still waiting for their trigger
+    /// None means both
+    pub can_be_executed: Option<bool>,
+    /// optional continuation cursor
+    ///
+    /// Only the messages strictly after the cursor in priority order are returned.
+    /// To get the next page, set it to the id of the last returned message.
+    pub cursor: Option<AsyncMessageId>,
+    /// optional maximum number of returned messages
+    pub limit: Option<usize>,
+}
+
+impl AsyncMessageFilter {
+    /// Checks whether a message matches the filter, regardless of the cursor and limit
+    pub fn matches(&self, message: &AsyncMessage) -> bool {
+        self.sender.map_or(true, |sender| message.sender == sender)
+            && self
+                .destination
+                .map_or(true, |destination| message.destination == destination)
+            && self
+                .handler
+                .as_ref()
+                .map_or(true, |handler| &message.handler == handler)
+            && self.valid_at.map_or(true, |slot| {
+                slot >= message.validity_start && slot < message.validity_end
+            })
+            && self.can_be_executed.map_or(true, |can_be_executed| {
+                message.can_be_executed == can_be_executed
+            })
+    }
+}
+
 impl AsyncPool {
     /// Creates an empty `AsyncPool`
     pub fn new(config: AsyncPoolConfig) -> AsyncPool {
@@ -131,7 +178,7 @@ impl AsyncPool {
         }
         let mut triggered = Vec::new();
         for (id, message) in self.messages.iter_mut() {
-            if let Some(filter) = &message.trigger && !message.can_be_executed && is_triggered(filter, ledger_changes)
+            if let Some(filter) = &message.trigger && !message.can_be_executed && filter.is_triggered(ledger_changes)
             {
                 message.can_be_executed = true;
                 triggered.push((*id, message.clone()));
@@ -234,11 +281,38 @@ impl AsyncPool {
             StreamingStep::Finished(None)
         }
     }
-}
 
-/// Check in the ledger changes if a message trigger has been triggered
-fn is_triggered(filter: &AsyncMessageTrigger, ledger_changes: &LedgerChanges) -> bool {
-    ledger_changes.has_changes(&filter.address, filter.datastore_key.clone())
+    /// Lists the messages of the pool matching a filter, from the highest to the lowest priority
+    ///
+    /// # Arguments
+    /// * `filter`: filter on the messages, with an optional cursor and limit for pagination
+    ///
+    /// # Returns
+    /// The matching `(message_id, message)` pairs
+    pub fn get_messages(&self, filter: &AsyncMessageFilter) -> Vec<(AsyncMessageId, AsyncMessage)> {
+        let left_bound = match filter.cursor {
+            Some(cursor) => Excluded(cursor),
+            None => Unbounded,
+        };
+        self.messages
+            .range((left_bound, Unbounded))
+            .filter(|(_, message)| filter.matches(message))
+            .take(filter.limit.unwrap_or(usize::MAX))
+            .map(|(id, message)| (*id, message.clone()))
+            .collect()
+    }
+
+    /// Compute the hash of the pool from scratch,
+    /// re-hashing every message instead of relying on their stored hashes
+    pub fn compute_hash(&self) -> Hash {
+        let mut hash = Hash::from_bytes(ASYNC_POOL_HASH_INITIAL_BYTES);
+        for message in self.messages.values() {
+            let mut message = message.clone();
+            message.compute_hash();
+            hash ^= message.hash;
+        }
+        hash
+    }
 }
 
 /// Serializer for `AsyncPool`
@@ -375,3 +449,65 @@ fn test_take_batch() {
     pool.take_batch_to_execute(Slot::new(2, 0), 19);
     assert_eq!(pool.messages.len(), 4);
 }
+
+#[test]
+fn test_get_messages() {
+    use massa_hash::Hash;
+    use massa_models::amount::Amount;
+    use std::str::FromStr;
+
+    let config = AsyncPoolConfig {
+        thread_count: 2,
+        max_length: 10,
+        max_async_message_data: 1_000_000,
+        bootstrap_part_size: 100,
+    };
+    let mut pool = AsyncPool::new(config);
+    let sender = Address(Hash::compute_from(b"abc"));
+    let destination = Address(Hash::compute_from(b"def"));
+    for i in 1..10 {
+        let message = AsyncMessage::new_with_hash(
+            Slot::new(0, 0),
+            i,
+            sender,
+            if i % 2 == 0 { destination } else { sender },
+            "function".to_string(),
+            i,
+            Amount::from_str("0.1").unwrap(),
+            Amount::from_str("0.3").unwrap(),
+            Slot::new(1, 0),
+            Slot::new(i, 0),
+            Vec::new(),
+            None,
+        );
+        pool.messages.insert(message.compute_id(), message);
+    }
+
+    // filter on the destination and the validity range
+    let filter = AsyncMessageFilter {
+        destination: Some(destination),
+        valid_at: Some(Slot::new(5, 0)),
+        ..Default::default()
+    };
+    let messages = pool.get_messages(&filter);
+    assert_eq!(messages.len(), 2);
+    assert!(messages
+        .iter()
+        .all(|(_, message)| message.destination == destination && message.emission_index > 5));
+
+    // paginate through all the messages
+    let mut filter = AsyncMessageFilter {
+        limit: Some(4),
+        ..Default::default()
+    };
+    let mut count = 0;
+    loop {
+        let page = pool.get_messages(&filter);
+        count += page.len();
+        match page.last() {
+            Some((id, _)) => filter.cursor = Some(*id),
+            None => break,
+        }
+    }
+    assert_eq!(count, 9);
+}
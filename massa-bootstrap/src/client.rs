@@ -157,6 +157,8 @@ async fn stream_final_state_and_consensus(
                 }
                 BootstrapServerMessage::SlotTooOld => {
                     info!("Slot is too old retry bootstrap from scratch");
+                    // drop what was received or restored so far
+                    global_bootstrap_state.final_state.write().reset();
                     *next_bootstrap_message = BootstrapClientMessage::AskBootstrapPart {
                         last_slot: None,
                         last_ledger_step: StreamingStep::Started,
@@ -409,6 +411,12 @@ async fn connect_to_server(
 
 /// Gets the state from a bootstrap server
 /// needs to be CANCELLABLE
+///
+/// If `final_state_restored` is true, the final state was restored from disk
+/// and only the changes that happened since its slot are asked to the server.
+/// If `graph_restored` is also true, the block graph was restored from disk as well:
+/// the clocks and versions are still checked and the peers asked to the server, but no state is asked.
+#[allow(clippy::too_many_arguments)]
 pub async fn get_state(
     bootstrap_config: &BootstrapConfig,
     final_state: Arc<RwLock<FinalState>>,
@@ -416,6 +424,8 @@ pub async fn get_state(
     version: Version,
     genesis_timestamp: MassaTime,
     end_timestamp: Option<MassaTime>,
+    final_state_restored: bool,
+    graph_restored: bool,
 ) -> Result<GlobalBootstrapState, BootstrapError> {
     massa_trace!("bootstrap.lib.get_state", {});
     let now = MassaTime::now()?;
@@ -423,7 +433,7 @@ pub async fn get_state(
     if now < genesis_timestamp {
         massa_trace!("bootstrap.lib.get_state.init_from_scratch", {});
         // init final state
-        {
+        if !final_state_restored {
             let mut final_state_guard = final_state.write();
             // load ledger from initial ledger file
             final_state_guard
@@ -447,14 +457,30 @@ pub async fn get_state(
     let mut shuffled_list = bootstrap_config.bootstrap_list.clone();
     shuffled_list.shuffle(&mut StdRng::from_entropy());
     let mut next_bootstrap_message: BootstrapClientMessage =
-        BootstrapClientMessage::AskBootstrapPart {
-            last_slot: None,
-            last_ledger_step: StreamingStep::Started,
-            last_pool_step: StreamingStep::Started,
-            last_cycle_step: StreamingStep::Started,
-            last_credits_step: StreamingStep::Started,
-            last_ops_step: StreamingStep::Started,
-            last_consensus_step: StreamingStep::Started,
+        if final_state_restored && graph_restored {
+            // the node resumes from its own final state and block graph: only ask for the peers
+            BootstrapClientMessage::AskBootstrapPeers
+        } else if final_state_restored {
+            // the final state is complete up to its slot: only ask for the missing changes
+            BootstrapClientMessage::AskBootstrapPart {
+                last_slot: Some(final_state.read().slot),
+                last_ledger_step: StreamingStep::Finished(None),
+                last_pool_step: StreamingStep::Finished(None),
+                last_cycle_step: StreamingStep::Finished(None),
+                last_credits_step: StreamingStep::Finished(None),
+                last_ops_step: StreamingStep::Finished(None),
+                last_consensus_step: StreamingStep::Started,
+            }
+        } else {
+            BootstrapClientMessage::AskBootstrapPart {
+                last_slot: None,
+                last_ledger_step: StreamingStep::Started,
+                last_pool_step: StreamingStep::Started,
+                last_cycle_step: StreamingStep::Started,
+                last_credits_step: StreamingStep::Started,
+                last_ops_step: StreamingStep::Started,
+                last_consensus_step: StreamingStep::Started,
+            }
         };
     let mut global_bootstrap_state = GlobalBootstrapState::new(final_state.clone());
     loop {
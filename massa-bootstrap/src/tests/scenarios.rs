@@ -76,6 +76,8 @@ async fn test_bootstrap_server() {
             disk_ledger_path: temp_dir.path().to_path_buf(),
             max_key_length: MAX_DATASTORE_KEY_LENGTH,
             max_ledger_part_size: 100_000,
+            archival: false,
+            tree_hash_activation_period: None,
         },
         async_pool_config: AsyncPoolConfig {
             thread_count,
@@ -96,6 +98,7 @@ async fn test_bootstrap_server() {
         final_history_length: 100,
         initial_seed_string: "".into(),
         initial_rolls_path: "".into(),
+        final_state_path: "".into(),
         thread_count,
         periods_per_cycle,
     };
@@ -167,6 +170,8 @@ async fn test_bootstrap_server() {
             Version::from_str("TEST.1.10").unwrap(),
             MassaTime::now().unwrap().saturating_sub(1000.into()),
             None,
+            false,
+            false,
         )
         .await
         .unwrap()
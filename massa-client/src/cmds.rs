@@ -4,7 +4,8 @@ use crate::repl::Output;
 use anyhow::{anyhow, bail, Error, Result};
 use console::style;
 use massa_models::api::{
-    AddressInfo, CompactAddressInfo, DatastoreEntryInput, EventFilter, OperationInput,
+    AddressHistoryInput, AddressInfo, CompactAddressInfo, DatastoreEntryInput, EventFilter,
+    GraphExportInput, GraphExportOutput, OperationInput,
 };
 use massa_models::api::{ReadOnlyBytecodeExecution, ReadOnlyCall};
 use massa_models::node::NodeId;
@@ -74,6 +75,33 @@ pub enum Command {
     #[strum(ascii_case_insensitive, message = "stops the node")]
     node_stop,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Path"),
+        message = "export a snapshot of the final state to the given file of the node"
+    )]
+    node_export_final_state_snapshot,
+
+    #[strum(
+        ascii_case_insensitive,
+        message = "recompute every final state component hash and report the ones differing from the stored hashes"
+    )]
+    node_check_final_state_integrity,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "format=dot|json start=MassaTime end=MassaTime path=Path"),
+        message = "export a time window of the consensus graph (parent links, status, fitness, cliques) as Graphviz DOT or JSON, optionally to a file"
+    )]
+    node_export_graph,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "OperationId1 OperationId2 ..."),
+        message = "show the call trees recorded for a list of operations (requires call tracing on the node)"
+    )]
+    trace_operation,
+
     #[strum(ascii_case_insensitive, message = "show staking addresses")]
     node_get_staking_addresses,
 
@@ -132,6 +160,13 @@ pub enum Command {
     )]
     get_addresses,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Address cursor=Period,Thread,IndexInBlock limit=usize"),
+        message = "list the final operations involving an address, most recent first (requires the address index on the node)"
+    )]
+    get_address_history,
+
     #[strum(
         ascii_case_insensitive,
         props(args = "Address Key"),
@@ -163,7 +198,7 @@ pub enum Command {
     #[strum(
         ascii_case_insensitive,
         props(
-            args = "start=Slot end=Slot emitter_address=Address caller_address=Address operation_id=OperationId is_final=bool is_error=bool"
+            args = "start=Slot end=Slot emitter_address=Address caller_address=Address operation_id=OperationId is_final=bool is_error=bool block_id=BlockId data_prefix=String data_contains=String order=asc|desc cursor=Period,Thread,IndexInSlot limit=usize"
         ),
         message = "show events emitted by smart contracts with various filters"
     )]
@@ -486,6 +521,74 @@ impl Command {
                 Ok(Box::new(()))
             }
 
+            Command::node_export_final_state_snapshot => {
+                if parameters.len() != 1 {
+                    bail!("wrong number of parameters");
+                }
+                let path = PathBuf::from(&parameters[0]);
+                match client.private.node_export_final_state_snapshot(path).await {
+                    Ok(slot) => {
+                        if !json {
+                            println!("Final state snapshot successfully exported at slot:");
+                        }
+                        Ok(Box::new(slot.to_string()))
+                    }
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
+            Command::node_check_final_state_integrity => {
+                match client.private.node_check_final_state_integrity().await {
+                    Ok(report) => Ok(Box::new(report)),
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
+            Command::node_export_graph => {
+                let p_list: [&str; 4] = ["format", "start", "end", "path"];
+                let mut p: HashMap<&str, &str> = HashMap::new();
+                for v in parameters {
+                    match v.split_once('=') {
+                        Some((key, value)) if p_list.contains(&key) => {
+                            p.insert(key, value);
+                        }
+                        _ => bail!("invalid parameter"),
+                    }
+                }
+                let input = GraphExportInput {
+                    format: parse_key_value(&p, p_list[0]).unwrap_or_default(),
+                    start: parse_key_value(&p, p_list[1]),
+                    end: parse_key_value(&p, p_list[2]),
+                };
+                let output = match client.private.node_export_graph(input).await {
+                    Ok(output) => output,
+                    Err(e) => rpc_error!(e),
+                };
+                if let Some(path) = parse_key_value::<PathBuf>(&p, p_list[3]) {
+                    let content = match output {
+                        GraphExportOutput::Dot(dot) => dot,
+                        GraphExportOutput::Json(graph) => serde_json::to_string_pretty(&graph)?,
+                    };
+                    std::fs::write(&path, content)?;
+                    if !json {
+                        println!("Graph successfully exported to {}", path.display());
+                    }
+                    return Ok(Box::new(()));
+                }
+                match output {
+                    GraphExportOutput::Dot(dot) => Ok(Box::new(dot)),
+                    GraphExportOutput::Json(graph) => Ok(Box::new(graph)),
+                }
+            }
+
+            Command::trace_operation => {
+                let operations = parse_vec::<OperationId>(parameters)?;
+                match client.private.trace_operation(operations).await {
+                    Ok(traces) => Ok(Box::new(traces)),
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
             Command::node_get_staking_addresses => {
                 match client.private.get_staking_addresses().await {
                     Ok(staking_addresses) => Ok(Box::new(staking_addresses)),
@@ -566,6 +669,32 @@ impl Command {
                 }
             }
 
+            Command::get_address_history => {
+                if parameters.is_empty() {
+                    bail!("wrong param numbers, expecting at least an address")
+                }
+                let address = parameters[0].parse::<Address>()?;
+                let p_list: [&str; 2] = ["cursor", "limit"];
+                let mut p: HashMap<&str, &str> = HashMap::new();
+                for v in &parameters[1..] {
+                    match v.split_once('=') {
+                        Some((key, value)) if p_list.contains(&key) => {
+                            p.insert(key, value);
+                        }
+                        _ => bail!("invalid parameter"),
+                    }
+                }
+                let input = AddressHistoryInput {
+                    address,
+                    cursor: parse_key_value(&p, p_list[0]),
+                    limit: parse_key_value(&p, p_list[1]),
+                };
+                match client.public.get_address_history(input).await {
+                    Ok(history) => Ok(Box::new(history)),
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
             Command::get_datastore_entry => {
                 if parameters.len() != 2 {
                     bail!("invalid number of parameters");
@@ -610,7 +739,7 @@ impl Command {
             }
 
             Command::get_filtered_sc_output_event => {
-                let p_list: [&str; 7] = [
+                let p_list: [&str; 13] = [
                     "start",
                     "end",
                     "emitter_address",
@@ -618,14 +747,21 @@ impl Command {
                     "operation_id",
                     "is_final",
                     "is_error",
+                    "block_id",
+                    "data_prefix",
+                    "data_contains",
+                    "order",
+                    "cursor",
+                    "limit",
                 ];
                 let mut p: HashMap<&str, &str> = HashMap::new();
                 for v in parameters {
-                    let s: Vec<&str> = v.split('=').collect();
-                    if s.len() == 2 && p_list.contains(&s[0]) {
-                        p.insert(s[0], s[1]);
-                    } else {
-                        bail!("invalid parameter");
+                    // event data filters may contain '='
+                    match v.split_once('=') {
+                        Some((key, value)) if p_list.contains(&key) => {
+                            p.insert(key, value);
+                        }
+                        _ => bail!("invalid parameter"),
                     }
                 }
                 let filter = EventFilter {
@@ -636,6 +772,12 @@ impl Command {
                     original_operation_id: parse_key_value(&p, p_list[4]),
                     is_final: parse_key_value(&p, p_list[5]),
                     is_error: parse_key_value(&p, p_list[6]),
+                    block_id: parse_key_value(&p, p_list[7]),
+                    data_prefix: parse_key_value(&p, p_list[8]),
+                    data_contains: parse_key_value(&p, p_list[9]),
+                    order: parse_key_value(&p, p_list[10]).unwrap_or_default(),
+                    cursor: parse_key_value(&p, p_list[11]),
+                    limit: parse_key_value(&p, p_list[12]),
                 };
                 match client.public.get_filtered_sc_output_event(filter).await {
                     Ok(events) => Ok(Box::new(events)),
@@ -986,6 +1128,7 @@ impl Command {
                         bytecode,
                         address,
                         operation_datastore: None, // TODO - #3072
+                        state_overrides: Vec::new(),
                     })
                     .await
                 {
@@ -1015,6 +1158,7 @@ impl Command {
                         target_function,
                         parameter,
                         max_gas,
+                        state_overrides: Vec::new(),
                     })
                     .await
                 {
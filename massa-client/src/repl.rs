@@ -6,10 +6,12 @@ use anyhow::Result;
 use console::style;
 use erased_serde::{Serialize, Serializer};
 use massa_models::api::{
-    AddressInfo, BlockInfo, DatastoreEntryOutput, EndorsementInfo, NodeStatus, OperationInfo,
+    AddressActivity, AddressInfo, BlockInfo, DatastoreEntryOutput, EndorsementInfo,
+    FinalStateIntegrityReport, NodeStatus, OperationInfo,
 };
 use massa_models::composite::PubkeySig;
-use massa_models::execution::ExecuteReadOnlyResponse;
+use massa_models::execution::{ExecuteReadOnlyResponse, ExecutionTrace};
+use massa_models::graph_export::GraphExport;
 use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::PreHashSet;
 use massa_models::{address::Address, operation::OperationId};
@@ -241,6 +243,21 @@ impl Output for NodeStatus {
     }
 }
 
+impl Output for GraphExport {
+    fn pretty_print(&self) {
+        match serde_json::to_string_pretty(self) {
+            Ok(graph) => println!("{}", graph),
+            Err(e) => println!("Error serializing the graph: {}", e),
+        }
+    }
+}
+
+impl Output for FinalStateIntegrityReport {
+    fn pretty_print(&self) {
+        println!("{}", self);
+    }
+}
+
 impl Output for BlockInfo {
     fn pretty_print(&self) {
         println!("{}", self);
@@ -321,6 +338,14 @@ impl Output for Vec<Address> {
     }
 }
 
+impl Output for Vec<AddressActivity> {
+    fn pretty_print(&self) {
+        for activity in self {
+            println!("{}", activity);
+        }
+    }
+}
+
 impl Output for Vec<SCOutputEvent> {
     fn pretty_print(&self) {
         for addr in self {
@@ -329,6 +354,17 @@ impl Output for Vec<SCOutputEvent> {
     }
 }
 
+impl Output for Vec<Option<ExecutionTrace>> {
+    fn pretty_print(&self) {
+        for trace in self {
+            match trace {
+                Some(trace) => println!("{}", trace),
+                None => println!("No call trace recorded for this operation\n"),
+            }
+        }
+    }
+}
+
 impl Output for PubkeySig {
     fn pretty_print(&self) {
         println!("{}", self);
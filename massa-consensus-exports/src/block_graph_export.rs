@@ -2,6 +2,7 @@ use massa_models::{
     address::Address,
     block::BlockId,
     clique::Clique,
+    graph_export::{GraphBlock, GraphBlockStatus, GraphClique, GraphEdge, GraphExport},
     prehash::{PreHashMap, PreHashSet},
     slot::Slot,
 };
@@ -27,3 +28,84 @@ pub struct BlockGraphExport {
     /// List of maximal cliques of compatible blocks.
     pub max_cliques: Vec<Clique>,
 }
+
+impl BlockGraphExport {
+    /// Builds the visualization export of the blocks of this graph part,
+    /// with their parent links and the cliques they belong to
+    pub fn to_graph_export(&self) -> GraphExport {
+        let clique_indexes = |id: &BlockId| -> Vec<usize> {
+            self.max_cliques
+                .iter()
+                .enumerate()
+                .filter(|(_, clique)| clique.block_ids.contains(id))
+                .map(|(index, _)| index)
+                .collect()
+        };
+        let mut export = GraphExport {
+            cliques: self
+                .max_cliques
+                .iter()
+                .map(|clique| GraphClique {
+                    fitness: clique.fitness,
+                    is_blockclique: clique.is_blockclique,
+                    block_count: clique.block_ids.len(),
+                })
+                .collect(),
+            ..Default::default()
+        };
+        for (id, block) in self.active_blocks.iter() {
+            export.blocks.push(GraphBlock {
+                id: *id,
+                slot: block.header.content.slot,
+                creator: block.header.creator_address,
+                status: if block.is_final {
+                    GraphBlockStatus::Final
+                } else {
+                    GraphBlockStatus::Active
+                },
+                endorsement_count: Some(block.header.content.endorsements.len() as u32),
+                fitness: Some(block.header.get_fitness()),
+                cliques: clique_indexes(id),
+            });
+            export
+                .edges
+                .extend(parent_edges(id, &block.header.content.parents));
+        }
+        for (id, (reason, (slot, creator, parents))) in self.discarded_blocks.iter() {
+            let reason = match reason {
+                DiscardReason::Invalid(reason) => format!("invalid: {}", reason),
+                DiscardReason::Stale => "stale".to_string(),
+                DiscardReason::Final => "final".to_string(),
+            };
+            export.blocks.push(GraphBlock {
+                id: *id,
+                slot: *slot,
+                creator: *creator,
+                status: GraphBlockStatus::Discarded(reason),
+                endorsement_count: None,
+                fitness: None,
+                cliques: Vec::new(),
+            });
+            export.edges.extend(parent_edges(id, parents));
+        }
+        export
+            .blocks
+            .sort_unstable_by_key(|block| (block.slot, block.id));
+        export
+    }
+}
+
+/// Links from a block to its parents, the parent of thread `i` being at index `i`
+fn parent_edges<'a>(
+    id: &'a BlockId,
+    parents: &'a [BlockId],
+) -> impl Iterator<Item = GraphEdge> + 'a {
+    parents
+        .iter()
+        .enumerate()
+        .map(move |(thread, parent)| GraphEdge {
+            source: *id,
+            target: *parent,
+            thread: thread as u8,
+        })
+}
@@ -1,5 +1,6 @@
 use massa_execution_exports::ExecutionController;
 use massa_models::block::{Block, BlockHeader, FilledBlock};
+use massa_models::clique::BlockcliqueChange;
 use massa_pool_exports::PoolController;
 use massa_pos_exports::SelectorController;
 use massa_protocol_exports::ProtocolCommandSender;
@@ -19,4 +20,5 @@ pub struct ConsensusChannels {
     pub block_sender: tokio::sync::broadcast::Sender<Block>,
     pub block_header_sender: tokio::sync::broadcast::Sender<BlockHeader>,
     pub filled_block_sender: tokio::sync::broadcast::Sender<FilledBlock>,
+    pub blockclique_change_sender: tokio::sync::broadcast::Sender<BlockcliqueChange>,
 }
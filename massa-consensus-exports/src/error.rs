@@ -41,6 +41,8 @@ pub enum ConsensusError {
     TransactionError(String),
     /// Protocol error {0}
     ProtocolError(#[from] ProtocolError),
+    /// persisted graph error: {0}
+    PersistedGraphError(String),
 }
 
 /// Internal error
@@ -65,6 +65,9 @@ impl ExportActiveBlock {
         // TODO change if we decide that endorsements are stored separately
         storage.store_endorsements(self.block.content.header.content.endorsements.clone());
 
+        // claim the operations that are already in storage, if any
+        storage.claim_operation_refs(&self.block.content.operations.iter().copied().collect());
+
         // Note: the block's parents are not claimed in the block's storage here but on graph inclusion
 
         // create ActiveBlock
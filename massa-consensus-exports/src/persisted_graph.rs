@@ -0,0 +1,439 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use crate::{
+    bootstrapable_graph::BootstrapableGraph,
+    error::ConsensusError,
+    export_active_block::{
+        ExportActiveBlock, ExportActiveBlockDeserializer, ExportActiveBlockSerializer,
+    },
+};
+use massa_models::{
+    block::WrappedBlock,
+    operation::{OperationsDeserializer, OperationsSerializer, WrappedOperation},
+    slot::Slot,
+};
+use massa_serialization::{
+    DeserializeError, Deserializer, SerializeError, Serializer, U32VarIntDeserializer,
+    U32VarIntSerializer,
+};
+use nom::error::{ContextError, ParseError};
+use nom::{error::context, multi::length_count, sequence::tuple, IResult, Parser};
+use std::{fs, ops::Bound::Included, path::Path};
+
+/// Active blocks of the graph, written to disk so that a restarted node can resume from them
+#[derive(Debug, Clone, Default)]
+pub struct PersistedGraph {
+    /// active blocks, final or not
+    pub active_blocks: Vec<ExportActiveBlock>,
+    /// operations of the active blocks that are still in storage
+    pub operations: Vec<WrappedOperation>,
+}
+
+/// Persisted graph checked against the final state
+#[derive(Debug, Clone)]
+pub struct RestoredGraph {
+    /// final blocks to initialize the graph with
+    pub graph: BootstrapableGraph,
+    /// non-final blocks to process again, sorted by slot
+    pub candidate_blocks: Vec<WrappedBlock>,
+    /// operations of the restored blocks
+    pub operations: Vec<WrappedOperation>,
+}
+
+impl PersistedGraph {
+    /// Writes the graph to `path`.
+    /// The graph is first written to a temporary file so that an interrupted write leaves the previous one intact.
+    pub fn write_to_disk(&self, path: &Path) -> Result<(), ConsensusError> {
+        let mut buffer = Vec::new();
+        PersistedGraphSerializer::new()
+            .serialize(self, &mut buffer)
+            .map_err(|err| ConsensusError::PersistedGraphError(err.to_string()))?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, buffer)?;
+        fs::rename(tmp_path, path)?;
+        Ok(())
+    }
+
+    /// Reads the graph written to `path`, if any
+    pub fn read_from_disk(
+        path: &Path,
+        deserializer: &PersistedGraphDeserializer,
+    ) -> Result<Option<Self>, ConsensusError> {
+        let buffer = match fs::read(path) {
+            Ok(buffer) => buffer,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let (rest, graph) = deserializer
+            .deserialize::<DeserializeError>(&buffer)
+            .map_err(|err| ConsensusError::PersistedGraphError(err.to_string()))?;
+        if !rest.is_empty() {
+            return Err(ConsensusError::PersistedGraphError(
+                "trailing bytes after the persisted graph".into(),
+            ));
+        }
+        Ok(Some(graph))
+    }
+
+    /// Checks the graph against the slot of the final state it is loaded with.
+    ///
+    /// Every slot up to `final_slot` must be final in the graph, meaning that in each thread
+    /// the latest final block is at or after the last slot of that thread up to `final_slot`.
+    /// Otherwise the final state went further than the graph and `None` is returned.
+    pub fn restore(self, final_slot: Slot, thread_count: u8) -> Option<RestoredGraph> {
+        let (final_blocks, candidate_blocks): (Vec<_>, Vec<_>) =
+            self.active_blocks.into_iter().partition(|b| b.is_final);
+
+        let mut latest_final_periods = vec![0u64; thread_count as usize];
+        for export_block in &final_blocks {
+            let slot = export_block.block.content.header.content.slot;
+            if let Some(period) = latest_final_periods.get_mut(slot.thread as usize) {
+                *period = std::cmp::max(*period, slot.period);
+            }
+        }
+        let covered = latest_final_periods
+            .iter()
+            .enumerate()
+            .all(|(thread, period)| {
+                if thread as u8 <= final_slot.thread {
+                    *period >= final_slot.period
+                } else {
+                    period.saturating_add(1) >= final_slot.period
+                }
+            });
+        if !covered {
+            return None;
+        }
+
+        let mut candidate_blocks: Vec<WrappedBlock> =
+            candidate_blocks.into_iter().map(|b| b.block).collect();
+        candidate_blocks.sort_unstable_by_key(|b| b.content.header.content.slot);
+        Some(RestoredGraph {
+            graph: BootstrapableGraph { final_blocks },
+            candidate_blocks,
+            operations: self.operations,
+        })
+    }
+}
+
+/// Basic serializer for `PersistedGraph`
+#[derive(Default)]
+pub struct PersistedGraphSerializer {
+    block_count_serializer: U32VarIntSerializer,
+    export_active_block_serializer: ExportActiveBlockSerializer,
+    operations_serializer: OperationsSerializer,
+}
+
+impl PersistedGraphSerializer {
+    /// Creates a `PersistedGraphSerializer`
+    pub fn new() -> Self {
+        Self {
+            block_count_serializer: U32VarIntSerializer::new(),
+            export_active_block_serializer: ExportActiveBlockSerializer::new(),
+            operations_serializer: OperationsSerializer::new(),
+        }
+    }
+}
+
+impl Serializer<PersistedGraph> for PersistedGraphSerializer {
+    fn serialize(
+        &self,
+        value: &PersistedGraph,
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), SerializeError> {
+        // block count
+        self.block_count_serializer.serialize(
+            &value
+                .active_blocks
+                .len()
+                .try_into()
+                .map_err(|_| SerializeError::NumberTooBig("Too many active blocks".to_string()))?,
+            buffer,
+        )?;
+
+        // active blocks
+        for export_active_block in &value.active_blocks {
+            self.export_active_block_serializer
+                .serialize(export_active_block, buffer)?;
+        }
+
+        // operations
+        self.operations_serializer
+            .serialize(&value.operations, buffer)?;
+
+        Ok(())
+    }
+}
+
+/// Basic deserializer for `PersistedGraph`
+pub struct PersistedGraphDeserializer {
+    block_count_deserializer: U32VarIntDeserializer,
+    export_active_block_deserializer: ExportActiveBlockDeserializer,
+    operations_deserializer: OperationsDeserializer,
+}
+
+impl PersistedGraphDeserializer {
+    /// Creates a `PersistedGraphDeserializer`
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        thread_count: u8,
+        endorsement_count: u32,
+        max_operations_per_block: u32,
+        max_datastore_value_length: u64,
+        max_function_name_length: u16,
+        max_parameters_size: u32,
+        max_op_datastore_entry_count: u64,
+        max_op_datastore_key_length: u8,
+        max_op_datastore_value_length: u64,
+    ) -> Self {
+        Self {
+            block_count_deserializer: U32VarIntDeserializer::new(Included(0), Included(u32::MAX)),
+            export_active_block_deserializer: ExportActiveBlockDeserializer::new(
+                thread_count,
+                endorsement_count,
+                max_operations_per_block,
+            ),
+            operations_deserializer: OperationsDeserializer::new(
+                u32::MAX,
+                max_datastore_value_length,
+                max_function_name_length,
+                max_parameters_size,
+                max_op_datastore_entry_count,
+                max_op_datastore_key_length,
+                max_op_datastore_value_length,
+            ),
+        }
+    }
+}
+
+impl Deserializer<PersistedGraph> for PersistedGraphDeserializer {
+    /// ## Example
+    /// ```rust
+    /// use massa_consensus_exports::persisted_graph::{PersistedGraph, PersistedGraphDeserializer, PersistedGraphSerializer};
+    /// use massa_serialization::{Deserializer, Serializer, DeserializeError};
+    /// let persisted_graph = PersistedGraph::default();
+    /// let mut buffer = Vec::new();
+    /// PersistedGraphSerializer::new().serialize(&persisted_graph, &mut buffer).unwrap();
+    /// let (rest, persisted_graph_deserialized) = PersistedGraphDeserializer::new(32, 16, 10, 100, 100, 100, 10, 10, 100).deserialize::<DeserializeError>(&buffer).unwrap();
+    /// let mut buffer2 = Vec::new();
+    /// PersistedGraphSerializer::new().serialize(&persisted_graph_deserialized, &mut buffer2).unwrap();
+    /// assert_eq!(buffer, buffer2);
+    /// assert_eq!(rest.len(), 0);
+    /// ```
+    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], PersistedGraph, E> {
+        context(
+            "Failed PersistedGraph deserialization",
+            tuple((
+                context(
+                    "Failed active_blocks deserialization",
+                    length_count(
+                        context("Failed active block count deserialization", |input| {
+                            self.block_count_deserializer.deserialize(input)
+                        }),
+                        context("Failed export_active_block deserialization", |input| {
+                            self.export_active_block_deserializer.deserialize(input)
+                        }),
+                    ),
+                ),
+                context("Failed operations deserialization", |input| {
+                    self.operations_deserializer.deserialize(input)
+                }),
+            )),
+        )
+        .map(|(active_blocks, operations)| PersistedGraph {
+            active_blocks,
+            operations,
+        })
+        .parse(buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_hash::Hash;
+    use massa_models::{
+        address::Address,
+        amount::Amount,
+        block::{Block, BlockHeader, BlockHeaderSerializer, BlockId, BlockSerializer},
+        config::{
+            ENDORSEMENT_COUNT, MAX_DATASTORE_VALUE_LENGTH, MAX_FUNCTION_NAME_LENGTH,
+            MAX_OPERATIONS_PER_BLOCK, MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+            MAX_OPERATION_DATASTORE_KEY_LENGTH, MAX_OPERATION_DATASTORE_VALUE_LENGTH,
+            MAX_PARAMETERS_SIZE, THREAD_COUNT,
+        },
+        operation::{Operation, OperationSerializer, OperationType},
+        wrapped::WrappedContent,
+    };
+    use massa_signature::KeyPair;
+
+    /// Create an active block at `slot`, with a parent in each thread
+    fn create_export_active_block(slot: Slot, is_final: bool) -> ExportActiveBlock {
+        let keypair = KeyPair::generate();
+        let parents: Vec<(BlockId, u64)> = (0..THREAD_COUNT)
+            .map(|i| (BlockId(Hash::compute_from(&[i])), slot.period - 1))
+            .collect();
+        let header = BlockHeader::new_wrapped(
+            BlockHeader {
+                slot,
+                parents: parents.iter().map(|(id, _)| *id).collect(),
+                operation_merkle_root: Hash::compute_from(&Vec::new()),
+                endorsements: Vec::new(),
+            },
+            BlockHeaderSerializer::new(),
+            &keypair,
+        )
+        .unwrap();
+        let block = Block::new_wrapped(
+            Block {
+                header,
+                operations: Vec::new(),
+            },
+            BlockSerializer::new(),
+            &keypair,
+        )
+        .unwrap();
+        ExportActiveBlock {
+            block,
+            parents,
+            is_final,
+        }
+    }
+
+    fn create_operation() -> WrappedOperation {
+        let keypair = KeyPair::generate();
+        let content = Operation {
+            fee: Amount::default(),
+            op: OperationType::Transaction {
+                recipient_address: Address::from_public_key(&KeyPair::generate().get_public_key()),
+                amount: Amount::default(),
+            },
+            expire_period: 10,
+        };
+        Operation::new_wrapped(content, OperationSerializer::new(), &keypair).unwrap()
+    }
+
+    fn block_slots(blocks: &[WrappedBlock]) -> Vec<Slot> {
+        blocks
+            .iter()
+            .map(|b| b.content.header.content.slot)
+            .collect()
+    }
+
+    #[test]
+    fn persisted_graph_serialization() {
+        let persisted_graph = PersistedGraph {
+            active_blocks: vec![
+                create_export_active_block(Slot::new(1, 0), true),
+                create_export_active_block(Slot::new(1, 1), false),
+            ],
+            operations: vec![create_operation()],
+        };
+        let mut buffer = Vec::new();
+        PersistedGraphSerializer::new()
+            .serialize(&persisted_graph, &mut buffer)
+            .unwrap();
+        let (rest, deserialized) = PersistedGraphDeserializer::new(
+            THREAD_COUNT,
+            ENDORSEMENT_COUNT,
+            MAX_OPERATIONS_PER_BLOCK,
+            MAX_DATASTORE_VALUE_LENGTH,
+            MAX_FUNCTION_NAME_LENGTH,
+            MAX_PARAMETERS_SIZE,
+            MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+            MAX_OPERATION_DATASTORE_KEY_LENGTH,
+            MAX_OPERATION_DATASTORE_VALUE_LENGTH,
+        )
+        .deserialize::<DeserializeError>(&buffer)
+        .unwrap();
+        assert!(rest.is_empty());
+
+        assert_eq!(
+            deserialized.active_blocks.len(),
+            persisted_graph.active_blocks.len()
+        );
+        for (deserialized, original) in deserialized
+            .active_blocks
+            .iter()
+            .zip(persisted_graph.active_blocks.iter())
+        {
+            assert_eq!(deserialized.block.id, original.block.id);
+            assert_eq!(
+                deserialized.block.serialized_data,
+                original.block.serialized_data
+            );
+            assert_eq!(deserialized.parents, original.parents);
+            assert_eq!(deserialized.is_final, original.is_final);
+        }
+        assert_eq!(
+            deserialized
+                .operations
+                .iter()
+                .map(|op| (op.id, op.serialized_data.clone()))
+                .collect::<Vec<_>>(),
+            persisted_graph
+                .operations
+                .iter()
+                .map(|op| (op.id, op.serialized_data.clone()))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn restore_graph_behind_final_state() {
+        // the final state is at (5, 0) but the graph is only final up to (4, 1)
+        let persisted_graph = PersistedGraph {
+            active_blocks: vec![
+                create_export_active_block(Slot::new(4, 0), true),
+                create_export_active_block(Slot::new(4, 1), true),
+                create_export_active_block(Slot::new(5, 0), false),
+            ],
+            operations: vec![create_operation()],
+        };
+        assert!(persisted_graph.restore(Slot::new(5, 0), 2).is_none());
+    }
+
+    #[test]
+    fn restore_graph_ahead_of_final_state() {
+        // the graph is final up to (6, 0), after the final state at (5, 0)
+        let operation = create_operation();
+        let persisted_graph = PersistedGraph {
+            active_blocks: vec![
+                create_export_active_block(Slot::new(5, 1), true),
+                create_export_active_block(Slot::new(7, 1), false),
+                create_export_active_block(Slot::new(6, 0), true),
+                create_export_active_block(Slot::new(7, 0), false),
+            ],
+            operations: vec![operation.clone()],
+        };
+        let restored = persisted_graph
+            .restore(Slot::new(5, 0), 2)
+            .expect("the graph covers the final state");
+
+        let final_slots: Vec<Slot> = restored
+            .graph
+            .final_blocks
+            .iter()
+            .map(|b| b.block.content.header.content.slot)
+            .collect();
+        assert_eq!(final_slots, vec![Slot::new(5, 1), Slot::new(6, 0)]);
+        assert_eq!(
+            block_slots(&restored.candidate_blocks),
+            vec![Slot::new(7, 0), Slot::new(7, 1)]
+        );
+        assert_eq!(
+            restored
+                .operations
+                .iter()
+                .map(|op| op.id)
+                .collect::<Vec<_>>(),
+            vec![operation.id]
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use massa_signature::KeyPair;
 use massa_time::MassaTime;
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ConsensusConfig {
@@ -54,4 +55,8 @@ pub struct ConsensusConfig {
     pub broadcast_blocks_capacity: usize,
     /// filled blocks sender(channel) capacity
     pub broadcast_filled_blocks_capacity: usize,
+    /// blockclique changes sender(channel) capacity
+    pub broadcast_blockclique_changes_capacity: usize,
+    /// file the active blocks are persisted to by a dedicated thread after each prune and on stop, `None` to disable
+    pub graph_store_path: Option<PathBuf>,
 }
@@ -37,6 +37,8 @@ impl Default for ConsensusConfig {
             broadcast_blocks_headers_capacity: 128,
             broadcast_blocks_capacity: 128,
             broadcast_filled_blocks_capacity: 128,
+            broadcast_blockclique_changes_capacity: 128,
+            graph_store_path: None,
         }
     }
 }
@@ -7,6 +7,8 @@ use massa_consensus_exports::{
     block_graph_export::BlockGraphExport,
     block_status::{BlockStatus, ExportCompiledBlock, HeaderOrBlock},
     error::ConsensusError,
+    export_active_block::ExportActiveBlock,
+    persisted_graph::PersistedGraph,
     ConsensusChannels, ConsensusConfig,
 };
 use massa_models::{
@@ -463,6 +465,26 @@ impl ConsensusState {
             .collect()
     }
 
+    /// Gets all active blocks with the operations that are still in their storage,
+    /// to be persisted on disk and reloaded on restart.
+    pub fn get_persisted_graph(&self) -> PersistedGraph {
+        let mut persisted_graph = PersistedGraph::default();
+        let mut operation_ids = PreHashSet::default();
+        for b_id in &self.active_index {
+            if let Some(BlockStatus::Active { a_block, storage }) = self.block_statuses.get(b_id) {
+                let export_block = ExportActiveBlock::from_active_block(a_block, storage);
+                let stored_operations = storage.read_operations();
+                for op_id in &export_block.block.content.operations {
+                    if let Some(op) = stored_operations.get(op_id) && operation_ids.insert(*op_id) {
+                        persisted_graph.operations.push(op.clone());
+                    }
+                }
+                persisted_graph.active_blocks.push(export_block);
+            }
+        }
+        persisted_graph
+    }
+
     /// get the current block wish list, including the operations hash.
     pub fn get_block_wishlist(
         &self,
@@ -12,7 +12,7 @@ use massa_models::{
     active_block::ActiveBlock,
     address::Address,
     block::{BlockId, WrappedHeader},
-    clique::Clique,
+    clique::{BlockcliqueChange, Clique},
     prehash::{PreHashMap, PreHashSet},
     slot::Slot,
 };
@@ -691,6 +691,7 @@ impl ConsensusState {
 
         // Get new blockclique block list with slots.
         let mut blockclique_changed = false;
+        let mut added_blocks: Vec<(Slot, BlockId)> = Vec::new();
         let new_blockclique: PreHashMap<BlockId, Slot> = self
             .get_blockclique()
             .iter()
@@ -709,6 +710,7 @@ impl ConsensusState {
                         _ => panic!("blockclique block not found in active blocks"),
                     };
                     new_blocks_storage.insert(*b_id, storage.clone());
+                    added_blocks.push((slot, *b_id));
                     (*b_id, slot)
                 }
             })
@@ -719,6 +721,9 @@ impl ConsensusState {
             // In that case, we mark the blockclique as having changed.
             blockclique_changed = true;
         }
+        if blockclique_changed && self.config.broadcast_enabled {
+            self.notify_blockclique_change(&finalized_blocks, added_blocks);
+        }
         // Overwrite previous blockclique.
         // Should still be done even if unchanged because elements were removed from it above.
         self.prev_blockclique = new_blockclique.clone();
@@ -742,6 +747,40 @@ impl ConsensusState {
             );
     }
 
+    /// Broadcast the candidate blocks that left and joined the blockclique,
+    /// and the slots whose candidate execution is rolled back as a consequence,
+    /// if the blockclique switched to other blocks or got a block at a slot already executed.
+    ///
+    /// Must be called before `self.prev_blockclique` is overwritten:
+    /// it then only contains the blocks that left the blockclique.
+    /// Must also be called before execution is notified of the new blockclique,
+    /// so that its active cursor is the last slot executed with the previous one.
+    ///
+    /// # Arguments:
+    /// * `finalized_blocks`: blocks that became final, and left the blockclique for that reason
+    /// * `added_blocks`: blocks that joined the blockclique, with their slots
+    fn notify_blockclique_change(
+        &self,
+        finalized_blocks: &HashMap<Slot, BlockId>,
+        added_blocks: Vec<(Slot, BlockId)>,
+    ) {
+        let removed_blocks: Vec<(Slot, BlockId)> = self
+            .prev_blockclique
+            .iter()
+            .filter(|(b_id, slot)| finalized_blocks.get(*slot) != Some(*b_id))
+            .map(|(b_id, slot)| (*slot, *b_id))
+            .collect();
+        let last_executed_slot = self.channels.execution_controller.get_stats().active_cursor;
+        if let Some(change) = compute_blockclique_change(
+            removed_blocks,
+            added_blocks,
+            last_executed_slot,
+            self.config.thread_count,
+        ) {
+            let _ = self.channels.blockclique_change_sender.send(change);
+        }
+    }
+
     /// call me if the block database changed
     /// Processing of final blocks, pruning.
     ///
@@ -870,3 +909,142 @@ impl ConsensusState {
         Ok(())
     }
 }
+
+/// Compute the change of the blockclique reported to external consumers
+///
+/// Candidate execution is truncated at the first changed slot and every slot executed from there is executed again,
+/// with or without a block: the slots from the first changed slot up to the last executed slot are rolled back.
+/// Blocks appended to the blockclique after the last executed slot roll nothing back:
+/// they are only reported along with a switch of the blockclique.
+///
+/// # Arguments:
+/// * `removed_blocks`: candidate blocks that left the blockclique, with their slots
+/// * `added_blocks`: blocks that joined the blockclique, with their slots
+/// * `last_executed_slot`: last slot executed with the previous blockclique
+/// * `thread_count`: number of threads
+///
+/// # Returns:
+/// The change, or `None` if no candidate block left the blockclique and no executed slot is rolled back
+fn compute_blockclique_change(
+    mut removed_blocks: Vec<(Slot, BlockId)>,
+    mut added_blocks: Vec<(Slot, BlockId)>,
+    last_executed_slot: Slot,
+    thread_count: u8,
+) -> Option<BlockcliqueChange> {
+    let first_changed_slot = removed_blocks
+        .iter()
+        .chain(added_blocks.iter())
+        .map(|(slot, _)| *slot)
+        .min()?;
+    let mut rolled_back_slots = Vec::new();
+    let mut slot = first_changed_slot;
+    while slot <= last_executed_slot {
+        rolled_back_slots.push(slot);
+        slot = match slot.get_next_slot(thread_count) {
+            Ok(next_slot) => next_slot,
+            Err(_) => break,
+        };
+    }
+    if removed_blocks.is_empty() && rolled_back_slots.is_empty() {
+        // plain append
+        return None;
+    }
+    removed_blocks.sort_unstable();
+    added_blocks.sort_unstable();
+
+    Some(BlockcliqueChange {
+        removed_blocks: removed_blocks.into_iter().map(|(_, b_id)| b_id).collect(),
+        added_blocks: added_blocks.into_iter().map(|(_, b_id)| b_id).collect(),
+        rolled_back_slots,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::compute_blockclique_change;
+    use massa_hash::Hash;
+    use massa_models::{block::BlockId, slot::Slot};
+
+    fn block_id(name: &str) -> BlockId {
+        BlockId(Hash::compute_from(name.as_bytes()))
+    }
+
+    #[test]
+    fn fork_switch() {
+        let thread_count = 2;
+        // previous blockclique: a1 (1,0), a2 (1,1), a3 (3,0)
+        // new blockclique: a1 (1,0), b2 (2,0), b3 (2,1), with blockless slots (1,1), (3,0) and (3,1)
+        // execution lags behind the current slot: it has executed up to (3,0)
+        let removed_blocks = vec![
+            (Slot::new(3, 0), block_id("a3")),
+            (Slot::new(1, 1), block_id("a2")),
+        ];
+        let added_blocks = vec![
+            (Slot::new(2, 1), block_id("b3")),
+            (Slot::new(2, 0), block_id("b2")),
+        ];
+
+        let change =
+            compute_blockclique_change(removed_blocks, added_blocks, Slot::new(3, 0), thread_count)
+                .unwrap();
+        assert_eq!(change.removed_blocks, vec![block_id("a2"), block_id("a3")]);
+        assert_eq!(change.added_blocks, vec![block_id("b2"), block_id("b3")]);
+        // every executed slot from the first changed one is executed again, with or without a block
+        assert_eq!(
+            change.rolled_back_slots,
+            vec![
+                Slot::new(1, 1),
+                Slot::new(2, 0),
+                Slot::new(2, 1),
+                Slot::new(3, 0),
+            ]
+        );
+
+        // a switch after the last executed slot rolls nothing back
+        let change = compute_blockclique_change(
+            vec![(Slot::new(4, 0), block_id("a4"))],
+            vec![(Slot::new(4, 0), block_id("b4"))],
+            Slot::new(3, 1),
+            thread_count,
+        )
+        .unwrap();
+        assert_eq!(change.removed_blocks, vec![block_id("a4")]);
+        assert_eq!(change.added_blocks, vec![block_id("b4")]);
+        assert!(change.rolled_back_slots.is_empty());
+
+        // blocks leaving the blockclique only because they became final do not change it
+        assert!(
+            compute_blockclique_change(Vec::new(), Vec::new(), Slot::new(3, 1), thread_count)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn block_append() {
+        let thread_count = 2;
+
+        // a block appended after the last executed slot is not a change
+        assert!(compute_blockclique_change(
+            Vec::new(),
+            vec![(Slot::new(4, 0), block_id("a4"))],
+            Slot::new(3, 1),
+            thread_count,
+        )
+        .is_none());
+
+        // a late block at a slot executed without block rolls back the executed slots from its slot
+        let change = compute_blockclique_change(
+            Vec::new(),
+            vec![(Slot::new(3, 0), block_id("a3"))],
+            Slot::new(3, 1),
+            thread_count,
+        )
+        .unwrap();
+        assert!(change.removed_blocks.is_empty());
+        assert_eq!(change.added_blocks, vec![block_id("a3")]);
+        assert_eq!(
+            change.rolled_back_slots,
+            vec![Slot::new(3, 0), Slot::new(3, 1)]
+        );
+    }
+}
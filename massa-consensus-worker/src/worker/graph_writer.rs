@@ -0,0 +1,80 @@
+//! Thread writing the persisted block graph to disk, so that the consensus thread
+//! only takes a snapshot of the graph and does not wait for its serialization and disk write.
+
+use std::{
+    path::PathBuf,
+    sync::mpsc::{self, TrySendError},
+    thread::{self, JoinHandle},
+};
+
+use massa_consensus_exports::persisted_graph::PersistedGraph;
+use tracing::log::{debug, warn};
+
+/// Writer of the persisted block graph, running in its own thread
+pub(crate) struct GraphWriter {
+    /// sender of the graphs to write, `None` once the writer is stopping
+    graph_sender: Option<mpsc::SyncSender<PersistedGraph>>,
+    /// thread of the writer
+    thread: Option<JoinHandle<()>>,
+}
+
+impl GraphWriter {
+    /// Starts the writer thread
+    ///
+    /// # Arguments
+    /// * `path`: file the graph is written to
+    pub fn new(path: PathBuf) -> Self {
+        // a single graph waits while another one is written: a newer one comes with the next prune
+        let (graph_sender, graph_receiver) = mpsc::sync_channel::<PersistedGraph>(1);
+        let thread = thread::Builder::new()
+            .name("consensus graph writer".into())
+            .spawn(move || {
+                while let Ok(persisted_graph) = graph_receiver.recv() {
+                    if let Err(err) = persisted_graph.write_to_disk(&path) {
+                        warn!("Error while persisting the block graph: {}", err);
+                    }
+                }
+            })
+            .expect("Can't spawn consensus graph writer thread.");
+        GraphWriter {
+            graph_sender: Some(graph_sender),
+            thread: Some(thread),
+        }
+    }
+
+    /// Hands a graph over to the writer thread without waiting.
+    /// The graph is skipped if the writer is still busy with the previous ones.
+    pub fn write(&self, persisted_graph: PersistedGraph) {
+        let Some(graph_sender) = &self.graph_sender else {
+            return;
+        };
+        match graph_sender.try_send(persisted_graph) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => {
+                debug!("Block graph writer busy, skipping this graph");
+            }
+            Err(TrySendError::Disconnected(_)) => {
+                warn!("Block graph writer stopped, the graph is not persisted");
+            }
+        }
+    }
+
+    /// Hands the last graph over to the writer thread and waits until it is written
+    pub fn stop(mut self, persisted_graph: PersistedGraph) {
+        if let Some(graph_sender) = self.graph_sender.take() {
+            if graph_sender.send(persisted_graph).is_err() {
+                warn!("Block graph writer stopped, the graph is not persisted");
+            }
+        }
+    }
+}
+
+impl Drop for GraphWriter {
+    /// Stops the writer thread once it is done with the graphs it was given
+    fn drop(&mut self) {
+        self.graph_sender = None;
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
@@ -23,7 +23,7 @@ use tracing::log::info;
 
 use crate::{commands::ConsensusCommand, state::ConsensusState};
 
-use super::ConsensusWorker;
+use super::{graph_writer::GraphWriter, ConsensusWorker};
 
 /// Creates genesis block in given thread.
 ///
@@ -169,6 +169,7 @@ impl ConsensusWorker {
             previous_slot,
             next_slot,
             next_instant,
+            graph_writer: config.graph_store_path.clone().map(GraphWriter::new),
         };
 
         if let Some(BootstrapableGraph { final_blocks }) = init_graph {
@@ -110,6 +110,21 @@ impl ConsensusWorker {
         (next_slot, next_instant)
     }
 
+    /// Hands the active blocks over to the graph writer if a graph store path is configured.
+    /// Only the snapshot of the graph is taken on the consensus thread.
+    fn persist_graph(&self) {
+        if let Some(graph_writer) = &self.graph_writer {
+            graph_writer.write(self.shared_state.read().get_persisted_graph());
+        }
+    }
+
+    /// Hands the last active blocks over to the graph writer and waits until they are written
+    fn stop_graph_writer(&mut self) {
+        if let Some(graph_writer) = self.graph_writer.take() {
+            graph_writer.stop(self.shared_state.read().get_persisted_graph());
+        }
+    }
+
     /// Runs in loop forever. This loop must stop every slot to perform operations on stats and graph
     /// but can be stopped anytime by a command received.
     pub fn run(&mut self) {
@@ -154,11 +169,13 @@ impl ConsensusWorker {
                             .prune()
                             .expect("Error while pruning");
                         last_prune = Instant::now();
+                        self.persist_graph();
                     }
                     self.previous_slot = Some(self.next_slot);
                     (self.next_slot, self.next_instant) = self.get_next_slot(Some(self.next_slot));
                 }
                 WaitingStatus::Disconnected => {
+                    self.stop_graph_writer();
                     break;
                 }
                 WaitingStatus::Interrupted => {
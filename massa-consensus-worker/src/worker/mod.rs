@@ -18,6 +18,7 @@ use crate::commands::ConsensusCommand;
 use crate::controller::ConsensusControllerImpl;
 use crate::manager::ConsensusManagerImpl;
 use crate::state::ConsensusState;
+use graph_writer::GraphWriter;
 
 /// The consensus worker structure that contains all information and tools for the consensus worker thread.
 pub struct ConsensusWorker {
@@ -33,8 +34,11 @@ pub struct ConsensusWorker {
     next_slot: Slot,
     /// Next slot instant
     next_instant: Instant,
+    /// Writer of the persisted graph, if a graph store path is configured
+    graph_writer: Option<GraphWriter>,
 }
 
+mod graph_writer;
 mod init;
 mod main_loop;
 
@@ -159,6 +159,15 @@ impl ExecutedOps {
             StreamingStep::Finished(None)
         }
     }
+
+    /// Compute the hash of the executed operations from scratch, regardless of the accumulated `hash`
+    pub fn compute_hash(&self) -> Hash {
+        let mut hash = Hash::from_bytes(EXECUTED_OPS_HASH_INITIAL_BYTES);
+        for op_id in self.sorted_ops.values().flatten() {
+            hash ^= *op_id.get_hash();
+        }
+        hash
+    }
 }
 
 #[test]
@@ -204,6 +213,13 @@ fn test_executed_ops_xor_computing() {
     // check that a.hash ^ $(change_b) = c.hash
     assert_eq!(a.hash, c.hash, "'a' and 'c' hashes are not equal");
 
+    // check that the accumulated hash matches the one computed from scratch
+    assert_eq!(
+        a.hash,
+        a.compute_hash(),
+        "'a' hash was not properly accumulated"
+    );
+
     // prune every element
     let prune_slot = Slot {
         period: 20,
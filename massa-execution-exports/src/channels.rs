@@ -0,0 +1,17 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! This module defines the channels used by the execution worker to broadcast its outputs
+
+use massa_models::block::FinalBlock;
+use massa_models::output_event::SCOutputEvent;
+
+/// Contains (a) channel(s) to send info to api
+#[derive(Clone)]
+pub struct ExecutionChannels {
+    /// Broadcast sender(channel) for the SC output events,
+    /// sent as candidate when a slot is executed and sent again as final when it becomes final
+    pub sc_event_sender: tokio::sync::broadcast::Sender<SCOutputEvent>,
+    /// Broadcast sender(channel) for the blocks that became final,
+    /// sent once the final execution of their slot has updated the final state hash
+    pub final_block_sender: tokio::sync::broadcast::Sender<FinalBlock>,
+}
@@ -5,19 +5,23 @@
 use crate::types::ReadOnlyExecutionRequest;
 use crate::ExecutionError;
 use crate::{ExecutionAddressInfo, ReadOnlyExecutionOutput};
-use massa_models::address::Address;
+use massa_async_pool::{AsyncMessageFilter, AsyncMessageId, AsyncMessageInfo};
+use massa_ledger_exports::LedgerEntryProof;
+use massa_models::address::{Address, AddressStorageReport};
 use massa_models::amount::Amount;
-use massa_models::api::EventFilter;
+use massa_models::api::{AddressActivity, AddressActivityCursor, EventFilter};
 use massa_models::block::BlockId;
+use massa_models::execution::ExecutionTrace;
 use massa_models::operation::OperationId;
 use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::PreHashMap;
 use massa_models::prehash::PreHashSet;
+use massa_models::receipt::OperationReceipt;
 use massa_models::slot::Slot;
 use massa_models::stats::ExecutionStats;
 use massa_storage::Storage;
-use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 /// interface that communicates with the execution worker thread
 pub trait ExecutionController: Send + Sync {
@@ -61,6 +65,60 @@ pub trait ExecutionController: Send + Sync {
         input: Vec<(Address, Vec<u8>)>,
     ) -> Vec<(Option<Vec<u8>>, Option<Vec<u8>>)>;
 
+    /// Get the final balances of addresses at the output of a past final slot.
+    /// Only available on archival nodes.
+    fn get_final_balances_at(
+        &self,
+        addresses: &[Address],
+        slot: Slot,
+    ) -> Result<Vec<Option<Amount>>, ExecutionError>;
+
+    /// Get final datastore entries at the output of a past final slot.
+    /// Only available on archival nodes.
+    fn get_final_data_entries_at(
+        &self,
+        input: Vec<(Address, Vec<u8>)>,
+        slot: Slot,
+    ) -> Result<Vec<Option<Vec<u8>>>, ExecutionError>;
+
+    /// Get the final datastore keys of addresses at the output of a past final slot.
+    /// Only available on archival nodes.
+    ///
+    /// # Return value
+    /// The datastore keys of each address, `None` if its ledger entry did not exist at that slot
+    #[allow(clippy::type_complexity)]
+    fn get_final_datastore_keys_at(
+        &self,
+        addresses: &[Address],
+        slot: Slot,
+    ) -> Result<Vec<Option<BTreeSet<Vec<u8>>>>, ExecutionError>;
+
+    /// Get a page of the final and candidate datastore keys of an address
+    ///
+    /// # Arguments
+    /// * `prefix`: prefix that the keys must start with
+    /// * `start_after`: if set, only keys strictly greater than it are returned
+    /// * `limit`: maximum number of keys returned for each view
+    ///
+    /// # Return value
+    /// The final and candidate keys, in ascending order
+    fn get_final_and_candidate_datastore_keys_page(
+        &self,
+        addr: &Address,
+        prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: usize,
+    ) -> (BTreeSet<Vec<u8>>, BTreeSet<Vec<u8>>);
+
+    /// Get proofs of final ledger entries against the final state hash
+    ///
+    /// # Arguments
+    /// * `entries`: addresses and datastore keys of the entries, `None` designating the balance
+    fn get_final_ledger_entry_proofs(
+        &self,
+        entries: Vec<(Address, Option<Vec<u8>>)>,
+    ) -> Result<Vec<LedgerEntryProof>, ExecutionError>;
+
     /// Returns for a given cycle the stakers taken into account
     /// by the selector. That correspond to the `roll_counts` in `cycle - 3`.
     ///
@@ -87,9 +145,43 @@ pub trait ExecutionController: Send + Sync {
         thread: u8,
     ) -> PreHashSet<OperationId>;
 
+    /// Get the execution receipts of a list of operations, `None` for the ones that were not executed
+    fn get_operation_receipts(&self, ops: &[OperationId]) -> Vec<Option<OperationReceipt>>;
+
+    /// Get at most `limit` final operations involving an address, most recent first,
+    /// starting strictly before `cursor` if it is set.
+    /// Fails if the address index is disabled.
+    fn get_address_history(
+        &self,
+        address: &Address,
+        cursor: Option<AddressActivityCursor>,
+        limit: usize,
+    ) -> Result<Vec<AddressActivity>, ExecutionError>;
+
+    /// Get the call traces of a list of operations, `None` for the ones that were not traced
+    /// or whose traces are not kept anymore
+    fn get_operation_call_traces(&self, ops: &[OperationId]) -> Vec<Option<ExecutionTrace>>;
+
+    /// Get the call traces of the operations and messages executed at a slot,
+    /// `None` if the traces of that slot are not available
+    fn get_slot_call_traces(&self, slot: Slot) -> Option<Vec<ExecutionTrace>>;
+
+    /// List the asynchronous messages of the final pool matching a filter, by decreasing priority
+    fn get_async_messages(&self, filter: &AsyncMessageFilter) -> Vec<AsyncMessageInfo>;
+
+    /// Get an asynchronous message of the final pool,
+    /// or what happened to it if it was executed or removed from the pool in a recent final slot
+    fn get_async_message(&self, id: &AsyncMessageId) -> Option<AsyncMessageInfo>;
+
     /// Gets information about a batch of addresses
     fn get_addresses_infos(&self, addresses: &[Address]) -> Vec<ExecutionAddressInfo>;
 
+    /// Gets the final and candidate storage used by a batch of addresses
+    fn get_addresses_storage(
+        &self,
+        addresses: &[Address],
+    ) -> Vec<(AddressStorageReport, AddressStorageReport)>;
+
     /// Get execution statistics
     fn get_stats(&self) -> ExecutionStats;
 
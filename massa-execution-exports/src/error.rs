@@ -3,6 +3,7 @@
 //! this file defines all possible execution error categories
 
 use displaydoc::Display;
+use massa_models::receipt::OperationExecutionError;
 use thiserror::Error;
 
 /// Errors of the execution component.
@@ -30,6 +31,12 @@ pub enum ExecutionError {
     /// `Transaction` error: {0}
     TransactionError(String),
 
+    /// Call coins transfer error: {0}
+    CallCoinsError(String),
+
+    /// VM error: {0}
+    VMError(String),
+
     /// Block gas error: {0}
     BlockGasError(String),
 
@@ -44,4 +51,31 @@ pub enum ExecutionError {
 
     /// Include operation error: {0}
     IncludeOperationError(String),
+
+    /// Ledger archive error: {0}
+    ArchiveError(String),
+
+    /// Ledger proof error: {0}
+    LedgerProofError(String),
+
+    /// Replay error: {0}
+    ReplayError(String),
+
+    /// Address index error: {0}
+    AddressIndexError(String),
+}
+
+impl From<&ExecutionError> for OperationExecutionError {
+    fn from(err: &ExecutionError) -> Self {
+        match err {
+            ExecutionError::RollBuyError(msg) => OperationExecutionError::RollBuy(msg.clone()),
+            ExecutionError::RollSellError(msg) => OperationExecutionError::RollSell(msg.clone()),
+            ExecutionError::TransactionError(msg) => {
+                OperationExecutionError::Transaction(msg.clone())
+            }
+            ExecutionError::CallCoinsError(msg) => OperationExecutionError::CallCoins(msg.clone()),
+            ExecutionError::VMError(msg) => OperationExecutionError::Bytecode(msg.clone()),
+            err => OperationExecutionError::Internal(err.to_string()),
+        }
+    }
 }
@@ -3,12 +3,12 @@
 //! This module represents an event store allowing to store, search and retrieve
 //! a config-limited number of execution-generated events
 
-use massa_models::api::EventFilter;
+use massa_models::api::{EventCursor, EventFilter, EventOrder};
 use massa_models::output_event::SCOutputEvent;
 use std::collections::VecDeque;
 
 /// Store for events emitted by smart contracts
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Clone, PartialEq)]
 pub struct EventStore(pub VecDeque<SCOutputEvent>);
 
 impl EventStore {
@@ -53,52 +53,97 @@ impl EventStore {
     /// * original caller address
     /// * operation id
     /// * is final
+    ///
+    /// The events are returned in the order in which they were pushed,
+    /// the ordering and the limit of the filter are left to the caller.
     pub fn get_filtered_sc_output_events(&self, filter: &EventFilter) -> VecDeque<SCOutputEvent> {
         self.0
             .iter()
-            .filter(|x| {
-                if let Some(start) = filter.start {
-                    if x.context.slot < start {
-                        return false;
-                    }
-                }
-                if let Some(end) = filter.end {
-                    if x.context.slot >= end {
-                        return false;
-                    }
-                }
-                if let Some(is_final) = filter.is_final {
-                    if x.context.is_final != is_final {
-                        return false;
-                    }
-                }
-                if let Some(is_error) = filter.is_error {
-                    if x.context.is_error != is_error {
-                        return false;
-                    }
-                }
-                match (filter.emitter_address, x.context.call_stack.front()) {
-                    (Some(addr1), Some(addr2)) if addr1 != *addr2 => return false,
-                    (Some(_), None) => return false,
-                    _ => (),
-                }
-                match (filter.original_caller_address, x.context.call_stack.back()) {
-                    (Some(addr1), Some(addr2)) if addr1 != *addr2 => return false,
-                    (Some(_), None) => return false,
-                    _ => (),
-                }
-                match (filter.original_operation_id, x.context.origin_operation_id) {
-                    (Some(addr1), Some(addr2)) if addr1 != addr2 => return false,
-                    (Some(_), None) => return false,
-                    _ => (),
-                }
-                true
-            })
+            .filter(|x| event_matches_filter(x, filter))
             .cloned()
             .collect()
     }
 }
 
+/// Check whether an event matches the given filter on:
+/// * start slot
+/// * end slot
+/// * emitter address
+/// * original caller address
+/// * operation id
+/// * is final
+/// * is error
+/// * block id
+/// * data prefix and contents
+/// * position relative to the continuation cursor, in the requested order
+pub fn event_matches_filter(event: &SCOutputEvent, filter: &EventFilter) -> bool {
+    if let Some(start) = filter.start {
+        if event.context.slot < start {
+            return false;
+        }
+    }
+    if let Some(end) = filter.end {
+        if event.context.slot >= end {
+            return false;
+        }
+    }
+    if let Some(is_final) = filter.is_final {
+        if event.context.is_final != is_final {
+            return false;
+        }
+    }
+    if let Some(is_error) = filter.is_error {
+        if event.context.is_error != is_error {
+            return false;
+        }
+    }
+    match (filter.emitter_address, event.context.call_stack.front()) {
+        (Some(addr1), Some(addr2)) if addr1 != *addr2 => return false,
+        (Some(_), None) => return false,
+        _ => (),
+    }
+    match (
+        filter.original_caller_address,
+        event.context.call_stack.back(),
+    ) {
+        (Some(addr1), Some(addr2)) if addr1 != *addr2 => return false,
+        (Some(_), None) => return false,
+        _ => (),
+    }
+    match (
+        filter.original_operation_id,
+        event.context.origin_operation_id,
+    ) {
+        (Some(addr1), Some(addr2)) if addr1 != addr2 => return false,
+        (Some(_), None) => return false,
+        _ => (),
+    }
+    if let Some(block_id) = filter.block_id {
+        if event.context.block != Some(block_id) {
+            return false;
+        }
+    }
+    if let Some(data_prefix) = &filter.data_prefix {
+        if !event.data.starts_with(data_prefix.as_str()) {
+            return false;
+        }
+    }
+    if let Some(data_contains) = &filter.data_contains {
+        if !event.data.contains(data_contains.as_str()) {
+            return false;
+        }
+    }
+    if let Some(cursor) = filter.cursor {
+        let position = EventCursor::from(&event.context);
+        match filter.order {
+            EventOrder::Ascending if position <= cursor => return false,
+            EventOrder::Descending if position >= cursor => return false,
+            _ => (),
+        }
+    }
+    true
+}
+
 #[test]
 fn test_prune() {
     use massa_models::output_event::{EventExecutionContext, SCOutputEvent};
@@ -127,3 +172,77 @@ fn test_prune() {
     assert_eq!(store.0[1].data, "8");
     assert_eq!(store.0[0].data, "7");
 }
+
+#[test]
+fn test_event_filter() {
+    use massa_hash::Hash;
+    use massa_models::block::BlockId;
+    use massa_models::output_event::{EventExecutionContext, SCOutputEvent};
+    use massa_models::slot::Slot;
+
+    let block_a = BlockId(Hash::compute_from(b"block_a"));
+    let block_b = BlockId(Hash::compute_from(b"block_b"));
+    let mut store = EventStore(VecDeque::new());
+    for (period, block, data) in [
+        (1, block_a, "transfer:alice"),
+        (2, block_a, "mint:bob"),
+        (3, block_b, "transfer:bob"),
+        (4, block_b, "burn:alice"),
+    ] {
+        store.push(SCOutputEvent {
+            context: EventExecutionContext {
+                slot: Slot::new(period, 0),
+                block: Some(block),
+                read_only: false,
+                index_in_slot: 0,
+                call_stack: VecDeque::new(),
+                origin_operation_id: None,
+                is_final: false,
+                is_error: false,
+            },
+            data: data.to_string(),
+        });
+    }
+    let periods = |filter: EventFilter| -> Vec<u64> {
+        store
+            .get_filtered_sc_output_events(&filter)
+            .into_iter()
+            .map(|event| event.context.slot.period)
+            .collect()
+    };
+
+    // block and data filters
+    let filter = EventFilter {
+        block_id: Some(block_b),
+        ..Default::default()
+    };
+    assert_eq!(periods(filter), vec![3, 4]);
+    let filter = EventFilter {
+        data_prefix: Some("transfer:".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(periods(filter), vec![1, 3]);
+    let filter = EventFilter {
+        data_contains: Some("bob".to_string()),
+        block_id: Some(block_a),
+        ..Default::default()
+    };
+    assert_eq!(periods(filter), vec![2]);
+
+    // the cursor excludes its own position and the events before it in the requested order
+    let cursor = EventCursor {
+        slot: Slot::new(2, 0),
+        index_in_slot: 0,
+    };
+    let filter = EventFilter {
+        cursor: Some(cursor),
+        ..Default::default()
+    };
+    assert_eq!(periods(filter), vec![3, 4]);
+    let filter = EventFilter {
+        cursor: Some(cursor),
+        order: EventOrder::Descending,
+        ..Default::default()
+    };
+    assert_eq!(periods(filter), vec![1]);
+}
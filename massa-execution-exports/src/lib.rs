@@ -25,6 +25,9 @@
 //! ## `config.rs`
 //! Contains configuration parameters for the execution system.
 //!
+//! ## `channels.rs`
+//! Defines the broadcast channels through which the execution worker publishes its outputs.
+//!
 //! ## `controller_traits.rs`
 //! Defines the `ExecutionManager` and `ExecutionController` traits for interacting with the execution worker.
 //!
@@ -44,15 +47,17 @@
 
 #![warn(missing_docs)]
 #![warn(unused_crate_dependencies)]
+mod channels;
 mod controller_traits;
 mod error;
 mod event_store;
 mod settings;
 mod types;
 
+pub use channels::ExecutionChannels;
 pub use controller_traits::{ExecutionController, ExecutionManager};
 pub use error::ExecutionError;
-pub use event_store::EventStore;
+pub use event_store::{event_matches_filter, EventStore};
 pub use massa_sc_runtime::GasCosts;
 pub use settings::{ExecutionConfig, StorageCostsConstants};
 pub use types::{
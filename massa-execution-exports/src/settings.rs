@@ -6,6 +6,7 @@ use massa_models::amount::Amount;
 use massa_sc_runtime::GasCosts;
 use massa_time::MassaTime;
 use num::rational::Ratio;
+use std::path::PathBuf;
 
 /// Storage cost constants
 #[derive(Debug, Clone, Copy)]
@@ -23,10 +24,39 @@ pub struct StorageCostsConstants {
 pub struct ExecutionConfig {
     /// read-only execution request queue length
     pub readonly_queue_length: usize,
-    /// maximum number of SC output events kept in cache
-    pub max_final_events: usize,
+    /// path to the final SC output events db directory
+    pub event_store_path: PathBuf,
+    /// number of periods during which final SC output events are kept
+    pub event_retention_periods: u64,
+    /// path to the final operation receipts db directory
+    pub receipt_store_path: PathBuf,
+    /// number of periods during which final operation receipts are kept
+    pub receipt_retention_periods: u64,
+    /// whether to record the call tree of every executed operation, message and read-only request
+    pub call_tracing: bool,
+    /// number of final slots whose call traces are kept in memory
+    pub call_trace_history_length: usize,
+    /// number of final slots whose asynchronous message statuses are kept in memory
+    pub async_message_status_history_length: usize,
+    /// whether to record the blocks executed at every final slot and the resulting final state hash
+    pub final_slot_record: bool,
+    /// path to the final slot records db directory
+    pub final_slot_record_path: PathBuf,
+    /// number of periods during which final slot records are kept
+    pub final_slot_record_retention_periods: u64,
+    /// whether to index the final operations by involved address
+    pub address_index: bool,
+    /// path to the address index db directory
+    pub address_index_path: PathBuf,
+    /// whether to execute the independent operations of a block in parallel
+    pub parallel_execution: bool,
+    /// maximum number of operations executed in parallel
+    pub parallel_execution_threads: usize,
     /// maximum available gas for asynchronous messages execution
     pub max_async_gas: u64,
+    /// period from which asynchronous messages can be sent with triggers other than a ledger change,
+    /// `None` while this network upgrade is not scheduled
+    pub async_message_triggers_activation_period: Option<u64>,
     /// maximum gas per block
     pub max_gas_per_block: u64,
     /// number of threads
@@ -61,6 +91,14 @@ pub struct ExecutionConfig {
     pub storage_costs_constants: StorageCostsConstants,
     /// Max gas for read only executions
     pub max_read_only_gas: u64,
+    /// Max total size of the final bytecodes kept in the bytecode cache, in bytes
+    pub bytecode_cache_size: usize,
     /// Gas costs
     pub gas_costs: GasCosts,
+    /// whether broadcast is enabled
+    pub broadcast_enabled: bool,
+    /// SC output events broadcast channel capacity
+    pub broadcast_sc_events_capacity: usize,
+    /// final blocks broadcast channel capacity
+    pub broadcast_final_blocks_capacity: usize,
 }
@@ -6,6 +6,7 @@ use crate::{ExecutionConfig, StorageCostsConstants};
 use massa_models::config::*;
 use massa_sc_runtime::GasCosts;
 use massa_time::MassaTime;
+use tempfile::TempDir;
 
 impl Default for ExecutionConfig {
     /// default configuration used for testing
@@ -22,8 +23,24 @@ impl Default for ExecutionConfig {
 
         Self {
             readonly_queue_length: 100,
-            max_final_events: 1000,
+            // unused by default (you can use `ExecutionConfig::sample()` to get
+            // them in a TempDir)
+            event_store_path: "".into(),
+            event_retention_periods: 1000,
+            receipt_store_path: "".into(),
+            receipt_retention_periods: 1000,
+            call_tracing: false,
+            call_trace_history_length: 10,
+            async_message_status_history_length: 10,
+            final_slot_record: false,
+            final_slot_record_path: "".into(),
+            final_slot_record_retention_periods: 1000,
+            address_index: false,
+            address_index_path: "".into(),
+            parallel_execution: false,
+            parallel_execution_threads: 4,
             max_async_gas: MAX_ASYNC_GAS,
+            async_message_triggers_activation_period: ASYNC_MESSAGE_TRIGGERS_ACTIVATION_PERIOD,
             thread_count: THREAD_COUNT,
             roll_price: ROLL_PRICE,
             cursor_delay: MassaTime::from_millis(0),
@@ -42,6 +59,7 @@ impl Default for ExecutionConfig {
             max_datastore_value_size: MAX_DATASTORE_VALUE_LENGTH,
             storage_costs_constants,
             max_read_only_gas: 100_000_000,
+            bytecode_cache_size: 10_000_000,
             gas_costs: GasCosts::new(
                 concat!(
                     env!("CARGO_MANIFEST_DIR"),
@@ -55,6 +73,27 @@ impl Default for ExecutionConfig {
                 .into(),
             )
             .unwrap(),
+            broadcast_enabled: false,
+            broadcast_sc_events_capacity: 5000,
+            broadcast_final_blocks_capacity: 128,
         }
     }
 }
+
+impl ExecutionConfig {
+    /// get the default testing configuration with its disk stores in a temporary directory,
+    /// which is removed when the returned `TempDir` is dropped
+    pub fn sample() -> (Self, TempDir) {
+        let disk_stores = TempDir::new().expect("cannot create temp directory");
+        (
+            Self {
+                event_store_path: disk_stores.path().join("events"),
+                receipt_store_path: disk_stores.path().join("receipts"),
+                final_slot_record_path: disk_stores.path().join("slot_records"),
+                address_index_path: disk_stores.path().join("address_index"),
+                ..Default::default()
+            },
+            disk_stores,
+        )
+    }
+}
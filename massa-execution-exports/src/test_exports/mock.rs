@@ -6,15 +6,18 @@ use crate::{
     ExecutionAddressInfo, ExecutionController, ExecutionError, ReadOnlyExecutionOutput,
     ReadOnlyExecutionRequest,
 };
-use massa_ledger_exports::LedgerEntry;
+use massa_async_pool::{AsyncMessageFilter, AsyncMessageId, AsyncMessageInfo};
+use massa_ledger_exports::{LedgerEntry, LedgerEntryProof};
 use massa_models::{
-    address::Address,
+    address::{Address, AddressStorageReport},
     amount::Amount,
-    api::EventFilter,
+    api::{AddressActivity, AddressActivityCursor, EventFilter},
     block::BlockId,
+    execution::ExecutionTrace,
     operation::OperationId,
     output_event::SCOutputEvent,
     prehash::{PreHashMap, PreHashSet},
+    receipt::OperationReceipt,
     slot::Slot,
     stats::ExecutionStats,
 };
@@ -22,7 +25,7 @@ use massa_storage::Storage;
 use massa_time::MassaTime;
 use parking_lot::Mutex;
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap},
     sync::{
         mpsc::{self, Receiver},
         Arc,
@@ -121,6 +124,8 @@ impl ExecutionController for MockExecutionController {
             final_block_count: 0,
             final_executed_operations_count: 0,
             active_cursor: Slot::new(0, 0),
+            bytecode_cache_hits: 0,
+            bytecode_cache_misses: 0,
         }
     }
 
@@ -177,10 +182,58 @@ impl ExecutionController for MockExecutionController {
         Vec::default()
     }
 
+    fn get_final_balances_at(
+        &self,
+        _addresses: &[Address],
+        _slot: Slot,
+    ) -> Result<Vec<Option<Amount>>, ExecutionError> {
+        Ok(Vec::default())
+    }
+
+    fn get_final_data_entries_at(
+        &self,
+        _input: Vec<(Address, Vec<u8>)>,
+        _slot: Slot,
+    ) -> Result<Vec<Option<Vec<u8>>>, ExecutionError> {
+        Ok(Vec::default())
+    }
+
+    fn get_final_datastore_keys_at(
+        &self,
+        _addresses: &[Address],
+        _slot: Slot,
+    ) -> Result<Vec<Option<BTreeSet<Vec<u8>>>>, ExecutionError> {
+        Ok(Vec::default())
+    }
+
+    fn get_final_and_candidate_datastore_keys_page(
+        &self,
+        _addr: &Address,
+        _prefix: &[u8],
+        _start_after: Option<&[u8]>,
+        _limit: usize,
+    ) -> (BTreeSet<Vec<u8>>, BTreeSet<Vec<u8>>) {
+        Default::default()
+    }
+
+    fn get_final_ledger_entry_proofs(
+        &self,
+        _entries: Vec<(Address, Option<Vec<u8>>)>,
+    ) -> Result<Vec<LedgerEntryProof>, ExecutionError> {
+        Ok(Vec::default())
+    }
+
     fn get_addresses_infos(&self, _addresses: &[Address]) -> Vec<ExecutionAddressInfo> {
         Vec::default()
     }
 
+    fn get_addresses_storage(
+        &self,
+        _addresses: &[Address],
+    ) -> Vec<(AddressStorageReport, AddressStorageReport)> {
+        Vec::default()
+    }
+
     fn get_cycle_active_rolls(&self, _cycle: u64) -> BTreeMap<Address, u64> {
         BTreeMap::default()
     }
@@ -219,6 +272,35 @@ impl ExecutionController for MockExecutionController {
             .unwrap()
     }
 
+    fn get_operation_receipts(&self, ops: &[OperationId]) -> Vec<Option<OperationReceipt>> {
+        vec![None; ops.len()]
+    }
+
+    fn get_address_history(
+        &self,
+        _: &Address,
+        _: Option<AddressActivityCursor>,
+        _: usize,
+    ) -> Result<Vec<AddressActivity>, ExecutionError> {
+        Ok(Vec::new())
+    }
+
+    fn get_operation_call_traces(&self, ops: &[OperationId]) -> Vec<Option<ExecutionTrace>> {
+        vec![None; ops.len()]
+    }
+
+    fn get_slot_call_traces(&self, _: Slot) -> Option<Vec<ExecutionTrace>> {
+        None
+    }
+
+    fn get_async_messages(&self, _: &AsyncMessageFilter) -> Vec<AsyncMessageInfo> {
+        Vec::new()
+    }
+
+    fn get_async_message(&self, _: &AsyncMessageId) -> Option<AsyncMessageInfo> {
+        None
+    }
+
     fn clone_box(&self) -> Box<dyn ExecutionController> {
         Box::new(self.clone())
     }
@@ -3,10 +3,18 @@
 //! This file exports useful types used to interact with the execution worker
 
 use crate::event_store::EventStore;
+use massa_async_pool::{AsyncMessageId, AsyncMessageStatus};
 use massa_final_state::StateChanges;
 use massa_models::datastore::Datastore;
 use massa_models::{
-    address::Address, address::ExecutionAddressCycleInfo, amount::Amount, block::BlockId,
+    address::Address,
+    address::ExecutionAddressCycleInfo,
+    amount::Amount,
+    block::BlockId,
+    execution::{AddressStateOverride, CallTrace, ExecutionTrace},
+    operation::{OperationId, WrappedOperation},
+    prehash::PreHashMap,
+    receipt::OperationReceipt,
     slot::Slot,
 };
 use std::collections::{BTreeMap, BTreeSet};
@@ -37,7 +45,7 @@ pub struct ExecutionAddressInfo {
 }
 
 /// structure describing the output of a single execution
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ExecutionOutput {
     /// slot
     pub slot: Slot,
@@ -47,6 +55,13 @@ pub struct ExecutionOutput {
     pub state_changes: StateChanges,
     /// events emitted by the execution step
     pub events: EventStore,
+    /// receipts of the operations executed during the execution step
+    pub receipts: PreHashMap<OperationId, OperationReceipt>,
+    /// call traces of the operations and messages executed during the execution step,
+    /// empty if call tracing is disabled
+    pub traces: Vec<ExecutionTrace>,
+    /// asynchronous messages executed or removed from the pool during the execution step
+    pub async_message_statuses: Vec<(AsyncMessageId, AsyncMessageStatus)>,
 }
 
 /// structure describing the output of a read only execution
@@ -58,6 +73,8 @@ pub struct ReadOnlyExecutionOutput {
     pub gas_cost: u64,
     /// Returned value from the module call
     pub call_result: Vec<u8>,
+    /// Call tree of the execution, if call tracing is enabled
+    pub trace: Option<CallTrace>,
 }
 
 /// structure describing different types of read-only execution request
@@ -69,6 +86,8 @@ pub struct ReadOnlyExecutionRequest {
     pub call_stack: Vec<ExecutionStackElement>,
     /// Target of the request
     pub target: ReadOnlyExecutionTarget,
+    /// Ledger state to assume for some addresses, applied before the execution
+    pub state_overrides: Vec<AddressStateOverride>,
 }
 
 /// structure describing different possible targets of a read-only execution request
@@ -86,6 +105,9 @@ pub enum ReadOnlyExecutionTarget {
         /// Parameter to pass to the target function
         parameter: Vec<u8>,
     },
+
+    /// Execute an operation as if it was included in a block of the next slot of its thread
+    Operation(WrappedOperation),
 }
 
 /// structure describing a read-only call
@@ -5,7 +5,7 @@ use massa_ledger_exports::{
 use massa_models::{
     address::Address, amount::Amount, operation::OperationId, prehash::PreHashMap, slot::Slot,
 };
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, VecDeque};
 
 #[derive(Default)]
 /// History of the outputs of recently executed slots.
@@ -128,6 +128,44 @@ impl ActiveHistory {
         HistorySearchResult::NoInfo
     }
 
+    /// Lazily query (from end to beginning) the active changes to the datastore keys of an address
+    /// that start with `prefix` and are strictly greater than `start_after` if set.
+    ///
+    /// Returns whether the ledger entry is reset (set or deleted) in the history,
+    /// in which case the final datastore keys must be ignored,
+    /// along with the newest presence status of every changed key.
+    pub fn fetch_datastore_key_changes(
+        &self,
+        addr: &Address,
+        prefix: &[u8],
+        start_after: Option<&[u8]>,
+    ) -> (bool, BTreeMap<Vec<u8>, bool>) {
+        let in_range = |key: &Vec<u8>| {
+            key.starts_with(prefix) && start_after.map_or(true, |after| key.as_slice() > after)
+        };
+        let mut changes = BTreeMap::new();
+        for output in self.0.iter().rev() {
+            match output.state_changes.ledger_changes.0.get(addr) {
+                Some(SetUpdateOrDelete::Set(LedgerEntry { datastore, .. })) => {
+                    for key in datastore.keys().filter(|key| in_range(key)) {
+                        changes.entry(key.clone()).or_insert(true);
+                    }
+                    return (true, changes);
+                }
+                Some(SetUpdateOrDelete::Update(LedgerEntryUpdate { datastore, .. })) => {
+                    for (key, update) in datastore.iter().filter(|(key, _)| in_range(key)) {
+                        changes
+                            .entry(key.clone())
+                            .or_insert(matches!(update, SetOrDelete::Set(_)));
+                    }
+                }
+                Some(SetUpdateOrDelete::Delete) => return (true, changes),
+                None => (),
+            }
+        }
+        (false, changes)
+    }
+
     /// Starting from the newest element in history, return the first existing roll change of `addr`.
     ///
     /// # Arguments
@@ -0,0 +1,164 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Module to index on disk the operations executed in final slots by involved address.
+//!
+//! Every final operation is recorded under each address it involves
+//! (creator, transaction recipient, called smart contract, roll buyer or seller),
+//! keyed by address, slot and index in the block so that the history of an address
+//! can be listed most recent first and paginated. Entries are never pruned.
+
+use crate::disk_store::{open_disk_store, CF_ERROR, CRUD_ERROR};
+use massa_models::{
+    address::Address,
+    api::{AddressActivity, AddressActivityCursor},
+    slot::Slot,
+};
+use rocksdb::{Direction, IteratorMode, WriteBatch, DB};
+use std::path::PathBuf;
+
+const ACTIVITY_CF: &str = "activity";
+const SER_ERROR: &str = "critical: address activity serialization failed";
+const DESER_ERROR: &str = "critical: address activity deserialization failed";
+
+/// Key of an operation in the activity column family:
+/// the address followed by the slot and the index of the operation in its block
+fn activity_key(address: &Address, slot: &Slot, index_in_block: u64) -> Vec<u8> {
+    [
+        &address.to_bytes()[..],
+        &slot.to_bytes_key()[..],
+        &index_in_block.to_be_bytes(),
+    ]
+    .concat()
+}
+
+/// Disk index of the final operations involving each address
+///
+/// Contains a `RocksDB` DB instance
+pub(crate) struct FinalAddressIndexDB {
+    db: DB,
+}
+
+impl FinalAddressIndexDB {
+    /// Create and initialize a new `FinalAddressIndexDB`.
+    ///
+    /// # Arguments
+    /// * path: path to the desired disk address index db directory
+    pub fn new(path: PathBuf) -> Self {
+        FinalAddressIndexDB {
+            db: open_disk_store(path, &[ACTIVITY_CF]),
+        }
+    }
+
+    /// Write the final operations of a slot under each address they involve.
+    ///
+    /// Operations are keyed by their position in the block, so a slot executed again
+    /// after a restart overwrites its entries instead of duplicating them.
+    ///
+    /// # Arguments
+    /// * activities: involved addresses along with the operations involving them
+    pub fn write_activities(&self, activities: &[(Address, AddressActivity)]) {
+        let handle = self.db.cf_handle(ACTIVITY_CF).expect(CF_ERROR);
+        let mut batch = WriteBatch::default();
+        for (address, activity) in activities {
+            batch.put_cf(
+                handle,
+                activity_key(address, &activity.slot, activity.index_in_block),
+                serde_json::to_vec(activity).expect(SER_ERROR),
+            );
+        }
+        self.db.write(batch).expect(CRUD_ERROR);
+    }
+
+    /// Get at most `limit` final operations involving an address, most recent first,
+    /// starting strictly before `cursor` if it is set
+    pub fn get_address_history(
+        &self,
+        address: &Address,
+        cursor: Option<AddressActivityCursor>,
+        limit: usize,
+    ) -> Vec<AddressActivity> {
+        let handle = self.db.cf_handle(ACTIVITY_CF).expect(CF_ERROR);
+        let start_key = match cursor {
+            Some(cursor) => activity_key(address, &cursor.slot, cursor.index_in_block),
+            None => activity_key(address, &Slot::new(u64::MAX, u8::MAX), u64::MAX),
+        };
+        self.db
+            .iterator_cf(handle, IteratorMode::From(&start_key, Direction::Reverse))
+            .map(|item| item.expect(CRUD_ERROR))
+            .take_while(|(key, _)| key.starts_with(address.to_bytes()))
+            // the cursor itself was already returned in the previous page
+            .filter(|(key, _)| cursor.is_none() || key[..] != start_key[..])
+            .take(limit)
+            .map(|(_, value)| serde_json::from_slice(&value).expect(DESER_ERROR))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FinalAddressIndexDB;
+    use massa_hash::Hash;
+    use massa_models::{
+        address::Address,
+        api::{AddressActivity, AddressActivityCursor},
+        operation::{AddressRole, OperationId},
+        slot::Slot,
+        wrapped::Id,
+    };
+    use tempfile::TempDir;
+
+    fn activity(period: u64, index_in_block: u64, role: AddressRole) -> AddressActivity {
+        AddressActivity {
+            operation_id: OperationId::new(Hash::compute_from(
+                &[period.to_be_bytes(), index_in_block.to_be_bytes()].concat(),
+            )),
+            slot: Slot::new(period, 0),
+            index_in_block,
+            roles: vec![role],
+            success: true,
+        }
+    }
+
+    #[test]
+    fn test_final_address_index_db() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = FinalAddressIndexDB::new(temp_dir.path().to_path_buf());
+        let address = Address(Hash::compute_from(b"address"));
+        let other_address = Address(Hash::compute_from(b"other_address"));
+
+        let mut expected = Vec::new();
+        for period in 1..=3u64 {
+            let mut activities = Vec::new();
+            for index_in_block in 0..2 {
+                let item = activity(period, index_in_block, AddressRole::Creator);
+                activities.push((address, item.clone()));
+                activities.push((
+                    other_address,
+                    activity(period, index_in_block, AddressRole::TransactionRecipient),
+                ));
+                expected.push(item);
+            }
+            db.write_activities(&activities);
+        }
+        expected.reverse();
+
+        // the history of an address is listed most recent first
+        assert_eq!(db.get_address_history(&address, None, usize::MAX), expected);
+
+        // pages continue strictly before the cursor
+        let page = db.get_address_history(&address, None, 4);
+        assert_eq!(page, expected[..4]);
+        let cursor = AddressActivityCursor::from(page.last().unwrap());
+        assert_eq!(
+            db.get_address_history(&address, Some(cursor), 4),
+            expected[4..]
+        );
+
+        // a slot written again does not duplicate its entries
+        db.write_activities(&[(address, activity(3, 1, AddressRole::Creator))]);
+        assert_eq!(
+            db.get_address_history(&address, None, usize::MAX).len(),
+            expected.len()
+        );
+    }
+}
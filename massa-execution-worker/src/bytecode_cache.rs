@@ -0,0 +1,213 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Least recently used cache of the final bytecodes read by the executions
+//! (see `SpeculativeLedger::get_bytecode`), keyed by bytecode hash so that
+//! the contracts deployed from the same bytecode share a single entry.
+//!
+//! An address is forgotten as soon as its bytecode is set by an execution (see `ExecutionContext::set_bytecode`)
+//! or changed in the final ledger (see `ExecutionState::apply_final_execution_output`).
+//! The final bytecodes are read and cached with the final state locked for reading,
+//! and forgotten with the final state locked for writing, so that a bytecode replaced in the final ledger is never cached.
+
+use massa_hash::Hash;
+use massa_models::{
+    address::Address,
+    prehash::{PreHashMap, PreHashSet},
+};
+use std::collections::BTreeMap;
+
+/// Cached bytecode
+#[derive(Debug)]
+struct CachedBytecode {
+    /// the bytecode
+    bytecode: Vec<u8>,
+    /// addresses whose final bytecode it is
+    addresses: PreHashSet<Address>,
+    /// last use of the bytecode, key in `BytecodeCache::last_uses`
+    last_use: u64,
+}
+
+/// Least recently used cache of final bytecodes.
+/// The default cache has a zero size and keeps nothing.
+#[derive(Debug, Default)]
+pub(crate) struct BytecodeCache {
+    /// maximum total size of the cached bytecodes, in bytes
+    max_size: usize,
+    /// total size of the cached bytecodes, in bytes
+    size: usize,
+    /// hash of the cached final bytecode of each address
+    address_hashes: PreHashMap<Address, Hash>,
+    /// cached bytecodes by hash
+    bytecodes: PreHashMap<Hash, CachedBytecode>,
+    /// hashes of the cached bytecodes by last use, least recently used first
+    last_uses: BTreeMap<u64, Hash>,
+    /// counter of the uses, giving the next last use
+    use_counter: u64,
+    /// number of bytecodes found in the cache
+    hits: u64,
+    /// number of bytecodes not found in the cache
+    misses: u64,
+}
+
+impl BytecodeCache {
+    /// Creates an empty cache
+    ///
+    /// # Arguments
+    /// * `max_size`: maximum total size of the cached bytecodes, in bytes
+    pub fn new(max_size: usize) -> Self {
+        BytecodeCache {
+            max_size,
+            ..Default::default()
+        }
+    }
+
+    /// Gets the cached final bytecode of an address, counting a hit or a miss
+    pub fn get(&mut self, address: &Address) -> Option<Vec<u8>> {
+        let cached = self
+            .address_hashes
+            .get(address)
+            .and_then(|hash| self.bytecodes.get_mut(hash).map(|cached| (*hash, cached)));
+        let Some((hash, cached)) = cached else {
+            self.misses += 1;
+            return None;
+        };
+        self.hits += 1;
+        self.last_uses.remove(&cached.last_use);
+        cached.last_use = self.use_counter;
+        self.use_counter += 1;
+        self.last_uses.insert(cached.last_use, hash);
+        Some(cached.bytecode.clone())
+    }
+
+    /// Caches the final bytecode of an address,
+    /// evicting the least recently used bytecodes if the cache gets too large
+    pub fn insert(&mut self, address: Address, bytecode: Vec<u8>) {
+        self.invalidate(&address);
+        if bytecode.len() > self.max_size {
+            return;
+        }
+        let hash = Hash::compute_from(&bytecode);
+        let last_use = self.use_counter;
+        self.use_counter += 1;
+        match self.bytecodes.get_mut(&hash) {
+            Some(cached) => {
+                self.last_uses.remove(&cached.last_use);
+                cached.last_use = last_use;
+                cached.addresses.insert(address);
+            }
+            None => {
+                self.size += bytecode.len();
+                self.bytecodes.insert(
+                    hash,
+                    CachedBytecode {
+                        bytecode,
+                        addresses: PreHashSet::from_iter([address]),
+                        last_use,
+                    },
+                );
+            }
+        }
+        self.last_uses.insert(last_use, hash);
+        self.address_hashes.insert(address, hash);
+        while self.size > self.max_size {
+            let Some(lru) = self.last_uses.keys().next().copied() else {
+                break;
+            };
+            if let Some(lru_hash) = self.last_uses.remove(&lru) {
+                self.remove_bytecode(&lru_hash);
+            }
+        }
+    }
+
+    /// Forgets the bytecode of an address, when it changes
+    pub fn invalidate(&mut self, address: &Address) {
+        let Some(hash) = self.address_hashes.remove(address) else {
+            return;
+        };
+        let unused = match self.bytecodes.get_mut(&hash) {
+            Some(cached) => {
+                cached.addresses.remove(address);
+                cached.addresses.is_empty()
+            }
+            None => false,
+        };
+        if unused {
+            if let Some(cached) = self.bytecodes.get(&hash) {
+                self.last_uses.remove(&cached.last_use);
+            }
+            self.remove_bytecode(&hash);
+        }
+    }
+
+    /// Returns the number of bytecodes found and not found in the cache so far
+    pub fn hits_and_misses(&self) -> (u64, u64) {
+        (self.hits, self.misses)
+    }
+
+    /// Removes a bytecode and its addresses, its last use being already removed
+    fn remove_bytecode(&mut self, hash: &Hash) {
+        if let Some(cached) = self.bytecodes.remove(hash) {
+            self.size -= cached.bytecode.len();
+            for address in cached.addresses.iter() {
+                self.address_hashes.remove(address);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BytecodeCache;
+    use massa_models::address::Address;
+    use massa_signature::KeyPair;
+
+    fn address() -> Address {
+        Address::from_public_key(&KeyPair::generate().get_public_key())
+    }
+
+    #[test]
+    fn test_bytecode_cache() {
+        let mut cache = BytecodeCache::new(10);
+        let (addr_a, addr_b, addr_c) = (address(), address(), address());
+
+        // a miss, then a hit once cached
+        assert_eq!(cache.get(&addr_a), None);
+        cache.insert(addr_a, vec![1; 4]);
+        assert_eq!(cache.get(&addr_a), Some(vec![1; 4]));
+        assert_eq!(cache.hits_and_misses(), (1, 1));
+
+        // addresses with the same bytecode share its entry
+        cache.insert(addr_b, vec![1; 4]);
+        assert_eq!(cache.size, 4);
+        assert_eq!(cache.get(&addr_b), Some(vec![1; 4]));
+
+        // invalidating an address keeps the bytecode for the others
+        cache.invalidate(&addr_a);
+        assert_eq!(cache.get(&addr_a), None);
+        assert_eq!(cache.get(&addr_b), Some(vec![1; 4]));
+        cache.invalidate(&addr_b);
+        assert_eq!(cache.size, 0);
+        assert!(cache.bytecodes.is_empty());
+        assert!(cache.last_uses.is_empty());
+
+        // a new bytecode replaces the previous one of an address
+        cache.insert(addr_a, vec![1; 4]);
+        cache.insert(addr_a, vec![2; 4]);
+        assert_eq!(cache.get(&addr_a), Some(vec![2; 4]));
+        assert_eq!(cache.size, 4);
+
+        // the least recently used bytecodes are evicted when the cache gets too large
+        cache.insert(addr_b, vec![3; 4]);
+        assert_eq!(cache.get(&addr_a), Some(vec![2; 4]));
+        cache.insert(addr_c, vec![4; 4]);
+        assert_eq!(cache.get(&addr_b), None);
+        assert_eq!(cache.get(&addr_a), Some(vec![2; 4]));
+        assert_eq!(cache.get(&addr_c), Some(vec![4; 4]));
+        assert_eq!(cache.size, 8);
+
+        // a bytecode larger than the cache is not cached
+        cache.insert(addr_b, vec![5; 11]);
+        assert_eq!(cache.get(&addr_b), None);
+        assert_eq!(cache.size, 8);
+    }
+}
@@ -7,11 +7,12 @@
 //! More generally, the context acts only on its own state
 //! and does not write anything persistent to the consensus state.
 
+use crate::bytecode_cache::BytecodeCache;
 use crate::speculative_async_pool::SpeculativeAsyncPool;
 use crate::speculative_executed_ops::SpeculativeExecutedOps;
-use crate::speculative_ledger::SpeculativeLedger;
+use crate::speculative_ledger::{LedgerAccess, SpeculativeLedger};
 use crate::{active_history::ActiveHistory, speculative_roll_state::SpeculativeRollState};
-use massa_async_pool::{AsyncMessage, AsyncMessageId};
+use massa_async_pool::{AsyncMessage, AsyncMessageId, AsyncMessageStatus};
 use massa_executed_ops::ExecutedOpsChanges;
 use massa_execution_exports::{
     EventStore, ExecutionConfig, ExecutionError, ExecutionOutput, ExecutionStackElement,
@@ -23,15 +24,18 @@ use massa_models::{
     address::Address,
     amount::Amount,
     block::BlockId,
+    execution::{AddressStateOverride, CallTrace, CoinTransfer, ExecutionTrace, TraceOrigin},
     operation::OperationId,
     output_event::{EventExecutionContext, SCOutputEvent},
+    prehash::PreHashMap,
+    receipt::OperationReceipt,
     slot::Slot,
 };
 use massa_pos_exports::PoSChanges;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use rand::SeedableRng;
 use rand_xoshiro::Xoshiro256PlusPlus;
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::sync::Arc;
 use tracing::debug;
 
@@ -66,6 +70,37 @@ pub struct ExecutionContextSnapshot {
     pub unsafe_rng: Xoshiro256PlusPlus,
 }
 
+/// The output of an execution on a forked `ExecutionContext` (see `ExecutionContext::fork`),
+/// that can be merged back into the context it was forked from.
+pub struct ExecutionForkOutput {
+    /// ledger changes caused by the execution on top of the ones of the original context
+    pub ledger_changes: LedgerChanges,
+
+    /// parts of the ledger read by the execution
+    pub ledger_reads: HashSet<LedgerAccess>,
+
+    /// operations executed
+    pub executed_ops: ExecutedOpsChanges,
+
+    /// counter of newly created addresses at the end of the execution
+    pub created_addr_index: u64,
+
+    /// counter of newly created messages at the end of the execution
+    pub created_message_index: u64,
+
+    /// unsafe random state at the end of the execution
+    pub unsafe_rng: Xoshiro256PlusPlus,
+
+    /// generated events
+    pub events: EventStore,
+
+    /// receipts of the executed operations
+    pub receipts: PreHashMap<OperationId, OperationReceipt>,
+
+    /// call traces of the executed operations
+    pub traces: Vec<ExecutionTrace>,
+}
+
 /// An execution context that needs to be initialized before executing bytecode,
 /// passed to the VM to interact with during bytecode execution (through ABIs),
 /// and read after execution to gather results.
@@ -118,6 +153,19 @@ pub struct ExecutionContext {
     /// generated events during this execution, with multiple indexes
     pub events: EventStore,
 
+    /// receipts of the operations executed so far during this execution
+    pub receipts: PreHashMap<OperationId, OperationReceipt>,
+
+    /// call tree being built for the current operation or message if call tracing is enabled,
+    /// the root call first and the current call at the back
+    pub call_trace_stack: Vec<CallTrace>,
+
+    /// call traces of the operations and messages executed so far during this execution
+    pub traces: Vec<ExecutionTrace>,
+
+    /// statuses of the asynchronous messages executed or removed from the pool so far during this execution
+    pub async_message_statuses: Vec<(AsyncMessageId, AsyncMessageStatus)>,
+
     /// Unsafe random state (can be predicted and manipulated)
     pub unsafe_rng: Xoshiro256PlusPlus,
 
@@ -136,6 +184,7 @@ impl ExecutionContext {
     ///
     /// # arguments
     /// * `final_state`: thread-safe access to the final state. Note that this will be used only for reading, never for writing
+    /// * `bytecode_cache`: thread-safe access to the cache of the final bytecodes
     ///
     /// # returns
     /// A new (empty) `ExecutionContext` instance
@@ -143,11 +192,13 @@ impl ExecutionContext {
         config: ExecutionConfig,
         final_state: Arc<RwLock<FinalState>>,
         active_history: Arc<RwLock<ActiveHistory>>,
+        bytecode_cache: Arc<Mutex<BytecodeCache>>,
     ) -> Self {
         ExecutionContext {
             speculative_ledger: SpeculativeLedger::new(
                 final_state.clone(),
                 active_history.clone(),
+                bytecode_cache,
                 config.max_datastore_key_length,
                 config.max_bytecode_size,
                 config.max_datastore_value_size,
@@ -171,6 +222,10 @@ impl ExecutionContext {
             stack: Default::default(),
             read_only: Default::default(),
             events: Default::default(),
+            receipts: Default::default(),
+            call_trace_stack: Default::default(),
+            traces: Default::default(),
+            async_message_statuses: Default::default(),
             unsafe_rng: Xoshiro256PlusPlus::from_seed([0u8; 32]),
             creator_address: Default::default(),
             origin_operation_id: Default::default(),
@@ -230,6 +285,84 @@ impl ExecutionContext {
         ));
     }
 
+    /// Creates a context executing on top of the current state of this one without modifying it.
+    /// Its output can then be merged back into this context (see `take_fork_output` and `merge_fork`).
+    ///
+    /// Only the speculative ledger of the fork sees the changes of this context:
+    /// its executed operations, asynchronous pool and roll state start from the active history.
+    ///
+    /// # Arguments
+    /// * `base_ledger_changes`: snapshot of the ledger changes of this context, shared between its forks
+    /// * `final_state`: thread-safe access to the final state. Note that this will be used only for reading, never for writing
+    /// * `active_history`: thread-safe access to the active execution history
+    /// * `bytecode_cache`: thread-safe access to the cache of the final bytecodes
+    pub(crate) fn fork(
+        &self,
+        base_ledger_changes: Arc<LedgerChanges>,
+        final_state: Arc<RwLock<FinalState>>,
+        active_history: Arc<RwLock<ActiveHistory>>,
+        bytecode_cache: Arc<Mutex<BytecodeCache>>,
+    ) -> Self {
+        ExecutionContext {
+            speculative_ledger: self.speculative_ledger.fork(base_ledger_changes),
+            slot: self.slot,
+            opt_block_id: self.opt_block_id,
+            created_addr_index: self.created_addr_index,
+            created_message_index: self.created_message_index,
+            unsafe_rng: self.unsafe_rng.clone(),
+            ..ExecutionContext::new(
+                self.config.clone(),
+                final_state,
+                active_history,
+                bytecode_cache,
+            )
+        }
+    }
+
+    /// Takes a snapshot (clone) of the ledger changes caused so far in the context
+    pub(crate) fn get_ledger_snapshot(&self) -> LedgerChanges {
+        self.speculative_ledger.get_snapshot()
+    }
+
+    /// Moves the output of the executions out of a forked context (see `fork`)
+    pub(crate) fn take_fork_output(&mut self) -> ExecutionForkOutput {
+        ExecutionForkOutput {
+            ledger_changes: self.speculative_ledger.take(),
+            ledger_reads: self.speculative_ledger.take_reads(),
+            executed_ops: self.speculative_executed_ops.take(),
+            created_addr_index: self.created_addr_index,
+            created_message_index: self.created_message_index,
+            unsafe_rng: self.unsafe_rng.clone(),
+            events: std::mem::take(&mut self.events),
+            receipts: std::mem::take(&mut self.receipts),
+            traces: std::mem::take(&mut self.traces),
+        }
+    }
+
+    /// Checks whether the executions on a fork of this context depend on their order within the slot,
+    /// which is the case if they created addresses, emitted asynchronous messages or drew random numbers.
+    /// Such an output can't be merged: the executions have to be done again on this context.
+    pub(crate) fn fork_is_order_dependent(&self, fork: &ExecutionForkOutput) -> bool {
+        fork.created_addr_index != self.created_addr_index
+            || fork.created_message_index != self.created_message_index
+            || fork.unsafe_rng != self.unsafe_rng
+    }
+
+    /// Merges the output of the executions on a fork of this context into it, as if they happened on it.
+    /// The parts of the ledger read by the fork must not have been written since it was forked.
+    pub(crate) fn merge_fork(&mut self, fork: ExecutionForkOutput) {
+        self.speculative_ledger.apply_changes(fork.ledger_changes);
+        for (op_id, op_valid_until_slot) in fork.executed_ops {
+            self.insert_executed_op(op_id, op_valid_until_slot);
+        }
+        // events are numbered again in the order of the context
+        for event in fork.events.0 {
+            self.event_emit(event);
+        }
+        self.receipts.extend(fork.receipts);
+        self.traces.extend(fork.traces);
+    }
+
     /// Create a new `ExecutionContext` for read-only execution
     /// This should be used before performing a read-only execution.
     ///
@@ -237,6 +370,7 @@ impl ExecutionContext {
     /// * `slot`: slot at which the execution will happen
     /// * `req`: parameters of the read only execution
     /// * `final_state`: thread-safe access to the final state. Note that this will be used only for reading, never for writing
+    /// * `bytecode_cache`: thread-safe access to the cache of the final bytecodes
     ///
     /// # returns
     /// A `ExecutionContext` instance ready for a read-only execution
@@ -247,6 +381,7 @@ impl ExecutionContext {
         call_stack: Vec<ExecutionStackElement>,
         final_state: Arc<RwLock<FinalState>>,
         active_history: Arc<RwLock<ActiveHistory>>,
+        bytecode_cache: Arc<Mutex<BytecodeCache>>,
     ) -> Self {
         // Deterministically seed the unsafe RNG to allow the bytecode to use it.
         // Note that consecutive read-only calls for the same slot will get the same random seed.
@@ -270,7 +405,7 @@ impl ExecutionContext {
             stack: call_stack,
             read_only: true,
             unsafe_rng,
-            ..ExecutionContext::new(config, final_state, active_history)
+            ..ExecutionContext::new(config, final_state, active_history, bytecode_cache)
         }
     }
 
@@ -301,6 +436,7 @@ impl ExecutionContext {
     /// * `slot`: slot at which the execution will happen
     /// * `opt_block_id`: optional ID of the block at that slot
     /// * `final_state`: thread-safe access to the final state. Note that this will be used only for reading, never for writing
+    /// * `bytecode_cache`: thread-safe access to the cache of the final bytecodes
     ///
     /// # returns
     /// A `ExecutionContext` instance
@@ -310,6 +446,7 @@ impl ExecutionContext {
         opt_block_id: Option<BlockId>,
         final_state: Arc<RwLock<FinalState>>,
         active_history: Arc<RwLock<ActiveHistory>>,
+        bytecode_cache: Arc<Mutex<BytecodeCache>>,
     ) -> Self {
         // Deterministically seed the unsafe RNG to allow the bytecode to use it.
 
@@ -331,7 +468,7 @@ impl ExecutionContext {
             slot,
             opt_block_id,
             unsafe_rng,
-            ..ExecutionContext::new(config, final_state, active_history)
+            ..ExecutionContext::new(config, final_state, active_history, bytecode_cache)
         }
     }
 
@@ -432,6 +569,14 @@ impl ExecutionContext {
         Ok(address)
     }
 
+    /// forces the ledger state of some addresses, used to simulate read-only executions
+    /// against a ledger that differs from the real one
+    pub fn apply_state_overrides(&mut self, state_overrides: &[AddressStateOverride]) {
+        for state_override in state_overrides {
+            self.speculative_ledger.apply_state_override(state_override);
+        }
+    }
+
     /// gets the bytecode of an address if it exists in the speculative ledger, or returns None
     pub fn get_bytecode(&self, address: &Address) -> Option<Vec<u8>> {
         self.speculative_ledger.get_bytecode(address)
@@ -578,7 +723,99 @@ impl ExecutionContext {
         }
         // do the transfer
         self.speculative_ledger
-            .transfer_coins(from_addr, to_addr, amount)
+            .transfer_coins(from_addr, to_addr, amount)?;
+        self.record_call_trace(|call| {
+            call.transfers.push(CoinTransfer {
+                from: from_addr,
+                to: to_addr,
+                amount,
+            })
+        });
+        Ok(())
+    }
+
+    /// Starts tracing the call tree of an operation or message execution if call tracing is enabled.
+    /// Any call tree left unfinished by a previous execution is discarded.
+    ///
+    /// # Arguments
+    /// * `target`: address the execution happens on
+    /// * `function`: executed function, if any
+    /// * `coins`: coins sent along with the execution
+    pub fn start_call_trace(&mut self, target: Address, function: Option<String>, coins: Amount) {
+        self.call_trace_stack.clear();
+        if self.config.call_tracing {
+            self.call_trace_stack
+                .push(CallTrace::new(target, function, coins));
+        }
+    }
+
+    /// Adds a nested call to the traced call tree, if an execution is being traced
+    pub fn push_call_trace(&mut self, target: Address, function: Option<String>, coins: Amount) {
+        if !self.call_trace_stack.is_empty() {
+            self.call_trace_stack
+                .push(CallTrace::new(target, function, coins));
+        }
+    }
+
+    /// Ends the current nested call of the traced call tree, attaching it to its caller
+    pub fn pop_call_trace(&mut self) {
+        if self.call_trace_stack.len() > 1 {
+            if let Some(call) = self.call_trace_stack.pop() {
+                self.record_call_trace(|caller| caller.calls.push(call));
+            }
+        }
+    }
+
+    /// Records something that happened during the current call of the traced call tree, if any
+    pub fn record_call_trace(&mut self, record: impl FnOnce(&mut CallTrace)) {
+        if let Some(call) = self.call_trace_stack.last_mut() {
+            record(call);
+        }
+    }
+
+    /// Finishes tracing the call tree of an execution.
+    /// Calls interrupted by an error are attached to their callers.
+    ///
+    /// # Arguments
+    /// * `gas_used`: gas consumed by the whole execution, if reported by the VM
+    /// * `error`: error that made the execution fail, if any
+    ///
+    /// # Returns
+    /// The root of the call tree, or `None` if the execution was not traced
+    pub fn finish_call_trace(
+        &mut self,
+        gas_used: Option<u64>,
+        error: Option<String>,
+    ) -> Option<CallTrace> {
+        while self.call_trace_stack.len() > 1 {
+            self.pop_call_trace();
+        }
+        let mut root = self.call_trace_stack.pop()?;
+        root.gas_used = gas_used;
+        root.error = error;
+        Some(root)
+    }
+
+    /// Finishes tracing the call tree of an operation or message execution
+    /// and keeps it in the traces of the slot, if the execution was traced
+    ///
+    /// # Arguments
+    /// * `origin`: executed operation or message
+    /// * `gas_used`: gas consumed by the whole execution, if reported by the VM
+    /// * `error`: error that made the execution fail, if any
+    pub fn store_call_trace(
+        &mut self,
+        origin: TraceOrigin,
+        gas_used: Option<u64>,
+        error: Option<String>,
+    ) {
+        if let Some(root) = self.finish_call_trace(gas_used, error) {
+            self.traces.push(ExecutionTrace {
+                slot: self.slot,
+                origin,
+                root,
+            });
+        }
     }
 
     /// Add a new asynchronous message to speculative pool
@@ -691,7 +928,13 @@ impl ExecutionContext {
         let deleted_messages = self
             .speculative_async_pool
             .settle_slot(&slot, &ledger_changes);
-        for (_msg_id, msg) in deleted_messages {
+        for (msg_id, msg) in deleted_messages {
+            let status = if slot >= msg.validity_end {
+                AsyncMessageStatus::Expired { slot }
+            } else {
+                AsyncMessageStatus::Dropped { slot }
+            };
+            self.async_message_statuses.push((msg_id, status));
             self.cancel_async_message(&msg);
         }
 
@@ -724,6 +967,9 @@ impl ExecutionContext {
             block_id: std::mem::take(&mut self.opt_block_id),
             state_changes,
             events: std::mem::take(&mut self.events),
+            receipts: std::mem::take(&mut self.receipts),
+            traces: std::mem::take(&mut self.traces),
+            async_message_statuses: std::mem::take(&mut self.async_message_statuses),
         }
     }
 
@@ -5,19 +5,27 @@
 
 use crate::execution::ExecutionState;
 use crate::request_queue::{RequestQueue, RequestWithResponseSender};
+use massa_async_pool::{AsyncMessageFilter, AsyncMessageId, AsyncMessageInfo};
 use massa_execution_exports::{
     ExecutionAddressInfo, ExecutionConfig, ExecutionController, ExecutionError, ExecutionManager,
     ReadOnlyExecutionOutput, ReadOnlyExecutionRequest,
 };
-use massa_models::api::EventFilter;
+use massa_ledger_exports::LedgerEntryProof;
+use massa_models::api::{AddressActivity, AddressActivityCursor, EventFilter};
+use massa_models::execution::ExecutionTrace;
 use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::{PreHashMap, PreHashSet};
+use massa_models::receipt::OperationReceipt;
 use massa_models::stats::ExecutionStats;
-use massa_models::{address::Address, amount::Amount, operation::OperationId};
+use massa_models::{
+    address::{Address, AddressStorageReport},
+    amount::Amount,
+    operation::OperationId,
+};
 use massa_models::{block::BlockId, slot::Slot};
 use massa_storage::Storage;
 use parking_lot::{Condvar, Mutex, RwLock};
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fmt::Display;
 use std::sync::Arc;
 use tracing::info;
@@ -63,14 +71,14 @@ impl ExecutionInputData {
             finalized_blocks: Default::default(),
             new_blockclique: Default::default(),
             block_storage: Default::default(),
-            readonly_requests: RequestQueue::new(config.max_final_events),
+            readonly_requests: RequestQueue::new(config.readonly_queue_length),
         }
     }
 
     /// Takes the current input data into a clone that is returned,
     /// and resets self.
     pub fn take(&mut self) -> Self {
-        let max_final_events = self.readonly_requests.capacity();
+        let readonly_queue_length = self.readonly_requests.capacity();
         ExecutionInputData {
             stop: std::mem::take(&mut self.stop),
             finalized_blocks: std::mem::take(&mut self.finalized_blocks),
@@ -78,7 +86,7 @@ impl ExecutionInputData {
             block_storage: std::mem::take(&mut self.block_storage),
             readonly_requests: std::mem::replace(
                 &mut self.readonly_requests,
-                RequestQueue::new(max_final_events),
+                RequestQueue::new(readonly_queue_length),
             ),
         }
     }
@@ -132,9 +140,12 @@ impl ExecutionController for ExecutionControllerImpl {
     /// * original caller address
     /// * operation id
     fn get_filtered_sc_output_event(&self, filter: EventFilter) -> Vec<SCOutputEvent> {
-        self.execution_state
+        // the final events are read from disk once the execution state is released
+        let query = self
+            .execution_state
             .read()
-            .get_filtered_sc_output_event(filter)
+            .get_filtered_sc_output_event(filter);
+        query.run()
     }
 
     /// Get a copy of a single datastore entry with its final and active values
@@ -169,6 +180,65 @@ impl ExecutionController for ExecutionControllerImpl {
         result
     }
 
+    /// Get the final balances of addresses at the output of a past final slot
+    fn get_final_balances_at(
+        &self,
+        addresses: &[Address],
+        slot: Slot,
+    ) -> Result<Vec<Option<Amount>>, ExecutionError> {
+        self.execution_state
+            .read()
+            .get_final_balances_at(addresses, slot)
+    }
+
+    /// Get final datastore entries at the output of a past final slot
+    fn get_final_data_entries_at(
+        &self,
+        input: Vec<(Address, Vec<u8>)>,
+        slot: Slot,
+    ) -> Result<Vec<Option<Vec<u8>>>, ExecutionError> {
+        self.execution_state
+            .read()
+            .get_final_data_entries_at(input, slot)
+    }
+
+    /// Get the final datastore keys of addresses at the output of a past final slot
+    fn get_final_datastore_keys_at(
+        &self,
+        addresses: &[Address],
+        slot: Slot,
+    ) -> Result<Vec<Option<BTreeSet<Vec<u8>>>>, ExecutionError> {
+        self.execution_state
+            .read()
+            .get_final_datastore_keys_at(addresses, slot)
+    }
+
+    /// Get a page of the final and candidate datastore keys of an address
+    fn get_final_and_candidate_datastore_keys_page(
+        &self,
+        addr: &Address,
+        prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: usize,
+    ) -> (BTreeSet<Vec<u8>>, BTreeSet<Vec<u8>>) {
+        self.execution_state
+            .read()
+            .get_final_and_candidate_datastore_keys_page(addr, prefix, start_after, limit)
+    }
+
+    /// Gets proofs of final ledger entries against the final state hash
+    ///
+    /// # Arguments
+    /// * `entries`: addresses and datastore keys of the entries, `None` for the balance
+    fn get_final_ledger_entry_proofs(
+        &self,
+        entries: Vec<(Address, Option<Vec<u8>>)>,
+    ) -> Result<Vec<LedgerEntryProof>, ExecutionError> {
+        self.execution_state
+            .read()
+            .get_final_ledger_entry_proofs(entries)
+    }
+
     /// Return the active rolls distribution for the given `cycle`
     fn get_cycle_active_rolls(&self, cycle: u64) -> BTreeMap<Address, u64> {
         self.execution_state.read().get_cycle_active_rolls(cycle)
@@ -226,6 +296,49 @@ impl ExecutionController for ExecutionControllerImpl {
             .unexecuted_ops_among(ops, thread)
     }
 
+    /// Get the execution receipts of a list of operations
+    fn get_operation_receipts(&self, ops: &[OperationId]) -> Vec<Option<OperationReceipt>> {
+        let exec_state = self.execution_state.read();
+        ops.iter()
+            .map(|op_id| exec_state.get_operation_receipt(op_id))
+            .collect()
+    }
+
+    /// Get at most `limit` final operations involving an address, most recent first
+    fn get_address_history(
+        &self,
+        address: &Address,
+        cursor: Option<AddressActivityCursor>,
+        limit: usize,
+    ) -> Result<Vec<AddressActivity>, ExecutionError> {
+        self.execution_state
+            .read()
+            .get_address_history(address, cursor, limit)
+    }
+
+    /// Get the call traces of a list of operations
+    fn get_operation_call_traces(&self, ops: &[OperationId]) -> Vec<Option<ExecutionTrace>> {
+        let exec_state = self.execution_state.read();
+        ops.iter()
+            .map(|op_id| exec_state.get_operation_call_trace(op_id))
+            .collect()
+    }
+
+    /// Get the call traces of the operations and messages executed at a slot
+    fn get_slot_call_traces(&self, slot: Slot) -> Option<Vec<ExecutionTrace>> {
+        self.execution_state.read().get_slot_call_traces(&slot)
+    }
+
+    /// List the asynchronous messages of the final pool matching a filter
+    fn get_async_messages(&self, filter: &AsyncMessageFilter) -> Vec<AsyncMessageInfo> {
+        self.execution_state.read().get_final_async_messages(filter)
+    }
+
+    /// Get an asynchronous message of the final pool, or what happened to it
+    fn get_async_message(&self, id: &AsyncMessageId) -> Option<AsyncMessageInfo> {
+        self.execution_state.read().get_final_async_message(id)
+    }
+
     /// Gets information about a batch of addresses
     fn get_addresses_infos(&self, addresses: &[Address]) -> Vec<ExecutionAddressInfo> {
         let mut res = Vec::with_capacity(addresses.len());
@@ -251,6 +364,18 @@ impl ExecutionController for ExecutionControllerImpl {
         res
     }
 
+    /// Gets the final and candidate storage used by a batch of addresses
+    fn get_addresses_storage(
+        &self,
+        addresses: &[Address],
+    ) -> Vec<(AddressStorageReport, AddressStorageReport)> {
+        let exec_state = self.execution_state.read();
+        addresses
+            .iter()
+            .map(|addr| exec_state.get_final_and_candidate_storage_reports(addr))
+            .collect()
+    }
+
     /// Get execution statistics
     fn get_stats(&self) -> ExecutionStats {
         self.execution_state.read().get_stats()
@@ -0,0 +1,61 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Tools shared by the disk stores of the execution worker
+//! (final events, receipts, slot records and address index).
+//!
+//! Each store is a `RocksDB` database with its own column families.
+//! The stores keeping a retention window key their records, or an index of them, by slot first,
+//! so that the records out of the window are pruned by reading the keys in order.
+
+use massa_models::slot::Slot;
+use rocksdb::{ColumnFamily, ColumnFamilyDescriptor, IteratorMode, Options, WriteBatch, DB};
+use std::path::PathBuf;
+
+pub(crate) const OPEN_ERROR: &str = "critical: rocksdb open operation failed";
+pub(crate) const CRUD_ERROR: &str = "critical: rocksdb crud operation failed";
+pub(crate) const CF_ERROR: &str = "critical: rocksdb column family operation failed";
+
+/// Open a disk store with the given column families, creating it if it does not exist
+pub(crate) fn open_disk_store(path: PathBuf, column_families: &[&str]) -> DB {
+    let mut db_opts = Options::default();
+    db_opts.create_if_missing(true);
+    db_opts.create_missing_column_families(true);
+
+    DB::open_cf_descriptors(
+        &db_opts,
+        path,
+        column_families
+            .iter()
+            .map(|name| ColumnFamilyDescriptor::new(*name, Options::default())),
+    )
+    .expect(OPEN_ERROR)
+}
+
+/// First slot of the retention window of a store that keeps `retention_periods` periods up to `slot`
+pub(crate) fn retention_start(slot: Slot, retention_periods: u64) -> Slot {
+    Slot::new(slot.period.saturating_sub(retention_periods), 0)
+}
+
+/// Add to a batch the deletion of the keys of a column family keyed by slot first,
+/// from its first key up to `end` excluded.
+///
+/// `on_prune` is called with each pruned key and value, to also delete what depends on them.
+pub(crate) fn prune_before<F>(
+    db: &DB,
+    batch: &mut WriteBatch,
+    handle: &ColumnFamily,
+    end: Slot,
+    mut on_prune: F,
+) where
+    F: FnMut(&mut WriteBatch, &[u8], &[u8]),
+{
+    let end_key = end.to_bytes_key();
+    for item in db.iterator_cf(handle, IteratorMode::Start) {
+        let (key, value) = item.expect(CRUD_ERROR);
+        if key[..] >= end_key[..] {
+            break;
+        }
+        on_prune(batch, &key[..], &value[..]);
+        batch.delete_cf(handle, key);
+    }
+}
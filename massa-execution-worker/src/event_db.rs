@@ -0,0 +1,393 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Module to persist on disk the SC output events that became final.
+//!
+//! Events are stored by slot and index in slot, and indexed by emitter address,
+//! original caller address and origin operation id so that filtered queries
+//! only go through the events they can match.
+//! Events older than the configured retention are pruned as new final slots are written.
+
+use crate::disk_store::{open_disk_store, prune_before, retention_start, CF_ERROR, CRUD_ERROR};
+use massa_execution_exports::event_matches_filter;
+use massa_models::{
+    api::{EventFilter, EventOrder},
+    output_event::SCOutputEvent,
+    slot::{Slot, SLOT_KEY_SIZE},
+};
+use rocksdb::{Direction, IteratorMode, WriteBatch, DB};
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+const EVENTS_CF: &str = "events";
+const EMITTER_INDEX_CF: &str = "emitter_index";
+const CALLER_INDEX_CF: &str = "caller_index";
+const OPERATION_INDEX_CF: &str = "operation_index";
+const SER_ERROR: &str = "critical: event serialization failed";
+const DESER_ERROR: &str = "critical: event deserialization failed";
+/// Size of an event key: the slot key followed by the index in slot
+const EVENT_KEY_SIZE: usize = SLOT_KEY_SIZE + 8;
+
+/// Key of an event in the events column family: the slot followed by the index in slot
+fn event_key(event: &SCOutputEvent) -> Vec<u8> {
+    [
+        &event.context.slot.to_bytes_key()[..],
+        &event.context.index_in_slot.to_be_bytes(),
+    ]
+    .concat()
+}
+
+/// Disk store of the final SC output events
+///
+/// Contains a `RocksDB` DB instance
+pub(crate) struct FinalEventDB {
+    db: DB,
+    retention_periods: u64,
+}
+
+impl FinalEventDB {
+    /// Create and initialize a new `FinalEventDB`.
+    ///
+    /// # Arguments
+    /// * path: path to the desired disk event db directory
+    /// * `retention_periods`: number of periods during which final events are kept
+    pub fn new(path: PathBuf, retention_periods: u64) -> Self {
+        FinalEventDB {
+            db: open_disk_store(
+                path,
+                &[
+                    EVENTS_CF,
+                    EMITTER_INDEX_CF,
+                    CALLER_INDEX_CF,
+                    OPERATION_INDEX_CF,
+                ],
+            ),
+            retention_periods,
+        }
+    }
+
+    /// Add the index entries of an event to a batch, or remove them if `delete` is set
+    fn index_event(&self, batch: &mut WriteBatch, event: &SCOutputEvent, key: &[u8], delete: bool) {
+        let mut indexes: Vec<(&str, Vec<u8>)> = Vec::new();
+        if let Some(emitter) = event.context.call_stack.front() {
+            indexes.push((EMITTER_INDEX_CF, [&emitter.to_bytes()[..], key].concat()));
+        }
+        if let Some(caller) = event.context.call_stack.back() {
+            indexes.push((CALLER_INDEX_CF, [&caller.to_bytes()[..], key].concat()));
+        }
+        if let Some(op_id) = event.context.origin_operation_id {
+            indexes.push((OPERATION_INDEX_CF, [&op_id.to_bytes()[..], key].concat()));
+        }
+        for (cf, index_key) in indexes {
+            let handle = self.db.cf_handle(cf).expect(CF_ERROR);
+            if delete {
+                batch.delete_cf(handle, index_key);
+            } else {
+                batch.put_cf(handle, index_key, []);
+            }
+        }
+    }
+
+    /// Write the final events of a slot and prune the events that are out of the retention window.
+    ///
+    /// Writing the events of a slot again overwrites them with the same keys,
+    /// so a slot executed again after a restart does not duplicate its events.
+    ///
+    /// # Arguments
+    /// * slot: final slot the events were emitted at
+    /// * events: final events emitted at that slot
+    pub fn write_events(&self, slot: Slot, events: &VecDeque<SCOutputEvent>) {
+        let handle = self.db.cf_handle(EVENTS_CF).expect(CF_ERROR);
+        let mut batch = WriteBatch::default();
+        for event in events {
+            let key = event_key(event);
+            self.index_event(&mut batch, event, &key, false);
+            batch.put_cf(handle, key, serde_json::to_vec(event).expect(SER_ERROR));
+        }
+
+        // prune the events emitted before the start of the retention window
+        prune_before(
+            &self.db,
+            &mut batch,
+            handle,
+            retention_start(slot, self.retention_periods),
+            |batch, key, value| {
+                let event: SCOutputEvent = serde_json::from_slice(value).expect(DESER_ERROR);
+                self.index_event(batch, &event, key, true);
+            },
+        );
+
+        self.db.write(batch).expect(CRUD_ERROR);
+    }
+
+    /// Get at most `filter.limit` final events matching a filter, in the order requested by the filter.
+    ///
+    /// The most selective index available in the filter is used:
+    /// operation id, then emitter address, then original caller address.
+    /// Without any of them, the events are read by slot.
+    /// In both cases only the keys between the start slot, the end slot and the cursor are read.
+    pub fn get_filtered_sc_output_events(&self, filter: &EventFilter) -> Vec<SCOutputEvent> {
+        let descending = filter.order == EventOrder::Descending;
+        let limit = filter.limit.unwrap_or(usize::MAX);
+
+        // bounds of the event keys to read, the lower one included and the upper one excluded
+        let mut lower = filter.start.map(|slot| slot.to_bytes_key().to_vec());
+        let mut upper = filter.end.map(|slot| slot.to_bytes_key().to_vec());
+        if let Some(cursor) = filter.cursor {
+            let cursor_key = [
+                &cursor.slot.to_bytes_key()[..],
+                &cursor.index_in_slot.to_be_bytes(),
+            ]
+            .concat();
+            // the event at the cursor itself is excluded by the filter
+            if descending {
+                upper = Some(upper.map_or(cursor_key.clone(), |upper| upper.min(cursor_key)));
+            } else {
+                lower = Some(lower.map_or(cursor_key.clone(), |lower| lower.max(cursor_key)));
+            }
+        }
+
+        let index: Option<(&str, Vec<u8>)> = if let Some(op_id) = filter.original_operation_id {
+            Some((OPERATION_INDEX_CF, op_id.to_bytes().to_vec()))
+        } else if let Some(emitter) = filter.emitter_address {
+            Some((EMITTER_INDEX_CF, emitter.to_bytes().to_vec()))
+        } else {
+            filter
+                .original_caller_address
+                .map(|caller| (CALLER_INDEX_CF, caller.to_bytes().to_vec()))
+        };
+        let events_handle = self.db.cf_handle(EVENTS_CF).expect(CF_ERROR);
+        let (handle, prefix) = match &index {
+            Some((cf, prefix)) => (self.db.cf_handle(cf).expect(CF_ERROR), prefix.clone()),
+            None => (events_handle, Vec::new()),
+        };
+
+        let (from, direction) = if descending {
+            let from = match &upper {
+                Some(upper) => [&prefix[..], upper].concat(),
+                None => [&prefix[..], &[u8::MAX; EVENT_KEY_SIZE]].concat(),
+            };
+            (from, Direction::Reverse)
+        } else {
+            let from = match &lower {
+                Some(lower) => [&prefix[..], lower].concat(),
+                None => prefix.clone(),
+            };
+            (from, Direction::Forward)
+        };
+
+        let mut events = Vec::new();
+        for item in self
+            .db
+            .iterator_cf(handle, IteratorMode::From(&from, direction))
+        {
+            if events.len() >= limit {
+                break;
+            }
+            let (db_key, value) = item.expect(CRUD_ERROR);
+            if !db_key.starts_with(&prefix) {
+                break;
+            }
+            let key = &db_key[prefix.len()..];
+            if let Some(lower) = &lower && key < &lower[..] {
+                if descending {
+                    break;
+                }
+                continue;
+            }
+            if let Some(upper) = &upper && key >= &upper[..] {
+                if descending {
+                    continue;
+                }
+                break;
+            }
+            let value = if index.is_some() {
+                match self.db.get_cf(events_handle, key).expect(CRUD_ERROR) {
+                    Some(value) => value,
+                    None => continue,
+                }
+            } else {
+                value.into_vec()
+            };
+            let event: SCOutputEvent = serde_json::from_slice(&value).expect(DESER_ERROR);
+            if event_matches_filter(&event, filter) {
+                events.push(event);
+            }
+        }
+        events
+    }
+}
+
+/// Query of the events matching a filter.
+///
+/// The candidate events are gathered while the execution state is held,
+/// the final events are read from disk when the query is run, once the execution state is released.
+pub(crate) struct FilteredEventsQuery {
+    /// disk store of the final events along with the filter of the final events to read
+    final_events: Option<(Arc<FinalEventDB>, EventFilter)>,
+    /// candidate events matching the filter, in ascending order
+    candidate_events: Vec<SCOutputEvent>,
+    /// order of the returned events
+    order: EventOrder,
+    /// maximum number of returned events
+    limit: usize,
+}
+
+impl FilteredEventsQuery {
+    /// Create a new `FilteredEventsQuery`
+    pub fn new(
+        final_events: Option<(Arc<FinalEventDB>, EventFilter)>,
+        candidate_events: Vec<SCOutputEvent>,
+        filter: &EventFilter,
+    ) -> Self {
+        FilteredEventsQuery {
+            final_events,
+            candidate_events,
+            order: filter.order,
+            limit: filter.limit.unwrap_or(usize::MAX),
+        }
+    }
+
+    /// Read the final events and return the events in the order requested by the filter,
+    /// starting after its cursor and up to its limit.
+    /// Final events always come before candidate ones in ascending order.
+    pub fn run(self) -> Vec<SCOutputEvent> {
+        let final_events = match &self.final_events {
+            Some((db, filter)) => db.get_filtered_sc_output_events(filter),
+            None => Vec::new(),
+        };
+        let mut candidate_events = self.candidate_events;
+        match self.order {
+            EventOrder::Ascending => final_events
+                .into_iter()
+                .chain(candidate_events)
+                .take(self.limit)
+                .collect(),
+            EventOrder::Descending => {
+                candidate_events.reverse();
+                candidate_events
+                    .into_iter()
+                    .chain(final_events)
+                    .take(self.limit)
+                    .collect()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FinalEventDB;
+    use massa_models::{
+        address::Address,
+        api::{EventCursor, EventFilter, EventOrder},
+        output_event::{EventExecutionContext, SCOutputEvent},
+        slot::Slot,
+    };
+    use massa_signature::KeyPair;
+    use std::collections::VecDeque;
+    use tempfile::TempDir;
+
+    fn event(slot: Slot, index_in_slot: u64, call_stack: Vec<Address>) -> SCOutputEvent {
+        SCOutputEvent {
+            context: EventExecutionContext {
+                slot,
+                block: None,
+                read_only: false,
+                index_in_slot,
+                call_stack: call_stack.into(),
+                origin_operation_id: None,
+                is_final: true,
+                is_error: false,
+            },
+            data: index_in_slot.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_final_event_db() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = FinalEventDB::new(temp_dir.path().to_path_buf(), 10);
+        let addr_a = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let addr_b = Address::from_public_key(&KeyPair::generate().get_public_key());
+
+        for period in 1..=5 {
+            let slot = Slot::new(period, 0);
+            let events = VecDeque::from([
+                event(slot, 0, vec![addr_a]),
+                event(slot, 1, vec![addr_b, addr_a]),
+            ]);
+            db.write_events(slot, &events);
+        }
+
+        // filter by slot range
+        let filter = EventFilter {
+            start: Some(Slot::new(2, 0)),
+            end: Some(Slot::new(4, 0)),
+            ..Default::default()
+        };
+        let keys: Vec<(u64, u64)> = db
+            .get_filtered_sc_output_events(&filter)
+            .into_iter()
+            .map(|e| (e.context.slot.period, e.context.index_in_slot))
+            .collect();
+        assert_eq!(keys, vec![(2, 0), (2, 1), (3, 0), (3, 1)]);
+
+        // filter by emitter and original caller through the indexes
+        let filter = EventFilter {
+            emitter_address: Some(addr_b),
+            ..Default::default()
+        };
+        assert_eq!(db.get_filtered_sc_output_events(&filter).len(), 5);
+        let filter = EventFilter {
+            original_caller_address: Some(addr_a),
+            start: Some(Slot::new(5, 0)),
+            ..Default::default()
+        };
+        assert_eq!(db.get_filtered_sc_output_events(&filter).len(), 2);
+
+        // paginate in descending order through the emitter index
+        let mut filter = EventFilter {
+            emitter_address: Some(addr_b),
+            order: EventOrder::Descending,
+            limit: Some(2),
+            ..Default::default()
+        };
+        let page = db.get_filtered_sc_output_events(&filter);
+        let periods: Vec<u64> = page.iter().map(|e| e.context.slot.period).collect();
+        assert_eq!(periods, vec![5, 4]);
+        filter.cursor = page.last().map(|e| EventCursor::from(&e.context));
+        let page = db.get_filtered_sc_output_events(&filter);
+        let periods: Vec<u64> = page.iter().map(|e| e.context.slot.period).collect();
+        assert_eq!(periods, vec![3, 2]);
+
+        // resume after a cursor in ascending order, filtering on the event data
+        let filter = EventFilter {
+            cursor: Some(EventCursor {
+                slot: Slot::new(4, 0),
+                index_in_slot: 0,
+            }),
+            data_prefix: Some("1".to_string()),
+            ..Default::default()
+        };
+        let keys: Vec<(u64, u64)> = db
+            .get_filtered_sc_output_events(&filter)
+            .into_iter()
+            .map(|e| (e.context.slot.period, e.context.index_in_slot))
+            .collect();
+        assert_eq!(keys, vec![(4, 1), (5, 1)]);
+
+        // events out of the retention window are pruned along with their index entries
+        db.write_events(Slot::new(13, 0), &VecDeque::new());
+        assert_eq!(
+            db.get_filtered_sc_output_events(&EventFilter::default())
+                .len(),
+            6
+        );
+        let filter = EventFilter {
+            emitter_address: Some(addr_b),
+            ..Default::default()
+        };
+        assert_eq!(db.get_filtered_sc_output_events(&filter).len(), 3);
+    }
+}
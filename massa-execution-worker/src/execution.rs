@@ -9,24 +9,39 @@
 //! * the output of the execution is extracted from the context
 
 use crate::active_history::{ActiveHistory, HistorySearchResult};
-use crate::context::ExecutionContext;
+use crate::address_index_db::FinalAddressIndexDB;
+use crate::bytecode_cache::BytecodeCache;
+use crate::context::{ExecutionContext, ExecutionForkOutput};
+use crate::event_db::{FilteredEventsQuery, FinalEventDB};
+use crate::fork_pool::ForkExecutionPool;
 use crate::interface_impl::InterfaceImpl;
+use crate::receipt_db::FinalReceiptDB;
+use crate::slot_record_db::{FinalSlotRecord, FinalSlotRecordDB};
+use crate::speculative_ledger::LedgerWrites;
 use crate::stats::ExecutionStatsCounter;
-use massa_async_pool::AsyncMessage;
+use massa_async_pool::{
+    AsyncMessage, AsyncMessageFilter, AsyncMessageId, AsyncMessageInfo, AsyncMessageStatus,
+};
 use massa_execution_exports::{
-    EventStore, ExecutionConfig, ExecutionError, ExecutionOutput, ExecutionStackElement,
-    ReadOnlyExecutionOutput, ReadOnlyExecutionRequest, ReadOnlyExecutionTarget,
+    EventStore, ExecutionChannels, ExecutionConfig, ExecutionError, ExecutionOutput,
+    ExecutionStackElement, ReadOnlyExecutionOutput, ReadOnlyExecutionRequest,
+    ReadOnlyExecutionTarget,
 };
 use massa_final_state::FinalState;
-use massa_ledger_exports::{SetOrDelete, SetUpdateOrDelete};
-use massa_models::address::ExecutionAddressCycleInfo;
-use massa_models::api::EventFilter;
+use massa_ledger_exports::{
+    balance_key, data_key, LedgerEntryProof, SetOrDelete, SetOrKeep, SetUpdateOrDelete,
+    BALANCE_IDENT, DATASTORE_IDENT,
+};
+use massa_models::address::{AddressStorageReport, ExecutionAddressCycleInfo};
+use massa_models::api::{AddressActivity, AddressActivityCursor, EventFilter};
+use massa_models::execution::{ExecutionTrace, TraceOrigin};
 use massa_models::output_event::SCOutputEvent;
-use massa_models::prehash::PreHashSet;
+use massa_models::prehash::{PreHashMap, PreHashSet};
+use massa_models::receipt::{OperationExecutionError, OperationReceipt};
 use massa_models::stats::ExecutionStats;
 use massa_models::{
     address::Address,
-    block::BlockId,
+    block::{BlockId, FinalBlock, WrappedBlock},
     operation::{OperationId, OperationType, WrappedOperation},
 };
 use massa_models::{amount::Amount, slot::Slot};
@@ -34,7 +49,7 @@ use massa_pos_exports::SelectorController;
 use massa_sc_runtime::Interface;
 use massa_storage::Storage;
 use parking_lot::{Mutex, RwLock};
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
 use std::sync::Arc;
 use tracing::{debug, info, warn};
 
@@ -60,16 +75,32 @@ pub(crate) struct ExecutionState {
     pub active_cursor: Slot,
     // a cursor pointing to the highest executed final slot
     pub final_cursor: Slot,
-    // store containing execution events that became final
-    final_events: EventStore,
+    // disk store containing execution events that became final
+    final_events: Arc<FinalEventDB>,
+    // disk store containing the receipts of the operations executed in final slots
+    final_receipts: FinalReceiptDB,
+    // call traces of the latest final slots, oldest first, kept in memory if call tracing is enabled
+    final_traces: VecDeque<(Slot, Vec<ExecutionTrace>)>,
+    // statuses of the asynchronous messages executed or removed from the pool in the latest final slots, oldest first
+    final_async_message_statuses: VecDeque<(Slot, Vec<(AsyncMessageId, AsyncMessageStatus)>)>,
+    // disk store of the inputs and outputs of the final slots, if they are recorded for replay
+    final_slot_records: Option<FinalSlotRecordDB>,
+    // disk index of the final operations by involved address, if enabled
+    final_address_index: Option<FinalAddressIndexDB>,
     // final state with atomic R/W access
     final_state: Arc<RwLock<FinalState>>,
+    // cache of the bytecodes read from the final ledger, shared by all the execution contexts
+    bytecode_cache: Arc<Mutex<BytecodeCache>>,
     // execution context (see documentation in context.rs)
     execution_context: Arc<Mutex<ExecutionContext>>,
-    // execution interface allowing the VM runtime to access the Massa context
-    execution_interface: Box<dyn Interface>,
+    // executor running operations and asynchronous messages on the execution context
+    executor: OperationExecutor,
+    // threads executing the operations of a block on forks of the execution context, if parallel execution is enabled
+    fork_pool: Option<ForkExecutionPool>,
     // execution statistics
     stats_counter: ExecutionStatsCounter,
+    // channels used to broadcast the execution outputs
+    channels: ExecutionChannels,
 }
 
 impl ExecutionState {
@@ -78,10 +109,15 @@ impl ExecutionState {
     /// # Arguments
     /// * `config`: execution configuration
     /// * `final_state`: atomic access to the final state
+    /// * `channels`: channels used to broadcast the execution outputs
     ///
     /// # returns
     /// A new `ExecutionState`
-    pub fn new(config: ExecutionConfig, final_state: Arc<RwLock<FinalState>>) -> ExecutionState {
+    pub fn new(
+        config: ExecutionConfig,
+        final_state: Arc<RwLock<FinalState>>,
+        channels: ExecutionChannels,
+    ) -> ExecutionState {
         // Get the slot at the output of which the final state is attached.
         // This should be among the latest final slots.
         let last_final_slot = final_state.read().slot;
@@ -89,39 +125,75 @@ impl ExecutionState {
         // Create default active history
         let active_history: Arc<RwLock<ActiveHistory>> = Default::default();
 
+        // Create an empty bytecode cache: it is not kept across restarts
+        let bytecode_cache = Arc::new(Mutex::new(BytecodeCache::new(config.bytecode_cache_size)));
+
         // Create an empty placeholder execution context, with shared atomic access
         let execution_context = Arc::new(Mutex::new(ExecutionContext::new(
             config.clone(),
             final_state.clone(),
             active_history.clone(),
+            bytecode_cache.clone(),
         )));
 
-        // Instantiate the interface providing ABI access to the VM, share the execution context with it
-        let execution_interface = Box::new(InterfaceImpl::new(
-            config.clone(),
-            execution_context.clone(),
-        ));
+        // Instantiate the executor running on the execution context
+        let executor = OperationExecutor::new(config.clone(), execution_context.clone());
 
         // build the execution state
         ExecutionState {
             final_state,
+            bytecode_cache,
             execution_context,
-            execution_interface,
+            executor,
+            // the threads executing operations in parallel are kept for the whole life of the execution state
+            fork_pool: config
+                .parallel_execution
+                .then(|| ForkExecutionPool::new(config.clone())),
             // empty execution output history: it is not recovered through bootstrap
             active_history,
-            // empty final event store: it is not recovered through bootstrap
-            final_events: Default::default(),
+            // final events are kept on disk across restarts: they are not recovered through bootstrap
+            final_events: Arc::new(FinalEventDB::new(
+                config.event_store_path.clone(),
+                config.event_retention_periods,
+            )),
+            // final receipts are kept on disk across restarts as well
+            final_receipts: FinalReceiptDB::new(
+                config.receipt_store_path.clone(),
+                config.receipt_retention_periods,
+            ),
+            // call traces are only kept in memory for debugging
+            final_traces: Default::default(),
+            // asynchronous message statuses are only kept in memory as well
+            final_async_message_statuses: Default::default(),
+            // final slots are recorded only if enabled, to be replayed offline
+            final_slot_records: config.final_slot_record.then(|| {
+                FinalSlotRecordDB::new(
+                    config.final_slot_record_path.clone(),
+                    config.final_slot_record_retention_periods,
+                )
+            }),
+            // final operations are indexed by address only if enabled
+            final_address_index: config
+                .address_index
+                .then(|| FinalAddressIndexDB::new(config.address_index_path.clone())),
             // no active slots executed yet: set active_cursor to the last final block
             active_cursor: last_final_slot,
             final_cursor: last_final_slot,
             stats_counter: ExecutionStatsCounter::new(config.stats_time_window_duration),
+            channels,
             config,
         }
     }
 
     /// Get execution statistics
     pub fn get_stats(&self) -> ExecutionStats {
-        self.stats_counter.get_stats(self.active_cursor)
+        let (bytecode_cache_hits, bytecode_cache_misses) =
+            self.bytecode_cache.lock().hits_and_misses();
+        self.stats_counter.get_stats(
+            self.active_cursor,
+            bytecode_cache_hits,
+            bytecode_cache_misses,
+        )
     }
 
     /// Applies the output of an execution to the final execution state.
@@ -142,10 +214,54 @@ impl ExecutionState {
             );
         }
 
-        // apply state changes to the final ledger
-        self.final_state
-            .write()
-            .finalize(exec_out.slot, exec_out.state_changes);
+        // append generated events and operation receipts to the final stores.
+        // This is done before finalizing the state: if the node stops in between,
+        // the slot is executed again on restart and they are overwritten with the same keys.
+        exec_out.events.finalize();
+        self.final_events
+            .write_events(exec_out.slot, &exec_out.events.0);
+        self.final_receipts
+            .write_receipts(exec_out.slot, &exec_out.receipts);
+
+        // keep the call traces of the slot, forgetting the oldest ones
+        if self.config.call_tracing {
+            self.final_traces
+                .push_back((exec_out.slot, std::mem::take(&mut exec_out.traces)));
+            while self.final_traces.len() > self.config.call_trace_history_length {
+                self.final_traces.pop_front();
+            }
+        }
+
+        // keep the statuses of the asynchronous messages of the slot, forgetting the oldest ones
+        self.final_async_message_statuses.push_back((
+            exec_out.slot,
+            std::mem::take(&mut exec_out.async_message_statuses),
+        ));
+        while self.final_async_message_statuses.len()
+            > self.config.async_message_status_history_length
+        {
+            self.final_async_message_statuses.pop_front();
+        }
+
+        // apply state changes to the final ledger,
+        // forgetting the cached bytecodes they change before the final state is unlocked
+        {
+            let mut final_state = self.final_state.write();
+            let mut bytecode_cache = self.bytecode_cache.lock();
+            for (addr, change) in exec_out.state_changes.ledger_changes.0.iter() {
+                let bytecode_changed = match change {
+                    SetUpdateOrDelete::Update(update) => {
+                        matches!(update.bytecode, SetOrKeep::Set(_))
+                    }
+                    SetUpdateOrDelete::Set(_) | SetUpdateOrDelete::Delete => true,
+                };
+                if bytecode_changed {
+                    bytecode_cache.invalidate(addr);
+                }
+            }
+            drop(bytecode_cache);
+            final_state.finalize(exec_out.slot, exec_out.state_changes);
+        }
 
         // update the final ledger's slot
         self.final_cursor = exec_out.slot;
@@ -156,10 +272,17 @@ impl ExecutionState {
             self.active_cursor = self.final_cursor;
         }
 
-        // append generated events to the final event store
-        exec_out.events.finalize();
-        self.final_events.extend(exec_out.events);
-        self.final_events.prune(self.config.max_final_events);
+        // broadcast the events of the slot again, now as final
+        self.broadcast_events(&exec_out.events);
+    }
+
+    /// Broadcasts the events emitted at an executed slot if broadcast is enabled
+    fn broadcast_events(&self, events: &EventStore) {
+        if self.config.broadcast_enabled {
+            for event in events.0.iter() {
+                let _ = self.channels.sc_event_sender.send(event.clone());
+            }
+        }
     }
 
     /// Applies an execution output to the active (non-final) state
@@ -178,9 +301,74 @@ impl ExecutionState {
         // update active cursor to reflect the new latest active slot
         self.active_cursor = exec_out.slot;
 
+        // broadcast the candidate events of the slot
+        self.broadcast_events(&exec_out.events);
+
         // add the execution output at the end of the output history
         self.active_history.write().0.push_back(exec_out);
     }
+}
+
+/// Executes operations and asynchronous messages on an execution context through the VM.
+///
+/// The execution state has one running on its own execution context.
+/// Others are created to execute operations on forks of it (see `ExecutionContext::fork`).
+pub(crate) struct OperationExecutor {
+    // execution config
+    config: ExecutionConfig,
+    // execution context (see documentation in context.rs)
+    execution_context: Arc<Mutex<ExecutionContext>>,
+    // execution interface allowing the VM runtime to access the Massa context
+    execution_interface: Box<dyn Interface>,
+}
+
+impl OperationExecutor {
+    /// Create an executor running on an execution context
+    ///
+    /// # Arguments
+    /// * `config`: execution configuration
+    /// * `execution_context`: execution context shared with the interface providing ABI access to the VM
+    pub fn new(config: ExecutionConfig, execution_context: Arc<Mutex<ExecutionContext>>) -> Self {
+        let execution_interface = Box::new(InterfaceImpl::new(
+            config.clone(),
+            execution_context.clone(),
+        ));
+        OperationExecutor {
+            config,
+            execution_context,
+            execution_interface,
+        }
+    }
+
+    /// Execute an operation on a fork of an execution context (see `ExecutionContext::fork`).
+    /// The remaining block gas is not checked: it has to be checked when merging the output.
+    ///
+    /// # Arguments
+    /// * `config`: execution configuration
+    /// * `fork`: forked execution context
+    /// * `operation`: operation to execute
+    /// * `block_slot`: slot of the block in which the op is included
+    ///
+    /// # Returns
+    /// The result of `execute_operation` and the output of the execution on the fork
+    pub fn execute_operation_on_fork(
+        config: ExecutionConfig,
+        fork: ExecutionContext,
+        operation: &WrappedOperation,
+        block_slot: Slot,
+    ) -> (Result<(), ExecutionError>, ExecutionForkOutput) {
+        let executor = OperationExecutor::new(config, Arc::new(Mutex::new(fork)));
+        let mut remaining_block_gas = u64::MAX;
+        let mut block_credits = Amount::zero();
+        let result = executor.execute_operation(
+            operation,
+            block_slot,
+            &mut remaining_block_gas,
+            &mut block_credits,
+        );
+        let output = context_guard!(executor).take_fork_output();
+        (result, output)
+    }
 
     /// Execute an operation in the context of a block.
     /// Assumes the execution context was initialized at the beginning of the slot.
@@ -275,6 +463,13 @@ impl ExecutionState {
             // set the context origin operation ID
             context.origin_operation_id = Some(operation_id);
 
+            // start tracing the calls made by the operation
+            let function = match &operation.content.op {
+                OperationType::ExecuteSC { .. } => Some("main".to_string()),
+                _ => None,
+            };
+            context.start_call_trace(sender_addr, function, Amount::zero());
+
             // execution context lock dropped here because the op-specific execution functions below acquire it again
         }
 
@@ -285,22 +480,23 @@ impl ExecutionState {
         *block_credits = new_block_credits;
 
         // Call the execution process specific to the operation type.
+        // Only the smart contract executions that ran in the VM report the gas they used.
         let execution_result = match &operation.content.op {
-            OperationType::ExecuteSC { .. } => {
-                self.execute_executesc_op(&operation.content.op, sender_addr)
-            }
+            OperationType::ExecuteSC { .. } => self
+                .execute_executesc_op(&operation.content.op, sender_addr)
+                .map(Some),
             OperationType::CallSC { .. } => {
                 self.execute_callsc_op(&operation.content.op, sender_addr)
             }
-            OperationType::RollBuy { .. } => {
-                self.execute_roll_buy_op(&operation.content.op, sender_addr)
-            }
-            OperationType::RollSell { .. } => {
-                self.execute_roll_sell_op(&operation.content.op, sender_addr)
-            }
-            OperationType::Transaction { .. } => {
-                self.execute_transaction_op(&operation.content.op, sender_addr)
-            }
+            OperationType::RollBuy { .. } => self
+                .execute_roll_buy_op(&operation.content.op, sender_addr)
+                .map(|_| None),
+            OperationType::RollSell { .. } => self
+                .execute_roll_sell_op(&operation.content.op, sender_addr)
+                .map(|_| None),
+            OperationType::Transaction { .. } => self
+                .execute_transaction_op(&operation.content.op, sender_addr)
+                .map(|_| None),
         };
 
         {
@@ -308,9 +504,11 @@ impl ExecutionState {
             let mut context = context_guard!(self);
 
             // check execution results
-            match execution_result {
-                Ok(_) => {}
+            let (gas_used, coins, error) = match execution_result {
+                Ok(gas_used) => (gas_used, self.get_operation_coins(operation), None),
                 Err(err) => {
+                    let receipt_error = OperationExecutionError::from(&err);
+
                     // an error occurred: emit error event and reset context to snapshot
                     let err = ExecutionError::RuntimeError(format!(
                         "runtime error when executing operation {}: {}",
@@ -318,13 +516,46 @@ impl ExecutionState {
                     ));
                     debug!("{}", &err);
                     context.reset_to_snapshot(context_snapshot, err);
+
+                    // the VM does not report the gas it used when it fails
+                    (None, Amount::zero(), Some(receipt_error))
                 }
-            }
+            };
+
+            // keep the receipt of the operation
+            let receipt = OperationReceipt {
+                operation_id,
+                slot: block_slot,
+                block_id: context.opt_block_id,
+                is_final: false,
+                success: error.is_none(),
+                error,
+                gas_used,
+                fee: operation.content.fee,
+                coins,
+            };
+            let trace_error = receipt.error.as_ref().map(|err| err.to_string());
+            context.store_call_trace(TraceOrigin::Operation(operation_id), gas_used, trace_error);
+            context.receipts.insert(operation_id, receipt);
         }
 
         Ok(())
     }
 
+    /// Get the amount of coins transferred or spent by an operation that executed successfully
+    fn get_operation_coins(&self, operation: &WrappedOperation) -> Amount {
+        match &operation.content.op {
+            OperationType::Transaction { amount, .. } => *amount,
+            OperationType::CallSC { coins, .. } => *coins,
+            OperationType::RollBuy { roll_count } => self
+                .config
+                .roll_price
+                .checked_mul_u64(*roll_count)
+                .unwrap_or_default(),
+            OperationType::RollSell { .. } | OperationType::ExecuteSC { .. } => Amount::zero(),
+        }
+    }
+
     /// Execute an operation of type `RollSell`
     /// Will panic if called with another operation type
     ///
@@ -470,11 +701,14 @@ impl ExecutionState {
     /// # Arguments
     /// * `operation`: the `WrappedOperation` to process, must be an `ExecuteSC`
     /// * `sender_addr`: address of the sender
+    ///
+    /// # Returns
+    /// The amount of gas used by the execution
     pub fn execute_executesc_op(
         &self,
         operation: &OperationType,
         sender_addr: Address,
-    ) -> Result<(), ExecutionError> {
+    ) -> Result<u64, ExecutionError> {
         // process ExecuteSC operations only
         let (bytecode, max_gas, datastore) = match &operation {
             OperationType::ExecuteSC {
@@ -509,17 +743,15 @@ impl ExecutionState {
             &*self.execution_interface,
             self.config.gas_costs.clone(),
         ) {
-            Ok(_response) => {}
+            Ok(response) => Ok(max_gas.saturating_sub(response.remaining_gas)),
             Err(err) => {
                 // there was an error during bytecode execution
-                return Err(ExecutionError::RuntimeError(format!(
+                Err(ExecutionError::VMError(format!(
                     "bytecode execution error: {}",
                     err
-                )));
+                )))
             }
         }
-
-        Ok(())
     }
 
     /// Execute an operation of type `CallSC`
@@ -530,11 +762,14 @@ impl ExecutionState {
     /// * `block_creator_addr`: address of the block creator
     /// * `operation_id`: ID of the operation
     /// * `sender_addr`: address of the sender
+    ///
+    /// # Returns
+    /// The amount of gas used by the execution as reported by the VM, or `None` if no function was called
     pub fn execute_callsc_op(
         &self,
         operation: &OperationType,
         sender_addr: Address,
-    ) -> Result<(), ExecutionError> {
+    ) -> Result<Option<u64>, ExecutionError> {
         // process CallSC operations only
         let (max_gas, target_addr, target_func, param, coins) = match &operation {
             OperationType::CallSC {
@@ -570,10 +805,11 @@ impl ExecutionState {
                     operation_datastore: None,
                 },
             ];
+            context.push_call_trace(target_addr, Some(target_func.clone()), coins);
 
             // Debit the sender's balance with the coins to transfer
             if let Err(err) = context.transfer_coins(Some(sender_addr), None, coins, false) {
-                return Err(ExecutionError::RuntimeError(format!(
+                return Err(ExecutionError::CallCoinsError(format!(
                     "failed to debit operation sender {} with {} operation coins: {}",
                     sender_addr, coins, err
                 )));
@@ -581,7 +817,7 @@ impl ExecutionState {
 
             // Credit the operation target with coins.
             if let Err(err) = context.transfer_coins(None, Some(target_addr), coins, false) {
-                return Err(ExecutionError::RuntimeError(format!(
+                return Err(ExecutionError::CallCoinsError(format!(
                     "failed to credit operation target {} with {} operation coins: {}",
                     target_addr, coins, err
                 )));
@@ -589,7 +825,7 @@ impl ExecutionState {
 
             // quit if there is no function to be called
             if target_func.is_empty() {
-                return Ok(());
+                return Ok(None);
             }
 
             // Load bytecode. Assume empty bytecode if not found.
@@ -605,17 +841,21 @@ impl ExecutionState {
             &*self.execution_interface,
             self.config.gas_costs.clone(),
         ) {
-            Ok(_response) => {}
+            Ok(response) => {
+                let gas_used = max_gas.saturating_sub(response.remaining_gas);
+                let mut context = context_guard!(self);
+                context.record_call_trace(|call| call.gas_used = Some(gas_used));
+                context.pop_call_trace();
+                Ok(Some(gas_used))
+            }
             Err(err) => {
                 // there was an error during bytecode execution
-                return Err(ExecutionError::RuntimeError(format!(
+                Err(ExecutionError::VMError(format!(
                     "bytecode execution error: {}",
                     err
-                )));
+                )))
             }
         }
-
-        Ok(())
     }
 
     /// Tries to execute an asynchronous message
@@ -631,6 +871,11 @@ impl ExecutionState {
     ) -> Result<(), ExecutionError> {
         // prepare execution context
         let context_snapshot;
+        let trace_origin = TraceOrigin::AsyncMessage {
+            sender: message.sender,
+            emission_slot: message.emission_slot,
+            emission_index: message.emission_index,
+        };
         let bytecode: Vec<u8> = {
             let mut context = context_guard!(self);
             context_snapshot = context.get_snapshot();
@@ -650,6 +895,11 @@ impl ExecutionState {
                     operation_datastore: None,
                 },
             ];
+            context.start_call_trace(
+                message.destination,
+                Some(message.handler.clone()),
+                message.coins,
+            );
 
             // If there is no target bytecode or if message data is invalid,
             // reimburse sender with coins and quit
@@ -665,6 +915,7 @@ impl ExecutionState {
                     };
                     context.reset_to_snapshot(context_snapshot, err.clone());
                     context.cancel_async_message(&message);
+                    context.store_call_trace(trace_origin, None, Some(err.to_string()));
                     return Err(err);
                 }
             };
@@ -680,6 +931,7 @@ impl ExecutionState {
                 ));
                 context.reset_to_snapshot(context_snapshot, err.clone());
                 context.cancel_async_message(&message);
+                context.store_call_trace(trace_origin, None, Some(err.to_string()));
                 return Err(err);
             }
 
@@ -687,7 +939,7 @@ impl ExecutionState {
         };
 
         // run the target function
-        if let Err(err) = massa_sc_runtime::run_function(
+        match massa_sc_runtime::run_function(
             &bytecode,
             message.max_gas,
             &message.handler,
@@ -695,20 +947,28 @@ impl ExecutionState {
             &*self.execution_interface,
             self.config.gas_costs.clone(),
         ) {
-            // execution failed: reset context to snapshot and reimburse sender
-            let err = ExecutionError::RuntimeError(format!(
-                "async message runtime execution error: {}",
-                err
-            ));
-            let mut context = context_guard!(self);
-            context.reset_to_snapshot(context_snapshot, err.clone());
-            context.cancel_async_message(&message);
-            Err(err)
-        } else {
-            Ok(())
+            Ok(response) => {
+                let gas_used = message.max_gas.saturating_sub(response.remaining_gas);
+                context_guard!(self).store_call_trace(trace_origin, Some(gas_used), None);
+                Ok(())
+            }
+            Err(err) => {
+                // execution failed: reset context to snapshot and reimburse sender
+                let err = ExecutionError::RuntimeError(format!(
+                    "async message runtime execution error: {}",
+                    err
+                ));
+                let mut context = context_guard!(self);
+                context.reset_to_snapshot(context_snapshot, err.clone());
+                context.cancel_async_message(&message);
+                context.store_call_trace(trace_origin, None, Some(err.to_string()));
+                Err(err)
+            }
         }
     }
+}
 
+impl ExecutionState {
     /// Executes a full slot (with or without a block inside) without causing any changes to the state,
     /// just yielding the execution output.
     ///
@@ -732,6 +992,7 @@ impl ExecutionState {
             exec_target.as_ref().map(|(b_id, _)| *b_id),
             self.final_state.clone(),
             self.active_history.clone(),
+            self.bytecode_cache.clone(),
         );
 
         // Get asynchronous messages to execute
@@ -743,9 +1004,18 @@ impl ExecutionState {
         // Try executing asynchronous messages.
         // Effects are cancelled on failure and the sender is reimbursed.
         for (opt_bytecode, message) in messages {
-            if let Err(err) = self.execute_async_message(message, opt_bytecode) {
+            let message_id = message.compute_id();
+            let result = self.executor.execute_async_message(message, opt_bytecode);
+            if let Err(err) = &result {
                 debug!("failed executing async message: {}", err);
             }
+            context_guard!(self).async_message_statuses.push((
+                message_id,
+                AsyncMessageStatus::Executed {
+                    slot: *slot,
+                    success: result.is_ok(),
+                },
+            ));
         }
 
         // Check if there is a block at this slot
@@ -803,19 +1073,31 @@ impl ExecutionState {
             // Set block credits
             let mut block_credits = self.config.block_reward;
 
-            // Try executing the operations of this block in the order in which they appear in the block.
+            // Try executing the operations of this block in the order in which they appear in the block,
+            // or with the same result if they are executed in parallel.
             // Errors are logged but do not interrupt the execution of the slot.
-            for operation in operations.into_iter() {
-                if let Err(err) = self.execute_operation(
-                    &operation,
+            if let Some(fork_pool) = &self.fork_pool {
+                self.execute_operations_in_parallel(
+                    fork_pool,
+                    &operations,
                     stored_block.content.header.content.slot,
+                    block_id,
                     &mut remaining_block_gas,
                     &mut block_credits,
-                ) {
-                    debug!(
-                        "failed executing operation {} in block {}: {}",
-                        operation.id, block_id, err
-                    );
+                );
+            } else {
+                for operation in operations.into_iter() {
+                    if let Err(err) = self.executor.execute_operation(
+                        &operation,
+                        stored_block.content.header.content.slot,
+                        &mut remaining_block_gas,
+                        &mut block_credits,
+                    ) {
+                        debug!(
+                            "failed executing operation {} in block {}: {}",
+                            operation.id, block_id, err
+                        );
+                    }
                 }
             }
 
@@ -895,6 +1177,149 @@ impl ExecutionState {
         context_guard!(self).settle_slot()
     }
 
+    /// Executes the operations of a block, running the ones that do not depend on each other in parallel,
+    /// with the same result as executing them one after another in the block order.
+    ///
+    /// Operations are executed by rounds of at most `parallel_execution_threads` operations.
+    /// The operations of a round are executed in parallel, each on its own fork of the execution context,
+    /// then their outputs are merged back into the execution context in the block order:
+    /// * an operation that read a part of the ledger written by an operation merged before it in the round
+    ///   is executed again on a fork of the current execution context
+    /// * an operation whose execution depends on the order of the operations within the slot
+    ///   (roll operations, address creations, emitted messages, random draws)
+    ///   is executed again on the execution context itself, and the next round starts after it
+    ///
+    /// # Arguments
+    /// * `fork_pool`: threads executing the operations of a round
+    /// * `operations`: operations of the block, in the block order
+    /// * `block_slot`: slot of the block
+    /// * `block_id`: id of the block
+    /// * `remaining_block_gas`: mutable reference towards the remaining gas in the block
+    /// * `block_credits`: mutable reference towards the total block reward/fee credits
+    fn execute_operations_in_parallel(
+        &self,
+        fork_pool: &ForkExecutionPool,
+        operations: &[WrappedOperation],
+        block_slot: Slot,
+        block_id: &BlockId,
+        remaining_block_gas: &mut u64,
+        block_credits: &mut Amount,
+    ) {
+        let round_size = self.config.parallel_execution_threads.max(1);
+        let mut next_index = 0;
+        while next_index < operations.len() {
+            let round = &operations[next_index..operations.len().min(next_index + round_size)];
+
+            // execute the operations of the round in parallel, except the roll operations
+            let batch: Vec<(WrappedOperation, Option<ExecutionContext>)> = {
+                let context = context_guard!(self);
+                let base_ledger_changes = Arc::new(context.get_ledger_snapshot());
+                round
+                    .iter()
+                    .map(|operation| {
+                        let fork = (!Self::is_roll_operation(operation)).then(|| {
+                            context.fork(
+                                base_ledger_changes.clone(),
+                                self.final_state.clone(),
+                                self.active_history.clone(),
+                                self.bytecode_cache.clone(),
+                            )
+                        });
+                        (operation.clone(), fork)
+                    })
+                    .collect()
+            };
+            let outputs = fork_pool.execute(batch, block_slot);
+
+            // merge the outputs in the block order
+            let mut round_writes = LedgerWrites::default();
+            for (operation, output) in round.iter().zip(outputs) {
+                next_index += 1;
+
+                // execute the operation again if it read a part of the ledger written in this round
+                let output = output.map(|(result, fork_output)| {
+                    if !round_writes.intersects(&fork_output.ledger_reads) {
+                        return (result, fork_output);
+                    }
+                    let fork = {
+                        let context = context_guard!(self);
+                        context.fork(
+                            Arc::new(context.get_ledger_snapshot()),
+                            self.final_state.clone(),
+                            self.active_history.clone(),
+                            self.bytecode_cache.clone(),
+                        )
+                    };
+                    OperationExecutor::execute_operation_on_fork(
+                        self.config.clone(),
+                        fork,
+                        operation,
+                        block_slot,
+                    )
+                });
+
+                let mut context = context_guard!(self);
+                let (result, ends_round) = match output {
+                    Some((result, fork_output))
+                        if !context.fork_is_order_dependent(&fork_output) =>
+                    {
+                        let result = result.and_then(|_| {
+                            // finish the checks that execute_operation does in sequence
+                            let new_remaining_block_gas = remaining_block_gas
+                                .checked_sub(operation.get_gas_usage())
+                                .ok_or_else(|| {
+                                    ExecutionError::NotEnoughGas(
+                                        "not enough remaining block gas to execute operation"
+                                            .to_string(),
+                                    )
+                                })?;
+                            if context.is_op_executed(&operation.id) {
+                                return Err(ExecutionError::IncludeOperationError(
+                                    "operation was executed previously".to_string(),
+                                ));
+                            }
+                            *remaining_block_gas = new_remaining_block_gas;
+                            *block_credits = block_credits.saturating_add(operation.content.fee);
+                            round_writes.extend(&fork_output.ledger_changes);
+                            context.merge_fork(fork_output);
+                            Ok(())
+                        });
+                        (result, false)
+                    }
+                    _ => {
+                        // execute the operation on the execution context and start a new round after it
+                        drop(context);
+                        let result = self.executor.execute_operation(
+                            operation,
+                            block_slot,
+                            remaining_block_gas,
+                            block_credits,
+                        );
+                        (result, true)
+                    }
+                };
+                if let Err(err) = result {
+                    debug!(
+                        "failed executing operation {} in block {}: {}",
+                        operation.id, block_id, err
+                    );
+                }
+                if ends_round {
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Checks whether an operation is a roll buy or sell,
+    /// whose execution depends on the roll state that is not forked (see `ExecutionContext::fork`)
+    fn is_roll_operation(operation: &WrappedOperation) -> bool {
+        matches!(
+            operation.content.op,
+            OperationType::RollBuy { .. } | OperationType::RollSell { .. }
+        )
+    }
+
     /// Execute a candidate slot
     pub fn execute_candidate_slot(
         &mut self,
@@ -965,7 +1390,10 @@ impl ExecutionState {
                 // speculative execution front result matches what we want to compute
 
                 // apply the cached output and return
+                self.index_final_slot(slot, exec_target, &exec_out.receipts);
                 self.apply_final_execution_output(exec_out);
+                self.broadcast_final_block(slot, exec_target);
+                self.record_final_slot(slot, exec_target);
 
                 debug!("execute_final_slot: found in cache, applied cache");
                 return;
@@ -994,10 +1422,146 @@ impl ExecutionState {
         debug!("execute_final_slot: execution finished");
 
         // apply execution output to final state
+        self.index_final_slot(slot, exec_target, &exec_out.receipts);
         self.apply_final_execution_output(exec_out);
+        self.broadcast_final_block(slot, exec_target);
+        self.record_final_slot(slot, exec_target);
         debug!("execute_final_slot: execution result applied");
     }
 
+    /// Broadcasts the block executed at a final slot along with the resulting final state hash,
+    /// if broadcast is enabled
+    fn broadcast_final_block(&self, slot: &Slot, exec_target: Option<&(BlockId, Storage)>) {
+        if !self.config.broadcast_enabled {
+            return;
+        }
+        let Some((block_id, block_store)) = exec_target else {
+            return;
+        };
+        let block = block_store
+            .read_blocks()
+            .get(block_id)
+            .expect("Missing block in storage.")
+            .clone();
+        let operations = {
+            let ops = block_store.read_operations();
+            block
+                .content
+                .operations
+                .iter()
+                .map(|op_id| {
+                    ops.get(op_id)
+                        .expect("block operation absent from storage")
+                        .clone()
+                })
+                .collect()
+        };
+        let _ = self.channels.final_block_sender.send(FinalBlock {
+            block_id: *block_id,
+            slot: *slot,
+            header: block.content.header,
+            operations,
+            final_state_hash: self.final_state.read().final_state_hash,
+        });
+    }
+
+    /// Indexes the operations executed at a final slot by the addresses they involve,
+    /// if the address index is enabled
+    fn index_final_slot(
+        &self,
+        slot: &Slot,
+        exec_target: Option<&(BlockId, Storage)>,
+        receipts: &PreHashMap<OperationId, OperationReceipt>,
+    ) {
+        let Some(index) = &self.final_address_index else {
+            return;
+        };
+        let Some((block_id, block_store)) = exec_target else {
+            return;
+        };
+        let blocks = block_store.read_blocks();
+        let block = blocks.get(block_id).expect("Missing block in storage.");
+        let ops = block_store.read_operations();
+        let mut activities = Vec::new();
+        for (index_in_block, op_id) in block.content.operations.iter().enumerate() {
+            // operations that were not executed (e.g. already executed or expired) are not indexed
+            let Some(receipt) = receipts.get(op_id) else {
+                continue;
+            };
+            let op = ops.get(op_id).expect("block operation absent from storage");
+            for (address, roles) in op.get_address_roles() {
+                activities.push((
+                    address,
+                    AddressActivity {
+                        operation_id: *op_id,
+                        slot: *slot,
+                        index_in_block: index_in_block as u64,
+                        roles,
+                        success: receipt.success,
+                    },
+                ));
+            }
+        }
+        index.write_activities(&activities);
+    }
+
+    /// Records the block executed at a final slot and the resulting final state hash,
+    /// if final slot recording is enabled
+    fn record_final_slot(&self, slot: &Slot, exec_target: Option<&(BlockId, Storage)>) {
+        let Some(records) = &self.final_slot_records else {
+            return;
+        };
+        let (block, operations, endorsed_blocks) = match exec_target {
+            Some((block_id, block_store)) => {
+                let blocks = block_store.read_blocks();
+                let block = blocks
+                    .get(block_id)
+                    .expect("Missing block in storage.")
+                    .clone();
+                let operations = {
+                    let ops = block_store.read_operations();
+                    block
+                        .content
+                        .operations
+                        .iter()
+                        .map(|op_id| {
+                            ops.get(op_id)
+                                .expect("block operation absent from storage")
+                                .clone()
+                        })
+                        .collect()
+                };
+                // endorsements of a block usually all target the same block
+                let endorsed_blocks: PreHashMap<BlockId, WrappedBlock> = block
+                    .content
+                    .header
+                    .content
+                    .endorsements
+                    .iter()
+                    .map(|endo| {
+                        let endorsed_block = blocks
+                            .get(&endo.content.endorsed_block)
+                            .expect("endorsed block absent from storage");
+                        (endorsed_block.id, endorsed_block.clone())
+                    })
+                    .collect();
+                (
+                    Some(block),
+                    operations,
+                    endorsed_blocks.into_values().collect(),
+                )
+            }
+            None => (None, Vec::new(), Vec::new()),
+        };
+        records.write_record(&FinalSlotRecord {
+            slot: *slot,
+            block,
+            operations,
+            endorsed_blocks,
+            final_state_hash: self.final_state.read().final_state_hash,
+        });
+    }
+
     /// Runs a read-only execution request.
     /// The executed bytecode appears to be able to read and write the consensus state,
     /// but all accumulated changes are simply returned as an `ExecutionOutput` object,
@@ -1024,24 +1588,50 @@ impl ExecutionState {
         }
 
         // set the execution slot to be the one after the latest executed active slot
-        let slot = self
+        let mut slot = self
             .active_cursor
             .get_next_slot(self.config.thread_count)
             .expect("slot overflow in readonly execution");
 
+        // an operation can only be executed in a block of the thread of its sender
+        if let ReadOnlyExecutionTarget::Operation(operation) = &req.target {
+            let op_thread = operation
+                .creator_address
+                .get_thread(self.config.thread_count);
+            while slot.thread != op_thread {
+                slot = slot
+                    .get_next_slot(self.config.thread_count)
+                    .expect("slot overflow in readonly execution");
+            }
+        }
+
         // create a readonly execution context
-        let execution_context = ExecutionContext::readonly(
+        let caller_addr = req.call_stack.last().map(|elem| elem.address);
+        let mut execution_context = ExecutionContext::readonly(
             self.config.clone(),
             slot,
             req.max_gas,
             req.call_stack,
             self.final_state.clone(),
             self.active_history.clone(),
+            self.bytecode_cache.clone(),
         );
 
+        // assume the requested ledger state for the overridden addresses
+        execution_context.apply_state_overrides(&req.state_overrides);
+
         // run the interpreter according to the target type
         let exec_response = match req.target {
             ReadOnlyExecutionTarget::BytecodeExecution(bytecode) => {
+                // start tracing the calls made by the bytecode
+                if let Some(caller_addr) = caller_addr {
+                    execution_context.start_call_trace(
+                        caller_addr,
+                        Some("main".to_string()),
+                        Amount::zero(),
+                    );
+                }
+
                 // set the execution context for execution
                 *context_guard!(self) = execution_context;
 
@@ -1049,7 +1639,7 @@ impl ExecutionState {
                 massa_sc_runtime::run_main(
                     &bytecode,
                     req.max_gas,
-                    &*self.execution_interface,
+                    &*self.executor.execution_interface,
                     self.config.gas_costs.clone(),
                 )
                 .map_err(|err| ExecutionError::RuntimeError(err.to_string()))?
@@ -1064,6 +1654,13 @@ impl ExecutionState {
                     .get_bytecode(&target_addr)
                     .unwrap_or_default();
 
+                // start tracing the calls made by the function
+                execution_context.start_call_trace(
+                    target_addr,
+                    Some(target_func.clone()),
+                    Amount::zero(),
+                );
+
                 // set the execution context for execution
                 *context_guard!(self) = execution_context;
 
@@ -1073,19 +1670,56 @@ impl ExecutionState {
                     req.max_gas,
                     &target_func,
                     &parameter,
-                    &*self.execution_interface,
+                    &*self.executor.execution_interface,
                     self.config.gas_costs.clone(),
                 )
                 .map_err(|err| ExecutionError::RuntimeError(err.to_string()))?
             }
+            ReadOnlyExecutionTarget::Operation(operation) => {
+                // set the execution context for execution
+                *context_guard!(self) = execution_context;
+
+                // execute the operation exactly like in a block,
+                // the error is returned if the operation could not be included
+                let mut remaining_block_gas = self.config.max_gas_per_block;
+                let mut block_credits = Amount::zero();
+                self.executor.execute_operation(
+                    &operation,
+                    slot,
+                    &mut remaining_block_gas,
+                    &mut block_credits,
+                )?;
+
+                // return the execution output, the receipt describes the outcome of the execution
+                let execution_output = context_guard!(self).settle_slot();
+                let gas_cost = execution_output
+                    .receipts
+                    .get(&operation.id)
+                    .and_then(|receipt| receipt.gas_used)
+                    .unwrap_or_default();
+                let trace = execution_output
+                    .traces
+                    .last()
+                    .map(|trace| trace.root.clone());
+                return Ok(ReadOnlyExecutionOutput {
+                    out: execution_output,
+                    gas_cost,
+                    call_result: Vec::new(),
+                    trace,
+                });
+            }
         };
 
         // return the execution output
-        let execution_output = context_guard!(self).settle_slot();
+        let gas_cost = req.max_gas.saturating_sub(exec_response.remaining_gas);
+        let mut context = context_guard!(self);
+        let trace = context.finish_call_trace(Some(gas_cost), None);
+        let execution_output = context.settle_slot();
         Ok(ReadOnlyExecutionOutput {
             out: execution_output,
-            gas_cost: req.max_gas.saturating_sub(exec_response.remaining_gas),
+            gas_cost,
             call_result: exec_response.ret,
+            trace,
         })
     }
 
@@ -1106,6 +1740,82 @@ impl ExecutionState {
         )
     }
 
+    /// Gets the final balances of addresses at the output of a past final slot
+    pub fn get_final_balances_at(
+        &self,
+        addresses: &[Address],
+        slot: Slot,
+    ) -> Result<Vec<Option<Amount>>, ExecutionError> {
+        let final_state = self.final_state.read();
+        addresses
+            .iter()
+            .map(|addr| {
+                final_state
+                    .ledger
+                    .get_balance_at(addr, &slot)
+                    .map_err(|err| ExecutionError::ArchiveError(err.to_string()))
+            })
+            .collect()
+    }
+
+    /// Gets final datastore entries at the output of a past final slot
+    pub fn get_final_data_entries_at(
+        &self,
+        input: Vec<(Address, Vec<u8>)>,
+        slot: Slot,
+    ) -> Result<Vec<Option<Vec<u8>>>, ExecutionError> {
+        let final_state = self.final_state.read();
+        input
+            .iter()
+            .map(|(addr, key)| {
+                final_state
+                    .ledger
+                    .get_data_entry_at(addr, key, &slot)
+                    .map_err(|err| ExecutionError::ArchiveError(err.to_string()))
+            })
+            .collect()
+    }
+
+    /// Gets the final datastore keys of addresses at the output of a past final slot
+    pub fn get_final_datastore_keys_at(
+        &self,
+        addresses: &[Address],
+        slot: Slot,
+    ) -> Result<Vec<Option<BTreeSet<Vec<u8>>>>, ExecutionError> {
+        let final_state = self.final_state.read();
+        addresses
+            .iter()
+            .map(|addr| {
+                final_state
+                    .ledger
+                    .get_datastore_keys_at(addr, &slot)
+                    .map_err(|err| ExecutionError::ArchiveError(err.to_string()))
+            })
+            .collect()
+    }
+
+    /// Gets proofs of final ledger entries against the final state hash.
+    /// A `None` datastore key designates the balance of the address.
+    pub fn get_final_ledger_entry_proofs(
+        &self,
+        entries: Vec<(Address, Option<Vec<u8>>)>,
+    ) -> Result<Vec<LedgerEntryProof>, ExecutionError> {
+        // hold the lock for all the entries so that they are proven against the same slot
+        let final_state = self.final_state.read();
+        entries
+            .into_iter()
+            .map(|(address, datastore_key)| {
+                let key = match datastore_key {
+                    Some(datastore_key) => data_key!(address, datastore_key),
+                    None => balance_key!(address),
+                };
+                final_state
+                    .get_ledger_entry_proof(key)
+                    .map_err(|err| ExecutionError::LedgerProofError(err.to_string()))
+            })
+            .collect()
+    }
+
     /// Gets roll counts both at the latest final and active executed slots
     pub fn get_final_and_candidate_rolls(&self, address: &Address) -> (u64, u64) {
         let final_rolls = self.final_state.read().pos_state.get_rolls_for(address);
@@ -1184,6 +1894,130 @@ impl ExecutionState {
         (final_keys, candidate_keys)
     }
 
+    /// Gets the storage used by an address and the coins locked to pay for it,
+    /// both at the latest final and active executed slots
+    pub fn get_final_and_candidate_storage_reports(
+        &self,
+        addr: &Address,
+    ) -> (AddressStorageReport, AddressStorageReport) {
+        let (final_balance, candidate_balance) = self.get_final_and_candidate_balance(addr);
+        let (final_keys, candidate_keys) = self.get_final_and_candidate_datastore_keys(addr);
+        let final_bytecode = self.final_state.read().ledger.get_bytecode(addr);
+        let candidate_bytecode = match self.active_history.read().fetch_bytecode(addr) {
+            HistorySearchResult::Present(bytecode) => Some(bytecode),
+            HistorySearchResult::NoInfo => final_bytecode.clone(),
+            HistorySearchResult::Absent => None,
+        };
+
+        let final_report = {
+            let final_state = self.final_state.read();
+            self.compute_storage_report(
+                final_balance.is_some(),
+                final_bytecode.map_or(0, |bytecode| bytecode.len()),
+                final_keys.iter().map(|key| {
+                    let value = final_state.ledger.get_data_entry(addr, key);
+                    (key.len(), value.map_or(0, |value| value.len()))
+                }),
+            )
+        };
+        let candidate_report = self.compute_storage_report(
+            candidate_balance.is_some(),
+            candidate_bytecode.map_or(0, |bytecode| bytecode.len()),
+            candidate_keys.iter().map(|key| {
+                let (_, value) = self.get_final_and_active_data_entry(addr, key);
+                (key.len(), value.map_or(0, |value| value.len()))
+            }),
+        );
+        (final_report, candidate_report)
+    }
+
+    /// Sums up the storage of a ledger entry from its bytecode size and its datastore entry sizes,
+    /// and computes the coins locked by it following the storage costs charged during execution
+    fn compute_storage_report(
+        &self,
+        exists: bool,
+        bytecode_size: usize,
+        datastore_entry_sizes: impl Iterator<Item = (usize, usize)>,
+    ) -> AddressStorageReport {
+        if !exists {
+            return AddressStorageReport::default();
+        }
+        let mut report = AddressStorageReport {
+            bytecode_size: bytecode_size as u64,
+            ..Default::default()
+        };
+        for (key_size, value_size) in datastore_entry_sizes {
+            report.datastore_entry_count += 1;
+            report.datastore_key_bytes += key_size as u64;
+            report.datastore_value_bytes += value_size as u64;
+        }
+        let costs = &self.config.storage_costs_constants;
+        report.locked_coins = costs
+            .ledger_entry_base_cost
+            .saturating_add(
+                costs
+                    .ledger_cost_per_byte
+                    .saturating_mul_u64(report.bytecode_size + report.datastore_value_bytes),
+            )
+            .saturating_add(
+                costs
+                    .ledger_entry_datastore_base_cost
+                    .saturating_mul_u64(report.datastore_entry_count),
+            );
+        report
+    }
+
+    /// Get a page of the final and active datastore keys of the given address
+    /// that start with `prefix` and are strictly greater than `start_after` if set
+    pub fn get_final_and_candidate_datastore_keys_page(
+        &self,
+        addr: &Address,
+        prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: usize,
+    ) -> (BTreeSet<Vec<u8>>, BTreeSet<Vec<u8>>) {
+        let final_keys = self
+            .final_state
+            .read()
+            .ledger
+            .get_datastore_keys_page(addr, prefix, start_after, limit)
+            .unwrap_or_default();
+        let (reset, changes) =
+            self.active_history
+                .read()
+                .fetch_datastore_key_changes(addr, prefix, start_after);
+
+        // every key deleted in the history can hide one final key of the page:
+        // fetch as many more final keys so that the candidate page can still be filled
+        let deleted_count = changes.values().filter(|present| !**present).count();
+        let mut candidate_keys = if reset {
+            BTreeSet::new()
+        } else if deleted_count == 0 {
+            final_keys.clone()
+        } else {
+            self.final_state
+                .read()
+                .ledger
+                .get_datastore_keys_page(
+                    addr,
+                    prefix,
+                    start_after,
+                    limit.saturating_add(deleted_count),
+                )
+                .unwrap_or_default()
+        };
+        for (key, present) in changes {
+            if present {
+                candidate_keys.insert(key);
+            } else {
+                candidate_keys.remove(&key);
+            }
+        }
+        let candidate_keys = candidate_keys.into_iter().take(limit).collect();
+
+        (final_keys, candidate_keys)
+    }
+
     /// Returns for a given cycle the stakers taken into account
     /// by the selector. That correspond to the `roll_counts` in `cycle - 3`.
     ///
@@ -1211,33 +2045,136 @@ impl ExecutionState {
     /// * original caller address
     /// * operation id
     /// * event state (final, candidate or both)
-    pub fn get_filtered_sc_output_event(&self, filter: EventFilter) -> Vec<SCOutputEvent> {
-        match filter.is_final {
-            Some(true) => self
-                .final_events
-                .get_filtered_sc_output_events(&filter)
-                .into_iter()
-                .collect(),
-            Some(false) => self
+    /// * block id
+    /// * event data
+    ///
+    /// The candidate events are read right away, while the final events are read from disk
+    /// by the returned query, which does not need the execution state (see `FilteredEventsQuery`).
+    pub fn get_filtered_sc_output_event(&self, filter: EventFilter) -> FilteredEventsQuery {
+        let candidate_events: Vec<SCOutputEvent> = match filter.is_final {
+            Some(true) => Vec::new(),
+            _ => self
                 .active_history
                 .read()
                 .0
                 .iter()
                 .flat_map(|item| item.events.get_filtered_sc_output_events(&filter))
                 .collect(),
-            None => self
-                .final_events
-                .get_filtered_sc_output_events(&filter)
-                .into_iter()
-                .chain(
-                    self.active_history
-                        .read()
-                        .0
-                        .iter()
-                        .flat_map(|item| item.events.get_filtered_sc_output_events(&filter)),
-                )
-                .collect(),
+        };
+        // the final events are read up to the current final slot only, so that the slots
+        // finalized while the disk is read are not returned twice
+        let final_events = match filter.is_final {
+            Some(false) => None,
+            _ => {
+                let final_end = self
+                    .final_cursor
+                    .get_next_slot(self.config.thread_count)
+                    .expect("final slot overflow in get_filtered_sc_output_event");
+                let final_filter = EventFilter {
+                    end: Some(filter.end.map_or(final_end, |end| end.min(final_end))),
+                    ..filter.clone()
+                };
+                Some((self.final_events.clone(), final_filter))
+            }
+        };
+        FilteredEventsQuery::new(final_events, candidate_events, &filter)
+    }
+
+    /// Get the receipt of an executed operation, looking first at the active slots
+    /// and then at the final receipts kept on disk
+    pub fn get_operation_receipt(&self, op_id: &OperationId) -> Option<OperationReceipt> {
+        let history = self.active_history.read();
+        for hist_item in history.0.iter().rev() {
+            if let Some(receipt) = hist_item.receipts.get(op_id) {
+                return Some(receipt.clone());
+            }
+        }
+        self.final_receipts.get_receipt(op_id)
+    }
+
+    /// Get at most `limit` final operations involving an address, most recent first,
+    /// starting strictly before `cursor` if it is set.
+    /// Fails if the address index is disabled.
+    pub fn get_address_history(
+        &self,
+        address: &Address,
+        cursor: Option<AddressActivityCursor>,
+        limit: usize,
+    ) -> Result<Vec<AddressActivity>, ExecutionError> {
+        let Some(index) = &self.final_address_index else {
+            return Err(ExecutionError::AddressIndexError(
+                "the address index is disabled on this node".into(),
+            ));
+        };
+        Ok(index.get_address_history(address, cursor, limit))
+    }
+
+    /// Get the call trace of an executed operation, looking first at the active slots
+    /// and then at the final slots whose traces are kept in memory
+    pub fn get_operation_call_trace(&self, op_id: &OperationId) -> Option<ExecutionTrace> {
+        let history = self.active_history.read();
+        history
+            .0
+            .iter()
+            .rev()
+            .flat_map(|output| output.traces.iter())
+            .chain(
+                self.final_traces
+                    .iter()
+                    .rev()
+                    .flat_map(|(_, traces)| traces.iter()),
+            )
+            .find(|trace| matches!(trace.origin, TraceOrigin::Operation(id) if id == *op_id))
+            .cloned()
+    }
+
+    /// Get the call traces of the operations and messages executed at a slot,
+    /// or `None` if the slot is neither active nor among the final slots whose traces are kept
+    pub fn get_slot_call_traces(&self, slot: &Slot) -> Option<Vec<ExecutionTrace>> {
+        let history = self.active_history.read();
+        if let Some(output) = history.0.iter().find(|output| output.slot == *slot) {
+            return Some(output.traces.clone());
+        }
+        self.final_traces
+            .iter()
+            .find(|(final_slot, _)| final_slot == slot)
+            .map(|(_, traces)| traces.clone())
+    }
+
+    /// List the asynchronous messages of the final pool matching a filter, by decreasing priority
+    pub fn get_final_async_messages(&self, filter: &AsyncMessageFilter) -> Vec<AsyncMessageInfo> {
+        self.final_state
+            .read()
+            .async_pool
+            .get_messages(filter)
+            .into_iter()
+            .map(|(id, message)| AsyncMessageInfo {
+                id,
+                message: Some(message),
+                status: AsyncMessageStatus::Pending,
+            })
+            .collect()
+    }
+
+    /// Get an asynchronous message of the final pool, or its latest status
+    /// if it left the pool in one of the final slots whose statuses are kept
+    pub fn get_final_async_message(&self, id: &AsyncMessageId) -> Option<AsyncMessageInfo> {
+        if let Some(message) = self.final_state.read().async_pool.messages.get(id) {
+            return Some(AsyncMessageInfo {
+                id: *id,
+                message: Some(message.clone()),
+                status: AsyncMessageStatus::Pending,
+            });
         }
+        self.final_async_message_statuses
+            .iter()
+            .rev()
+            .find_map(|(_, statuses)| statuses.iter().find(|(msg_id, _)| msg_id == id))
+            .map(|(_, status)| AsyncMessageInfo {
+                id: *id,
+                message: None,
+                status: *status,
+            })
     }
 
     /// List which operations inside the provided list were not executed
@@ -0,0 +1,135 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Pool of threads executing the operations of a block on forks of the execution context
+//! (see `ExecutionState::execute_operations_in_parallel`).
+//!
+//! The threads are started along with the execution state and kept until it is dropped,
+//! so that executing a block in parallel does not spawn any thread.
+
+use crate::context::{ExecutionContext, ExecutionForkOutput};
+use crate::execution::OperationExecutor;
+use massa_execution_exports::{ExecutionConfig, ExecutionError};
+use massa_models::{operation::WrappedOperation, slot::Slot};
+use parking_lot::Mutex;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+/// Result and output of the execution of an operation on a fork
+/// (see `OperationExecutor::execute_operation_on_fork`)
+pub(crate) type ForkExecution = (Result<(), ExecutionError>, ExecutionForkOutput);
+
+/// Operation to execute on a fork, and where to send the execution back
+struct ForkJob {
+    /// index of the operation in the executed batch
+    index: usize,
+    /// fork of the execution context to execute the operation on
+    fork: ExecutionContext,
+    /// operation to execute
+    operation: WrappedOperation,
+    /// slot of the block in which the operation is included
+    block_slot: Slot,
+    /// sender of the executions of the batch
+    execution_sender: Sender<(usize, ForkExecution)>,
+}
+
+/// Persistent pool of threads executing operations on forks of the execution context
+pub(crate) struct ForkExecutionPool {
+    /// sender of the operations to execute, `None` once the pool is stopping
+    job_sender: Option<Sender<ForkJob>>,
+    /// threads of the pool
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl ForkExecutionPool {
+    /// Start the threads of the pool
+    ///
+    /// # Arguments
+    /// * `config`: execution configuration, giving the number of threads in `parallel_execution_threads`
+    pub fn new(config: ExecutionConfig) -> Self {
+        let (job_sender, job_receiver) = channel::<ForkJob>();
+        let job_receiver = Arc::new(Mutex::new(job_receiver));
+        let threads = (0..config.parallel_execution_threads.max(1))
+            .map(|index| {
+                let config = config.clone();
+                let job_receiver = job_receiver.clone();
+                std::thread::Builder::new()
+                    .name(format!("fork_execution_{}", index))
+                    .spawn(move || loop {
+                        // the receiver lock is released before the job is executed
+                        let job = job_receiver.lock().recv();
+                        let Ok(job) = job else {
+                            // the pool is stopping
+                            break;
+                        };
+                        let execution = OperationExecutor::execute_operation_on_fork(
+                            config.clone(),
+                            job.fork,
+                            &job.operation,
+                            job.block_slot,
+                        );
+                        // the batch is dropped if another of its executions panicked
+                        let _ = job.execution_sender.send((job.index, execution));
+                    })
+                    .expect("failed to spawn fork execution thread")
+            })
+            .collect();
+        ForkExecutionPool {
+            job_sender: Some(job_sender),
+            threads,
+        }
+    }
+
+    /// Execute a batch of operations in parallel, each on its own fork
+    ///
+    /// # Arguments
+    /// * `batch`: the operations with the fork to execute each of them on, or `None` to skip it
+    /// * `block_slot`: slot of the block in which the operations are included
+    ///
+    /// # Returns
+    /// The executions, in the order of the batch, `None` for the skipped operations
+    pub fn execute(
+        &self,
+        batch: Vec<(WrappedOperation, Option<ExecutionContext>)>,
+        block_slot: Slot,
+    ) -> Vec<Option<ForkExecution>> {
+        let job_sender = self
+            .job_sender
+            .as_ref()
+            .expect("fork execution pool stopped");
+        let (execution_sender, execution_receiver) = channel();
+        let mut executions: Vec<Option<ForkExecution>> = batch.iter().map(|_| None).collect();
+        let mut expected_count = 0;
+        for (index, (operation, fork)) in batch.into_iter().enumerate() {
+            if let Some(fork) = fork {
+                job_sender
+                    .send(ForkJob {
+                        index,
+                        fork,
+                        operation,
+                        block_slot,
+                        execution_sender: execution_sender.clone(),
+                    })
+                    .expect("fork execution threads stopped");
+                expected_count += 1;
+            }
+        }
+        drop(execution_sender);
+        for (index, execution) in execution_receiver.iter() {
+            executions[index] = Some(execution);
+            expected_count -= 1;
+        }
+        assert_eq!(expected_count, 0, "parallel operation execution panicked");
+        executions
+    }
+}
+
+impl Drop for ForkExecutionPool {
+    /// Stop the threads of the pool once they are done with their current job
+    fn drop(&mut self) {
+        self.job_sender = None;
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+    }
+}
@@ -7,14 +7,19 @@
 
 use crate::context::ExecutionContext;
 use anyhow::{anyhow, bail, Result};
-use massa_async_pool::{AsyncMessage, AsyncMessageTrigger};
+use massa_async_pool::{
+    AsyncMessage, AsyncMessageTrigger, AsyncMessageTriggerDeserializer,
+    ASYNC_MESSAGE_TRIGGER_ARGUMENT_V1,
+};
 use massa_execution_exports::ExecutionConfig;
 use massa_execution_exports::ExecutionStackElement;
-use massa_models::config::MAX_DATASTORE_KEY_LENGTH;
+use massa_models::config::{MAX_ASYNC_MESSAGE_TRIGGER_LENGTH, MAX_DATASTORE_KEY_LENGTH};
+use massa_models::execution::DatastoreAccess;
 use massa_models::{
     address::Address, amount::Amount, slot::Slot, timeslots::get_block_slot_timestamp,
 };
 use massa_sc_runtime::{Interface, InterfaceClone};
+use massa_serialization::{DeserializeError, Deserializer};
 use parking_lot::Mutex;
 use rand::Rng;
 use std::collections::BTreeSet;
@@ -32,6 +37,18 @@ macro_rules! context_guard {
     };
 }
 
+/// helper for recording a datastore access in the traced call tree
+macro_rules! trace_datastore_access {
+    ($context:ident, $accesses:ident, $addr:expr, $key:expr) => {
+        $context.record_call_trace(|call| {
+            call.$accesses.push(DatastoreAccess {
+                address: $addr,
+                key: $key.to_vec(),
+            })
+        })
+    };
+}
+
 /// an implementation of the Interface trait (see massa-sc-runtime crate)
 #[derive(Clone)]
 pub struct InterfaceImpl {
@@ -51,6 +68,36 @@ impl InterfaceImpl {
         InterfaceImpl { config, context }
     }
 
+    /// Decodes the trigger given to `send_message` in its versioned argument `ASYNC_MESSAGE_TRIGGER_ARGUMENT_V1`
+    ///
+    /// # Arguments
+    /// * `emission_slot`: slot at which the message is sent
+    /// * `serialized_trigger`: trigger serialized by `AsyncMessageTriggerSerializer`
+    ///
+    /// # Returns
+    /// An error if the network upgrade activating those triggers has not happened at the emission slot,
+    /// or if the trigger is invalid
+    fn versioned_message_trigger(
+        &self,
+        emission_slot: Slot,
+        serialized_trigger: &[u8],
+    ) -> Result<AsyncMessageTrigger> {
+        match self.config.async_message_triggers_activation_period {
+            Some(period) if emission_slot.period >= period => {}
+            _ => bail!("message triggers other than a ledger change are not activated yet"),
+        }
+        if serialized_trigger.len() > MAX_ASYNC_MESSAGE_TRIGGER_LENGTH {
+            bail!("message trigger is too long")
+        }
+        let (rest, trigger) = AsyncMessageTriggerDeserializer::new(MAX_DATASTORE_KEY_LENGTH as u32)
+            .deserialize::<DeserializeError>(serialized_trigger)
+            .map_err(|err| anyhow!("invalid message trigger: {}", err))?;
+        if !rest.is_empty() {
+            bail!("invalid message trigger: trailing bytes")
+        }
+        Ok(trigger)
+    }
+
     #[cfg(any(feature = "gas_calibration", feature = "benchmarking"))]
     /// Used to create an default interface to run SC in a test environment
     pub fn new_default(
@@ -61,8 +108,12 @@ impl InterfaceImpl {
 
         let config = ExecutionConfig::default();
         let (final_state, _tempfile, _tempdir) = crate::tests::get_sample_state().unwrap();
-        let mut execution_context =
-            ExecutionContext::new(config.clone(), final_state, Default::default());
+        let mut execution_context = ExecutionContext::new(
+            config.clone(),
+            final_state,
+            Default::default(),
+            Default::default(),
+        );
         execution_context.stack = vec![ExecutionStackElement {
             address: sender_addr,
             coins: Amount::zero(),
@@ -153,6 +204,7 @@ impl Interface for InterfaceImpl {
             owned_addresses: vec![to_address],
             operation_datastore: None,
         });
+        context.push_call_trace(to_address, None, coins);
 
         // return the target bytecode
         Ok(bytecode)
@@ -166,6 +218,7 @@ impl Interface for InterfaceImpl {
         if context.stack.pop().is_none() {
             bail!("call stack out of bounds")
         }
+        context.pop_call_trace();
 
         Ok(())
     }
@@ -248,7 +301,8 @@ impl Interface for InterfaceImpl {
     /// The datastore value matching the provided key, if found, otherwise an error.
     fn raw_get_data_for(&self, address: &str, key: &[u8]) -> Result<Vec<u8>> {
         let addr = &massa_models::address::Address::from_str(address)?;
-        let context = context_guard!(self);
+        let mut context = context_guard!(self);
+        trace_datastore_access!(context, datastore_reads, *addr, key);
         match context.get_data_entry(addr, key) {
             Some(value) => Ok(value),
             _ => bail!("data entry not found"),
@@ -266,6 +320,7 @@ impl Interface for InterfaceImpl {
     fn raw_set_data_for(&self, address: &str, key: &[u8], value: &[u8]) -> Result<()> {
         let addr = massa_models::address::Address::from_str(address)?;
         let mut context = context_guard!(self);
+        trace_datastore_access!(context, datastore_writes, addr, key);
         context.set_data_entry(&addr, key.to_vec(), value.to_vec())?;
         Ok(())
     }
@@ -279,7 +334,9 @@ impl Interface for InterfaceImpl {
     /// * value: value to append
     fn raw_append_data_for(&self, address: &str, key: &[u8], value: &[u8]) -> Result<()> {
         let addr = massa_models::address::Address::from_str(address)?;
-        context_guard!(self).append_data_entry(&addr, key.to_vec(), value.to_vec())?;
+        let mut context = context_guard!(self);
+        trace_datastore_access!(context, datastore_writes, addr, key);
+        context.append_data_entry(&addr, key.to_vec(), value.to_vec())?;
         Ok(())
     }
 
@@ -291,7 +348,9 @@ impl Interface for InterfaceImpl {
     /// * key: string key of the datastore entry to delete
     fn raw_delete_data_for(&self, address: &str, key: &[u8]) -> Result<()> {
         let addr = &massa_models::address::Address::from_str(address)?;
-        context_guard!(self).delete_data_entry(addr, key)?;
+        let mut context = context_guard!(self);
+        trace_datastore_access!(context, datastore_writes, *addr, key);
+        context.delete_data_entry(addr, key)?;
         Ok(())
     }
 
@@ -305,7 +364,8 @@ impl Interface for InterfaceImpl {
     /// true if the address exists and has the entry matching the provided key in its datastore, otherwise false
     fn has_data_for(&self, address: &str, key: &[u8]) -> Result<bool> {
         let addr = massa_models::address::Address::from_str(address)?;
-        let context = context_guard!(self);
+        let mut context = context_guard!(self);
+        trace_datastore_access!(context, datastore_reads, addr, key);
         Ok(context.has_data_entry(&addr, key))
     }
 
@@ -317,8 +377,9 @@ impl Interface for InterfaceImpl {
     /// # Returns
     /// The datastore value matching the provided key, if found, otherwise an error.
     fn raw_get_data(&self, key: &[u8]) -> Result<Vec<u8>> {
-        let context = context_guard!(self);
+        let mut context = context_guard!(self);
         let addr = context.get_current_address()?;
+        trace_datastore_access!(context, datastore_reads, addr, key);
         match context.get_data_entry(&addr, key) {
             Some(data) => Ok(data),
             _ => bail!("data entry not found"),
@@ -336,6 +397,7 @@ impl Interface for InterfaceImpl {
     fn raw_set_data(&self, key: &[u8], value: &[u8]) -> Result<()> {
         let mut context = context_guard!(self);
         let addr = context.get_current_address()?;
+        trace_datastore_access!(context, datastore_writes, addr, key);
         context.set_data_entry(&addr, key.to_vec(), value.to_vec())?;
         Ok(())
     }
@@ -350,6 +412,7 @@ impl Interface for InterfaceImpl {
     fn raw_append_data(&self, key: &[u8], value: &[u8]) -> Result<()> {
         let mut context = context_guard!(self);
         let addr = context.get_current_address()?;
+        trace_datastore_access!(context, datastore_writes, addr, key);
         context.append_data_entry(&addr, key.to_vec(), value.to_vec())?;
         Ok(())
     }
@@ -362,6 +425,7 @@ impl Interface for InterfaceImpl {
     fn raw_delete_data(&self, key: &[u8]) -> Result<()> {
         let mut context = context_guard!(self);
         let addr = context.get_current_address()?;
+        trace_datastore_access!(context, datastore_writes, addr, key);
         context.delete_data_entry(&addr, key)?;
         Ok(())
     }
@@ -374,8 +438,9 @@ impl Interface for InterfaceImpl {
     /// # Returns
     /// true if the address exists and has the entry matching the provided key in its datastore, otherwise false
     fn has_data(&self, key: &[u8]) -> Result<bool> {
-        let context = context_guard!(self);
+        let mut context = context_guard!(self);
         let addr = context.get_current_address()?;
+        trace_datastore_access!(context, datastore_reads, addr, key);
         Ok(context.has_data_entry(&addr, key))
     }
 
@@ -653,6 +718,11 @@ impl Interface for InterfaceImpl {
     /// * `fee`: Fee to pay
     /// * `raw_coins`: Coins given by the sender
     /// * `data`: Message data
+    /// * `filter`: Optional trigger of the message: an address and an optional datastore key to watch.
+    ///   Once the network upgrade activating the other triggers has happened,
+    ///   the versioned trigger argument `ASYNC_MESSAGE_TRIGGER_ARGUMENT_V1` in place of the address
+    ///   with a trigger serialized in the versioned format in place of the key can also be given
+    ///   (see `InterfaceImpl::versioned_message_trigger`).
     fn send_message(
         &self,
         target_address: &str,
@@ -679,6 +749,24 @@ impl Interface for InterfaceImpl {
         let fee = Amount::from_raw(raw_fee);
         execution_context.transfer_coins(Some(sender), None, coins, true)?;
         execution_context.transfer_coins(Some(sender), None, fee, true)?;
+        let trigger = match filter {
+            None => None,
+            Some((ASYNC_MESSAGE_TRIGGER_ARGUMENT_V1, serialized_trigger)) => Some(
+                self.versioned_message_trigger(emission_slot, serialized_trigger.unwrap_or(&[]))?,
+            ),
+            Some((addr, key)) => {
+                let datastore_key = key.map(|k| k.to_vec());
+                if let Some(ref k) = datastore_key {
+                    if k.len() > MAX_DATASTORE_KEY_LENGTH as usize {
+                        bail!("datastore key is too long")
+                    }
+                }
+                Some(AsyncMessageTrigger::LedgerChange {
+                    address: Address::from_str(addr)?,
+                    datastore_key,
+                })
+            }
+        };
         execution_context.push_new_message(AsyncMessage::new_with_hash(
             emission_slot,
             emission_index,
@@ -691,20 +779,7 @@ impl Interface for InterfaceImpl {
             Slot::new(validity_start.0, validity_start.1),
             Slot::new(validity_end.0, validity_end.1),
             data.to_vec(),
-            filter
-                .map(|(addr, key)| {
-                    let datastore_key = key.map(|k| k.to_vec());
-                    if let Some(ref k) = datastore_key {
-                        if k.len() > MAX_DATASTORE_KEY_LENGTH as usize {
-                            bail!("datastore key is too long")
-                        }
-                    }
-                    Ok::<AsyncMessageTrigger, _>(AsyncMessageTrigger {
-                        address: Address::from_str(addr)?,
-                        datastore_key,
-                    })
-                })
-                .transpose()?,
+            trigger,
         ));
         execution_context.created_message_index += 1;
         Ok(())
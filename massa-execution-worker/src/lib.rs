@@ -62,9 +62,36 @@
 //! It also serves as an access point to the current execution state and speculative ledger
 //! as defined in `speculative_ledger.rs`.
 //!
+//! ## `fork_pool.rs`
+//! Persistent pool of threads executing the operations of a block in parallel on forks of the execution context.
+//!
+//! ## `disk_store.rs`
+//! Opens and prunes the disk stores below, which share the same layout conventions.
+//!
+//! ## `event_db.rs`
+//! Persists the final execution events on disk for a configurable number of periods,
+//! indexed by emitter address, original caller address and operation id.
+//!
+//! ## `receipt_db.rs`
+//! Persists the receipts of the operations executed in final slots on disk
+//! for a configurable number of periods.
+//!
+//! ## `slot_record_db.rs`
+//! Optionally records on disk the blocks executed at final slots and the resulting final state hashes,
+//! for a configurable number of periods.
+//!
+//! ## `address_index_db.rs`
+//! Optionally indexes on disk the operations executed in final slots by the addresses they involve.
+//!
+//! ## `replay.rs`
+//! Executes the recorded final slots again offline from a final state snapshot,
+//! checking that the same final state hashes are obtained.
+//!
 //! ## `speculative_ledger.rs`
 //! A speculative (non-final) ledger that supports canceling already-executed operations
 //! in the case of some blockclique changes.
+//! It can also be forked to execute the operations of a block in parallel,
+//! recording the parts of the ledger each operation read to detect conflicts between them.
 //!
 //! ## `speculative_executed_ops.rs`
 //! A speculative (non-final) list of previously executed operations to prevent reuse.
@@ -84,11 +111,19 @@
 #![feature(option_get_or_insert_default)]
 
 mod active_history;
+mod address_index_db;
+mod bytecode_cache;
 mod context;
 mod controller;
+mod disk_store;
+mod event_db;
 mod execution;
+mod fork_pool;
 mod interface_impl;
+mod receipt_db;
+mod replay;
 mod request_queue;
+mod slot_record_db;
 mod slot_sequencer;
 mod speculative_async_pool;
 mod speculative_executed_ops;
@@ -97,6 +132,7 @@ mod speculative_roll_state;
 mod stats;
 mod worker;
 
+pub use replay::replay_final_slots;
 pub use worker::start_execution_worker;
 
 #[cfg(any(feature = "gas_calibration", feature = "benchmarking"))]
@@ -0,0 +1,153 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Module to persist on disk the receipts of the operations executed in final slots.
+//!
+//! Receipts are stored by operation id, and indexed by slot so that
+//! the receipts older than the configured retention can be pruned as new final slots are written.
+
+use crate::disk_store::{open_disk_store, prune_before, retention_start, CF_ERROR, CRUD_ERROR};
+use massa_models::{
+    operation::OperationId,
+    prehash::PreHashMap,
+    receipt::OperationReceipt,
+    slot::{Slot, SLOT_KEY_SIZE},
+};
+use rocksdb::{WriteBatch, DB};
+use std::path::PathBuf;
+
+const RECEIPTS_CF: &str = "receipts";
+const SLOT_INDEX_CF: &str = "slot_index";
+const SER_ERROR: &str = "critical: receipt serialization failed";
+const DESER_ERROR: &str = "critical: receipt deserialization failed";
+
+/// Disk store of the final operation receipts
+///
+/// Contains a `RocksDB` DB instance
+pub(crate) struct FinalReceiptDB {
+    db: DB,
+    retention_periods: u64,
+}
+
+impl FinalReceiptDB {
+    /// Create and initialize a new `FinalReceiptDB`.
+    ///
+    /// # Arguments
+    /// * path: path to the desired disk receipt db directory
+    /// * `retention_periods`: number of periods during which final receipts are kept
+    pub fn new(path: PathBuf, retention_periods: u64) -> Self {
+        FinalReceiptDB {
+            db: open_disk_store(path, &[RECEIPTS_CF, SLOT_INDEX_CF]),
+            retention_periods,
+        }
+    }
+
+    /// Write the final receipts of a slot and prune the receipts that are out of the retention window.
+    ///
+    /// Receipts are keyed by operation id, so a slot executed again after a restart
+    /// overwrites its receipts instead of duplicating them.
+    ///
+    /// # Arguments
+    /// * slot: final slot the operations were executed at
+    /// * receipts: receipts of the operations executed at that slot
+    pub fn write_receipts(&self, slot: Slot, receipts: &PreHashMap<OperationId, OperationReceipt>) {
+        let receipts_handle = self.db.cf_handle(RECEIPTS_CF).expect(CF_ERROR);
+        let index_handle = self.db.cf_handle(SLOT_INDEX_CF).expect(CF_ERROR);
+        let mut batch = WriteBatch::default();
+        for (op_id, receipt) in receipts {
+            let mut receipt = receipt.clone();
+            receipt.is_final = true;
+            batch.put_cf(
+                receipts_handle,
+                op_id.to_bytes(),
+                serde_json::to_vec(&receipt).expect(SER_ERROR),
+            );
+            batch.put_cf(
+                index_handle,
+                [&slot.to_bytes_key()[..], op_id.to_bytes()].concat(),
+                [],
+            );
+        }
+
+        // prune the receipts of the slots before the start of the retention window
+        prune_before(
+            &self.db,
+            &mut batch,
+            index_handle,
+            retention_start(slot, self.retention_periods),
+            |batch, key, _| batch.delete_cf(receipts_handle, &key[SLOT_KEY_SIZE..]),
+        );
+
+        self.db.write(batch).expect(CRUD_ERROR);
+    }
+
+    /// Get the final receipt of an operation, if it is still kept
+    pub fn get_receipt(&self, op_id: &OperationId) -> Option<OperationReceipt> {
+        let handle = self.db.cf_handle(RECEIPTS_CF).expect(CF_ERROR);
+        self.db
+            .get_cf(handle, op_id.to_bytes())
+            .expect(CRUD_ERROR)
+            .map(|value| serde_json::from_slice(&value).expect(DESER_ERROR))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FinalReceiptDB;
+    use massa_hash::Hash;
+    use massa_models::{
+        amount::Amount,
+        operation::OperationId,
+        prehash::PreHashMap,
+        receipt::{OperationExecutionError, OperationReceipt},
+        slot::Slot,
+        wrapped::Id,
+    };
+    use tempfile::TempDir;
+
+    fn receipt(op_id: OperationId, slot: Slot, success: bool) -> OperationReceipt {
+        OperationReceipt {
+            operation_id: op_id,
+            slot,
+            block_id: None,
+            is_final: false,
+            success,
+            error: (!success).then(|| OperationExecutionError::Transaction("failed".to_string())),
+            gas_used: None,
+            fee: Amount::from_raw(1),
+            coins: Amount::zero(),
+        }
+    }
+
+    #[test]
+    fn test_final_receipt_db() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = FinalReceiptDB::new(temp_dir.path().to_path_buf(), 10);
+
+        let mut op_ids = Vec::new();
+        for period in 1..=5u64 {
+            let slot = Slot::new(period, 0);
+            let op_id = OperationId::new(Hash::compute_from(&period.to_be_bytes()));
+            let mut receipts = PreHashMap::default();
+            receipts.insert(op_id, receipt(op_id, slot, period % 2 == 0));
+            db.write_receipts(slot, &receipts);
+            op_ids.push(op_id);
+        }
+
+        // receipts are read back as final
+        let stored = db.get_receipt(&op_ids[1]).expect("receipt not found");
+        assert!(stored.is_final);
+        assert!(stored.success);
+        assert_eq!(stored.slot, Slot::new(2, 0));
+        let stored = db.get_receipt(&op_ids[2]).expect("receipt not found");
+        assert_eq!(
+            stored.error,
+            Some(OperationExecutionError::Transaction("failed".to_string()))
+        );
+
+        // receipts out of the retention window are pruned
+        db.write_receipts(Slot::new(13, 0), &PreHashMap::default());
+        assert!(db.get_receipt(&op_ids[0]).is_none());
+        assert!(db.get_receipt(&op_ids[1]).is_none());
+        assert!(db.get_receipt(&op_ids[2]).is_some());
+    }
+}
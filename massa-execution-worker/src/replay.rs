@@ -0,0 +1,100 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Offline replay of recorded final slots.
+//!
+//! Starting from a final state loaded at some slot, the final slots recorded after it
+//! (see `slot_record_db.rs`) are executed again in order through `ExecutionState::execute_final_slot`,
+//! and the final state hash obtained after each slot is checked against the recorded one.
+//! Everything needed is read from the records, so that the replay does not need any network access.
+
+use crate::execution::ExecutionState;
+use crate::slot_record_db::FinalSlotRecordDB;
+use massa_execution_exports::{ExecutionChannels, ExecutionConfig, ExecutionError};
+use massa_final_state::FinalState;
+use massa_models::slot::Slot;
+use massa_pos_exports::SelectorController;
+use massa_storage::Storage;
+use parking_lot::RwLock;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::{debug, info};
+
+/// Replay the recorded final slots following the slot of the final state.
+///
+/// # Arguments
+/// * `config`: execution configuration, the final slots are not recorded again during the replay
+/// * `final_state`: final state at the slot the replay starts from
+/// * `selector`: selector controller, its draws must be computed from the same final state
+/// * `channels`: execution channels
+/// * `record_path`: path of the disk final slot record db to read from
+/// * `last_slot`: slot to stop at, if any, otherwise all the recorded slots are replayed
+///
+/// # Returns
+/// The last replayed slot, or an error if a slot is missing from the records
+/// or if a replayed final state hash differs from the recorded one
+pub fn replay_final_slots(
+    config: ExecutionConfig,
+    final_state: Arc<RwLock<FinalState>>,
+    selector: Box<dyn SelectorController>,
+    channels: ExecutionChannels,
+    record_path: PathBuf,
+    last_slot: Option<Slot>,
+) -> Result<Slot, ExecutionError> {
+    let thread_count = config.thread_count;
+    let records = FinalSlotRecordDB::new(record_path, config.final_slot_record_retention_periods);
+    let mut execution_state = ExecutionState::new(
+        ExecutionConfig {
+            final_slot_record: false,
+            ..config
+        },
+        final_state.clone(),
+        channels,
+    );
+
+    let mut current_slot = final_state.read().slot;
+    info!(
+        "replaying the recorded final slots after slot {}",
+        current_slot
+    );
+    for record in records.get_records_after(current_slot) {
+        if let Some(last_slot) = last_slot && record.slot > last_slot {
+            break;
+        }
+        let expected_slot = current_slot
+            .get_next_slot(thread_count)
+            .map_err(|err| ExecutionError::ReplayError(err.to_string()))?;
+        if record.slot != expected_slot {
+            return Err(ExecutionError::ReplayError(format!(
+                "no record for slot {}",
+                expected_slot
+            )));
+        }
+
+        // gather the recorded block and its dependencies in a storage
+        let exec_target = record.block.map(|block| {
+            let mut storage = Storage::create_root();
+            storage.store_operations(record.operations);
+            for endorsed_block in record.endorsed_blocks {
+                storage.store_block(endorsed_block);
+            }
+            let block_id = block.id;
+            storage.store_block(block);
+            (block_id, storage)
+        });
+
+        execution_state.execute_final_slot(&record.slot, exec_target.as_ref(), selector.clone());
+
+        let replayed_hash = final_state.read().final_state_hash;
+        if replayed_hash != record.final_state_hash {
+            return Err(ExecutionError::ReplayError(format!(
+                "final state hash mismatch at slot {}: replayed {} but recorded {}",
+                record.slot, replayed_hash, record.final_state_hash
+            )));
+        }
+        debug!("replayed final slot {}", record.slot);
+        current_slot = record.slot;
+    }
+
+    info!("replay done up to slot {}", current_slot);
+    Ok(current_slot)
+}
@@ -0,0 +1,142 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Module to record on disk the input and the output of the execution of every final slot,
+//! so that the slots can later be re-executed offline (see `replay.rs`).
+//!
+//! Records are stored by slot, and the records older than the configured retention are pruned
+//! as new final slots are written: a replay has to start from a final state within the retention window.
+
+use crate::disk_store::{open_disk_store, prune_before, retention_start, CF_ERROR, CRUD_ERROR};
+use massa_hash::Hash;
+use massa_models::{block::WrappedBlock, operation::WrappedOperation, slot::Slot};
+use rocksdb::{Direction, IteratorMode, WriteBatch, DB};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+const RECORDS_CF: &str = "records";
+const SER_ERROR: &str = "critical: final slot record serialization failed";
+const DESER_ERROR: &str = "critical: final slot record deserialization failed";
+
+/// Everything needed to execute a final slot again, and the final state hash it resulted in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct FinalSlotRecord {
+    /// executed final slot
+    pub slot: Slot,
+    /// block executed at the slot, `None` for a miss
+    pub block: Option<WrappedBlock>,
+    /// operations of the block, in the block order
+    pub operations: Vec<WrappedOperation>,
+    /// blocks endorsed by the endorsements of the block
+    pub endorsed_blocks: Vec<WrappedBlock>,
+    /// final state hash at the output of the slot
+    pub final_state_hash: Hash,
+}
+
+/// Disk store of the final slot records
+///
+/// Contains a `RocksDB` DB instance
+pub(crate) struct FinalSlotRecordDB {
+    db: DB,
+    retention_periods: u64,
+}
+
+impl FinalSlotRecordDB {
+    /// Create and initialize a new `FinalSlotRecordDB`.
+    ///
+    /// # Arguments
+    /// * path: path to the desired disk slot record db directory
+    /// * `retention_periods`: number of periods during which final slot records are kept
+    pub fn new(path: PathBuf, retention_periods: u64) -> Self {
+        FinalSlotRecordDB {
+            db: open_disk_store(path, &[RECORDS_CF]),
+            retention_periods,
+        }
+    }
+
+    /// Write the record of a final slot, overwriting any previous record of that slot,
+    /// and prune the records that are out of the retention window
+    pub fn write_record(&self, record: &FinalSlotRecord) {
+        let handle = self.db.cf_handle(RECORDS_CF).expect(CF_ERROR);
+        let mut batch = WriteBatch::default();
+        prune_before(
+            &self.db,
+            &mut batch,
+            handle,
+            retention_start(record.slot, self.retention_periods),
+            |_, _, _| {},
+        );
+        batch.put_cf(
+            handle,
+            record.slot.to_bytes_key(),
+            serde_json::to_vec(record).expect(SER_ERROR),
+        );
+        self.db.write(batch).expect(CRUD_ERROR);
+    }
+
+    /// Iterate over the records of the slots after a given slot, in slot order
+    pub fn get_records_after(&self, slot: Slot) -> impl Iterator<Item = FinalSlotRecord> + '_ {
+        let handle = self.db.cf_handle(RECORDS_CF).expect(CF_ERROR);
+        self.db
+            .iterator_cf(
+                handle,
+                IteratorMode::From(&slot.to_bytes_key(), Direction::Forward),
+            )
+            .map(|item| {
+                let (_, value) = item.expect(CRUD_ERROR);
+                serde_json::from_slice::<FinalSlotRecord>(&value).expect(DESER_ERROR)
+            })
+            .filter(move |record| record.slot > slot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FinalSlotRecord, FinalSlotRecordDB};
+    use massa_hash::Hash;
+    use massa_models::slot::Slot;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_final_slot_record_db() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = FinalSlotRecordDB::new(temp_dir.path().to_path_buf(), 10);
+
+        // records are written out of order, and one of them is overwritten
+        for period in [3u64, 1, 2, 1] {
+            db.write_record(&FinalSlotRecord {
+                slot: Slot::new(period, 0),
+                block: None,
+                operations: Vec::new(),
+                endorsed_blocks: Vec::new(),
+                final_state_hash: Hash::compute_from(&period.to_be_bytes()),
+            });
+        }
+
+        // they are read back in slot order, starting after the given slot
+        let records: Vec<FinalSlotRecord> = db.get_records_after(Slot::new(1, 0)).collect();
+        assert_eq!(
+            records.iter().map(|record| record.slot).collect::<Vec<_>>(),
+            vec![Slot::new(2, 0), Slot::new(3, 0)]
+        );
+        assert_eq!(
+            records[0].final_state_hash,
+            Hash::compute_from(&2u64.to_be_bytes())
+        );
+        assert_eq!(db.get_records_after(Slot::new(0, 0)).count(), 3);
+
+        // records out of the retention window are pruned
+        db.write_record(&FinalSlotRecord {
+            slot: Slot::new(12, 0),
+            block: None,
+            operations: Vec::new(),
+            endorsed_blocks: Vec::new(),
+            final_state_hash: Hash::compute_from(&12u64.to_be_bytes()),
+        });
+        assert_eq!(
+            db.get_records_after(Slot::new(0, 0))
+                .map(|record| record.slot)
+                .collect::<Vec<_>>(),
+            vec![Slot::new(2, 0), Slot::new(3, 0), Slot::new(12, 0)]
+        );
+    }
+}
@@ -6,16 +6,92 @@
 //! but keeps track of the changes that were applied to it since its creation.
 
 use crate::active_history::{ActiveHistory, HistorySearchResult};
+use crate::bytecode_cache::BytecodeCache;
 use massa_execution_exports::ExecutionError;
 use massa_execution_exports::StorageCostsConstants;
 use massa_final_state::FinalState;
-use massa_ledger_exports::{Applicable, LedgerChanges, SetOrDelete, SetUpdateOrDelete};
-use massa_models::{address::Address, amount::Amount};
-use parking_lot::RwLock;
-use std::collections::BTreeSet;
+use massa_ledger_exports::{Applicable, LedgerChanges, SetOrDelete, SetOrKeep, SetUpdateOrDelete};
+use massa_models::{
+    address::Address, amount::Amount, execution::AddressStateOverride, prehash::PreHashSet,
+};
+use parking_lot::{Mutex, RwLock};
+use std::collections::{BTreeSet, HashSet};
 use std::sync::Arc;
 use tracing::debug;
 
+/// Part of the ledger read by an execution,
+/// used to detect conflicts between operations executed in parallel
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum LedgerAccess {
+    /// existence of the ledger entry of an address
+    Entry(Address),
+    /// balance of an address
+    Balance(Address),
+    /// bytecode of an address
+    Bytecode(Address),
+    /// list of the datastore keys of an address
+    DatastoreKeys(Address),
+    /// datastore entry of an address
+    DatastoreEntry(Address, Vec<u8>),
+}
+
+impl LedgerAccess {
+    /// Address whose ledger entry is accessed
+    fn address(&self) -> &Address {
+        match self {
+            LedgerAccess::Entry(addr)
+            | LedgerAccess::Balance(addr)
+            | LedgerAccess::Bytecode(addr)
+            | LedgerAccess::DatastoreKeys(addr)
+            | LedgerAccess::DatastoreEntry(addr, _) => addr,
+        }
+    }
+}
+
+/// Parts of the ledger written by a sequence of ledger changes
+#[derive(Default)]
+pub(crate) struct LedgerWrites {
+    /// addresses whose whole ledger entry was set or deleted
+    entries: PreHashSet<Address>,
+    /// other written parts of the ledger
+    accesses: HashSet<LedgerAccess>,
+}
+
+impl LedgerWrites {
+    /// Adds the parts of the ledger written by some ledger changes
+    pub fn extend(&mut self, changes: &LedgerChanges) {
+        for (addr, change) in &changes.0 {
+            match change {
+                SetUpdateOrDelete::Set(_) | SetUpdateOrDelete::Delete => {
+                    self.entries.insert(*addr);
+                }
+                SetUpdateOrDelete::Update(update) => {
+                    if let SetOrKeep::Set(_) = update.balance {
+                        self.accesses.insert(LedgerAccess::Balance(*addr));
+                    }
+                    if let SetOrKeep::Set(_) = update.bytecode {
+                        self.accesses.insert(LedgerAccess::Bytecode(*addr));
+                    }
+                    if !update.datastore.is_empty() {
+                        self.accesses.insert(LedgerAccess::DatastoreKeys(*addr));
+                    }
+                    for key in update.datastore.keys() {
+                        self.accesses
+                            .insert(LedgerAccess::DatastoreEntry(*addr, key.clone()));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Checks whether any of the given read parts of the ledger was written
+    pub fn intersects(&self, reads: &HashSet<LedgerAccess>) -> bool {
+        reads
+            .iter()
+            .any(|read| self.entries.contains(read.address()) || self.accesses.contains(read))
+    }
+}
+
 /// The `SpeculativeLedger` contains an thread-safe shared reference to the final ledger (read-only),
 /// a list of existing changes that happened o the ledger since its finality,
 /// as well as an extra list of "added" changes.
@@ -30,12 +106,22 @@ pub(crate) struct SpeculativeLedger {
     /// Slots should be consecutive, newest at the back.
     active_history: Arc<RwLock<ActiveHistory>>,
 
+    /// Cache of the bytecodes read from the final ledger, shared by all the executions
+    bytecode_cache: Arc<Mutex<BytecodeCache>>,
+
     /// list of ledger changes that were applied to this `SpeculativeLedger` since its creation
     #[cfg(all(not(feature = "gas_calibration"), not(feature = "benchmarking")))]
     added_changes: LedgerChanges,
     #[cfg(any(feature = "gas_calibration", feature = "benchmarking"))]
     pub added_changes: LedgerChanges,
 
+    /// changes applied before the added ones: those of the ledger this one was forked from (see `fork`),
+    /// or the state overrides of a read-only execution (see `apply_state_override`)
+    base_changes: Arc<LedgerChanges>,
+
+    /// parts of the ledger read so far, recorded only by forked ledgers
+    reads: Option<Mutex<HashSet<LedgerAccess>>>,
+
     /// max datastore key length
     max_datastore_key_length: u8,
 
@@ -55,9 +141,11 @@ impl SpeculativeLedger {
     /// # Arguments
     /// * `final_state`: thread-safe shared access to the final state (for reading only)
     /// * `active_history`: thread-safe shared access the speculative execution history
+    /// * `bytecode_cache`: thread-safe shared access to the cache of the final bytecodes
     pub fn new(
         final_state: Arc<RwLock<FinalState>>,
         active_history: Arc<RwLock<ActiveHistory>>,
+        bytecode_cache: Arc<Mutex<BytecodeCache>>,
         max_datastore_key_length: u8,
         max_bytecode_size: u64,
         max_datastore_value_size: u64,
@@ -66,7 +154,10 @@ impl SpeculativeLedger {
         SpeculativeLedger {
             final_state,
             added_changes: Default::default(),
+            base_changes: Default::default(),
+            reads: None,
             active_history,
+            bytecode_cache,
             max_datastore_key_length,
             max_datastore_value_size,
             max_bytecode_size,
@@ -90,6 +181,75 @@ impl SpeculativeLedger {
         self.added_changes = snapshot;
     }
 
+    /// Creates a `SpeculativeLedger` on top of the current state of this one, without any added changes.
+    /// The forked ledger records the parts of the ledger it reads (see `take_reads`).
+    ///
+    /// # Arguments
+    /// * `base_changes`: snapshot of the changes of this ledger (see `get_snapshot`), shared between forks
+    pub fn fork(&self, base_changes: Arc<LedgerChanges>) -> Self {
+        SpeculativeLedger {
+            final_state: self.final_state.clone(),
+            active_history: self.active_history.clone(),
+            bytecode_cache: self.bytecode_cache.clone(),
+            added_changes: Default::default(),
+            base_changes,
+            reads: Some(Default::default()),
+            max_datastore_key_length: self.max_datastore_key_length,
+            max_datastore_value_size: self.max_datastore_value_size,
+            max_bytecode_size: self.max_bytecode_size,
+            storage_costs_constants: self.storage_costs_constants,
+        }
+    }
+
+    /// Returns the parts of the ledger read so far by a forked `SpeculativeLedger`,
+    /// and resets them to nothing
+    pub fn take_reads(&mut self) -> HashSet<LedgerAccess> {
+        self.reads
+            .as_mut()
+            .map(|reads| std::mem::take(reads.get_mut()))
+            .unwrap_or_default()
+    }
+
+    /// Applies ledger changes on top of the added ones, such as the output of a fork (see `fork`)
+    pub fn apply_changes(&mut self, changes: LedgerChanges) {
+        self.added_changes.apply(changes);
+    }
+
+    /// Records a read part of the ledger if the `SpeculativeLedger` is a fork
+    fn record_read(&self, access: LedgerAccess) {
+        if let Some(reads) = &self.reads {
+            reads.lock().insert(access);
+        }
+    }
+
+    /// Forces the state of an address, creating it if it does not exist.
+    /// The override is applied to the base changes, below the added ones,
+    /// so that it is visible to the execution without being part of its output.
+    /// No storage costs are charged: this is only meant for read-only executions.
+    ///
+    /// # Arguments
+    /// * `state_override`: the balance, bytecode and datastore entries to assume for the address
+    pub fn apply_state_override(&mut self, state_override: &AddressStateOverride) {
+        let addr = state_override.address;
+        let mut changes = LedgerChanges::default();
+        if !self.entry_exists(&addr) {
+            changes.create_address(&addr);
+        }
+        if let Some(balance) = state_override.balance {
+            changes.set_balance(addr, balance);
+        }
+        if let Some(bytecode) = &state_override.bytecode {
+            changes.set_bytecode(addr, bytecode.clone());
+        }
+        for entry in &state_override.datastore {
+            match &entry.value {
+                Some(value) => changes.set_data_entry(addr, entry.key.clone(), value.clone()),
+                None => changes.delete_data_entry(addr, entry.key.clone()),
+            }
+        }
+        Arc::make_mut(&mut self.base_changes).apply(changes);
+    }
+
     /// Gets the effective balance of an address
     ///
     /// # Arguments:
@@ -98,13 +258,16 @@ impl SpeculativeLedger {
     /// # Returns
     /// Some(Amount) if the address was found, otherwise None
     pub fn get_balance(&self, addr: &Address) -> Option<Amount> {
-        // try to read from added changes > history > final_state
+        self.record_read(LedgerAccess::Balance(*addr));
+        // try to read from added changes > base changes > history > final_state
         self.added_changes.get_balance_or_else(addr, || {
-            match self.active_history.read().fetch_balance(addr) {
-                HistorySearchResult::Present(par_balance) => Some(par_balance),
-                HistorySearchResult::NoInfo => self.final_state.read().ledger.get_balance(addr),
-                HistorySearchResult::Absent => None,
-            }
+            self.base_changes.get_balance_or_else(addr, || {
+                match self.active_history.read().fetch_balance(addr) {
+                    HistorySearchResult::Present(par_balance) => Some(par_balance),
+                    HistorySearchResult::NoInfo => self.final_state.read().ledger.get_balance(addr),
+                    HistorySearchResult::Absent => None,
+                }
+            })
         })
     }
 
@@ -116,16 +279,34 @@ impl SpeculativeLedger {
     /// # Returns
     /// `Some(Vec<u8>)` if the address was found, otherwise None
     pub fn get_bytecode(&self, addr: &Address) -> Option<Vec<u8>> {
-        // try to read from added changes > history > final_state
+        self.record_read(LedgerAccess::Bytecode(*addr));
+        // try to read from added changes > base changes > history > final_state
         self.added_changes.get_bytecode_or_else(addr, || {
-            match self.active_history.read().fetch_bytecode(addr) {
-                HistorySearchResult::Present(bytecode) => Some(bytecode),
-                HistorySearchResult::NoInfo => self.final_state.read().ledger.get_bytecode(addr),
-                HistorySearchResult::Absent => None,
-            }
+            self.base_changes.get_bytecode_or_else(addr, || {
+                match self.active_history.read().fetch_bytecode(addr) {
+                    HistorySearchResult::Present(bytecode) => Some(bytecode),
+                    HistorySearchResult::NoInfo => self.get_final_bytecode(addr),
+                    HistorySearchResult::Absent => None,
+                }
+            })
         })
     }
 
+    /// Gets the bytecode of an address in the final ledger, through the bytecode cache.
+    /// The final state stays locked while the cache is filled,
+    /// so that the bytecode can't change in the final ledger in between.
+    fn get_final_bytecode(&self, addr: &Address) -> Option<Vec<u8>> {
+        let final_state = self.final_state.read();
+        if let Some(bytecode) = self.bytecode_cache.lock().get(addr) {
+            return Some(bytecode);
+        }
+        let bytecode = final_state.ledger.get_bytecode(addr);
+        if let Some(bytecode) = &bytecode {
+            self.bytecode_cache.lock().insert(*addr, bytecode.clone());
+        }
+        bytecode
+    }
+
     /// Transfers coins from one address to another.
     /// No changes are retained in case of failure.
     /// The spending address, if defined, must exist.
@@ -227,13 +408,18 @@ impl SpeculativeLedger {
     /// # Returns
     /// true if the address was found, otherwise false
     pub fn entry_exists(&self, addr: &Address) -> bool {
-        // try to read from added changes > history > final_state
+        self.record_read(LedgerAccess::Entry(*addr));
+        // try to read from added changes > base changes > history > final_state
         self.added_changes.entry_exists_or_else(addr, || {
-            match self.active_history.read().fetch_balance(addr) {
-                HistorySearchResult::Present(_balance) => true,
-                HistorySearchResult::NoInfo => self.final_state.read().ledger.entry_exists(addr),
-                HistorySearchResult::Absent => false,
-            }
+            self.base_changes.entry_exists_or_else(addr, || {
+                match self.active_history.read().fetch_balance(addr) {
+                    HistorySearchResult::Present(_balance) => true,
+                    HistorySearchResult::NoInfo => {
+                        self.final_state.read().ledger.entry_exists(addr)
+                    }
+                    HistorySearchResult::Absent => false,
+                }
+            })
         })
     }
 
@@ -358,8 +544,9 @@ impl SpeculativeLedger {
                 })?;
             self.transfer_coins(Some(*caller_addr), None, bytecode_storage_cost)?;
         }
-        // set the bytecode of that address
+        // set the bytecode of that address, forgetting its cached final bytecode
         self.added_changes.set_bytecode(*addr, bytecode);
+        self.bytecode_cache.lock().invalidate(addr);
 
         Ok(())
     }
@@ -372,16 +559,18 @@ impl SpeculativeLedger {
     /// # Returns
     /// `Some(Vec<Vec<u8>>)` for found keys, `None` if the address does not exist.
     pub fn get_keys(&self, addr: &Address) -> Option<BTreeSet<Vec<u8>>> {
+        self.record_read(LedgerAccess::DatastoreKeys(*addr));
         let mut keys: Option<BTreeSet<Vec<u8>>> =
             self.final_state.read().ledger.get_datastore_keys(addr);
 
-        // here, traverse the history from oldest to newest with added_changes at the end, applying additions and deletions
+        // here, traverse the history from oldest to newest with base_changes and added_changes at the end,
+        // applying additions and deletions
         let active_history = self.active_history.read();
         let changes_iterator = active_history
             .0
             .iter()
             .map(|item| &item.state_changes.ledger_changes)
-            .chain(std::iter::once(&self.added_changes));
+            .chain([&*self.base_changes, &self.added_changes]);
         for ledger_changes in changes_iterator {
             match ledger_changes.get(addr) {
                 // address absent from the changes
@@ -422,19 +611,22 @@ impl SpeculativeLedger {
     /// # Returns
     /// `Some(Vec<u8>)` if the value was found, `None` if the address does not exist or if the key is not in its datastore.
     pub fn get_data_entry(&self, addr: &Address, key: &[u8]) -> Option<Vec<u8>> {
-        // try to read from added changes > history > final_state
+        self.record_read(LedgerAccess::DatastoreEntry(*addr, key.to_vec()));
+        // try to read from added changes > base changes > history > final_state
         self.added_changes.get_data_entry_or_else(addr, key, || {
-            match self
-                .active_history
-                .read()
-                .fetch_active_history_data_entry(addr, key)
-            {
-                HistorySearchResult::Present(entry) => Some(entry),
-                HistorySearchResult::NoInfo => {
-                    self.final_state.read().ledger.get_data_entry(addr, key)
+            self.base_changes.get_data_entry_or_else(addr, key, || {
+                match self
+                    .active_history
+                    .read()
+                    .fetch_active_history_data_entry(addr, key)
+                {
+                    HistorySearchResult::Present(entry) => Some(entry),
+                    HistorySearchResult::NoInfo => {
+                        self.final_state.read().ledger.get_data_entry(addr, key)
+                    }
+                    HistorySearchResult::Absent => None,
                 }
-                HistorySearchResult::Absent => None,
-            }
+            })
         })
     }
 
@@ -447,19 +639,22 @@ impl SpeculativeLedger {
     /// # Returns
     /// true if the key exists in the address datastore, false otherwise
     pub fn has_data_entry(&self, addr: &Address, key: &[u8]) -> bool {
-        // try to read from added changes > history > final_state
+        self.record_read(LedgerAccess::DatastoreEntry(*addr, key.to_vec()));
+        // try to read from added changes > base changes > history > final_state
         self.added_changes.has_data_entry_or_else(addr, key, || {
-            match self
-                .active_history
-                .read()
-                .fetch_active_history_data_entry(addr, key)
-            {
-                HistorySearchResult::Present(_entry) => true,
-                HistorySearchResult::NoInfo => {
-                    self.final_state.read().ledger.has_data_entry(addr, key)
+            self.base_changes.has_data_entry_or_else(addr, key, || {
+                match self
+                    .active_history
+                    .read()
+                    .fetch_active_history_data_entry(addr, key)
+                {
+                    HistorySearchResult::Present(_entry) => true,
+                    HistorySearchResult::NoInfo => {
+                        self.final_state.read().ledger.has_data_entry(addr, key)
+                    }
+                    HistorySearchResult::Absent => false,
                 }
-                HistorySearchResult::Absent => false,
-            }
+            })
         })
     }
 
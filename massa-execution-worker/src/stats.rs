@@ -63,7 +63,16 @@ impl ExecutionStatsCounter {
     }
 
     /// get statistics
-    pub fn get_stats(&self, active_cursor: Slot) -> ExecutionStats {
+    ///
+    /// # Arguments
+    /// * `active_cursor`: active execution cursor slot
+    /// * `bytecode_cache_hits`, `bytecode_cache_misses`: bytecodes found and not found in the bytecode cache since the node started
+    pub fn get_stats(
+        &self,
+        active_cursor: Slot,
+        bytecode_cache_hits: u64,
+        bytecode_cache_misses: u64,
+    ) -> ExecutionStats {
         let current_time = MassaTime::now().expect("could not get current time");
         let start_time = current_time.saturating_sub(self.time_window_duration);
         let map_func = |pair: &(usize, MassaTime)| -> usize {
@@ -80,6 +89,8 @@ impl ExecutionStatsCounter {
             time_window_start: start_time,
             time_window_end: current_time,
             active_cursor,
+            bytecode_cache_hits,
+            bytecode_cache_misses,
         }
     }
 }
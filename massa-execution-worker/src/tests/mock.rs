@@ -123,6 +123,8 @@ pub fn get_sample_state() -> Result<(Arc<RwLock<FinalState>>, NamedTempFile, Tem
         initial_rolls_path: rolls_file.path().to_path_buf(),
         initial_seed_string: "".to_string(),
         periods_per_cycle: 10,
+        // kept in the returned ledger directory so that it is deleted along with it
+        final_state_path: tempdir.path().join("final_state"),
     };
     let (_, selector_controller) = start_selector_worker(SelectorConfig::default())
         .expect("could not start selector controller");
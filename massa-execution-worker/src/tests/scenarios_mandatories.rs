@@ -1,38 +1,64 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
-use crate::start_execution_worker;
+use crate::context::ExecutionContext;
+use crate::execution::ExecutionState;
+use crate::interface_impl::InterfaceImpl;
+use crate::slot_record_db::FinalSlotRecordDB;
 use crate::tests::mock::{create_block, get_random_address_full, get_sample_state};
+use crate::{replay_final_slots, start_execution_worker};
+use massa_async_pool::{
+    AsyncMessageFilter, AsyncMessageStatus, AsyncMessageTrigger, AsyncMessageTriggerSerializer,
+    Change, ASYNC_MESSAGE_TRIGGER_ARGUMENT_V1,
+};
 use massa_execution_exports::{
-    ExecutionConfig, ExecutionController, ExecutionError, ReadOnlyExecutionRequest,
-    ReadOnlyExecutionTarget,
+    ExecutionChannels, ExecutionConfig, ExecutionController, ExecutionError, ExecutionStackElement,
+    ReadOnlyExecutionRequest, ReadOnlyExecutionTarget,
+};
+use massa_hash::Hash;
+use massa_ledger_exports::{LedgerChanges, LedgerEntry, SetUpdateOrDelete};
+use massa_models::config::{
+    LEDGER_ENTRY_BASE_SIZE, LEDGER_ENTRY_DATASTORE_BASE_SIZE, MAX_ASYNC_MESSAGE_TRIGGER_LENGTH,
+    MAX_DATASTORE_KEY_LENGTH,
 };
-use massa_models::config::{LEDGER_ENTRY_BASE_SIZE, LEDGER_ENTRY_DATASTORE_BASE_SIZE};
 use massa_models::prehash::PreHashMap;
-use massa_models::{address::Address, amount::Amount, slot::Slot};
 use massa_models::{
-    api::EventFilter,
+    address::{Address, AddressStorageReport},
+    amount::Amount,
+    slot::Slot,
+};
+use massa_models::{
+    api::{EventCursor, EventFilter, EventOrder},
     block::BlockId,
     datastore::Datastore,
+    execution::{AddressStateOverride, DatastoreEntryOverride},
     operation::{Operation, OperationSerializer, OperationType, WrappedOperation},
+    receipt::OperationExecutionError,
     wrapped::WrappedContent,
 };
+use massa_sc_runtime::Interface;
+use massa_serialization::Serializer;
 use massa_signature::KeyPair;
 use massa_storage::Storage;
 use massa_time::MassaTime;
 use num::rational::Ratio;
+use parking_lot::Mutex;
 use serial_test::serial;
 use std::{
-    cmp::Reverse, collections::BTreeMap, collections::HashMap, str::FromStr, time::Duration,
+    cmp::Reverse, collections::BTreeMap, collections::HashMap, str::FromStr, sync::Arc,
+    time::Duration,
 };
+use tokio::sync::broadcast;
 
 #[test]
 #[serial]
 fn test_execution_shutdown() {
     let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+    let (exec_cfg, _keep_config_dir) = ExecutionConfig::sample();
     let (mut manager, _controller) = start_execution_worker(
-        ExecutionConfig::default(),
+        exec_cfg,
         sample_state.clone(),
         sample_state.read().pos_state.selector.clone(),
+        get_sample_channels(),
     );
     manager.stop();
 }
@@ -41,10 +67,12 @@ fn test_execution_shutdown() {
 #[serial]
 fn test_sending_command() {
     let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+    let (exec_cfg, _keep_config_dir) = ExecutionConfig::sample();
     let (mut manager, controller) = start_execution_worker(
-        ExecutionConfig::default(),
+        exec_cfg,
         sample_state.clone(),
         sample_state.read().pos_state.selector.clone(),
+        get_sample_channels(),
     );
     controller.update_blockclique_status(
         Default::default(),
@@ -58,10 +86,12 @@ fn test_sending_command() {
 #[serial]
 fn test_readonly_execution() {
     let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+    let (exec_cfg, _keep_config_dir) = ExecutionConfig::sample();
     let (mut manager, controller) = start_execution_worker(
-        ExecutionConfig::default(),
+        exec_cfg,
         sample_state.clone(),
         sample_state.read().pos_state.selector.clone(),
+        get_sample_channels(),
     );
     let mut res = controller
         .execute_readonly_request(ReadOnlyExecutionRequest {
@@ -70,6 +100,7 @@ fn test_readonly_execution() {
             target: ReadOnlyExecutionTarget::BytecodeExecution(
                 include_bytes!("./wasm/event_test.wasm").to_vec(),
             ),
+            state_overrides: vec![],
         })
         .expect("readonly execution failed");
 
@@ -79,6 +110,89 @@ fn test_readonly_execution() {
     manager.stop();
 }
 
+#[test]
+#[serial]
+fn readonly_execution_state_overrides() {
+    let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+    let (exec_cfg, _keep_config_dir) = ExecutionConfig::sample();
+    let (mut manager, controller) = start_execution_worker(
+        exec_cfg.clone(),
+        sample_state.clone(),
+        sample_state.read().pos_state.selector.clone(),
+        get_sample_channels(),
+    );
+    init_execution_worker(&exec_cfg, &Storage::create_root(), controller.clone());
+    // an address that is not in the ledger sends coins
+    let (sender_address, sender_keypair) = get_random_address_full();
+    let (recipient_address, _) = get_random_address_full();
+    let operation = Operation::new_wrapped(
+        Operation {
+            fee: Amount::zero(),
+            expire_period: 10,
+            op: OperationType::Transaction {
+                recipient_address,
+                amount: Amount::from_str("100").unwrap(),
+            },
+        },
+        OperationSerializer::new(),
+        &sender_keypair,
+    )
+    .unwrap();
+    let dry_run = |state_overrides| {
+        controller.execute_readonly_request(ReadOnlyExecutionRequest {
+            max_gas: operation.get_gas_usage(),
+            call_stack: vec![],
+            target: ReadOnlyExecutionTarget::Operation(operation.clone()),
+            state_overrides,
+        })
+    };
+    // the operation cannot be executed without overrides since its sender is not in the ledger
+    assert!(dry_run(vec![]).is_err());
+    // the overridden balance of the sender is visible to the execution
+    let out = dry_run(vec![AddressStateOverride {
+        address: sender_address,
+        balance: Some(Amount::from_str("1000").unwrap()),
+        bytecode: None,
+        datastore: vec![DatastoreEntryOverride {
+            key: b"key".to_vec(),
+            value: Some(b"value".to_vec()),
+        }],
+    }])
+    .expect("dry-run failed")
+    .out;
+    assert!(out.receipts[&operation.id].success);
+    // the output only holds the changes made by the execution, not the overrides
+    let ledger_changes = out.state_changes.ledger_changes;
+    assert_eq!(
+        ledger_changes.get_balance_or_else(&sender_address, || None),
+        Some(Amount::from_str("900").unwrap())
+    );
+    assert_eq!(
+        ledger_changes.get_balance_or_else(&recipient_address, || None),
+        Some(Amount::from_str("100").unwrap())
+    );
+    assert_eq!(
+        ledger_changes.get_data_entry_or_else(&sender_address, b"key", || None),
+        None
+    );
+    // the overrides are not kept after the execution
+    assert!(sample_state
+        .read()
+        .ledger
+        .get_balance(&sender_address)
+        .is_none());
+
+    manager.stop();
+}
+
+/// Execution channels whose broadcasts are not listened to
+fn get_sample_channels() -> ExecutionChannels {
+    ExecutionChannels {
+        sc_event_sender: broadcast::channel(5000).0,
+        final_block_sender: broadcast::channel(128).0,
+    }
+}
+
 /// Feeds the execution worker with genesis blocks to start it
 fn init_execution_worker(
     config: &ExecutionConfig,
@@ -116,10 +230,11 @@ fn init_execution_worker(
 #[ignore]
 fn test_nested_call_gas_usage() {
     // setup the period duration
+    let (sample_config, _keep_config_dir) = ExecutionConfig::sample();
     let exec_cfg = ExecutionConfig {
         t0: 100.into(),
         cursor_delay: 0.into(),
-        ..ExecutionConfig::default()
+        ..sample_config
     };
     // get a sample final state
     let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
@@ -130,6 +245,7 @@ fn test_nested_call_gas_usage() {
         exec_cfg.clone(),
         sample_state.clone(),
         sample_state.read().pos_state.selector.clone(),
+        get_sample_channels(),
     );
     // initialize the execution system with genesis blocks
     init_execution_worker(&exec_cfg, &storage, controller.clone());
@@ -258,11 +374,12 @@ fn test_nested_call_gas_usage() {
 #[serial]
 fn send_and_receive_async_message() {
     // setup the period duration and the maximum gas for asynchronous messages execution
+    let (sample_config, _keep_config_dir) = ExecutionConfig::sample();
     let exec_cfg = ExecutionConfig {
         t0: 100.into(),
         max_async_gas: 100_000,
         cursor_delay: 0.into(),
-        ..ExecutionConfig::default()
+        ..sample_config
     };
     // get a sample final state
     let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
@@ -274,6 +391,7 @@ fn send_and_receive_async_message() {
         exec_cfg.clone(),
         sample_state.clone(),
         sample_state.read().pos_state.selector.clone(),
+        get_sample_channels(),
     );
     // initialize the execution system with genesis blocks
     init_execution_worker(&exec_cfg, &storage, controller.clone());
@@ -337,10 +455,11 @@ fn send_and_receive_async_message() {
 #[serial]
 fn local_execution() {
     // setup the period duration and cursor delay
+    let (sample_config, _keep_config_dir) = ExecutionConfig::sample();
     let exec_cfg = ExecutionConfig {
         t0: 100.into(),
         cursor_delay: 0.into(),
-        ..ExecutionConfig::default()
+        ..sample_config
     };
     // get a sample final state
     let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
@@ -352,6 +471,7 @@ fn local_execution() {
         exec_cfg.clone(),
         sample_state.clone(),
         sample_state.read().pos_state.selector.clone(),
+        get_sample_channels(),
     );
     // initialize the execution system with genesis blocks
     init_execution_worker(&exec_cfg, &storage, controller.clone());
@@ -438,10 +558,11 @@ fn local_execution() {
 #[serial]
 fn sc_deployment() {
     // setup the period duration and cursor delay
+    let (sample_config, _keep_config_dir) = ExecutionConfig::sample();
     let exec_cfg = ExecutionConfig {
         t0: 100.into(),
         cursor_delay: 0.into(),
-        ..ExecutionConfig::default()
+        ..sample_config
     };
     // get a sample final state
     let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
@@ -453,6 +574,7 @@ fn sc_deployment() {
         exec_cfg.clone(),
         sample_state.clone(),
         sample_state.read().pos_state.selector.clone(),
+        get_sample_channels(),
     );
     // initialize the execution system with genesis blocks
     init_execution_worker(&exec_cfg, &storage, controller.clone());
@@ -520,11 +642,12 @@ fn sc_deployment() {
 #[serial]
 fn send_and_receive_async_message_with_trigger() {
     // setup the period duration and the maximum gas for asynchronous messages execution
+    let (sample_config, _keep_config_dir) = ExecutionConfig::sample();
     let exec_cfg = ExecutionConfig {
         t0: 100.into(),
         max_async_gas: 1_000_000_000,
         cursor_delay: 0.into(),
-        ..ExecutionConfig::default()
+        ..sample_config
     };
     // get a sample final state
     let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
@@ -537,6 +660,7 @@ fn send_and_receive_async_message_with_trigger() {
         exec_cfg.clone(),
         sample_state.clone(),
         sample_state.read().pos_state.selector.clone(),
+        get_sample_channels(),
     );
     // initialize the execution system with genesis blocks
     init_execution_worker(&exec_cfg, &storage, controller.clone());
@@ -619,6 +743,18 @@ fn send_and_receive_async_message_with_trigger() {
     assert!(events.len() == 3, "Three event was expected");
     assert_eq!(events[0].data, "Triggered");
 
+    // the message is still waiting for its trigger in the final pool
+    let waiting_messages = controller.get_async_messages(&AsyncMessageFilter {
+        can_be_executed: Some(false),
+        ..Default::default()
+    });
+    assert_eq!(waiting_messages.len(), 1);
+    let message_info = controller
+        .get_async_message(&waiting_messages[0].id)
+        .expect("message not found");
+    assert_eq!(message_info.status, AsyncMessageStatus::Pending);
+    assert!(message_info.message.is_some());
+
     // keypair associated to thread 2
     let keypair =
         KeyPair::from_str("S12APSAzMPsJjVGWzUJ61ZwwGFTNapA4YtArMKDyW4edLu6jHvCr").unwrap();
@@ -661,10 +797,12 @@ fn send_and_receive_async_message_with_trigger() {
 #[serial]
 pub fn send_and_receive_transaction() {
     // setup the period duration
+    let (sample_config, _keep_config_dir) = ExecutionConfig::sample();
     let exec_cfg = ExecutionConfig {
         t0: 100.into(),
         cursor_delay: 0.into(),
-        ..ExecutionConfig::default()
+        call_tracing: true,
+        ..sample_config
     };
     // get a sample final state
     let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
@@ -676,6 +814,7 @@ pub fn send_and_receive_transaction() {
         exec_cfg.clone(),
         sample_state.clone(),
         sample_state.read().pos_state.selector.clone(),
+        get_sample_channels(),
     );
     // initialize the execution system with genesis blocks
     init_execution_worker(&exec_cfg, &storage, controller.clone());
@@ -697,6 +836,7 @@ pub fn send_and_receive_transaction() {
         &sender_keypair,
     )
     .unwrap();
+    let operation_id = operation.id;
     // create the block containing the transaction operation
     storage.store_operations(vec![operation.clone()]);
     let block = create_block(KeyPair::generate(), vec![operation], Slot::new(1, 0)).unwrap();
@@ -731,6 +871,460 @@ pub fn send_and_receive_transaction() {
                     .saturating_mul_u64(LEDGER_ENTRY_BASE_SIZE as u64)
             )
     );
+    // check that the transfer was recorded in the call trace of the operation
+    let trace = controller.get_operation_call_traces(&[operation_id])[0]
+        .clone()
+        .expect("operation call trace not found");
+    assert_eq!(
+        trace.root.target,
+        Address::from_public_key(&sender_keypair.get_public_key())
+    );
+    assert_eq!(trace.root.transfers.len(), 1);
+    assert_eq!(trace.root.transfers[0].to, Some(recipient_address));
+    // stop the execution controller
+    manager.stop();
+}
+
+/// # Context
+///
+/// Replay of recorded final slots
+///
+/// 1. a block containing a transaction is executed as final, with final slot recording enabled
+/// 2. the recorded slots are replayed on a fresh copy of the initial final state,
+///    which reaches the same final state hash
+/// 3. once the final state hash of the last record is tampered with, the replay fails
+#[test]
+#[serial]
+pub fn replay_recorded_final_slots() {
+    let (sample_config, _keep_config_dir) = ExecutionConfig::sample();
+    let exec_cfg = ExecutionConfig {
+        t0: 100.into(),
+        cursor_delay: 0.into(),
+        final_slot_record: true,
+        ..sample_config
+    };
+    // execute and record the final slots
+    let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+    let mut storage = Storage::create_root();
+    let (mut manager, controller) = start_execution_worker(
+        exec_cfg.clone(),
+        sample_state.clone(),
+        sample_state.read().pos_state.selector.clone(),
+        get_sample_channels(),
+    );
+    init_execution_worker(&exec_cfg, &storage, controller.clone());
+    let sender_keypair =
+        KeyPair::from_str("S1JJeHiZv1C1zZN5GLFcbz6EXYiccmUPLkYuDFA3kayjxP39kFQ").unwrap();
+    let (recipient_address, _keypair) = get_random_address_full();
+    let operation = Operation::new_wrapped(
+        Operation {
+            fee: Amount::zero(),
+            expire_period: 10,
+            op: OperationType::Transaction {
+                recipient_address,
+                amount: Amount::from_str("100").unwrap(),
+            },
+        },
+        OperationSerializer::new(),
+        &sender_keypair,
+    )
+    .unwrap();
+    storage.store_operations(vec![operation.clone()]);
+    let block = create_block(KeyPair::generate(), vec![operation], Slot::new(1, 0)).unwrap();
+    storage.store_block(block.clone());
+    let mut finalized_blocks: HashMap<Slot, BlockId> = Default::default();
+    finalized_blocks.insert(block.content.header.content.slot, block.id);
+    let mut block_storage: PreHashMap<BlockId, Storage> = Default::default();
+    block_storage.insert(block.id, storage.clone());
+    controller.update_blockclique_status(finalized_blocks, Default::default(), block_storage);
+    std::thread::sleep(Duration::from_millis(100));
+    // stopping the worker closes the records
+    manager.stop();
+    assert_eq!(sample_state.read().slot, Slot::new(1, 0));
+    let recorded_hash = sample_state.read().final_state_hash;
+
+    // the replay on a fresh final state reaches the recorded final state
+    let (replay_state, _keep_replay_file, _keep_replay_dir) = get_sample_state().unwrap();
+    let last_slot = replay_final_slots(
+        exec_cfg.clone(),
+        replay_state.clone(),
+        replay_state.read().pos_state.selector.clone(),
+        get_sample_channels(),
+        exec_cfg.final_slot_record_path.clone(),
+        None,
+    )
+    .expect("replay failed");
+    assert_eq!(last_slot, Slot::new(1, 0));
+    assert_eq!(replay_state.read().final_state_hash, recorded_hash);
+    assert_eq!(
+        replay_state.read().ledger.get_balance(&recipient_address),
+        sample_state.read().ledger.get_balance(&recipient_address)
+    );
+
+    // tamper with the final state hash recorded for the last slot
+    {
+        let records = FinalSlotRecordDB::new(
+            exec_cfg.final_slot_record_path.clone(),
+            exec_cfg.final_slot_record_retention_periods,
+        );
+        let mut record = records
+            .get_records_after(Slot::new(0, 0))
+            .last()
+            .expect("no slot was recorded");
+        assert_eq!(record.slot, Slot::new(1, 0));
+        record.final_state_hash = Hash::compute_from(b"tampered");
+        records.write_record(&record);
+    }
+
+    // the replay detects the mismatch
+    let (replay_state, _keep_replay_file, _keep_replay_dir) = get_sample_state().unwrap();
+    let res = replay_final_slots(
+        exec_cfg.clone(),
+        replay_state.clone(),
+        replay_state.read().pos_state.selector.clone(),
+        get_sample_channels(),
+        exec_cfg.final_slot_record_path.clone(),
+        None,
+    );
+    assert!(matches!(res, Err(ExecutionError::ReplayError(_))));
+}
+
+/// # Context
+///
+/// Execution of the operations of a block in parallel
+///
+/// 1. senders of the thread of the block are funded, and a smart contract is set in the final ledger
+/// 2. the block contains operations of distinct senders:
+///    smart contract executions writing the datastore of their sender,
+///    calls sending coins to the same smart contract,
+///    calls of the smart contract writing the same key of its datastore,
+///    and a transaction
+/// 3. the block is executed with and without parallel execution, giving the same execution output
+#[test]
+#[serial]
+pub fn parallel_execution_matches_sequential_execution() {
+    let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+    let (sample_config, _keep_config_dir) = ExecutionConfig::sample();
+    let thread_count = sample_config.thread_count;
+
+    // distinct senders in the thread of the block
+    let senders: Vec<KeyPair> = std::iter::repeat_with(KeyPair::generate)
+        .filter(|keypair| {
+            Address::from_public_key(&keypair.get_public_key()).get_thread(thread_count) == 0
+        })
+        .take(7)
+        .collect();
+    let (contract_address, _keypair) = get_random_address_full();
+    let (recipient_address, _keypair) = get_random_address_full();
+
+    // fund the senders and set the smart contract in the final ledger
+    // you can check the source code of the following wasm file in massa-unit-tests-src
+    let datastore_bytecode = include_bytes!("./wasm/datastore_manipulations.wasm");
+    let mut funding = LedgerChanges::default();
+    for keypair in &senders {
+        funding.0.insert(
+            Address::from_public_key(&keypair.get_public_key()),
+            SetUpdateOrDelete::Set(LedgerEntry {
+                balance: Amount::from_str("1000").unwrap(),
+                ..Default::default()
+            }),
+        );
+    }
+    funding.0.insert(
+        contract_address,
+        SetUpdateOrDelete::Set(LedgerEntry {
+            balance: Amount::from_str("1000").unwrap(),
+            bytecode: datastore_bytecode.to_vec(),
+            ..Default::default()
+        }),
+    );
+    let final_slot = sample_state.read().slot;
+    sample_state
+        .write()
+        .ledger
+        .apply_changes(funding, final_slot);
+
+    let call_contract = |keypair: &KeyPair, target_func: &str, coins: &str| {
+        Operation::new_wrapped(
+            Operation {
+                fee: Amount::from_str("1").unwrap(),
+                expire_period: 10,
+                op: OperationType::CallSC {
+                    max_gas: 1_000_000,
+                    target_addr: contract_address,
+                    target_func: target_func.to_string(),
+                    param: Vec::new(),
+                    coins: Amount::from_str(coins).unwrap(),
+                },
+            },
+            OperationSerializer::new(),
+            keypair,
+        )
+        .unwrap()
+    };
+    let operations = vec![
+        // each execution writes the datastore of its own sender
+        create_execute_sc_operation(&senders[0], datastore_bytecode, BTreeMap::default()).unwrap(),
+        create_execute_sc_operation(&senders[1], datastore_bytecode, BTreeMap::default()).unwrap(),
+        // both calls credit the balance of the smart contract
+        call_contract(&senders[2], "", "10"),
+        call_contract(&senders[3], "", "20"),
+        // both calls write the same key of the smart contract datastore
+        call_contract(&senders[4], "main", "0"),
+        call_contract(&senders[5], "main", "0"),
+        Operation::new_wrapped(
+            Operation {
+                fee: Amount::from_str("1").unwrap(),
+                expire_period: 10,
+                op: OperationType::Transaction {
+                    recipient_address,
+                    amount: Amount::from_str("100").unwrap(),
+                },
+            },
+            OperationSerializer::new(),
+            &senders[6],
+        )
+        .unwrap(),
+    ];
+    let block = create_block(KeyPair::generate(), operations.clone(), Slot::new(1, 0)).unwrap();
+    let mut storage = Storage::create_root();
+    storage.store_operations(operations);
+    storage.store_block(block.clone());
+    let exec_target = (block.id, storage);
+    let selector = sample_state.read().pos_state.selector.clone();
+
+    // execute the same block in parallel and in sequence, without applying the outputs
+    let execute_block = |parallel_execution: bool| {
+        let (sample_config, _keep_config_dir) = ExecutionConfig::sample();
+        let execution_state = ExecutionState::new(
+            ExecutionConfig {
+                call_tracing: true,
+                parallel_execution,
+                ..sample_config
+            },
+            sample_state.clone(),
+            get_sample_channels(),
+        );
+        execution_state.execute_slot(&Slot::new(1, 0), Some(&exec_target), selector.clone())
+    };
+
+    let sequential_output = execute_block(false);
+    assert_eq!(sequential_output.receipts.len(), 7);
+    assert!(sequential_output
+        .receipts
+        .values()
+        .any(|receipt| receipt.success));
+    assert!(sequential_output
+        .state_changes
+        .ledger_changes
+        .get_balance_or_else(&recipient_address, || None)
+        .is_some());
+    assert_eq!(execute_block(true), sequential_output);
+}
+
+#[test]
+#[serial]
+pub fn dry_run_transaction() {
+    // setup the period duration
+    let (sample_config, _keep_config_dir) = ExecutionConfig::sample();
+    let exec_cfg = ExecutionConfig {
+        t0: 100.into(),
+        cursor_delay: 0.into(),
+        ..sample_config
+    };
+    // get a sample final state
+    let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+    // start the execution worker
+    let (mut manager, controller) = start_execution_worker(
+        exec_cfg.clone(),
+        sample_state.clone(),
+        sample_state.read().pos_state.selector.clone(),
+        get_sample_channels(),
+    );
+    // initialize the execution system with genesis blocks
+    init_execution_worker(&exec_cfg, &Storage::create_root(), controller.clone());
+    // generate the sender_keypair and recipient_address
+    let sender_keypair =
+        KeyPair::from_str("S1JJeHiZv1C1zZN5GLFcbz6EXYiccmUPLkYuDFA3kayjxP39kFQ").unwrap();
+    let (recipient_address, _keypair) = get_random_address_full();
+    let dry_run = |amount: &str| {
+        let operation = Operation::new_wrapped(
+            Operation {
+                fee: Amount::zero(),
+                expire_period: 10,
+                op: OperationType::Transaction {
+                    recipient_address,
+                    amount: Amount::from_str(amount).unwrap(),
+                },
+            },
+            OperationSerializer::new(),
+            &sender_keypair,
+        )
+        .unwrap();
+        let operation_id = operation.id;
+        let mut res = controller
+            .execute_readonly_request(ReadOnlyExecutionRequest {
+                max_gas: operation.get_gas_usage(),
+                call_stack: vec![],
+                target: ReadOnlyExecutionTarget::Operation(operation),
+                state_overrides: vec![],
+            })
+            .expect("dry-run failed");
+        let receipt = res
+            .out
+            .receipts
+            .remove(&operation_id)
+            .expect("receipt not found");
+        (receipt, res.out.state_changes.ledger_changes)
+    };
+    // the transaction succeeds and credits the recipient in the returned changes only
+    let (receipt, ledger_changes) = dry_run("100");
+    assert!(receipt.success);
+    assert_eq!(receipt.coins, Amount::from_str("100").unwrap());
+    assert_eq!(receipt.gas_used, None, "transactions do not run in the VM");
+    assert!(ledger_changes
+        .get_balance_or_else(&recipient_address, || None)
+        .is_some());
+    assert!(sample_state
+        .read()
+        .ledger
+        .get_balance(&recipient_address)
+        .is_none());
+    // the transaction fails if the sender cannot afford it
+    let (receipt, _) = dry_run("1000000000");
+    assert!(!receipt.success);
+    assert!(matches!(
+        receipt.error,
+        Some(OperationExecutionError::Transaction(_))
+    ));
+    // stop the execution controller
+    manager.stop();
+}
+
+#[test]
+#[serial]
+pub fn dry_run_execute_sc() {
+    // setup the period duration
+    let (sample_config, _keep_config_dir) = ExecutionConfig::sample();
+    let exec_cfg = ExecutionConfig {
+        t0: 100.into(),
+        cursor_delay: 0.into(),
+        ..sample_config
+    };
+    // get a sample final state
+    let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+    // start the execution worker
+    let (mut manager, controller) = start_execution_worker(
+        exec_cfg.clone(),
+        sample_state.clone(),
+        sample_state.read().pos_state.selector.clone(),
+        get_sample_channels(),
+    );
+    // initialize the execution system with genesis blocks
+    init_execution_worker(&exec_cfg, &Storage::create_root(), controller.clone());
+    // keypair associated to thread 0
+    let keypair = KeyPair::from_str("S1JJeHiZv1C1zZN5GLFcbz6EXYiccmUPLkYuDFA3kayjxP39kFQ").unwrap();
+    let dry_run = |bytecode: &[u8]| {
+        let operation =
+            create_execute_sc_operation(&keypair, bytecode, BTreeMap::default()).unwrap();
+        let operation_id = operation.id;
+        let mut res = controller
+            .execute_readonly_request(ReadOnlyExecutionRequest {
+                max_gas: operation.get_gas_usage(),
+                call_stack: vec![],
+                target: ReadOnlyExecutionTarget::Operation(operation),
+                state_overrides: vec![],
+            })
+            .expect("dry-run failed");
+        let receipt = res
+            .out
+            .receipts
+            .remove(&operation_id)
+            .expect("receipt not found");
+        (receipt, res.gas_cost)
+    };
+    // a successful execution reports the gas used by the VM, below the operation gas
+    let (receipt, gas_cost) = dry_run(include_bytes!("./wasm/event_test.wasm"));
+    assert!(receipt.success);
+    let gas_used = receipt.gas_used.expect("the VM gas was expected");
+    assert!(gas_used > 0 && gas_used < 1_000_000);
+    assert_eq!(gas_cost, gas_used);
+    // a failed execution is typed, and the VM reports no gas on failure
+    let (receipt, _) = dry_run(include_bytes!("./wasm/execution_error.wasm"));
+    assert!(!receipt.success);
+    assert!(matches!(
+        receipt.error,
+        Some(OperationExecutionError::Bytecode(_))
+    ));
+    assert_eq!(receipt.gas_used, None);
+    // stop the execution controller
+    manager.stop();
+}
+
+/// Check the call tree traced for an operation making a nested call:
+/// `deploy_sc.wasm` creates a smart contract and calls its constructor.
+#[test]
+#[serial]
+pub fn nested_call_trace() {
+    // setup the period duration
+    let (sample_config, _keep_config_dir) = ExecutionConfig::sample();
+    let exec_cfg = ExecutionConfig {
+        t0: 100.into(),
+        cursor_delay: 0.into(),
+        ..sample_config
+    };
+    // get a sample final state
+    let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+    // start the execution worker
+    let (mut manager, controller) = start_execution_worker(
+        exec_cfg.clone(),
+        sample_state.clone(),
+        sample_state.read().pos_state.selector.clone(),
+        get_sample_channels(),
+    );
+    // initialize the execution system with genesis blocks
+    init_execution_worker(&exec_cfg, &Storage::create_root(), controller.clone());
+    // keypair associated to thread 0
+    let keypair = KeyPair::from_str("S1JJeHiZv1C1zZN5GLFcbz6EXYiccmUPLkYuDFA3kayjxP39kFQ").unwrap();
+    let sender_addr = Address::from_public_key(&keypair.get_public_key());
+    // you can check the source code of the following wasm files in massa-unit-tests-src
+    let mut datastore = BTreeMap::new();
+    datastore.insert(
+        b"smart-contract".to_vec(),
+        include_bytes!("./wasm/init_sc.wasm").to_vec(),
+    );
+    let operation =
+        create_execute_sc_operation(&keypair, include_bytes!("./wasm/deploy_sc.wasm"), datastore)
+            .unwrap();
+    let operation_id = operation.id;
+    let res = controller
+        .execute_readonly_request(ReadOnlyExecutionRequest {
+            max_gas: operation.get_gas_usage(),
+            call_stack: vec![],
+            target: ReadOnlyExecutionTarget::Operation(operation),
+            state_overrides: vec![],
+        })
+        .expect("dry-run failed");
+    let receipt = res
+        .out
+        .receipts
+        .get(&operation_id)
+        .expect("receipt not found");
+    assert!(receipt.success);
+    let trace = res.trace.expect("the execution was expected to be traced");
+    // the root is the main function of the operation, with the gas reported by the VM
+    assert_eq!(trace.target, sender_addr);
+    assert_eq!(trace.function.as_deref(), Some("main"));
+    assert_eq!(trace.gas_used, receipt.gas_used);
+    assert!(trace.error.is_none());
+    // the constructor call is attached to the root, on the created smart contract
+    assert_eq!(trace.calls.len(), 1, "one nested call was expected");
+    let constructor_call = &trace.calls[0];
+    assert_ne!(constructor_call.target, sender_addr);
+    assert_eq!(constructor_call.coins, Amount::zero());
+    assert!(constructor_call.calls.is_empty());
+    // the VM reports the gas of nested calls to the calling module only
+    assert_eq!(constructor_call.gas_used, None);
     // stop the execution controller
     manager.stop();
 }
@@ -739,10 +1333,11 @@ pub fn send_and_receive_transaction() {
 #[serial]
 pub fn roll_buy() {
     // setup the period duration
+    let (sample_config, _keep_config_dir) = ExecutionConfig::sample();
     let exec_cfg = ExecutionConfig {
         t0: 100.into(),
         cursor_delay: 0.into(),
-        ..ExecutionConfig::default()
+        ..sample_config
     };
     // get a sample final state
     let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
@@ -754,6 +1349,7 @@ pub fn roll_buy() {
         exec_cfg.clone(),
         sample_state.clone(),
         sample_state.read().pos_state.selector.clone(),
+        get_sample_channels(),
     );
     // initialize the execution system with genesis blocks
     init_execution_worker(&exec_cfg, &storage, controller.clone());
@@ -805,12 +1401,13 @@ pub fn roll_sell() {
     // Check for resulting roll count + resulting deferred credits
 
     // setup the period duration
+    let (sample_config, _keep_config_dir) = ExecutionConfig::sample();
     let mut exec_cfg = ExecutionConfig {
         t0: 100.into(),
         periods_per_cycle: 2,
         thread_count: 2,
         cursor_delay: 0.into(),
-        ..Default::default()
+        ..sample_config
     };
     // turn off roll selling on missed block opportunities
     // otherwise balance will be credited with those sold roll (and we need to check the balance for
@@ -827,6 +1424,7 @@ pub fn roll_sell() {
         exec_cfg.clone(),
         sample_state.clone(),
         sample_state.read().pos_state.selector.clone(),
+        get_sample_channels(),
     );
     // initialize the execution system with genesis blocks
     init_execution_worker(&exec_cfg, &storage, controller.clone());
@@ -923,12 +1521,17 @@ pub fn roll_sell() {
 #[serial]
 fn sc_execution_error() {
     // setup the period duration and the maximum gas for asynchronous messages execution
+    let (sample_config, _keep_config_dir) = ExecutionConfig::sample();
     let exec_cfg = ExecutionConfig {
         t0: 100.into(),
         max_async_gas: 100_000,
         cursor_delay: 0.into(),
-        ..ExecutionConfig::default()
+        broadcast_enabled: true,
+        ..sample_config
     };
+    // listen to the broadcast events
+    let channels = get_sample_channels();
+    let mut sc_event_receiver = channels.sc_event_sender.subscribe();
     // get a sample final state
     let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
 
@@ -939,6 +1542,7 @@ fn sc_execution_error() {
         exec_cfg.clone(),
         sample_state.clone(),
         sample_state.read().pos_state.selector.clone(),
+        channels,
     );
     // initialize the execution system with genesis blocks
     init_execution_worker(&exec_cfg, &storage, controller.clone());
@@ -978,19 +1582,224 @@ fn sc_execution_error() {
         .data
         .contains("runtime error when executing operation"));
     assert!(events[1].data.contains("address parsing error"));
+    // the final events were broadcast as well
+    let broadcast_event = std::iter::from_fn(|| sc_event_receiver.try_recv().ok())
+        .find(|event| event.context.is_final)
+        .expect("a final event was expected to be broadcast");
+    assert_eq!(
+        broadcast_event.data,
+        "event generated before the sc failure"
+    );
     // stop the execution controller
     manager.stop();
 }
 
+/// # Context
+///
+/// Broadcast of the blocks that became final, with the final state hash obtained after their execution
+///
+/// 1. a block is executed at a final slot that was not executed as a candidate
+/// 2. a block is executed as a candidate along with the next slot,
+///    then at a final slot from the cached candidate execution
+/// 3. both blocks are broadcast with their operations and the final state hash after their slot
+#[test]
+#[serial]
+fn final_block_broadcast() {
+    let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+    let (sample_config, _keep_config_dir) = ExecutionConfig::sample();
+    let channels = get_sample_channels();
+    let mut final_block_receiver = channels.final_block_sender.subscribe();
+    let mut execution_state = ExecutionState::new(
+        ExecutionConfig {
+            broadcast_enabled: true,
+            ..sample_config
+        },
+        sample_state.clone(),
+        channels,
+    );
+    let selector = sample_state.read().pos_state.selector.clone();
+    let exec_target = |slot: Slot, operations: Vec<WrappedOperation>| {
+        let block = create_block(KeyPair::generate(), operations.clone(), slot).unwrap();
+        let mut storage = Storage::create_root();
+        storage.store_operations(operations);
+        storage.store_block(block.clone());
+        (block.id, storage)
+    };
+
+    // final slot executed without candidate execution
+    let keypair = KeyPair::from_str("S1JJeHiZv1C1zZN5GLFcbz6EXYiccmUPLkYuDFA3kayjxP39kFQ").unwrap();
+    let (recipient_address, _keypair) = get_random_address_full();
+    let operation = Operation::new_wrapped(
+        Operation {
+            fee: Amount::from_str("10").unwrap(),
+            expire_period: 10,
+            op: OperationType::Transaction {
+                recipient_address,
+                amount: Amount::from_str("100").unwrap(),
+            },
+        },
+        OperationSerializer::new(),
+        &keypair,
+    )
+    .unwrap();
+    let first_target = exec_target(Slot::new(1, 0), vec![operation.clone()]);
+    execution_state.execute_final_slot(&Slot::new(1, 0), Some(&first_target), selector.clone());
+    let final_block = final_block_receiver.try_recv().unwrap();
+    assert_eq!(final_block.block_id, first_target.0);
+    assert_eq!(final_block.slot, Slot::new(1, 0));
+    assert_eq!(final_block.operations, vec![operation]);
+    assert_eq!(
+        final_block.final_state_hash,
+        sample_state.read().final_state_hash
+    );
+
+    // final slot found at the front of the candidate executions
+    let second_target = exec_target(Slot::new(1, 1), Vec::new());
+    let third_target = exec_target(Slot::new(2, 0), Vec::new());
+    execution_state.execute_candidate_slot(
+        &Slot::new(1, 1),
+        Some(&second_target),
+        selector.clone(),
+    );
+    execution_state.execute_candidate_slot(&Slot::new(2, 0), Some(&third_target), selector.clone());
+    execution_state.execute_final_slot(&Slot::new(1, 1), Some(&second_target), selector);
+    // the next candidate execution was kept: the cached execution was used
+    assert_eq!(execution_state.active_cursor, Slot::new(2, 0));
+    let final_block = final_block_receiver.try_recv().unwrap();
+    assert_eq!(final_block.block_id, second_target.0);
+    assert_eq!(final_block.slot, Slot::new(1, 1));
+    assert!(final_block.operations.is_empty());
+    assert_eq!(
+        final_block.final_state_hash,
+        sample_state.read().final_state_hash
+    );
+    // nothing else was broadcast
+    assert!(final_block_receiver.try_recv().is_err());
+}
+
+/// # Context
+///
+/// Filters the events of a final slot and of two candidate slots:
+/// 1. final events come before candidate ones, and after them in descending order
+/// 2. the limit and the cursor paginate across final and candidate events
+/// 3. the block id and the finality restrict the returned events
+#[test]
+#[serial]
+fn filtered_events_across_final_and_candidate_slots() {
+    let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+    let (sample_config, _keep_config_dir) = ExecutionConfig::sample();
+    let mut execution_state =
+        ExecutionState::new(sample_config, sample_state.clone(), get_sample_channels());
+    let selector = sample_state.read().pos_state.selector.clone();
+    // keypair associated to thread 0
+    let keypair = KeyPair::from_str("S1JJeHiZv1C1zZN5GLFcbz6EXYiccmUPLkYuDFA3kayjxP39kFQ").unwrap();
+    // a block with an operation emitting a single event
+    let exec_target = |slot: Slot| {
+        // the datastore makes the operations of the different slots distinct
+        let datastore = BTreeMap::from([(vec![slot.period as u8], Vec::new())]);
+        let operation = create_execute_sc_operation(
+            &keypair,
+            include_bytes!("./wasm/event_test.wasm"),
+            datastore,
+        )
+        .unwrap();
+        let block = create_block(KeyPair::generate(), vec![operation.clone()], slot).unwrap();
+        let mut storage = Storage::create_root();
+        storage.store_operations(vec![operation]);
+        storage.store_block(block.clone());
+        (block.id, storage)
+    };
+    let final_target = exec_target(Slot::new(1, 0));
+    let candidate_target = exec_target(Slot::new(2, 0));
+    execution_state.execute_final_slot(&Slot::new(1, 0), Some(&final_target), selector.clone());
+    execution_state.execute_final_slot(&Slot::new(1, 1), None, selector.clone());
+    execution_state.execute_candidate_slot(
+        &Slot::new(2, 0),
+        Some(&candidate_target),
+        selector.clone(),
+    );
+    execution_state.execute_candidate_slot(&Slot::new(2, 1), None, selector.clone());
+    execution_state.execute_candidate_slot(
+        &Slot::new(3, 0),
+        Some(&exec_target(Slot::new(3, 0))),
+        selector,
+    );
+    let periods = |filter: EventFilter| -> Vec<u64> {
+        execution_state
+            .get_filtered_sc_output_event(filter)
+            .into_iter()
+            .map(|event| event.context.slot.period)
+            .collect()
+    };
+
+    // final events first in ascending order, last in descending order
+    assert_eq!(periods(EventFilter::default()), vec![1, 2, 3]);
+    assert_eq!(
+        periods(EventFilter {
+            order: EventOrder::Descending,
+            ..Default::default()
+        }),
+        vec![3, 2, 1]
+    );
+
+    // paginate from the candidate events to the final ones in descending order
+    let mut filter = EventFilter {
+        order: EventOrder::Descending,
+        limit: Some(2),
+        ..Default::default()
+    };
+    let page = execution_state.get_filtered_sc_output_event(filter.clone());
+    assert_eq!(page.len(), 2);
+    filter.cursor = page.last().map(|event| EventCursor::from(&event.context));
+    assert_eq!(periods(filter), vec![1]);
+
+    // paginate from the final events to the candidate ones in ascending order
+    let mut filter = EventFilter {
+        limit: Some(1),
+        ..Default::default()
+    };
+    let page = execution_state.get_filtered_sc_output_event(filter.clone());
+    assert!(page[0].context.is_final);
+    filter.cursor = page.last().map(|event| EventCursor::from(&event.context));
+    filter.limit = Some(5);
+    assert_eq!(periods(filter), vec![2, 3]);
+
+    // restrict to a block or to the final events
+    assert_eq!(
+        periods(EventFilter {
+            block_id: Some(candidate_target.0),
+            ..Default::default()
+        }),
+        vec![2]
+    );
+    assert_eq!(
+        periods(EventFilter {
+            is_final: Some(true),
+            order: EventOrder::Descending,
+            ..Default::default()
+        }),
+        vec![1]
+    );
+    assert_eq!(
+        periods(EventFilter {
+            is_final: Some(false),
+            limit: Some(1),
+            ..Default::default()
+        }),
+        vec![2]
+    );
+}
+
 #[test]
 #[serial]
 fn sc_datastore() {
     // setup the period duration and the maximum gas for asynchronous messages execution
+    let (sample_config, _keep_config_dir) = ExecutionConfig::sample();
     let exec_cfg = ExecutionConfig {
         t0: 100.into(),
         max_async_gas: 100_000,
         cursor_delay: 0.into(),
-        ..ExecutionConfig::default()
+        ..sample_config
     };
     // get a sample final state
     let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
@@ -1002,6 +1811,7 @@ fn sc_datastore() {
         exec_cfg.clone(),
         sample_state.clone(),
         sample_state.read().pos_state.selector.clone(),
+        get_sample_channels(),
     );
     // initialize the execution system with genesis blocks
     init_execution_worker(&exec_cfg, &storage, controller.clone());
@@ -1043,11 +1853,12 @@ fn sc_datastore() {
 #[serial]
 fn set_bytecode_error() {
     // setup the period duration and the maximum gas for asynchronous messages execution
+    let (sample_config, _keep_config_dir) = ExecutionConfig::sample();
     let exec_cfg = ExecutionConfig {
         t0: 100.into(),
         max_async_gas: 100_000,
         cursor_delay: 0.into(),
-        ..ExecutionConfig::default()
+        ..sample_config
     };
     // get a sample final state
     let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
@@ -1059,6 +1870,7 @@ fn set_bytecode_error() {
         exec_cfg.clone(),
         sample_state.clone(),
         sample_state.read().pos_state.selector.clone(),
+        get_sample_channels(),
     );
     // initialize the execution system with genesis blocks
     init_execution_worker(&exec_cfg, &storage, controller.clone());
@@ -1104,11 +1916,12 @@ fn set_bytecode_error() {
 #[serial]
 fn datastore_manipulations() {
     // setup the period duration and the maximum gas for asynchronous messages execution
+    let (sample_config, _keep_config_dir) = ExecutionConfig::sample();
     let exec_cfg = ExecutionConfig {
         t0: 100.into(),
         max_async_gas: 100_000,
         cursor_delay: 0.into(),
-        ..ExecutionConfig::default()
+        ..sample_config
     };
     // get a sample final state
     let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
@@ -1120,6 +1933,7 @@ fn datastore_manipulations() {
         exec_cfg.clone(),
         sample_state.clone(),
         sample_state.read().pos_state.selector.clone(),
+        get_sample_channels(),
     );
     // initialize the execution system with genesis blocks
     init_execution_worker(&exec_cfg, &storage, controller.clone());
@@ -1191,6 +2005,14 @@ fn datastore_manipulations() {
             )
     );
 
+    // the storage report accounts for the datastore entry left
+    let (storage_report, _) = controller
+        .get_addresses_storage(&[Address::from_public_key(&keypair.get_public_key())])
+        .remove(0);
+    assert_eq!(storage_report.datastore_entry_count, 1);
+    assert_eq!(storage_report.datastore_key_bytes, key.len() as u64);
+    assert_eq!(storage_report.datastore_value_bytes, value_len);
+
     // stop the execution controller
     manager.stop();
 }
@@ -1201,10 +2023,11 @@ fn datastore_manipulations() {
 fn events_from_switching_blockclique() {
     // Compile the `./wasm_tests` and generate a block with `event_test.wasm`
     // as data. Then we check if we get an event as expected.
+    let (sample_config, _keep_config_dir) = ExecutionConfig::sample();
     let exec_cfg = ExecutionConfig {
         t0: 100.into(),
         cursor_delay: 0.into(),
-        ..ExecutionConfig::default()
+        ..sample_config
     };
     let storage: Storage = Storage::create_root();
     let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
@@ -1212,6 +2035,7 @@ fn events_from_switching_blockclique() {
         exec_cfg.clone(),
         sample_state.clone(),
         sample_state.read().pos_state.selector.clone(),
+        get_sample_channels(),
     );
     // initialize the execution system with genesis blocks
     init_execution_worker(&exec_cfg, &storage, controller.clone());
@@ -1277,6 +2101,165 @@ fn events_from_switching_blockclique() {
     manager.stop();
 }
 
+/// # Context
+///
+/// Storage reported for a batch of addresses
+///
+/// 1. an address with a bytecode and datastore entries in the final ledger reports their sizes
+///    and the coins locked by their storage costs, as final and as candidate storage
+/// 2. an address absent from the ledger reports no storage
+#[test]
+#[serial]
+fn get_addresses_storage_reports() {
+    let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+    let (exec_cfg, _keep_config_dir) = ExecutionConfig::sample();
+    let costs = exec_cfg.storage_costs_constants;
+    let address = Address::from_public_key(&KeyPair::generate().get_public_key());
+    let absent_address = Address::from_public_key(&KeyPair::generate().get_public_key());
+    let mut changes = LedgerChanges::default();
+    changes.0.insert(
+        address,
+        SetUpdateOrDelete::Set(LedgerEntry {
+            balance: Amount::from_str("1000").unwrap(),
+            bytecode: vec![0; 100],
+            datastore: BTreeMap::from([(vec![1; 3], vec![2; 10]), (vec![3; 5], vec![4; 20])]),
+        }),
+    );
+    let final_slot = sample_state.read().slot;
+    sample_state
+        .write()
+        .ledger
+        .apply_changes(changes, final_slot);
+    let (mut manager, controller) = start_execution_worker(
+        exec_cfg,
+        sample_state.clone(),
+        sample_state.read().pos_state.selector.clone(),
+        get_sample_channels(),
+    );
+
+    let storage_report = AddressStorageReport {
+        bytecode_size: 100,
+        datastore_entry_count: 2,
+        datastore_key_bytes: 8,
+        datastore_value_bytes: 30,
+        locked_coins: costs
+            .ledger_entry_base_cost
+            .saturating_add(costs.ledger_cost_per_byte.saturating_mul_u64(130))
+            .saturating_add(costs.ledger_entry_datastore_base_cost.saturating_mul_u64(2)),
+    };
+    assert_eq!(
+        controller.get_addresses_storage(&[address, absent_address]),
+        vec![
+            (storage_report.clone(), storage_report),
+            (Default::default(), Default::default())
+        ]
+    );
+
+    manager.stop();
+}
+
+/// # Context
+///
+/// Sending an asynchronous message with a trigger other than a ledger change through the runtime interface
+///
+/// 1. the trigger is given serialized in place of the datastore key, with the versioned trigger argument in place of the address
+/// 2. before the network upgrade activating those triggers, the versioned trigger argument is rejected
+/// 3. once activated, the message is emitted with the trigger, even if it is longer than a datastore key
+/// 4. a trigger longer than the maximum trigger length is rejected
+#[test]
+#[serial]
+fn send_message_triggers_activation() {
+    let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+    let keypair = KeyPair::from_str("S1JJeHiZv1C1zZN5GLFcbz6EXYiccmUPLkYuDFA3kayjxP39kFQ").unwrap();
+    let sender = Address::from_public_key(&keypair.get_public_key());
+    let trigger = AsyncMessageTrigger::All(vec![
+        AsyncMessageTrigger::BalanceAbove {
+            address: sender,
+            threshold: Amount::from_str("10").unwrap(),
+        },
+        AsyncMessageTrigger::BytecodeChange { address: sender },
+        AsyncMessageTrigger::Any(
+            (1..=12)
+                .map(|threshold| AsyncMessageTrigger::BalanceBelow {
+                    address: sender,
+                    threshold: Amount::from_mantissa_scale(threshold, 0),
+                })
+                .collect(),
+        ),
+    ]);
+    let mut serialized_trigger = Vec::new();
+    AsyncMessageTriggerSerializer::new()
+        .serialize(&trigger, &mut serialized_trigger)
+        .unwrap();
+    assert!(serialized_trigger.len() > MAX_DATASTORE_KEY_LENGTH as usize);
+
+    let send_message = |activation_period, serialized_trigger: &[u8]| {
+        let config = ExecutionConfig {
+            async_message_triggers_activation_period: activation_period,
+            ..ExecutionConfig::default()
+        };
+        let mut context = ExecutionContext::new(
+            config.clone(),
+            sample_state.clone(),
+            Default::default(),
+            Default::default(),
+        );
+        context.slot = Slot::new(1, 0);
+        context.stack = vec![ExecutionStackElement {
+            address: sender,
+            coins: Amount::zero(),
+            owned_addresses: vec![sender],
+            operation_datastore: None,
+        }];
+        let context = Arc::new(Mutex::new(context));
+        let result = InterfaceImpl::new(config, context.clone()).send_message(
+            &sender.to_string(),
+            "receive",
+            (1, 1),
+            (10, 0),
+            100_000,
+            0,
+            0,
+            &[],
+            Some((ASYNC_MESSAGE_TRIGGER_ARGUMENT_V1, Some(serialized_trigger))),
+        );
+        let emitted_triggers: Vec<_> = context
+            .lock()
+            .settle_slot()
+            .state_changes
+            .async_pool_changes
+            .0
+            .into_iter()
+            .filter_map(|change| match change {
+                Change::Add(_, message) => Some(message.trigger),
+                _ => None,
+            })
+            .collect();
+        (result, emitted_triggers)
+    };
+
+    // the network upgrade is not scheduled
+    let (result, emitted_triggers) = send_message(None, &serialized_trigger);
+    assert!(result.is_err());
+    assert!(emitted_triggers.is_empty());
+
+    // the network upgrade is scheduled after the slot of the execution
+    let (result, emitted_triggers) = send_message(Some(2), &serialized_trigger);
+    assert!(result.is_err());
+    assert!(emitted_triggers.is_empty());
+
+    // the network upgrade has happened
+    let (result, emitted_triggers) = send_message(Some(1), &serialized_trigger);
+    result.unwrap();
+    assert_eq!(emitted_triggers, vec![Some(trigger)]);
+
+    // the trigger is too long
+    let (result, emitted_triggers) =
+        send_message(Some(1), &[0; MAX_ASYNC_MESSAGE_TRIGGER_LENGTH + 1]);
+    assert!(result.is_err());
+    assert!(emitted_triggers.is_empty());
+}
+
 /// Create an operation for the given sender with `data` as bytecode.
 /// Return a result that should be unwrapped in the root `#[test]` routine.
 fn create_execute_sc_operation(
@@ -1334,11 +2317,12 @@ fn create_call_sc_operation(
 #[serial]
 fn sc_builtins() {
     // setup the period duration and the maximum gas for asynchronous messages execution
+    let (sample_config, _keep_config_dir) = ExecutionConfig::sample();
     let exec_cfg = ExecutionConfig {
         t0: 100.into(),
         max_async_gas: 100_000,
         cursor_delay: 0.into(),
-        ..ExecutionConfig::default()
+        ..sample_config
     };
     // get a sample final state
     let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
@@ -1350,6 +2334,7 @@ fn sc_builtins() {
         exec_cfg.clone(),
         sample_state.clone(),
         sample_state.read().pos_state.selector.clone(),
+        get_sample_channels(),
     );
     // initialize the execution system with genesis blocks
     init_execution_worker(&exec_cfg, &storage, controller.clone());
@@ -50,6 +50,9 @@ fn test_active_history_deferred_credits() {
             executed_ops_changes: Default::default(),
         },
         events: Default::default(),
+        receipts: Default::default(),
+        traces: Default::default(),
+        async_message_statuses: Default::default(),
     };
 
     let active_history = ActiveHistory {
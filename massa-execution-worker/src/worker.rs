@@ -10,7 +10,7 @@ use crate::execution::ExecutionState;
 use crate::request_queue::RequestQueue;
 use crate::slot_sequencer::SlotSequencer;
 use massa_execution_exports::{
-    ExecutionConfig, ExecutionController, ExecutionError, ExecutionManager,
+    ExecutionChannels, ExecutionConfig, ExecutionController, ExecutionError, ExecutionManager,
     ReadOnlyExecutionOutput, ReadOnlyExecutionRequest,
 };
 use massa_final_state::FinalState;
@@ -228,6 +228,8 @@ impl ExecutionThread {
 /// # parameters
 /// * `config`: execution configuration
 /// * `final_state`: a thread-safe shared access to the final state for reading and writing
+/// * `selector`: proof-of-stake selector controller
+/// * `channels`: channels used to broadcast the execution outputs
 ///
 /// # Returns
 /// A pair `(execution_manager, execution_controller)` where:
@@ -237,11 +239,13 @@ pub fn start_execution_worker(
     config: ExecutionConfig,
     final_state: Arc<RwLock<FinalState>>,
     selector: Box<dyn SelectorController>,
+    channels: ExecutionChannels,
 ) -> (Box<dyn ExecutionManager>, Box<dyn ExecutionController>) {
     // create an execution state
     let execution_state = Arc::new(RwLock::new(ExecutionState::new(
         config.clone(),
         final_state,
+        channels,
     )));
 
     // define the input data interface
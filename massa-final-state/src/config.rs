@@ -29,4 +29,6 @@ pub struct FinalStateConfig {
     pub initial_seed_string: String,
     /// initial rolls file path
     pub initial_rolls_path: PathBuf,
+    /// path to the disk final state db directory (every component except the ledger)
+    pub final_state_path: PathBuf,
 }
@@ -15,4 +15,8 @@ pub enum FinalStateError {
     LedgerError(String),
     /// PoS error: {0}
     PosError(String),
+    /// final state restore error: {0}
+    RestoreError(String),
+    /// final state snapshot error: {0}
+    SnapshotError(String),
 }
@@ -5,15 +5,26 @@
 //! the output of a given final slot (the latest executed final slot),
 //! and need to be bootstrapped by nodes joining the network.
 
-use crate::{config::FinalStateConfig, error::FinalStateError, state_changes::StateChanges};
-use massa_async_pool::{AsyncMessageId, AsyncPool, AsyncPoolChanges, Change};
+use crate::{
+    config::FinalStateConfig, error::FinalStateError, final_state_db::FinalStateDB,
+    snapshot::FinalStateSnapshot, state_changes::StateChanges,
+};
+use massa_async_pool::{AsyncMessage, AsyncMessageId, AsyncPool, AsyncPoolChanges, Change};
 use massa_executed_ops::ExecutedOps;
 use massa_hash::{Hash, HASH_SIZE_BYTES};
-use massa_ledger_exports::{get_address_from_key, LedgerChanges, LedgerController};
-use massa_models::{slot::Slot, streaming_step::StreamingStep};
-use massa_pos_exports::{DeferredCredits, PoSFinalState, SelectorController};
-use std::collections::VecDeque;
-use tracing::{debug, info};
+use massa_ledger_exports::{
+    get_address_from_key, Applicable, LedgerChanges, LedgerController, LedgerEntryProof,
+};
+use massa_models::{
+    api::{FinalStateComponentHash, FinalStateIntegrityReport},
+    operation::OperationId,
+    prehash::PreHashSet,
+    slot::Slot,
+    streaming_step::StreamingStep,
+};
+use massa_pos_exports::{CycleInfo, DeferredCredits, PoSFinalState, SelectorController};
+use std::collections::{BTreeMap, VecDeque};
+use tracing::{debug, info, warn};
 
 /// Represents a final state `(ledger, async pool, executed_ops and the state of the PoS)`
 pub struct FinalState {
@@ -34,9 +45,55 @@ pub struct FinalState {
     pub changes_history: VecDeque<(Slot, StateChanges)>,
     /// hash of the final state, it is computed on finality
     pub final_state_hash: Hash,
+    /// disk persistence of the components that are not part of the disk ledger
+    pub(crate) db: FinalStateDB,
 }
 
 const FINAL_STATE_HASH_INITIAL_BYTES: &[u8; 32] = &[0; HASH_SIZE_BYTES];
+/// hash of an empty disk ledger
+const LEDGER_HASH_INITIAL_BYTES: &[u8; 32] = &[0; HASH_SIZE_BYTES];
+
+/// Final state integrity check started by `FinalState::start_integrity_check`,
+/// waiting for the scan of the ledger snapshot
+pub struct FinalStateIntegrityCheck {
+    /// final slot at the output of which the check was started
+    slot: Slot,
+    /// ledger hash maintained incrementally
+    stored_ledger_hash: Hash,
+    /// waits for the ledger hash recomputed from the ledger snapshot
+    computed_ledger_hash: Box<dyn FnOnce() -> Hash + Send>,
+    /// checked hashes of the other components, in the order they enter the final state hash
+    components: Vec<FinalStateComponentHash>,
+    /// final state hash maintained incrementally
+    final_state_hash: Hash,
+}
+
+impl FinalStateIntegrityCheck {
+    /// Waits for the ledger scan, then recomputes the final state hash from the fresh component hashes
+    pub fn complete(self) -> FinalStateIntegrityReport {
+        let mut components = vec![FinalStateComponentHash {
+            component: "ledger".to_string(),
+            stored_hash: self.stored_ledger_hash,
+            computed_hash: (self.computed_ledger_hash)(),
+        }];
+        components.extend(self.components);
+        // recomputed hashes concatenated in the order of `compute_state_hash_at_slot`
+        let mut hash_concat: Vec<u8> = Vec::new();
+        for component in &components {
+            hash_concat.extend(component.computed_hash.to_bytes());
+        }
+        components.push(FinalStateComponentHash {
+            component: "final_state".to_string(),
+            stored_hash: self.final_state_hash,
+            computed_hash: Hash::compute_from(&hash_concat),
+        });
+
+        FinalStateIntegrityReport {
+            slot: self.slot,
+            components,
+        }
+    }
+}
 
 impl FinalState {
     /// Initializes a new `FinalState`
@@ -67,6 +124,9 @@ impl FinalState {
         // create a default executed ops
         let executed_ops = ExecutedOps::new(config.executed_ops_config.clone());
 
+        // open the disk final state
+        let db = FinalStateDB::new(config.final_state_path.clone(), &config);
+
         // create the final state
         Ok(FinalState {
             slot,
@@ -77,9 +137,242 @@ impl FinalState {
             executed_ops,
             changes_history: Default::default(), // no changes in history
             final_state_hash: Hash::from_bytes(FINAL_STATE_HASH_INITIAL_BYTES),
+            db,
         })
     }
 
+    /// Restores the final state persisted on disk by a previous run of the node.
+    /// Must be called on a freshly created `FinalState`.
+    ///
+    /// The persisted slot is checked against the disk ledger slot, the components are loaded
+    /// from disk, then the final state hash is recomputed and compared to the persisted one.
+    ///
+    /// # Returns
+    /// `Ok(true)` if the final state was restored, `Ok(false)` if nothing was persisted
+    pub fn restore_from_disk(&mut self) -> Result<bool, FinalStateError> {
+        let (slot, persisted_hash) = match self.db.get_metadata()? {
+            Some(metadata) => metadata,
+            None => return Ok(false),
+        };
+        // the metadata is written last: a ledger at another slot means an interrupted write
+        let ledger_slot = self.ledger.get_slot();
+        if ledger_slot != Some(slot) {
+            return Err(FinalStateError::RestoreError(format!(
+                "the disk ledger is attached to slot {:?} while the final state was persisted at slot {}",
+                ledger_slot, slot
+            )));
+        }
+        self.db.load_async_pool(&mut self.async_pool)?;
+        self.db.load_pos_state(&mut self.pos_state)?;
+        self.db.load_executed_ops(&mut self.executed_ops)?;
+        if self.pos_state.cycle_history.is_empty() {
+            return Err(FinalStateError::RestoreError(
+                "persisted cycle history is empty".into(),
+            ));
+        }
+        self.slot = slot;
+
+        self.compute_state_hash_at_slot(slot);
+        if self.final_state_hash != persisted_hash {
+            return Err(FinalStateError::RestoreError(format!(
+                "final state hash mismatch at slot {}: computed {} but {} was persisted",
+                slot, self.final_state_hash, persisted_hash
+            )));
+        }
+
+        // nodes that did not restart created their PoS state on an empty disk ledger
+        self.pos_state.initial_ledger_hash = Hash::from_bytes(LEDGER_HASH_INITIAL_BYTES);
+
+        info!("final state restored from disk at slot {}", slot);
+        Ok(true)
+    }
+
+    /// Resets the final state to its initial empty value, on disk and in memory.
+    /// Used when the persisted final state cannot be used or resumed from.
+    pub fn reset(&mut self) {
+        warn!("resetting the final state");
+        self.ledger.reset();
+        self.db.clear();
+        self.slot = Slot::new(0, self.config.thread_count.saturating_sub(1));
+        self.async_pool = AsyncPool::new(self.config.async_pool_config.clone());
+        self.pos_state.cycle_history.clear();
+        self.pos_state.deferred_credits = DeferredCredits::default();
+        self.pos_state.initial_ledger_hash = Hash::from_bytes(LEDGER_HASH_INITIAL_BYTES);
+        self.executed_ops = ExecutedOps::new(self.config.executed_ops_config.clone());
+        self.changes_history.clear();
+        self.final_state_hash = Hash::from_bytes(FINAL_STATE_HASH_INITIAL_BYTES);
+    }
+
+    /// Persists every component of the final state on disk.
+    ///
+    /// Used once the final state was obtained through bootstrap,
+    /// later slots are persisted incrementally by `finalize`.
+    pub fn write_to_disk(&mut self) {
+        // ledger parts do not carry the ledger slot, attach it before hashing
+        // so that the ledger slot matches the persisted metadata
+        self.ledger
+            .apply_changes(LedgerChanges::default(), self.slot);
+        self.compute_state_hash_at_slot(self.slot);
+        self.db.write_all(
+            self.slot,
+            self.final_state_hash,
+            &self.async_pool,
+            &self.pos_state,
+            &self.executed_ops,
+        );
+    }
+
+    /// Creates a snapshot of the whole final state at its current slot.
+    pub fn create_snapshot(&self) -> Result<FinalStateSnapshot, FinalStateError> {
+        let mut ledger_parts = Vec::new();
+        let mut cursor = StreamingStep::Started;
+        loop {
+            let (part, new_cursor) = self.get_snapshot_ledger_part(cursor)?;
+            if part.is_empty() {
+                break;
+            }
+            ledger_parts.push(part);
+            cursor = new_cursor;
+        }
+        let mut snapshot = self.create_snapshot_tail(self.slot)?;
+        snapshot.ledger_parts = ledger_parts;
+        Ok(snapshot)
+    }
+
+    /// Gets the ledger part following `cursor`, an empty part meaning the end of the ledger.
+    /// Used to read the ledger of a snapshot without holding the final state for the whole read.
+    pub fn get_snapshot_ledger_part(
+        &self,
+        cursor: StreamingStep<Vec<u8>>,
+    ) -> Result<(Vec<u8>, StreamingStep<Vec<u8>>), FinalStateError> {
+        self.ledger
+            .get_ledger_part(cursor)
+            .map_err(|err| FinalStateError::LedgerError(err.to_string()))
+    }
+
+    /// Creates a snapshot of the final state at its current slot, without the ledger parts.
+    ///
+    /// The ledger parts are read separately with `get_snapshot_ledger_part`, starting when
+    /// the final state was at `ledger_start_slot`. The ledger changes finalized since then
+    /// are included so that they are applied on top of the parts, which may be older.
+    pub fn create_snapshot_tail(
+        &self,
+        ledger_start_slot: Slot,
+    ) -> Result<FinalStateSnapshot, FinalStateError> {
+        let mut ledger_changes = LedgerChanges::default();
+        if self.slot != ledger_start_slot {
+            let first_missing_slot = ledger_start_slot
+                .get_next_slot(self.config.thread_count)
+                .map_err(|err| FinalStateError::InvalidSlot(err.to_string()))?;
+            match self.changes_history.front() {
+                Some((front_slot, _)) if *front_slot <= first_missing_slot => {}
+                _ => {
+                    return Err(FinalStateError::SnapshotError(format!(
+                    "the changes finalized since slot {} are no longer in the final state history",
+                    ledger_start_slot
+                )))
+                }
+            }
+            for (slot, changes) in &self.changes_history {
+                if *slot > ledger_start_slot {
+                    ledger_changes.apply(changes.ledger_changes.clone());
+                }
+            }
+        }
+        Ok(FinalStateSnapshot {
+            slot: self.slot,
+            final_state_hash: self.final_state_hash,
+            ledger_parts: Vec::new(),
+            ledger_changes,
+            async_pool_messages: self.async_pool.messages.values().cloned().collect(),
+            cycle_history: self.pos_state.cycle_history.iter().cloned().collect(),
+            deferred_credits: self.pos_state.deferred_credits.clone(),
+            executed_ops: self.executed_ops.sorted_ops.clone(),
+        })
+    }
+
+    /// Replaces the whole final state by the content of a snapshot and persists it on disk.
+    ///
+    /// The final state hash is recomputed and compared to the one of the snapshot,
+    /// the final state is reset if they do not match.
+    pub fn load_snapshot(&mut self, snapshot: FinalStateSnapshot) -> Result<(), FinalStateError> {
+        self.reset();
+        if let Err(err) = self.set_snapshot_parts(
+            snapshot.ledger_parts,
+            snapshot.async_pool_messages,
+            snapshot.cycle_history,
+            snapshot.deferred_credits,
+            snapshot.executed_ops,
+        ) {
+            self.reset();
+            return Err(err);
+        }
+        self.slot = snapshot.slot;
+        // also attaches the ledger to the snapshot slot
+        self.ledger
+            .apply_changes(snapshot.ledger_changes, self.slot);
+        self.compute_state_hash_at_slot(self.slot);
+        if self.final_state_hash != snapshot.final_state_hash {
+            let err = FinalStateError::RestoreError(format!(
+                "snapshot hash mismatch at slot {}: computed {} but the snapshot contains {}",
+                snapshot.slot, self.final_state_hash, snapshot.final_state_hash
+            ));
+            self.reset();
+            return Err(err);
+        }
+        self.db.write_all(
+            self.slot,
+            self.final_state_hash,
+            &self.async_pool,
+            &self.pos_state,
+            &self.executed_ops,
+        );
+        info!("final state loaded from snapshot at slot {}", self.slot);
+        Ok(())
+    }
+
+    /// Sets the components of a snapshot, recomputing their hashes.
+    fn set_snapshot_parts(
+        &mut self,
+        ledger_parts: Vec<Vec<u8>>,
+        async_pool_messages: Vec<AsyncMessage>,
+        cycle_history: Vec<CycleInfo>,
+        deferred_credits: DeferredCredits,
+        executed_ops: BTreeMap<Slot, PreHashSet<OperationId>>,
+    ) -> Result<(), FinalStateError> {
+        for part in ledger_parts {
+            self.ledger
+                .set_ledger_part(part)
+                .map_err(|err| FinalStateError::LedgerError(err.to_string()))?;
+        }
+        self.async_pool.set_pool_part(
+            async_pool_messages
+                .into_iter()
+                .map(|message| (message.compute_id(), message))
+                .collect(),
+        );
+        if cycle_history.is_empty() {
+            return Err(FinalStateError::RestoreError(
+                "snapshot cycle history is empty".into(),
+            ));
+        }
+        for cycle_info in cycle_history {
+            // checked here because `set_cycle_history_part` panics on a gap
+            if let Some(last_cycle) = self.pos_state.cycle_history.back() {
+                if last_cycle.cycle.checked_add(1) != Some(cycle_info.cycle) {
+                    return Err(FinalStateError::RestoreError(format!(
+                        "snapshot cycle history is not contiguous: cycle {} follows cycle {}",
+                        cycle_info.cycle, last_cycle.cycle
+                    )));
+                }
+            }
+            self.pos_state.set_cycle_history_part(Some(cycle_info));
+        }
+        self.pos_state.set_deferred_credits_part(deferred_credits);
+        self.executed_ops.set_executed_ops_part(executed_ops);
+        Ok(())
+    }
+
     /// Compute the current state hash.
     ///
     /// Used when finalizing a slot.
@@ -122,6 +415,95 @@ impl FinalState {
         );
     }
 
+    /// Get the proof of a ledger key against the current final state hash.
+    /// Fails until the ledger hash is computed from the ledger tree root.
+    ///
+    /// # Arguments
+    /// * `key`: serialized ledger key
+    pub fn get_ledger_entry_proof(
+        &self,
+        key: Vec<u8>,
+    ) -> Result<LedgerEntryProof, FinalStateError> {
+        if !self.ledger.is_tree_hash_active() {
+            return Err(FinalStateError::LedgerError(
+                "ledger entry proofs are not available before the activation of the ledger tree hash"
+                    .into(),
+            ));
+        }
+        let (value, tree_proof) = self.ledger.get_entry_proof(&key);
+        // the parts hashes must be listed in the order of `compute_state_hash_at_slot`
+        let mut final_state_parts_hashes =
+            vec![self.async_pool.hash, self.pos_state.deferred_credits.hash];
+        let n = (self.pos_state.cycle_history.len() == self.config.pos_config.cycle_history_length)
+            as usize;
+        final_state_parts_hashes.extend(
+            self.pos_state
+                .cycle_history
+                .iter()
+                .skip(n)
+                .map(|cycle_info| cycle_info.cycle_global_hash),
+        );
+        final_state_parts_hashes.push(self.executed_ops.hash);
+        Ok(LedgerEntryProof {
+            key,
+            value,
+            tree_proof,
+            slot: self.slot,
+            final_state_parts_hashes,
+            final_state_hash: self.final_state_hash,
+        })
+    }
+
+    /// Starts recomputing every component hash from scratch, including a full scan of the ledger,
+    /// to compare them to the hashes maintained incrementally by the final state.
+    ///
+    /// The in-memory components are hashed right away while the ledger is scanned
+    /// on a snapshot of the disk ledger, so that the final state does not need to be held
+    /// until the check is completed with `FinalStateIntegrityCheck::complete`.
+    pub fn start_integrity_check(&self) -> FinalStateIntegrityCheck {
+        let mut components = Vec::new();
+        let mut check_component = |component: String, stored_hash: Hash, computed_hash: Hash| {
+            components.push(FinalStateComponentHash {
+                component,
+                stored_hash,
+                computed_hash,
+            });
+        };
+        check_component(
+            "async_pool".to_string(),
+            self.async_pool.hash,
+            self.async_pool.compute_hash(),
+        );
+        check_component(
+            "deferred_credits".to_string(),
+            self.pos_state.deferred_credits.hash,
+            self.pos_state.deferred_credits.compute_hash(),
+        );
+        // skip the bootstrap safety cycle if there is one, as in the final state hash
+        let n = (self.pos_state.cycle_history.len() == self.config.pos_config.cycle_history_length)
+            as usize;
+        for cycle_info in self.pos_state.cycle_history.iter().skip(n) {
+            check_component(
+                format!("cycle {}", cycle_info.cycle),
+                cycle_info.cycle_global_hash,
+                cycle_info.compute_hashes().2,
+            );
+        }
+        check_component(
+            "executed_ops".to_string(),
+            self.executed_ops.hash,
+            self.executed_ops.compute_hash(),
+        );
+
+        FinalStateIntegrityCheck {
+            slot: self.slot,
+            stored_ledger_hash: self.ledger.get_ledger_hash(),
+            computed_ledger_hash: self.ledger.compute_ledger_hash_on_snapshot(),
+            components,
+            final_state_hash: self.final_state_hash,
+        }
+    }
+
     /// Performs the initial draws.
     pub fn compute_initial_draws(&mut self) -> Result<(), FinalStateError> {
         self.pos_state
@@ -161,14 +543,6 @@ impl FinalState {
         self.executed_ops
             .apply_changes(changes.executed_ops_changes.clone(), self.slot);
 
-        // push history element and limit history size
-        if self.config.final_history_length > 0 {
-            while self.changes_history.len() >= self.config.final_history_length {
-                self.changes_history.pop_front();
-            }
-            self.changes_history.push_back((slot, changes));
-        }
-
         // compute the final state hash
         self.compute_state_hash_at_slot(slot);
 
@@ -176,6 +550,24 @@ impl FinalState {
         let cycle = slot.get_cycle(self.config.periods_per_cycle);
         self.pos_state
             .feed_cycle_state_hash(cycle, self.final_state_hash);
+
+        // persist the changes, the ledger being already written to disk
+        self.db.write_changes(
+            slot,
+            self.final_state_hash,
+            &changes,
+            &self.async_pool,
+            &self.pos_state,
+            &self.executed_ops,
+        );
+
+        // push history element and limit history size
+        if self.config.final_history_length > 0 {
+            while self.changes_history.len() >= self.config.final_history_length {
+                self.changes_history.pop_front();
+            }
+            self.changes_history.push_back((slot, changes));
+        }
     }
 
     /// Used for bootstrap.
@@ -219,7 +611,11 @@ impl FinalState {
             }
             index
         } else {
-            return Ok(Vec::new());
+            // this is only called when the given slot differs from the final state one
+            // so an empty history means that the changes after `slot` are unavailable
+            return Err(FinalStateError::InvalidSlot(
+                "changes history is empty".to_string(),
+            ));
         };
         let mut res_changes: Vec<(Slot, StateChanges)> = Vec::new();
         for (slot, changes) in self.changes_history.range((position_slot as usize)..) {
@@ -332,19 +728,357 @@ impl FinalState {
 #[cfg(test)]
 mod tests {
 
-    use std::collections::VecDeque;
+    use std::{collections::VecDeque, io::Write, str::FromStr, sync::mpsc::Receiver};
 
-    use crate::StateChanges;
-    use massa_async_pool::test_exports::get_random_message;
-    use massa_ledger_exports::SetUpdateOrDelete;
-    use massa_models::{address::Address, slot::Slot};
+    use super::FinalState;
+    use crate::{
+        FinalStateConfig, FinalStateSnapshotDeserializer, FinalStateSnapshotWriter, StateChanges,
+    };
+    use massa_async_pool::{test_exports::get_random_message, AsyncPoolConfig};
+    use massa_executed_ops::ExecutedOpsConfig;
+    use massa_hash::Hash;
+    use massa_ledger_exports::{
+        LedgerChanges, LedgerConfig, LedgerEntry, LedgerEntryUpdate, SetOrKeep, SetUpdateOrDelete,
+    };
+    use massa_ledger_worker::FinalLedger;
+    use massa_models::{
+        address::Address,
+        amount::Amount,
+        config::{
+            MAX_ASYNC_MESSAGE_DATA, MAX_ASYNC_POOL_LENGTH, MAX_DATASTORE_ENTRY_COUNT,
+            MAX_DATASTORE_KEY_LENGTH, MAX_DATASTORE_VALUE_LENGTH, MAX_DEFERRED_CREDITS_LENGTH,
+            MAX_EXECUTED_OPS_LENGTH, MAX_LEDGER_CHANGES_COUNT, MAX_OPERATIONS_PER_BLOCK,
+            MAX_PRODUCTION_STATS_LENGTH, MAX_ROLLS_COUNT_LENGTH, PERIODS_PER_CYCLE,
+            POS_SAVED_CYCLES, THREAD_COUNT,
+        },
+        operation::OperationId,
+        slot::Slot,
+        streaming_step::StreamingStep,
+    };
+    use massa_pos_exports::{
+        test_exports::{MockSelectorController, MockSelectorControllerMessage},
+        PoSConfig,
+    };
+    use massa_serialization::{DeserializeError, Deserializer};
     use massa_signature::KeyPair;
+    use tempfile::{NamedTempFile, TempDir};
 
     fn get_random_address() -> Address {
         let keypair = KeyPair::generate();
         Address::from_public_key(&keypair.get_public_key())
     }
 
+    fn get_disk_config(
+        ledger_dir: &TempDir,
+        state_dir: &TempDir,
+        rolls_file: &NamedTempFile,
+    ) -> FinalStateConfig {
+        FinalStateConfig {
+            ledger_config: LedgerConfig {
+                disk_ledger_path: ledger_dir.path().to_path_buf(),
+                ..Default::default()
+            },
+            async_pool_config: AsyncPoolConfig::default(),
+            executed_ops_config: ExecutedOpsConfig {
+                thread_count: THREAD_COUNT,
+                bootstrap_part_size: 10,
+            },
+            pos_config: PoSConfig {
+                periods_per_cycle: PERIODS_PER_CYCLE,
+                thread_count: THREAD_COUNT,
+                cycle_history_length: POS_SAVED_CYCLES,
+                credits_bootstrap_part_size: 10,
+            },
+            final_history_length: 10,
+            thread_count: THREAD_COUNT,
+            periods_per_cycle: PERIODS_PER_CYCLE,
+            initial_seed_string: "".to_string(),
+            initial_rolls_path: rolls_file.path().to_path_buf(),
+            final_state_path: state_dir.path().to_path_buf(),
+        }
+    }
+
+    fn get_snapshot_deserializer() -> FinalStateSnapshotDeserializer {
+        FinalStateSnapshotDeserializer::new(
+            THREAD_COUNT,
+            MAX_ASYNC_POOL_LENGTH,
+            MAX_ASYNC_MESSAGE_DATA,
+            MAX_LEDGER_CHANGES_COUNT,
+            MAX_DATASTORE_KEY_LENGTH,
+            MAX_DATASTORE_VALUE_LENGTH,
+            MAX_DATASTORE_ENTRY_COUNT,
+            POS_SAVED_CYCLES as u64,
+            MAX_ROLLS_COUNT_LENGTH,
+            MAX_PRODUCTION_STATS_LENGTH,
+            MAX_DEFERRED_CREDITS_LENGTH,
+            MAX_EXECUTED_OPS_LENGTH,
+            MAX_OPERATIONS_PER_BLOCK as u64,
+        )
+    }
+
+    fn open_final_state(
+        config: &FinalStateConfig,
+    ) -> (FinalState, Receiver<MockSelectorControllerMessage>) {
+        let (selector, selector_receiver) = MockSelectorController::new_with_receiver();
+        let final_state = FinalState::new(
+            config.clone(),
+            Box::new(FinalLedger::new(config.ledger_config.clone())),
+            selector,
+        )
+        .expect("could not create the final state");
+        (final_state, selector_receiver)
+    }
+
+    #[test]
+    fn restore_from_disk_round_trip() {
+        let ledger_dir = TempDir::new().unwrap();
+        let state_dir = TempDir::new().unwrap();
+        let mut rolls_file = NamedTempFile::new().unwrap();
+        rolls_file.write_all(b"{}").unwrap();
+        let config = get_disk_config(&ledger_dir, &state_dir, &rolls_file);
+
+        // start from the initial state and finalize a slot with changes in every component
+        let address = get_random_address();
+        let balance = Amount::from_str("42").unwrap();
+        let message = get_random_message(None);
+        let message_id = message.compute_id();
+        let op_id = OperationId::new(Hash::compute_from(b"executed op"));
+        let (slot, hash) = {
+            let (mut final_state, _selector_receiver) = open_final_state(&config);
+            final_state.pos_state.create_initial_cycle();
+            final_state.write_to_disk();
+
+            let mut changes = StateChanges::default();
+            changes.ledger_changes.0.insert(
+                address,
+                SetUpdateOrDelete::Set(LedgerEntry {
+                    balance,
+                    ..Default::default()
+                }),
+            );
+            changes
+                .async_pool_changes
+                .0
+                .push(massa_async_pool::Change::Add(message_id, message));
+            changes.executed_ops_changes.insert(op_id, Slot::new(10, 0));
+            let slot = Slot::new(1, 0);
+            final_state.finalize(slot, changes);
+            (slot, final_state.final_state_hash)
+        };
+
+        // reopen and restore
+        let (mut final_state, _selector_receiver) = open_final_state(&config);
+        assert!(final_state.restore_from_disk().expect("restore failed"));
+        assert_eq!(final_state.slot, slot);
+        assert_eq!(final_state.final_state_hash, hash);
+        assert_eq!(final_state.ledger.get_balance(&address), Some(balance));
+        assert!(final_state.async_pool.messages.contains_key(&message_id));
+        assert!(final_state.executed_ops.contains(&op_id));
+        assert!(!final_state.pos_state.cycle_history.is_empty());
+    }
+
+    #[test]
+    fn restore_from_disk_rejects_interrupted_write() {
+        let ledger_dir = TempDir::new().unwrap();
+        let state_dir = TempDir::new().unwrap();
+        let mut rolls_file = NamedTempFile::new().unwrap();
+        rolls_file.write_all(b"{}").unwrap();
+        let config = get_disk_config(&ledger_dir, &state_dir, &rolls_file);
+
+        {
+            let (mut final_state, _selector_receiver) = open_final_state(&config);
+            final_state.pos_state.create_initial_cycle();
+            final_state.write_to_disk();
+            // the ledger of the next slot is written but the metadata is not
+            let mut ledger_changes = LedgerChanges::default();
+            ledger_changes.0.insert(
+                get_random_address(),
+                SetUpdateOrDelete::Update(LedgerEntryUpdate {
+                    balance: SetOrKeep::Set(Amount::from_str("1").unwrap()),
+                    ..Default::default()
+                }),
+            );
+            final_state
+                .ledger
+                .apply_changes(ledger_changes, Slot::new(1, 0));
+        }
+
+        let (mut final_state, _selector_receiver) = open_final_state(&config);
+        assert!(final_state.restore_from_disk().is_err());
+    }
+
+    #[test]
+    fn integrity_check_during_finalization() {
+        let ledger_dir = TempDir::new().unwrap();
+        let state_dir = TempDir::new().unwrap();
+        let mut rolls_file = NamedTempFile::new().unwrap();
+        rolls_file.write_all(b"{}").unwrap();
+        let config = get_disk_config(&ledger_dir, &state_dir, &rolls_file);
+        let (mut final_state, _selector_receiver) = open_final_state(&config);
+        final_state.pos_state.create_initial_cycle();
+
+        let address = get_random_address();
+        let mut changes = StateChanges::default();
+        changes.ledger_changes.0.insert(
+            address,
+            SetUpdateOrDelete::Set(LedgerEntry {
+                balance: Amount::from_str("42").unwrap(),
+                ..Default::default()
+            }),
+        );
+        final_state.finalize(Slot::new(1, 0), changes);
+
+        // the check does not hold the final state while the ledger is scanned
+        let integrity_check = final_state.start_integrity_check();
+        let mut changes = StateChanges::default();
+        changes
+            .ledger_changes
+            .0
+            .insert(address, SetUpdateOrDelete::Delete);
+        final_state.finalize(Slot::new(1, 1), changes);
+        let report = integrity_check.complete();
+        assert_eq!(report.slot, Slot::new(1, 0));
+        assert!(report.is_consistent(), "{}", report);
+
+        let report = final_state.start_integrity_check().complete();
+        assert_eq!(report.slot, Slot::new(1, 1));
+        assert!(report.is_consistent(), "{}", report);
+    }
+
+    #[test]
+    fn snapshot_round_trip_during_finalization() {
+        let ledger_dir = TempDir::new().unwrap();
+        let state_dir = TempDir::new().unwrap();
+        let mut rolls_file = NamedTempFile::new().unwrap();
+        rolls_file.write_all(b"{}").unwrap();
+        let config = get_disk_config(&ledger_dir, &state_dir, &rolls_file);
+        let (mut final_state, _selector_receiver) = open_final_state(&config);
+        final_state.pos_state.create_initial_cycle();
+        final_state.write_to_disk();
+
+        // populate the ledger, the async pool and the executed ops
+        let address = get_random_address();
+        let other_address = get_random_address();
+        let message = get_random_message(None);
+        let message_id = message.compute_id();
+        let op_id = OperationId::new(Hash::compute_from(b"executed op"));
+        let mut changes = StateChanges::default();
+        changes.ledger_changes.0.insert(
+            address,
+            SetUpdateOrDelete::Set(LedgerEntry {
+                balance: Amount::from_str("42").unwrap(),
+                ..Default::default()
+            }),
+        );
+        changes
+            .async_pool_changes
+            .0
+            .push(massa_async_pool::Change::Add(message_id, message));
+        changes.executed_ops_changes.insert(op_id, Slot::new(10, 0));
+        final_state.finalize(Slot::new(1, 0), changes);
+
+        // export the ledger part by part, finalizing another slot before the end
+        let ledger_start_slot = final_state.slot;
+        let mut writer = FinalStateSnapshotWriter::new(Vec::new()).unwrap();
+        let mut cursor = StreamingStep::Started;
+        loop {
+            let (part, new_cursor) = final_state.get_snapshot_ledger_part(cursor).unwrap();
+            if part.is_empty() {
+                break;
+            }
+            writer.write_ledger_part(&part).unwrap();
+            cursor = new_cursor;
+        }
+        let mut changes = StateChanges::default();
+        changes.ledger_changes.0.insert(
+            address,
+            SetUpdateOrDelete::Update(LedgerEntryUpdate {
+                balance: SetOrKeep::Set(Amount::from_str("7").unwrap()),
+                ..Default::default()
+            }),
+        );
+        changes.ledger_changes.0.insert(
+            other_address,
+            SetUpdateOrDelete::Set(LedgerEntry {
+                balance: Amount::from_str("3").unwrap(),
+                ..Default::default()
+            }),
+        );
+        final_state.finalize(Slot::new(1, 1), changes);
+        let tail = final_state.create_snapshot_tail(ledger_start_slot).unwrap();
+        let bytes = writer.finish(&tail).unwrap();
+
+        // load the snapshot in another node
+        let (rest, snapshot) = get_snapshot_deserializer()
+            .deserialize::<DeserializeError>(&bytes)
+            .unwrap();
+        assert!(rest.is_empty());
+        let other_ledger_dir = TempDir::new().unwrap();
+        let other_state_dir = TempDir::new().unwrap();
+        let other_config = get_disk_config(&other_ledger_dir, &other_state_dir, &rolls_file);
+        {
+            let (mut loaded_state, _selector_receiver) = open_final_state(&other_config);
+            loaded_state.load_snapshot(snapshot).unwrap();
+            assert_eq!(loaded_state.slot, Slot::new(1, 1));
+            assert_eq!(loaded_state.final_state_hash, final_state.final_state_hash);
+            assert_eq!(
+                loaded_state.ledger.get_balance(&address),
+                Some(Amount::from_str("7").unwrap())
+            );
+            assert_eq!(
+                loaded_state.ledger.get_balance(&other_address),
+                Some(Amount::from_str("3").unwrap())
+            );
+            assert!(loaded_state.async_pool.messages.contains_key(&message_id));
+            assert!(loaded_state.executed_ops.contains(&op_id));
+            assert_eq!(
+                loaded_state.pos_state.cycle_history,
+                final_state.pos_state.cycle_history
+            );
+        }
+
+        // the loaded snapshot was persisted
+        let (mut restored_state, _selector_receiver) = open_final_state(&other_config);
+        assert!(restored_state.restore_from_disk().unwrap());
+        assert_eq!(
+            restored_state.final_state_hash,
+            final_state.final_state_hash
+        );
+    }
+
+    #[test]
+    fn load_snapshot_rejects_hash_mismatch() {
+        let ledger_dir = TempDir::new().unwrap();
+        let state_dir = TempDir::new().unwrap();
+        let mut rolls_file = NamedTempFile::new().unwrap();
+        rolls_file.write_all(b"{}").unwrap();
+        let config = get_disk_config(&ledger_dir, &state_dir, &rolls_file);
+        let (mut final_state, _selector_receiver) = open_final_state(&config);
+        final_state.pos_state.create_initial_cycle();
+        let address = get_random_address();
+        let mut changes = StateChanges::default();
+        changes.ledger_changes.0.insert(
+            address,
+            SetUpdateOrDelete::Set(LedgerEntry {
+                balance: Amount::from_str("42").unwrap(),
+                ..Default::default()
+            }),
+        );
+        final_state.finalize(Slot::new(1, 0), changes);
+
+        let mut snapshot = final_state.create_snapshot().unwrap();
+        snapshot.final_state_hash = Hash::compute_from(b"tampered");
+        let other_ledger_dir = TempDir::new().unwrap();
+        let other_state_dir = TempDir::new().unwrap();
+        let other_config = get_disk_config(&other_ledger_dir, &other_state_dir, &rolls_file);
+        let (mut loaded_state, _selector_receiver) = open_final_state(&other_config);
+        assert!(loaded_state.load_snapshot(snapshot).is_err());
+        // nothing from the snapshot is kept
+        assert_eq!(loaded_state.slot, Slot::new(0, THREAD_COUNT - 1));
+        assert_eq!(loaded_state.ledger.get_balance(&address), None);
+        assert!(!loaded_state.restore_from_disk().unwrap());
+    }
+
     #[test]
     fn get_state_changes_part() {
         let message = get_random_message(None);
@@ -0,0 +1,465 @@
+//! Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Module to persist on disk the final state components that are not part of the disk ledger
+//! (asynchronous pool, proof-of-stake state and executed operations),
+//! along with the final slot and the final state hash they are attached to.
+//!
+//! The disk ledger and this database are written in separate commits: the ledger first,
+//! then the components and finally the slot and hash metadata. The metadata is only written
+//! once everything else is on disk, and is checked against the ledger slot on restore.
+
+use crate::{config::FinalStateConfig, error::FinalStateError, state_changes::StateChanges};
+use massa_async_pool::{
+    AsyncMessage, AsyncMessageDeserializer, AsyncMessageIdSerializer, AsyncMessageSerializer,
+    AsyncPool, Change,
+};
+use massa_executed_ops::{ExecutedOps, ExecutedOpsDeserializer, ExecutedOpsSerializer};
+use massa_hash::{Hash, HASH_SIZE_BYTES};
+use massa_models::{
+    address::Address,
+    amount::Amount,
+    operation::OperationId,
+    prehash::{PreHashMap, PreHashSet},
+    slot::{Slot, SlotDeserializer, SlotSerializer, SLOT_KEY_SIZE},
+};
+use massa_pos_exports::{
+    CycleInfo, CycleInfoDeserializer, CycleInfoSerializer, DeferredCredits,
+    DeferredCreditsDeserializer, DeferredCreditsSerializer, PoSFinalState,
+};
+use massa_serialization::{DeserializeError, Deserializer, Serializer};
+use rocksdb::{ColumnFamilyDescriptor, IteratorMode, Options, WriteBatch, DB};
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::ops::Bound::{Excluded, Included};
+use std::path::PathBuf;
+
+const METADATA_CF: &str = "metadata";
+const ASYNC_POOL_CF: &str = "async_pool";
+const CYCLE_HISTORY_CF: &str = "cycle_history";
+const DEFERRED_CREDITS_CF: &str = "deferred_credits";
+const EXECUTED_OPS_CF: &str = "executed_ops";
+const OPEN_ERROR: &str = "critical: rocksdb open operation failed";
+const CRUD_ERROR: &str = "critical: rocksdb crud operation failed";
+const CF_ERROR: &str = "critical: rocksdb column family operation failed";
+const SER_ERROR: &str = "critical: final state serialization failed";
+const SLOT_KEY: &[u8; 1] = b"s";
+const FINAL_STATE_HASH_KEY: &[u8; 1] = b"h";
+
+/// Disk final state DB module
+///
+/// Contains a `RocksDB` DB instance
+pub(crate) struct FinalStateDB {
+    db: DB,
+    slot_serializer: SlotSerializer,
+    slot_deserializer: SlotDeserializer,
+    message_id_serializer: AsyncMessageIdSerializer,
+    message_serializer: AsyncMessageSerializer,
+    message_deserializer: AsyncMessageDeserializer,
+    cycle_info_serializer: CycleInfoSerializer,
+    cycle_info_deserializer: CycleInfoDeserializer,
+    deferred_credits_serializer: DeferredCreditsSerializer,
+    deferred_credits_deserializer: DeferredCreditsDeserializer,
+    executed_ops_serializer: ExecutedOpsSerializer,
+    executed_ops_deserializer: ExecutedOpsDeserializer,
+    /// temporary directory of the database, deleted once the database is closed
+    #[cfg(any(test, feature = "testing"))]
+    _temp_dir: Option<tempfile::TempDir>,
+}
+
+impl Debug for FinalStateDB {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:#?}", self.db)
+    }
+}
+
+impl FinalStateDB {
+    /// Create and initialize a new `FinalStateDB`.
+    ///
+    /// # Arguments
+    /// * path: path to the desired disk final state db directory
+    /// * config: final state configuration, used to bound the deserializers
+    pub fn new(path: PathBuf, config: &FinalStateConfig) -> Self {
+        let mut db_opts = Options::default();
+        db_opts.create_if_missing(true);
+        db_opts.create_missing_column_families(true);
+
+        let db = DB::open_cf_descriptors(
+            &db_opts,
+            path,
+            vec![
+                ColumnFamilyDescriptor::new(METADATA_CF, Options::default()),
+                ColumnFamilyDescriptor::new(ASYNC_POOL_CF, Options::default()),
+                ColumnFamilyDescriptor::new(CYCLE_HISTORY_CF, Options::default()),
+                ColumnFamilyDescriptor::new(DEFERRED_CREDITS_CF, Options::default()),
+                ColumnFamilyDescriptor::new(EXECUTED_OPS_CF, Options::default()),
+            ],
+        )
+        .expect(OPEN_ERROR);
+
+        // the data is written by the node itself: only the structural bounds are enforced
+        FinalStateDB {
+            db,
+            slot_serializer: SlotSerializer::new(),
+            slot_deserializer: SlotDeserializer::new(
+                (Included(0), Included(u64::MAX)),
+                (Included(0), Excluded(config.thread_count)),
+            ),
+            message_id_serializer: AsyncMessageIdSerializer::new(),
+            message_serializer: AsyncMessageSerializer::new(),
+            message_deserializer: AsyncMessageDeserializer::new(
+                config.thread_count,
+                config.async_pool_config.max_async_message_data,
+                config.ledger_config.max_key_length as u32,
+            ),
+            cycle_info_serializer: CycleInfoSerializer::new(),
+            cycle_info_deserializer: CycleInfoDeserializer::new(u64::MAX, u64::MAX),
+            deferred_credits_serializer: DeferredCreditsSerializer::new(),
+            deferred_credits_deserializer: DeferredCreditsDeserializer::new(
+                config.thread_count,
+                u64::MAX,
+            ),
+            executed_ops_serializer: ExecutedOpsSerializer::new(),
+            executed_ops_deserializer: ExecutedOpsDeserializer::new(
+                config.thread_count,
+                u64::MAX,
+                u64::MAX,
+            ),
+            #[cfg(any(test, feature = "testing"))]
+            _temp_dir: None,
+        }
+    }
+
+    /// Create a `FinalStateDB` in a temporary directory that is deleted along with it
+    #[cfg(any(test, feature = "testing"))]
+    pub fn new_temporary(config: &FinalStateConfig) -> Self {
+        let temp_dir = tempfile::TempDir::new().expect("cannot create temp directory");
+        let mut db = FinalStateDB::new(temp_dir.path().to_path_buf(), config);
+        db._temp_dir = Some(temp_dir);
+        db
+    }
+
+    /// Get the slot and the final state hash persisted along with the final state.
+    ///
+    /// # Returns
+    /// `None` if no final state was ever persisted
+    pub fn get_metadata(&self) -> Result<Option<(Slot, Hash)>, FinalStateError> {
+        let handle = self.db.cf_handle(METADATA_CF).expect(CF_ERROR);
+        let slot_bytes = self.db.get_cf(handle, SLOT_KEY).expect(CRUD_ERROR);
+        let hash_bytes = self
+            .db
+            .get_cf(handle, FINAL_STATE_HASH_KEY)
+            .expect(CRUD_ERROR);
+        match (slot_bytes, hash_bytes) {
+            (Some(slot_bytes), Some(hash_bytes)) => {
+                let (_, slot) = self
+                    .slot_deserializer
+                    .deserialize::<DeserializeError>(&slot_bytes)
+                    .map_err(|err| {
+                        FinalStateError::RestoreError(format!("invalid persisted slot: {}", err))
+                    })?;
+                let hash_bytes: [u8; HASH_SIZE_BYTES] = hash_bytes.try_into().map_err(|_| {
+                    FinalStateError::RestoreError("invalid persisted final state hash".into())
+                })?;
+                Ok(Some((slot, Hash::from_bytes(&hash_bytes))))
+            }
+            (None, None) => Ok(None),
+            _ => Err(FinalStateError::RestoreError(
+                "incomplete final state metadata".into(),
+            )),
+        }
+    }
+
+    /// Load the persisted asynchronous messages into the given pool.
+    /// The pool hash is recomputed while inserting the messages.
+    pub fn load_async_pool(&self, async_pool: &mut AsyncPool) -> Result<(), FinalStateError> {
+        let handle = self.db.cf_handle(ASYNC_POOL_CF).expect(CF_ERROR);
+        let mut messages = BTreeMap::new();
+        for (_, value) in self.db.iterator_cf(handle, IteratorMode::Start).flatten() {
+            let (rest, mut message) = self
+                .message_deserializer
+                .deserialize::<DeserializeError>(&value)
+                .map_err(|err| {
+                    FinalStateError::RestoreError(format!("invalid persisted message: {}", err))
+                })?;
+            // the execution flag is not part of the message serialization, it is appended to it
+            message.can_be_executed = match rest {
+                [0] => false,
+                [1] => true,
+                _ => {
+                    return Err(FinalStateError::RestoreError(
+                        "invalid persisted message execution flag".into(),
+                    ))
+                }
+            };
+            messages.insert(message.compute_id(), message);
+        }
+        async_pool.set_pool_part(messages);
+        Ok(())
+    }
+
+    /// Load the persisted cycle history and deferred credits into the given PoS state.
+    /// The cycle and credits hashes are recomputed while loading.
+    pub fn load_pos_state(&self, pos_state: &mut PoSFinalState) -> Result<(), FinalStateError> {
+        // cycle keys are big endian so the iteration goes from the oldest to the newest cycle
+        let handle = self.db.cf_handle(CYCLE_HISTORY_CF).expect(CF_ERROR);
+        for (_, value) in self.db.iterator_cf(handle, IteratorMode::Start).flatten() {
+            let (_, cycle_info) = self
+                .cycle_info_deserializer
+                .deserialize::<DeserializeError>(&value)
+                .map_err(|err| {
+                    FinalStateError::RestoreError(format!("invalid persisted cycle: {}", err))
+                })?;
+            if let Some(last_cycle) = pos_state.cycle_history.back() {
+                if last_cycle.cycle.checked_add(1) != Some(cycle_info.cycle) {
+                    return Err(FinalStateError::RestoreError(format!(
+                        "persisted cycle history is not contiguous: cycle {} follows cycle {}",
+                        cycle_info.cycle, last_cycle.cycle
+                    )));
+                }
+            }
+            pos_state.set_cycle_history_part(Some(cycle_info));
+        }
+
+        let handle = self.db.cf_handle(DEFERRED_CREDITS_CF).expect(CF_ERROR);
+        for (_, value) in self.db.iterator_cf(handle, IteratorMode::Start).flatten() {
+            let (_, credits) = self
+                .deferred_credits_deserializer
+                .deserialize::<DeserializeError>(&value)
+                .map_err(|err| {
+                    FinalStateError::RestoreError(format!(
+                        "invalid persisted deferred credits: {}",
+                        err
+                    ))
+                })?;
+            pos_state.set_deferred_credits_part(credits);
+        }
+        Ok(())
+    }
+
+    /// Load the persisted executed operations.
+    /// The executed operations hash is recomputed while loading.
+    pub fn load_executed_ops(&self, executed_ops: &mut ExecutedOps) -> Result<(), FinalStateError> {
+        let handle = self.db.cf_handle(EXECUTED_OPS_CF).expect(CF_ERROR);
+        for (_, value) in self.db.iterator_cf(handle, IteratorMode::Start).flatten() {
+            let (_, ops) = self
+                .executed_ops_deserializer
+                .deserialize::<DeserializeError>(&value)
+                .map_err(|err| {
+                    FinalStateError::RestoreError(format!(
+                        "invalid persisted executed operations: {}",
+                        err
+                    ))
+                })?;
+            executed_ops.set_executed_ops_part(ops);
+        }
+        Ok(())
+    }
+
+    /// Persist the changes of a newly finalized slot.
+    ///
+    /// Only the elements touched by `changes` are rewritten,
+    /// the components are expected to already contain the applied changes.
+    pub fn write_changes(
+        &self,
+        slot: Slot,
+        final_state_hash: Hash,
+        changes: &StateChanges,
+        async_pool: &AsyncPool,
+        pos_state: &PoSFinalState,
+        executed_ops: &ExecutedOps,
+    ) {
+        let mut batch = WriteBatch::default();
+
+        // async pool
+        let handle = self.db.cf_handle(ASYNC_POOL_CF).expect(CF_ERROR);
+        for change in changes.async_pool_changes.0.iter() {
+            let id = match change {
+                Change::Add(id, _) | Change::Activate(id) | Change::Delete(id) => id,
+            };
+            let mut key = Vec::new();
+            self.message_id_serializer
+                .serialize(id, &mut key)
+                .expect(SER_ERROR);
+            match async_pool.messages.get(id) {
+                Some(message) => batch.put_cf(handle, key, self.serialize_message(message)),
+                None => batch.delete_cf(handle, key),
+            }
+        }
+
+        // PoS cycle history: the latest cycle is the only one that can change,
+        // the cycles that went out of the history are removed
+        let handle = self.db.cf_handle(CYCLE_HISTORY_CF).expect(CF_ERROR);
+        if let Some(cycle_info) = pos_state.cycle_history.back() {
+            batch.put_cf(
+                handle,
+                cycle_info.cycle.to_be_bytes(),
+                self.serialize_cycle_info(cycle_info),
+            );
+        }
+        if let Some(cycle_info) = pos_state.cycle_history.front() {
+            batch.delete_range_cf(handle, 0u64.to_be_bytes(), cycle_info.cycle.to_be_bytes());
+        }
+
+        // PoS deferred credits
+        let handle = self.db.cf_handle(DEFERRED_CREDITS_CF).expect(CF_ERROR);
+        for credits_slot in changes.pos_changes.deferred_credits.credits.keys() {
+            match pos_state.deferred_credits.credits.get(credits_slot) {
+                Some(credits) => batch.put_cf(
+                    handle,
+                    credits_slot.to_bytes_key(),
+                    self.serialize_deferred_credits(credits_slot, credits.clone()),
+                ),
+                None => batch.delete_cf(handle, credits_slot.to_bytes_key()),
+            }
+        }
+
+        // executed operations: write the touched expiry slots then remove the pruned ones
+        let handle = self.db.cf_handle(EXECUTED_OPS_CF).expect(CF_ERROR);
+        for expiry_slot in changes.executed_ops_changes.values() {
+            if let Some(ids) = executed_ops.sorted_ops.get(expiry_slot) {
+                batch.put_cf(
+                    handle,
+                    expiry_slot.to_bytes_key(),
+                    self.serialize_executed_ops(expiry_slot, ids),
+                );
+            }
+        }
+        batch.delete_range_cf(handle, [0u8; SLOT_KEY_SIZE], slot.to_bytes_key());
+
+        self.db.write(batch).expect(CRUD_ERROR);
+        self.write_metadata(slot, final_state_hash);
+    }
+
+    /// Persist every final state component, overwriting the previously persisted ones.
+    /// Used after the final state was obtained by other means than finalizing slots (bootstrap, snapshot).
+    ///
+    /// The previous metadata is deleted along with the old components,
+    /// so an interrupted write leaves nothing that could be restored.
+    pub fn write_all(
+        &self,
+        slot: Slot,
+        final_state_hash: Hash,
+        async_pool: &AsyncPool,
+        pos_state: &PoSFinalState,
+        executed_ops: &ExecutedOps,
+    ) {
+        let mut batch = WriteBatch::default();
+        self.clear_batch(&mut batch);
+
+        let handle = self.db.cf_handle(ASYNC_POOL_CF).expect(CF_ERROR);
+        for (id, message) in async_pool.messages.iter() {
+            let mut key = Vec::new();
+            self.message_id_serializer
+                .serialize(id, &mut key)
+                .expect(SER_ERROR);
+            batch.put_cf(handle, key, self.serialize_message(message));
+        }
+
+        let handle = self.db.cf_handle(CYCLE_HISTORY_CF).expect(CF_ERROR);
+        for cycle_info in pos_state.cycle_history.iter() {
+            batch.put_cf(
+                handle,
+                cycle_info.cycle.to_be_bytes(),
+                self.serialize_cycle_info(cycle_info),
+            );
+        }
+
+        let handle = self.db.cf_handle(DEFERRED_CREDITS_CF).expect(CF_ERROR);
+        for (credits_slot, credits) in pos_state.deferred_credits.credits.iter() {
+            batch.put_cf(
+                handle,
+                credits_slot.to_bytes_key(),
+                self.serialize_deferred_credits(credits_slot, credits.clone()),
+            );
+        }
+
+        let handle = self.db.cf_handle(EXECUTED_OPS_CF).expect(CF_ERROR);
+        for (expiry_slot, ids) in executed_ops.sorted_ops.iter() {
+            batch.put_cf(
+                handle,
+                expiry_slot.to_bytes_key(),
+                self.serialize_executed_ops(expiry_slot, ids),
+            );
+        }
+
+        self.db.write(batch).expect(CRUD_ERROR);
+        self.write_metadata(slot, final_state_hash);
+    }
+
+    /// Delete everything that was persisted
+    pub fn clear(&self) {
+        let mut batch = WriteBatch::default();
+        self.clear_batch(&mut batch);
+        self.db.write(batch).expect(CRUD_ERROR);
+    }
+
+    /// Add the deletion of every persisted key to the given batch
+    fn clear_batch(&self, batch: &mut WriteBatch) {
+        for cf in [
+            METADATA_CF,
+            ASYNC_POOL_CF,
+            CYCLE_HISTORY_CF,
+            DEFERRED_CREDITS_CF,
+            EXECUTED_OPS_CF,
+        ] {
+            let handle = self.db.cf_handle(cf).expect(CF_ERROR);
+            for (key, _) in self.db.iterator_cf(handle, IteratorMode::Start).flatten() {
+                batch.delete_cf(handle, key);
+            }
+        }
+    }
+
+    /// Write the final slot and final state hash, once every component is written
+    fn write_metadata(&self, slot: Slot, final_state_hash: Hash) {
+        let handle = self.db.cf_handle(METADATA_CF).expect(CF_ERROR);
+        let mut slot_bytes = Vec::new();
+        self.slot_serializer
+            .serialize(&slot, &mut slot_bytes)
+            .expect(SER_ERROR);
+        let mut batch = WriteBatch::default();
+        batch.put_cf(handle, SLOT_KEY, slot_bytes);
+        batch.put_cf(handle, FINAL_STATE_HASH_KEY, final_state_hash.to_bytes());
+        self.db.write(batch).expect(CRUD_ERROR);
+    }
+
+    fn serialize_message(&self, message: &AsyncMessage) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.message_serializer
+            .serialize(message, &mut bytes)
+            .expect(SER_ERROR);
+        bytes.push(u8::from(message.can_be_executed));
+        bytes
+    }
+
+    fn serialize_cycle_info(&self, cycle_info: &CycleInfo) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.cycle_info_serializer
+            .serialize(cycle_info, &mut bytes)
+            .expect(SER_ERROR);
+        bytes
+    }
+
+    fn serialize_deferred_credits(
+        &self,
+        credits_slot: &Slot,
+        credits: PreHashMap<Address, Amount>,
+    ) -> Vec<u8> {
+        let mut slot_credits = DeferredCredits::default();
+        slot_credits.credits.insert(*credits_slot, credits);
+        let mut bytes = Vec::new();
+        self.deferred_credits_serializer
+            .serialize(&slot_credits, &mut bytes)
+            .expect(SER_ERROR);
+        bytes
+    }
+
+    fn serialize_executed_ops(&self, expiry_slot: &Slot, ids: &PreHashSet<OperationId>) -> Vec<u8> {
+        let mut slot_ops = BTreeMap::new();
+        slot_ops.insert(*expiry_slot, ids.clone());
+        let mut bytes = Vec::new();
+        self.executed_ops_serializer
+            .serialize(&slot_ops, &mut bytes)
+            .expect(SER_ERROR);
+        bytes
+    }
+}
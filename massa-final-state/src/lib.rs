@@ -17,6 +17,14 @@
 //! It can be manipulated using `StateChanges` (see `state_changes.rs`).
 //! The `FinalState` is bootstrapped using tooling available in bootstrap.rs
 //!
+//! ## `final_state_db.rs`
+//! Persists on disk the final state components that are not part of the disk ledger,
+//! so that a restarting node can resume from its own final state.
+//!
+//! ## `snapshot.rs`
+//! Defines a versioned snapshot format of the whole final state,
+//! used to export it to a file and to start a node from that file.
+//!
 //! ## `state_changes.rs`
 //! Represents a list of changes the final state.
 //! It can be modified, combined or applied to the final ledger.
@@ -42,11 +50,17 @@
 mod config;
 mod error;
 mod final_state;
+mod final_state_db;
+mod snapshot;
 mod state_changes;
 
 pub use config::FinalStateConfig;
 pub use error::FinalStateError;
-pub use final_state::FinalState;
+pub use final_state::{FinalState, FinalStateIntegrityCheck};
+pub use snapshot::{
+    FinalStateSnapshot, FinalStateSnapshotDeserializer, FinalStateSnapshotSerializer,
+    FinalStateSnapshotWriter, FINAL_STATE_SNAPSHOT_VERSION,
+};
 pub use state_changes::{StateChanges, StateChangesDeserializer, StateChangesSerializer};
 
 #[cfg(test)]
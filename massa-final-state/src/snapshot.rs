@@ -0,0 +1,466 @@
+//! Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! This file defines a versioned snapshot format of the whole final state,
+//! used to start nodes from a file instead of bootstrapping them from the network.
+//!
+//! The ledger parts come first and are terminated by an empty part, so that a snapshot
+//! can be written while the ledger is read part by part (see `FinalStateSnapshotWriter`).
+
+use crate::error::FinalStateError;
+use massa_async_pool::{AsyncMessage, AsyncMessageDeserializer, AsyncMessageSerializer};
+use massa_executed_ops::{ExecutedOpsDeserializer, ExecutedOpsSerializer};
+use massa_hash::{Hash, HashDeserializer, HashSerializer};
+use massa_ledger_exports::{LedgerChanges, LedgerChangesDeserializer, LedgerChangesSerializer};
+use massa_models::{
+    operation::OperationId,
+    prehash::PreHashSet,
+    serialization::{VecU8Deserializer, VecU8Serializer},
+    slot::{Slot, SlotDeserializer, SlotSerializer},
+};
+use massa_pos_exports::{
+    CycleInfo, CycleInfoDeserializer, CycleInfoSerializer, DeferredCredits,
+    DeferredCreditsDeserializer, DeferredCreditsSerializer,
+};
+use massa_serialization::{
+    Deserializer, SerializeError, Serializer, U64VarIntDeserializer, U64VarIntSerializer,
+};
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    combinator::value,
+    error::{context, ContextError, ParseError},
+    multi::{length_count, many_till},
+    sequence::tuple,
+    IResult, Parser,
+};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::ops::Bound::{Excluded, Included};
+
+/// Current version of the final state snapshot format.
+/// Must be incremented on every change of the format.
+pub const FINAL_STATE_SNAPSHOT_VERSION: u64 = 0;
+
+/// Snapshot of every final state component at a given final slot
+#[derive(Debug, Clone)]
+pub struct FinalStateSnapshot {
+    /// slot at the output of which the snapshot was taken
+    pub slot: Slot,
+    /// final state hash at `slot`, verified on import
+    pub final_state_hash: Hash,
+    /// ledger parts as returned by `LedgerController::get_ledger_part`
+    pub ledger_parts: Vec<Vec<u8>>,
+    /// ledger changes finalized while the ledger parts were read, applied on top of them
+    pub ledger_changes: LedgerChanges,
+    /// asynchronous pool messages, including their execution flag
+    pub async_pool_messages: Vec<AsyncMessage>,
+    /// proof-of-stake cycle history, `front = oldest`
+    pub cycle_history: Vec<CycleInfo>,
+    /// proof-of-stake deferred credits
+    pub deferred_credits: DeferredCredits,
+    /// executed operations sorted by expiry slot
+    pub executed_ops: BTreeMap<Slot, PreHashSet<OperationId>>,
+}
+
+/// Basic `FinalStateSnapshot` serializer
+pub struct FinalStateSnapshotSerializer {
+    u64_serializer: U64VarIntSerializer,
+    slot_serializer: SlotSerializer,
+    hash_serializer: HashSerializer,
+    ledger_part_serializer: VecU8Serializer,
+    ledger_changes_serializer: LedgerChangesSerializer,
+    message_serializer: AsyncMessageSerializer,
+    cycle_info_serializer: CycleInfoSerializer,
+    deferred_credits_serializer: DeferredCreditsSerializer,
+    executed_ops_serializer: ExecutedOpsSerializer,
+}
+
+impl Default for FinalStateSnapshotSerializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FinalStateSnapshotSerializer {
+    /// Creates a `FinalStateSnapshotSerializer`
+    pub fn new() -> Self {
+        Self {
+            u64_serializer: U64VarIntSerializer::new(),
+            slot_serializer: SlotSerializer::new(),
+            hash_serializer: HashSerializer::new(),
+            ledger_part_serializer: VecU8Serializer::new(),
+            ledger_changes_serializer: LedgerChangesSerializer::new(),
+            message_serializer: AsyncMessageSerializer::new(),
+            cycle_info_serializer: CycleInfoSerializer::new(),
+            deferred_credits_serializer: DeferredCreditsSerializer::new(),
+            executed_ops_serializer: ExecutedOpsSerializer::new(),
+        }
+    }
+}
+
+impl FinalStateSnapshotSerializer {
+    /// Serializes the format version, which starts the snapshot
+    fn serialize_version(&self, buffer: &mut Vec<u8>) -> Result<(), SerializeError> {
+        self.u64_serializer
+            .serialize(&FINAL_STATE_SNAPSHOT_VERSION, buffer)
+    }
+
+    /// Serializes a non-empty ledger part
+    fn serialize_ledger_part(
+        &self,
+        part: &Vec<u8>,
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), SerializeError> {
+        if part.is_empty() {
+            return Err(SerializeError::GeneralError(
+                "empty ledger parts terminate the ledger".into(),
+            ));
+        }
+        self.ledger_part_serializer.serialize(part, buffer)
+    }
+
+    /// Serializes everything that follows the ledger parts, starting with their terminator
+    fn serialize_tail(
+        &self,
+        value: &FinalStateSnapshot,
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), SerializeError> {
+        // empty ledger part terminating the ledger
+        self.ledger_part_serializer.serialize(&Vec::new(), buffer)?;
+
+        // ledger changes finalized during the export
+        self.ledger_changes_serializer
+            .serialize(&value.ledger_changes, buffer)?;
+
+        // slot and final state hash
+        self.slot_serializer.serialize(&value.slot, buffer)?;
+        self.hash_serializer
+            .serialize(&value.final_state_hash, buffer)?;
+
+        // async pool messages followed by their execution flag
+        // which is not part of the message serialization
+        self.u64_serializer
+            .serialize(&(value.async_pool_messages.len() as u64), buffer)?;
+        for message in &value.async_pool_messages {
+            self.message_serializer.serialize(message, buffer)?;
+            buffer.push(u8::from(message.can_be_executed));
+        }
+
+        // PoS cycle history
+        self.u64_serializer
+            .serialize(&(value.cycle_history.len() as u64), buffer)?;
+        for cycle_info in &value.cycle_history {
+            self.cycle_info_serializer.serialize(cycle_info, buffer)?;
+        }
+
+        // PoS deferred credits
+        self.deferred_credits_serializer
+            .serialize(&value.deferred_credits, buffer)?;
+
+        // executed operations
+        self.executed_ops_serializer
+            .serialize(&value.executed_ops, buffer)?;
+        Ok(())
+    }
+}
+
+impl Serializer<FinalStateSnapshot> for FinalStateSnapshotSerializer {
+    fn serialize(
+        &self,
+        value: &FinalStateSnapshot,
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), SerializeError> {
+        self.serialize_version(buffer)?;
+        for part in &value.ledger_parts {
+            self.serialize_ledger_part(part, buffer)?;
+        }
+        self.serialize_tail(value, buffer)
+    }
+}
+
+/// Writes a `FinalStateSnapshot` piece by piece,
+/// so that the whole ledger never has to be held in memory
+pub struct FinalStateSnapshotWriter<W: Write> {
+    writer: W,
+    serializer: FinalStateSnapshotSerializer,
+}
+
+impl<W: Write> FinalStateSnapshotWriter<W> {
+    /// Starts a snapshot on `writer`
+    pub fn new(writer: W) -> Result<Self, FinalStateError> {
+        let mut snapshot_writer = FinalStateSnapshotWriter {
+            writer,
+            serializer: FinalStateSnapshotSerializer::new(),
+        };
+        let mut buffer = Vec::new();
+        snapshot_writer
+            .serializer
+            .serialize_version(&mut buffer)
+            .map_err(|err| FinalStateError::SnapshotError(err.to_string()))?;
+        snapshot_writer.write(&buffer)?;
+        Ok(snapshot_writer)
+    }
+
+    /// Writes a ledger part as returned by `LedgerController::get_ledger_part`
+    pub fn write_ledger_part(&mut self, part: &Vec<u8>) -> Result<(), FinalStateError> {
+        let mut buffer = Vec::new();
+        self.serializer
+            .serialize_ledger_part(part, &mut buffer)
+            .map_err(|err| FinalStateError::SnapshotError(err.to_string()))?;
+        self.write(&buffer)
+    }
+
+    /// Ends the ledger and writes the rest of `snapshot`, whose `ledger_parts` are ignored.
+    /// Returns the underlying writer, flushed.
+    pub fn finish(mut self, snapshot: &FinalStateSnapshot) -> Result<W, FinalStateError> {
+        let mut buffer = Vec::new();
+        self.serializer
+            .serialize_tail(snapshot, &mut buffer)
+            .map_err(|err| FinalStateError::SnapshotError(err.to_string()))?;
+        self.write(&buffer)?;
+        self.writer
+            .flush()
+            .map_err(|err| FinalStateError::SnapshotError(err.to_string()))?;
+        Ok(self.writer)
+    }
+
+    fn write(&mut self, buffer: &[u8]) -> Result<(), FinalStateError> {
+        self.writer
+            .write_all(buffer)
+            .map_err(|err| FinalStateError::SnapshotError(err.to_string()))
+    }
+}
+
+/// Basic `FinalStateSnapshot` deserializer
+pub struct FinalStateSnapshotDeserializer {
+    version_deserializer: U64VarIntDeserializer,
+    slot_deserializer: SlotDeserializer,
+    hash_deserializer: HashDeserializer,
+    ledger_part_deserializer: VecU8Deserializer,
+    ledger_changes_deserializer: LedgerChangesDeserializer,
+    messages_length_deserializer: U64VarIntDeserializer,
+    message_deserializer: AsyncMessageDeserializer,
+    cycle_history_length_deserializer: U64VarIntDeserializer,
+    cycle_info_deserializer: CycleInfoDeserializer,
+    deferred_credits_deserializer: DeferredCreditsDeserializer,
+    executed_ops_deserializer: ExecutedOpsDeserializer,
+}
+
+impl FinalStateSnapshotDeserializer {
+    /// Creates a `FinalStateSnapshotDeserializer`
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        thread_count: u8,
+        max_async_pool_length: u64,
+        max_async_message_data: u64,
+        max_ledger_changes_count: u64,
+        max_datastore_key_length: u8,
+        max_datastore_value_length: u64,
+        max_datastore_entry_count: u64,
+        cycle_history_length: u64,
+        max_rolls_length: u64,
+        max_production_stats_length: u64,
+        max_credits_length: u64,
+        max_executed_ops_length: u64,
+        max_operations_per_block: u64,
+    ) -> Self {
+        Self {
+            version_deserializer: U64VarIntDeserializer::new(
+                Included(FINAL_STATE_SNAPSHOT_VERSION),
+                Included(FINAL_STATE_SNAPSHOT_VERSION),
+            ),
+            slot_deserializer: SlotDeserializer::new(
+                (Included(u64::MIN), Included(u64::MAX)),
+                (Included(0), Excluded(thread_count)),
+            ),
+            hash_deserializer: HashDeserializer::new(),
+            ledger_part_deserializer: VecU8Deserializer::new(Included(1), Included(u64::MAX)),
+            ledger_changes_deserializer: LedgerChangesDeserializer::new(
+                max_ledger_changes_count,
+                max_datastore_key_length,
+                max_datastore_value_length,
+                max_datastore_entry_count,
+            ),
+            messages_length_deserializer: U64VarIntDeserializer::new(
+                Included(u64::MIN),
+                Included(max_async_pool_length),
+            ),
+            message_deserializer: AsyncMessageDeserializer::new(
+                thread_count,
+                max_async_message_data,
+                max_datastore_key_length as u32,
+            ),
+            cycle_history_length_deserializer: U64VarIntDeserializer::new(
+                Included(u64::MIN),
+                Included(cycle_history_length),
+            ),
+            cycle_info_deserializer: CycleInfoDeserializer::new(
+                max_rolls_length,
+                max_production_stats_length,
+            ),
+            deferred_credits_deserializer: DeferredCreditsDeserializer::new(
+                thread_count,
+                max_credits_length,
+            ),
+            executed_ops_deserializer: ExecutedOpsDeserializer::new(
+                thread_count,
+                max_executed_ops_length,
+                max_operations_per_block,
+            ),
+        }
+    }
+}
+
+impl Deserializer<FinalStateSnapshot> for FinalStateSnapshotDeserializer {
+    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], FinalStateSnapshot, E> {
+        context(
+            "Failed FinalStateSnapshot deserialization",
+            tuple((
+                context("Failed version deserialization", |input| {
+                    self.version_deserializer.deserialize(input)
+                }),
+                context(
+                    "Failed ledger_parts deserialization",
+                    many_till(
+                        |input| self.ledger_part_deserializer.deserialize(input),
+                        // an empty part, that is a zero length, terminates the ledger
+                        tag(&[0]),
+                    ),
+                ),
+                context("Failed ledger_changes deserialization", |input| {
+                    self.ledger_changes_deserializer.deserialize(input)
+                }),
+                context("Failed slot deserialization", |input| {
+                    self.slot_deserializer.deserialize(input)
+                }),
+                context("Failed final_state_hash deserialization", |input| {
+                    self.hash_deserializer.deserialize(input)
+                }),
+                context(
+                    "Failed async_pool_messages deserialization",
+                    length_count(
+                        |input| self.messages_length_deserializer.deserialize(input),
+                        tuple((
+                            |input| self.message_deserializer.deserialize(input),
+                            alt((value(true, tag(&[1])), value(false, tag(&[0])))),
+                        )),
+                    ),
+                ),
+                context(
+                    "Failed cycle_history deserialization",
+                    length_count(
+                        |input| self.cycle_history_length_deserializer.deserialize(input),
+                        |input| self.cycle_info_deserializer.deserialize(input),
+                    ),
+                ),
+                context("Failed deferred_credits deserialization", |input| {
+                    self.deferred_credits_deserializer.deserialize(input)
+                }),
+                context("Failed executed_ops deserialization", |input| {
+                    self.executed_ops_deserializer.deserialize(input)
+                }),
+            )),
+        )
+        .map(
+            |(
+                _version,
+                (ledger_parts, _),
+                ledger_changes,
+                slot,
+                final_state_hash,
+                messages,
+                cycle_history,
+                deferred_credits,
+                executed_ops,
+            )| FinalStateSnapshot {
+                slot,
+                final_state_hash,
+                ledger_parts,
+                ledger_changes,
+                async_pool_messages: messages
+                    .into_iter()
+                    .map(|(mut message, can_be_executed): (AsyncMessage, bool)| {
+                        message.can_be_executed = can_be_executed;
+                        message
+                    })
+                    .collect(),
+                cycle_history,
+                deferred_credits,
+                executed_ops,
+            },
+        )
+        .parse(buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_async_pool::test_exports::get_random_message;
+    use massa_models::config::{
+        MAX_ASYNC_MESSAGE_DATA, MAX_ASYNC_POOL_LENGTH, MAX_DATASTORE_ENTRY_COUNT,
+        MAX_DATASTORE_KEY_LENGTH, MAX_DATASTORE_VALUE_LENGTH, MAX_DEFERRED_CREDITS_LENGTH,
+        MAX_EXECUTED_OPS_LENGTH, MAX_LEDGER_CHANGES_COUNT, MAX_OPERATIONS_PER_BLOCK,
+        MAX_PRODUCTION_STATS_LENGTH, MAX_ROLLS_COUNT_LENGTH, POS_SAVED_CYCLES, THREAD_COUNT,
+    };
+    use massa_serialization::DeserializeError;
+
+    #[test]
+    fn test_snapshot_serialization() {
+        let executed_message = get_random_message(None);
+        let mut pending_message = get_random_message(None);
+        pending_message.can_be_executed = false;
+        let snapshot = FinalStateSnapshot {
+            slot: Slot::new(12, 3),
+            final_state_hash: Hash::compute_from(b"final state"),
+            ledger_parts: vec![vec![1, 2, 3], vec![4, 5]],
+            ledger_changes: LedgerChanges::default(),
+            async_pool_messages: vec![executed_message, pending_message],
+            cycle_history: Vec::new(),
+            deferred_credits: DeferredCredits::default(),
+            executed_ops: BTreeMap::new(),
+        };
+
+        let mut bytes = Vec::new();
+        FinalStateSnapshotSerializer::new()
+            .serialize(&snapshot, &mut bytes)
+            .unwrap();
+        let (rest, deserialized) = FinalStateSnapshotDeserializer::new(
+            THREAD_COUNT,
+            MAX_ASYNC_POOL_LENGTH,
+            MAX_ASYNC_MESSAGE_DATA,
+            MAX_LEDGER_CHANGES_COUNT,
+            MAX_DATASTORE_KEY_LENGTH,
+            MAX_DATASTORE_VALUE_LENGTH,
+            MAX_DATASTORE_ENTRY_COUNT,
+            POS_SAVED_CYCLES as u64,
+            MAX_ROLLS_COUNT_LENGTH,
+            MAX_PRODUCTION_STATS_LENGTH,
+            MAX_DEFERRED_CREDITS_LENGTH,
+            MAX_EXECUTED_OPS_LENGTH,
+            MAX_OPERATIONS_PER_BLOCK as u64,
+        )
+        .deserialize::<DeserializeError>(&bytes)
+        .unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(deserialized.slot, snapshot.slot);
+        assert_eq!(deserialized.final_state_hash, snapshot.final_state_hash);
+        assert_eq!(deserialized.ledger_parts, snapshot.ledger_parts);
+        assert_eq!(
+            deserialized.async_pool_messages,
+            snapshot.async_pool_messages
+        );
+        assert!(deserialized.deferred_credits.credits.is_empty());
+        assert!(deserialized.executed_ops.is_empty());
+
+        // writing the snapshot piece by piece gives the same bytes
+        let mut writer = FinalStateSnapshotWriter::new(Vec::new()).unwrap();
+        for part in &snapshot.ledger_parts {
+            writer.write_ledger_part(part).unwrap();
+        }
+        assert_eq!(writer.finish(&snapshot).unwrap(), bytes);
+    }
+}
@@ -18,7 +18,7 @@ use nom::{
 };
 
 /// represents changes that can be applied to the execution state
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Clone, PartialEq)]
 pub struct StateChanges {
     /// ledger changes
     pub ledger_changes: LedgerChanges,
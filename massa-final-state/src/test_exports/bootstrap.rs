@@ -11,7 +11,7 @@ use massa_ledger_exports::LedgerController;
 use massa_models::slot::Slot;
 use massa_pos_exports::PoSFinalState;
 
-use crate::{FinalState, FinalStateConfig, StateChanges};
+use crate::{final_state_db::FinalStateDB, FinalState, FinalStateConfig, StateChanges};
 
 /// Create a `FinalState` from pre-set values
 pub fn create_final_state(
@@ -23,6 +23,7 @@ pub fn create_final_state(
     pos_state: PoSFinalState,
     executed_ops: ExecutedOps,
 ) -> FinalState {
+    let db = FinalStateDB::new_temporary(&config);
     FinalState {
         config,
         slot,
@@ -32,6 +33,7 @@ pub fn create_final_state(
         pos_state,
         executed_ops,
         final_state_hash: Hash::from_bytes(&[0; HASH_SIZE_BYTES]),
+        db,
     }
 }
 
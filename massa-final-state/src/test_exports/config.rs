@@ -4,7 +4,7 @@
 
 use std::path::PathBuf;
 
-use crate::{FinalState, FinalStateConfig};
+use crate::{final_state_db::FinalStateDB, FinalState, FinalStateConfig};
 use massa_async_pool::{AsyncPool, AsyncPoolConfig};
 use massa_executed_ops::{ExecutedOps, ExecutedOpsConfig};
 use massa_hash::{Hash, HASH_SIZE_BYTES};
@@ -29,6 +29,7 @@ impl FinalState {
             pos_state,
             executed_ops: ExecutedOps::new(config.executed_ops_config.clone()),
             changes_history: Default::default(),
+            db: FinalStateDB::new_temporary(&config),
             config,
             final_state_hash: Hash::from_bytes(&[0; HASH_SIZE_BYTES]),
         }
@@ -56,6 +57,8 @@ impl Default for FinalStateConfig {
             periods_per_cycle: 100,
             initial_rolls_path: PathBuf::new(),
             initial_seed_string: "".to_string(),
+            // unused by `create_final_state` which uses a temporary directory
+            final_state_path: "".into(),
         }
     }
 }
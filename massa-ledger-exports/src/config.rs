@@ -17,4 +17,9 @@ pub struct LedgerConfig {
     pub max_key_length: u8,
     /// max ledger part size
     pub max_ledger_part_size: u64,
+    /// keep the history of every ledger entry to answer queries about past final slots
+    pub archival: bool,
+    /// period from which the ledger hash is computed from the ledger tree root,
+    /// `None` while this network upgrade is not scheduled
+    pub tree_hash_activation_period: Option<u64>,
 }
@@ -5,7 +5,7 @@ use massa_models::{
 use std::collections::BTreeSet;
 use std::fmt::Debug;
 
-use crate::{LedgerChanges, LedgerError};
+use crate::{LedgerChanges, LedgerError, LedgerTreeProof};
 
 pub trait LedgerController: Send + Sync + Debug {
     /// Allows applying `LedgerChanges` to the final ledger
@@ -14,6 +14,9 @@ pub trait LedgerController: Send + Sync + Debug {
     /// Loads ledger from file
     fn load_initial_ledger(&mut self) -> Result<(), LedgerError>;
 
+    /// Deletes every entry of the ledger, leaving it empty
+    fn reset(&mut self);
+
     /// Gets the balance of a ledger entry
     ///
     /// # Returns
@@ -58,9 +61,71 @@ pub trait LedgerController: Send + Sync + Debug {
     /// A `BTreeSet` of the datastore keys
     fn get_datastore_keys(&self, addr: &Address) -> Option<BTreeSet<Vec<u8>>>;
 
+    /// Get a page of the datastore keys of an address starting with a given prefix,
+    /// only keeping keys strictly greater than `start_after` if set.
+    ///
+    /// # Returns
+    /// The first `limit` matching keys, or `None` if the ledger entry was not found
+    fn get_datastore_keys_page(
+        &self,
+        addr: &Address,
+        prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: usize,
+    ) -> Option<BTreeSet<Vec<u8>>>;
+
+    /// Get the balance of an address at the output of a past final slot.
+    /// Only available on archival nodes, for slots covered by the archive.
+    ///
+    /// # Returns
+    /// The balance, or `None` if the ledger entry did not exist at that slot
+    fn get_balance_at(&self, addr: &Address, slot: &Slot) -> Result<Option<Amount>, LedgerError>;
+
+    /// Get a datastore entry of an address at the output of a past final slot.
+    /// Only available on archival nodes, for slots covered by the archive.
+    ///
+    /// # Returns
+    /// The datastore value, or `None` if the datastore entry did not exist at that slot
+    fn get_data_entry_at(
+        &self,
+        addr: &Address,
+        key: &[u8],
+        slot: &Slot,
+    ) -> Result<Option<Vec<u8>>, LedgerError>;
+
+    /// Get the datastore keys of an address at the output of a past final slot.
+    /// Only available on archival nodes, for slots covered by the archive.
+    ///
+    /// # Returns
+    /// The datastore keys, or `None` if the ledger entry did not exist at that slot
+    fn get_datastore_keys_at(
+        &self,
+        addr: &Address,
+        slot: &Slot,
+    ) -> Result<Option<BTreeSet<Vec<u8>>>, LedgerError>;
+
+    /// Get the slot the disk ledger is attached to, `None` if it was never attached to one
+    fn get_slot(&self) -> Option<Slot>;
+
     /// Get the current disk ledger hash
     fn get_ledger_hash(&self) -> Hash;
 
+    /// Compute the disk ledger hash from scratch with a full scan of a snapshot of the ledger.
+    ///
+    /// The snapshot is taken before returning, the scan runs in the background
+    /// and the returned closure waits for its result.
+    fn compute_ledger_hash_on_snapshot(&self) -> Box<dyn FnOnce() -> Hash + Send>;
+
+    /// Whether the current ledger hash is computed from the ledger tree root,
+    /// entry proofs can only be linked to the ledger hash in that case
+    fn is_tree_hash_active(&self) -> bool;
+
+    /// Get the value of a ledger key and its proof against the ledger tree root
+    ///
+    /// # Returns
+    /// A tuple containing the value, `None` if the key is absent, and its proof
+    fn get_entry_proof(&self, key: &[u8]) -> (Option<Vec<u8>>, LedgerTreeProof);
+
     /// Get a part of the ledger
     /// Used for bootstrap
     /// Return: Tuple with data and last key
@@ -835,6 +835,78 @@ impl LedgerChanges {
         }
     }
 
+    /// Tries to return the new balance of an address if the ledger changes set it.
+    ///
+    /// # Arguments
+    /// * `addr`: target address
+    ///
+    /// # Returns
+    /// * Some(v) if the balance is set to v, or Some(0) if the entry is deleted
+    /// * None if the balance is not changed
+    pub fn get_balance_change(&self, addr: &Address) -> Option<Amount> {
+        match self.0.get(addr) {
+            // This ledger entry is being replaced by a new one: return its balance
+            Some(SetUpdateOrDelete::Set(v)) => Some(v.balance),
+
+            // This ledger entry is being updated: return the balance only if it is set
+            Some(SetUpdateOrDelete::Update(LedgerEntryUpdate { balance, .. })) => match balance {
+                SetOrKeep::Set(v) => Some(*v),
+                SetOrKeep::Keep => None,
+            },
+
+            // This ledger entry is being deleted: its balance drops to zero
+            Some(SetUpdateOrDelete::Delete) => Some(Amount::zero()),
+
+            // This ledger entry is not being changed.
+            None => None,
+        }
+    }
+
+    /// Tries to return whether the bytecode of an address is changed in the ledger changes.
+    ///
+    /// # Arguments
+    /// * `addr`: target address
+    ///
+    /// # Returns
+    /// * true if the entry is replaced or deleted, or if its bytecode is set
+    pub fn has_bytecode_change(&self, addr: &Address) -> bool {
+        match self.0.get(addr) {
+            Some(SetUpdateOrDelete::Set(_)) | Some(SetUpdateOrDelete::Delete) => true,
+            Some(SetUpdateOrDelete::Update(LedgerEntryUpdate { bytecode, .. })) => {
+                matches!(bytecode, SetOrKeep::Set(_))
+            }
+            None => false,
+        }
+    }
+
+    /// Tries to return whether a datastore entry whose key starts with a given prefix
+    /// is changed for an address in the ledger changes.
+    ///
+    /// # Arguments
+    /// * `addr`: target address
+    /// * `prefix`: datastore key prefix
+    ///
+    /// # Returns
+    /// * true if a matching datastore entry is changed, or if the entry is deleted
+    pub fn has_datastore_prefix_changes(&self, addr: &Address, prefix: &[u8]) -> bool {
+        match self.0.get(addr) {
+            // This ledger entry is being replaced by a new one:
+            // check if the new datastore contains a key with that prefix
+            Some(SetUpdateOrDelete::Set(v)) => v.datastore.keys().any(|k| k.starts_with(prefix)),
+
+            // This ledger entry is being updated: check the updated datastore keys
+            Some(SetUpdateOrDelete::Update(LedgerEntryUpdate { datastore, .. })) => {
+                datastore.keys().any(|k| k.starts_with(prefix))
+            }
+
+            // This ledger entry is being deleted: return true
+            Some(SetUpdateOrDelete::Delete) => true,
+
+            // This ledger entry is not being changed.
+            None => false,
+        }
+    }
+
     /// Tries to return whether a datastore entry exists for a given address,
     /// or gets it from a function if the datastore entry's status is unknown.
     ///
@@ -10,6 +10,7 @@ mod error;
 mod key;
 mod ledger_changes;
 mod ledger_entry;
+mod proof;
 mod types;
 
 pub use config::LedgerConfig;
@@ -25,6 +26,11 @@ pub use ledger_changes::{
     LedgerEntryUpdateDeserializer, LedgerEntryUpdateSerializer,
 };
 pub use ledger_entry::{LedgerEntry, LedgerEntryDeserializer, LedgerEntrySerializer};
+pub use proof::{
+    ledger_entry_hash, ledger_tree_internal_hash, ledger_tree_leaf_hash, ledger_tree_path,
+    ledger_tree_path_bit, LedgerEntryProof, LedgerTreeProof, LEDGER_TREE_DEPTH,
+    LEDGER_TREE_EMPTY_HASH_BYTES,
+};
 pub use types::{Applicable, SetOrDelete, SetOrKeep, SetUpdateOrDelete};
 
 #[cfg(feature = "testing")]
@@ -0,0 +1,149 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! This file defines the inclusion and absence proofs of ledger entries.
+//!
+//! The disk ledger commits to its entries with a sparse Merkle tree:
+//! * the path of an entry in the tree is given by the bits of the hash of its key
+//! * a subtree containing a single entry is replaced by the leaf of that entry
+//! * an empty subtree hashes to zero
+
+use massa_hash::{Hash, HASH_SIZE_BYTES};
+use massa_models::slot::{Slot, SlotSerializer};
+use massa_serialization::{Serializer, U64VarIntSerializer};
+use serde::{Deserialize, Serialize};
+
+/// Bytes of the hash of an empty subtree of the ledger tree
+pub const LEDGER_TREE_EMPTY_HASH_BYTES: &[u8; HASH_SIZE_BYTES] = &[0; HASH_SIZE_BYTES];
+
+/// Number of levels of the ledger tree below its root
+pub const LEDGER_TREE_DEPTH: usize = HASH_SIZE_BYTES * 8;
+
+const LEAF_PREFIX: u8 = 0;
+const INTERNAL_PREFIX: u8 = 1;
+
+/// Hash of a ledger key and its value
+pub fn ledger_entry_hash(key: &[u8], value: &[u8]) -> Hash {
+    let mut len_bytes = Vec::new();
+    // u64 varint serialization never fails
+    U64VarIntSerializer::new()
+        .serialize(&(key.len() as u64), &mut len_bytes)
+        .unwrap();
+    Hash::compute_from(&[&len_bytes, key, value].concat())
+}
+
+/// Path of a ledger key in the ledger tree
+pub fn ledger_tree_path(key: &[u8]) -> Hash {
+    Hash::compute_from(key)
+}
+
+/// Direction taken by `path` at the given depth of the ledger tree, `true` being right
+pub fn ledger_tree_path_bit(path: &Hash, depth: usize) -> bool {
+    path.to_bytes()[depth / 8] & (0x80 >> (depth % 8)) != 0
+}
+
+/// Hash of a leaf of the ledger tree
+pub fn ledger_tree_leaf_hash(path: &Hash, entry_hash: &Hash) -> Hash {
+    Hash::compute_from(&[&[LEAF_PREFIX], path.to_bytes(), entry_hash.to_bytes()].concat())
+}
+
+/// Hash of an internal node of the ledger tree
+pub fn ledger_tree_internal_hash(left: &Hash, right: &Hash) -> Hash {
+    Hash::compute_from(&[&[INTERNAL_PREFIX], left.to_bytes(), right.to_bytes()].concat())
+}
+
+/// Proof that a ledger key has a given value or is absent in the ledger tree
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LedgerTreeProof {
+    /// hashes of the siblings met along the path of the key, from the root
+    pub siblings: Vec<Hash>,
+    /// path and entry hash of the leaf ending the path of the key, `None` if the path ends in an empty subtree
+    pub leaf: Option<(Hash, Hash)>,
+}
+
+impl LedgerTreeProof {
+    /// Computes the ledger tree root implied by the proof for the given ledger key and value,
+    /// `value` being `None` to check the absence of the key.
+    ///
+    /// # Returns
+    /// The root hash, or `None` if the proof does not match the key and value
+    pub fn compute_root(&self, key: &[u8], value: Option<&[u8]>) -> Option<Hash> {
+        if self.siblings.len() > LEDGER_TREE_DEPTH {
+            return None;
+        }
+        let path = ledger_tree_path(key);
+        let mut hash = match (&self.leaf, value) {
+            (Some((leaf_path, entry_hash)), Some(value)) => {
+                if *leaf_path != path || *entry_hash != ledger_entry_hash(key, value) {
+                    return None;
+                }
+                ledger_tree_leaf_hash(leaf_path, entry_hash)
+            }
+            (Some((leaf_path, entry_hash)), None) => {
+                // the leaf of another key must share the path of the absent key down to its position
+                if *leaf_path == path
+                    || (0..self.siblings.len()).any(|depth| {
+                        ledger_tree_path_bit(leaf_path, depth) != ledger_tree_path_bit(&path, depth)
+                    })
+                {
+                    return None;
+                }
+                ledger_tree_leaf_hash(leaf_path, entry_hash)
+            }
+            (None, None) => Hash::from_bytes(LEDGER_TREE_EMPTY_HASH_BYTES),
+            (None, Some(_)) => return None,
+        };
+        for (depth, sibling) in self.siblings.iter().enumerate().rev() {
+            hash = if ledger_tree_path_bit(&path, depth) {
+                ledger_tree_internal_hash(sibling, &hash)
+            } else {
+                ledger_tree_internal_hash(&hash, sibling)
+            };
+        }
+        Some(hash)
+    }
+}
+
+/// Proof that a ledger key has a given value, or is absent, in the final state at a given slot
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LedgerEntryProof {
+    /// ledger key
+    pub key: Vec<u8>,
+    /// value associated to the key, `None` if the key is absent
+    pub value: Option<Vec<u8>>,
+    /// proof of the key in the ledger tree
+    pub tree_proof: LedgerTreeProof,
+    /// final slot at the output of which the proof was made
+    pub slot: Slot,
+    /// hashes of the other final state components,
+    /// in the order they are concatenated after the ledger hash
+    pub final_state_parts_hashes: Vec<Hash>,
+    /// final state hash at `slot`
+    pub final_state_hash: Hash,
+}
+
+impl LedgerEntryProof {
+    /// Checks that the proof links the key and value to its final state hash.
+    ///
+    /// Note that the final state hash itself must be obtained from a trusted source.
+    pub fn verify(&self) -> bool {
+        let root = match self
+            .tree_proof
+            .compute_root(&self.key, self.value.as_deref())
+        {
+            Some(root) => root,
+            None => return false,
+        };
+        // the ledger hash also commits to the ledger slot
+        let mut slot_bytes = Vec::new();
+        // Slot serialization never fails
+        SlotSerializer::new()
+            .serialize(&self.slot, &mut slot_bytes)
+            .unwrap();
+        let ledger_hash = root ^ Hash::compute_from(&slot_bytes);
+        let mut hash_concat = ledger_hash.to_bytes().to_vec();
+        for part_hash in &self.final_state_parts_hashes {
+            hash_concat.extend(part_hash.to_bytes());
+        }
+        Hash::compute_from(&hash_concat) == self.final_state_hash
+    }
+}
@@ -2,7 +2,10 @@
 /// This file defines testing tools related to the configuration
 use massa_models::{
     address::Address,
-    config::{LEDGER_PART_SIZE_MESSAGE_BYTES, MAX_DATASTORE_KEY_LENGTH, THREAD_COUNT},
+    config::{
+        LEDGER_PART_SIZE_MESSAGE_BYTES, LEDGER_TREE_HASH_ACTIVATION_PERIOD,
+        MAX_DATASTORE_KEY_LENGTH, THREAD_COUNT,
+    },
 };
 use std::collections::HashMap;
 use std::io::Seek;
@@ -21,6 +24,8 @@ impl Default for LedgerConfig {
             thread_count: THREAD_COUNT,
             max_key_length: MAX_DATASTORE_KEY_LENGTH,
             max_ledger_part_size: LEDGER_PART_SIZE_MESSAGE_BYTES,
+            archival: false,
+            tree_hash_activation_period: LEDGER_TREE_HASH_ACTIVATION_PERIOD,
         }
     }
 }
@@ -43,6 +48,8 @@ impl LedgerConfig {
                 max_key_length: MAX_DATASTORE_KEY_LENGTH,
                 max_ledger_part_size: LEDGER_PART_SIZE_MESSAGE_BYTES,
                 thread_count: THREAD_COUNT,
+                archival: false,
+                tree_hash_activation_period: LEDGER_TREE_HASH_ACTIVATION_PERIOD,
             },
             initial_ledger,
             disk_ledger,
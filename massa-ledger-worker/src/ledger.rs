@@ -5,7 +5,7 @@
 use crate::ledger_db::{LedgerDB, LedgerSubEntry};
 use massa_hash::Hash;
 use massa_ledger_exports::{
-    LedgerChanges, LedgerConfig, LedgerController, LedgerEntry, LedgerError,
+    LedgerChanges, LedgerConfig, LedgerController, LedgerEntry, LedgerError, LedgerTreeProof,
 };
 use massa_models::{
     address::Address,
@@ -40,6 +40,8 @@ impl FinalLedger {
             config.thread_count,
             config.max_key_length,
             config.max_ledger_part_size,
+            config.archival,
+            config.tree_hash_activation_period,
         );
 
         // generate the final ledger
@@ -159,11 +161,109 @@ impl LedgerController for FinalLedger {
         }
     }
 
+    /// Get a page of the datastore keys of an address starting with a given prefix.
+    ///
+    /// # Returns
+    /// The first `limit` keys strictly greater than `start_after`, or `None` if the ledger entry was not found
+    fn get_datastore_keys_page(
+        &self,
+        addr: &Address,
+        prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: usize,
+    ) -> Option<BTreeSet<Vec<u8>>> {
+        match self.entry_exists(addr) {
+            true => {
+                Some(
+                    self.sorted_ledger
+                        .get_datastore_keys_page(addr, prefix, start_after, limit),
+                )
+            }
+            false => None,
+        }
+    }
+
+    /// Deletes every entry of the disk ledger
+    fn reset(&mut self) {
+        self.sorted_ledger.reset();
+    }
+
+    /// Gets the balance of a ledger entry at the output of a past final slot
+    ///
+    /// # Returns
+    /// The balance, or None if the ledger entry did not exist at that slot
+    fn get_balance_at(&self, addr: &Address, slot: &Slot) -> Result<Option<Amount>, LedgerError> {
+        let amount_deserializer =
+            AmountDeserializer::new(Included(Amount::MIN), Included(Amount::MAX));
+        Ok(self
+            .sorted_ledger
+            .get_sub_entry_at(addr, LedgerSubEntry::Balance, slot)?
+            .map(|bytes| {
+                amount_deserializer
+                    .deserialize::<DeserializeError>(&bytes)
+                    .expect("critical: invalid balance format")
+                    .1
+            }))
+    }
+
+    /// Gets a copy of a datastore value at the output of a past final slot
+    ///
+    /// # Returns
+    /// The datastore value, or None if the datastore entry did not exist at that slot
+    fn get_data_entry_at(
+        &self,
+        addr: &Address,
+        key: &[u8],
+        slot: &Slot,
+    ) -> Result<Option<Vec<u8>>, LedgerError> {
+        self.sorted_ledger
+            .get_sub_entry_at(addr, LedgerSubEntry::Datastore(key.to_owned()), slot)
+    }
+
+    /// Get every key of the datastore of an address at the output of a past final slot
+    ///
+    /// # Returns
+    /// The datastore keys, or None if the ledger entry did not exist at that slot
+    fn get_datastore_keys_at(
+        &self,
+        addr: &Address,
+        slot: &Slot,
+    ) -> Result<Option<BTreeSet<Vec<u8>>>, LedgerError> {
+        if self
+            .sorted_ledger
+            .get_sub_entry_at(addr, LedgerSubEntry::Balance, slot)?
+            .is_none()
+        {
+            return Ok(None);
+        }
+        Ok(Some(self.sorted_ledger.get_datastore_keys_at(addr, slot)?))
+    }
+
+    /// Get the slot the disk ledger is attached to
+    fn get_slot(&self) -> Option<Slot> {
+        self.sorted_ledger.get_slot()
+    }
+
     /// Get the current disk ledger hash
     fn get_ledger_hash(&self) -> Hash {
         self.sorted_ledger.get_ledger_hash()
     }
 
+    /// Compute the disk ledger hash from scratch with a full scan of a snapshot of the ledger
+    fn compute_ledger_hash_on_snapshot(&self) -> Box<dyn FnOnce() -> Hash + Send> {
+        self.sorted_ledger.compute_ledger_hash_on_snapshot()
+    }
+
+    /// Whether the current ledger hash is computed from the ledger tree root
+    fn is_tree_hash_active(&self) -> bool {
+        self.sorted_ledger.is_tree_hash_active()
+    }
+
+    /// Get the value of a ledger key and its proof against the ledger tree root
+    fn get_entry_proof(&self, key: &[u8]) -> (Option<Vec<u8>>, LedgerTreeProof) {
+        self.sorted_ledger.get_entry_proof(key)
+    }
+
     /// Get a part of the disk ledger.
     ///
     /// Solely used by the bootstrap.
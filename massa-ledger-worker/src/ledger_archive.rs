@@ -0,0 +1,157 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Versioned history of the disk ledger entries, kept by archival nodes.
+//!
+//! Every value written to or deleted from the ledger is also stored in its own column family
+//! of the ledger database, indexed by the ledger key followed by the slot of the change.
+//! The value of a key as of a given slot is then the latest version at or before that slot.
+//!
+//! The ledger key is stored as the address and identifier followed by the length of the
+//! remaining bytes, so that the versions of a key never interleave with the ones of a longer key.
+
+use massa_ledger_exports::DATASTORE_IDENT;
+use massa_models::{
+    address::{Address, ADDRESS_SIZE_BYTES},
+    slot::{Slot, SLOT_KEY_SIZE},
+};
+use rocksdb::{ColumnFamily, Direction, IteratorMode, WriteBatch, DB};
+use std::collections::BTreeSet;
+
+const CRUD_ERROR: &str = "critical: rocksdb crud operation failed";
+const ARCHIVE_KEY_ERROR: &str = "critical: archived ledger key is corrupted";
+const DELETED_IDENT: u8 = 0u8;
+const SET_IDENT: u8 = 1u8;
+
+/// Archive prefix shared by every version of a ledger key
+fn archive_prefix(key: &[u8]) -> Vec<u8> {
+    let (head, rest) = key.split_at((ADDRESS_SIZE_BYTES + 1).min(key.len()));
+    // datastore keys are shorter than 256 bytes
+    [head, &[rest.len() as u8], rest].concat()
+}
+
+/// Ledger key of the versions sharing an archive prefix
+fn ledger_key(prefix: &[u8]) -> Vec<u8> {
+    let (head, rest) = prefix.split_at((ADDRESS_SIZE_BYTES + 1).min(prefix.len()));
+    [head, rest.get(1..).unwrap_or_default()].concat()
+}
+
+/// Archive the value of a ledger key at a given slot, `None` meaning that the key was deleted
+pub(crate) fn put_archived_value(
+    batch: &mut WriteBatch,
+    handle: &ColumnFamily,
+    key: &[u8],
+    slot: &Slot,
+    value: Option<&[u8]>,
+) {
+    let archive_key = [&archive_prefix(key)[..], &slot.to_bytes_key()].concat();
+    match value {
+        Some(value) => batch.put_cf(handle, archive_key, [&[SET_IDENT], value].concat()),
+        None => batch.put_cf(handle, archive_key, [DELETED_IDENT]),
+    }
+}
+
+/// Get the value that a ledger key had at the output of a given slot
+///
+/// # Returns
+/// The value, or `None` if the key did not exist at that slot
+pub(crate) fn get_archived_value(
+    db: &DB,
+    handle: &ColumnFamily,
+    key: &[u8],
+    slot: &Slot,
+) -> Option<Vec<u8>> {
+    let prefix = archive_prefix(key);
+    let archive_key = [&prefix[..], &slot.to_bytes_key()].concat();
+    // the first entry at or before the key is its latest version at or before the slot, if any
+    let (found_key, value) = db
+        .iterator_cf(handle, IteratorMode::From(&archive_key, Direction::Reverse))
+        .next()?
+        .expect(CRUD_ERROR);
+    if found_key.len() != prefix.len() + SLOT_KEY_SIZE || !found_key.starts_with(&prefix) {
+        return None;
+    }
+    match value.split_first() {
+        Some((&SET_IDENT, value)) => Some(value.to_vec()),
+        _ => None,
+    }
+}
+
+/// Get the datastore keys that an address had at the output of a given slot
+pub(crate) fn get_archived_datastore_keys(
+    db: &DB,
+    handle: &ColumnFamily,
+    addr: &Address,
+    slot: &Slot,
+) -> BTreeSet<Vec<u8>> {
+    let prefix = [&addr.to_bytes()[..], &[DATASTORE_IDENT]].concat();
+    let mut keys = BTreeSet::new();
+    // versions are sorted by datastore key, then by slot
+    let mut latest: Option<(Vec<u8>, bool)> = None;
+    for item in db.iterator_cf(handle, IteratorMode::From(&prefix, Direction::Forward)) {
+        let (archive_key, value) = item.expect(CRUD_ERROR);
+        if !archive_key.starts_with(&prefix) {
+            break;
+        }
+        let (datastore_key, version_slot) = archive_key[prefix.len() + 1..]
+            .split_at(archive_key.len() - prefix.len() - 1 - SLOT_KEY_SIZE);
+        let version_slot = Slot::from_bytes_key(version_slot.try_into().expect(ARCHIVE_KEY_ERROR));
+        // flush the latest version of the previous datastore key
+        if let Some((latest_key, exists)) = &latest {
+            if latest_key != datastore_key {
+                if *exists {
+                    keys.insert(latest_key.clone());
+                }
+                latest = None;
+            }
+        }
+        if version_slot <= *slot {
+            latest = Some((datastore_key.to_vec(), value.first() == Some(&SET_IDENT)));
+        }
+    }
+    if let Some((latest_key, true)) = latest {
+        keys.insert(latest_key);
+    }
+    keys
+}
+
+/// Prepare the archive for a ledger that was reset then reloaded as of a given slot.
+///
+/// The versions archived after that slot are dropped, and the keys that existed at that slot
+/// according to the archive but are absent from the reloaded ledger are archived as deleted.
+/// The values of the reloaded ledger are archived separately.
+pub(crate) fn resync_archive(
+    batch: &mut WriteBatch,
+    db: &DB,
+    handle: &ColumnFamily,
+    ledger_handle: &ColumnFamily,
+    slot: &Slot,
+) {
+    let archive_deletion = |batch: &mut WriteBatch, prefix: &[u8], exists: bool| {
+        let key = ledger_key(prefix);
+        if exists && db.get_cf(ledger_handle, &key).expect(CRUD_ERROR).is_none() {
+            put_archived_value(batch, handle, &key, slot, None);
+        }
+    };
+    // versions are sorted by archive prefix, then by slot
+    let mut latest: Option<(Vec<u8>, bool)> = None;
+    for item in db.iterator_cf(handle, IteratorMode::Start) {
+        let (archive_key, value) = item.expect(CRUD_ERROR);
+        let (prefix, version_slot) = archive_key.split_at(archive_key.len() - SLOT_KEY_SIZE);
+        let version_slot = Slot::from_bytes_key(version_slot.try_into().expect(ARCHIVE_KEY_ERROR));
+        // flush the latest version of the previous key
+        if let Some((latest_prefix, exists)) = &latest {
+            if latest_prefix.as_slice() != prefix {
+                archive_deletion(batch, latest_prefix, *exists);
+                latest = None;
+            }
+        }
+        if version_slot > *slot {
+            batch.delete_cf(handle, &archive_key);
+        } else {
+            latest = Some((prefix.to_vec(), value.first() == Some(&SET_IDENT)));
+        }
+    }
+    if let Some((latest_prefix, exists)) = latest {
+        archive_deletion(batch, &latest_prefix, exists);
+    }
+}
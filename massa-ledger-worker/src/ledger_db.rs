@@ -2,6 +2,10 @@
 
 //! Module to interact with the disk ledger
 
+use crate::ledger_archive::{
+    get_archived_datastore_keys, get_archived_value, put_archived_value, resync_archive,
+};
+use crate::ledger_tree::{compute_root_hash, get_proof, get_root_hash, LedgerTreeBatch};
 use massa_hash::{Hash, HASH_SIZE_BYTES};
 use massa_ledger_exports::*;
 use massa_models::{
@@ -9,19 +13,20 @@ use massa_models::{
     amount::AmountSerializer,
     error::ModelsError,
     serialization::{VecU8Deserializer, VecU8Serializer},
-    slot::{Slot, SlotSerializer},
+    slot::{Slot, SlotDeserializer, SlotSerializer},
     streaming_step::StreamingStep,
 };
-use massa_serialization::{Deserializer, Serializer, U64VarIntSerializer};
+use massa_serialization::{DeserializeError, Deserializer, Serializer};
 use nom::multi::many0;
 use nom::sequence::tuple;
 use rocksdb::{
-    ColumnFamily, ColumnFamilyDescriptor, Direction, IteratorMode, Options, ReadOptions,
+    ColumnFamily, ColumnFamilyDescriptor, Direction, IteratorMode, Options, ReadOptions, Snapshot,
     WriteBatch, DB,
 };
 use std::ops::Bound;
 use std::path::PathBuf;
 use std::rc::Rc;
+use std::sync::Arc;
 use std::{collections::BTreeMap, fmt::Debug};
 use std::{
     collections::{BTreeSet, HashMap},
@@ -33,13 +38,22 @@ use massa_models::amount::{Amount, AmountDeserializer};
 
 const LEDGER_CF: &str = "ledger";
 const METADATA_CF: &str = "metadata";
+const TREE_CF: &str = "tree";
+const ARCHIVE_CF: &str = "archive";
 const OPEN_ERROR: &str = "critical: rocksdb open operation failed";
 const CRUD_ERROR: &str = "critical: rocksdb crud operation failed";
 const CF_ERROR: &str = "critical: rocksdb column family operation failed";
+const HASH_SCAN_ERROR: &str = "critical: ledger hash scan thread failed";
 const LEDGER_HASH_ERROR: &str = "critical: saved ledger hash is corrupted";
-const KEY_LEN_SER_ERROR: &str = "critical: key length serialization failed";
+const SLOT_ERROR: &str = "critical: saved ledger slot is corrupted";
+const ARCHIVE_SLOT_ERROR: &str = "critical: saved archive slot is corrupted";
 const SLOT_KEY: &[u8; 1] = b"s";
 const LEDGER_HASH_KEY: &[u8; 1] = b"h";
+const ARCHIVE_START_KEY: &[u8; 2] = b"as";
+const ARCHIVE_END_KEY: &[u8; 2] = b"ae";
+const ARCHIVE_RESYNC_KEY: &[u8; 2] = b"ar";
+const TREE_BUILT_KEY: &[u8; 2] = b"tb";
+const TREE_BUILD_CHUNK_SIZE: usize = 10_000;
 const LEDGER_HASH_INITIAL_BYTES: &[u8; 32] = &[0; HASH_SIZE_BYTES];
 
 /// Ledger sub entry enum
@@ -56,13 +70,14 @@ pub enum LedgerSubEntry {
 ///
 /// Contains a `RocksDB` DB instance
 pub(crate) struct LedgerDB {
-    db: DB,
+    db: Arc<DB>,
     thread_count: u8,
     amount_serializer: AmountSerializer,
     slot_serializer: SlotSerializer,
-    len_serializer: U64VarIntSerializer,
     max_datastore_key_length: u8,
     ledger_part_size_message_bytes: u64,
+    archival: bool,
+    tree_hash_activation_period: Option<u64>,
     #[cfg(feature = "testing")]
     amount_deserializer: AmountDeserializer,
 }
@@ -73,6 +88,33 @@ impl Debug for LedgerDB {
     }
 }
 
+/// Compute the ledger hash from a full scan of a snapshot of the ledger
+fn compute_snapshot_ledger_hash(db: &DB, snapshot: &Snapshot, tree_hash_active: bool) -> Hash {
+    let handle = db.cf_handle(LEDGER_CF).expect(CF_ERROR);
+    let entries = snapshot
+        .iterator_cf(handle, IteratorMode::Start)
+        .map(|item| {
+            let (key, value) = item.expect(CRUD_ERROR);
+            (ledger_tree_path(&key), ledger_entry_hash(&key, &value))
+        });
+    let mut ledger_hash = if tree_hash_active {
+        compute_root_hash(entries.collect())
+    } else {
+        entries.fold(
+            Hash::from_bytes(LEDGER_HASH_INITIAL_BYTES),
+            |ledger_hash, (_, entry_hash)| ledger_hash ^ entry_hash,
+        )
+    };
+    let metadata_handle = db.cf_handle(METADATA_CF).expect(CF_ERROR);
+    if let Some(slot_bytes) = snapshot
+        .get_cf(metadata_handle, SLOT_KEY)
+        .expect(CRUD_ERROR)
+    {
+        ledger_hash ^= Hash::compute_from(&slot_bytes);
+    }
+    ledger_hash
+}
+
 /// For a given start prefix (inclusive), returns the correct end prefix (non-inclusive).
 /// This assumes the key bytes are ordered in lexicographical order.
 /// Since key length is not limited, for some case we return `None` because there is
@@ -100,10 +142,14 @@ fn test_end_prefix() {
 pub struct LedgerBatch {
     // Rocksdb write batch
     write_batch: WriteBatch,
-    // Ledger hash state in the current batch
+    // Legacy ledger hash state in the current batch
     ledger_hash: Hash,
     // Added entry hashes in the current batch
     aeh_list: BTreeMap<Vec<u8>, Hash>,
+    // Ledger tree updates in the current batch, `None` for deleted keys
+    tree_updates: BTreeMap<Vec<u8>, Option<Hash>>,
+    // Slot at which the changes of the batch are archived, if any
+    archive_slot: Option<Slot>,
 }
 
 impl LedgerBatch {
@@ -112,6 +158,8 @@ impl LedgerBatch {
             write_batch: WriteBatch::default(),
             ledger_hash,
             aeh_list: BTreeMap::new(),
+            tree_updates: BTreeMap::new(),
+            archive_slot: None,
         }
     }
 }
@@ -121,11 +169,15 @@ impl LedgerDB {
     ///
     /// # Arguments
     /// * path: path to the desired disk ledger db directory
+    /// * archival: whether to keep the history of every ledger entry
+    /// * `tree_hash_activation_period`: period from which the ledger hash is the ledger tree root
     pub fn new(
         path: PathBuf,
         thread_count: u8,
         max_datastore_key_length: u8,
         ledger_part_size_message_bytes: u64,
+        archival: bool,
+        tree_hash_activation_period: Option<u64>,
     ) -> Self {
         let mut db_opts = Options::default();
         db_opts.create_if_missing(true);
@@ -137,24 +189,78 @@ impl LedgerDB {
             vec![
                 ColumnFamilyDescriptor::new(LEDGER_CF, Options::default()),
                 ColumnFamilyDescriptor::new(METADATA_CF, Options::default()),
+                ColumnFamilyDescriptor::new(TREE_CF, Options::default()),
+                ColumnFamilyDescriptor::new(ARCHIVE_CF, Options::default()),
             ],
         )
         .expect(OPEN_ERROR);
 
-        LedgerDB {
-            db,
+        let ledger_db = LedgerDB {
+            db: Arc::new(db),
             thread_count,
             amount_serializer: AmountSerializer::new(),
             slot_serializer: SlotSerializer::new(),
-            len_serializer: U64VarIntSerializer::new(),
             max_datastore_key_length,
             ledger_part_size_message_bytes,
+            archival,
+            tree_hash_activation_period,
             #[cfg(feature = "testing")]
             amount_deserializer: AmountDeserializer::new(
                 Bound::Included(Amount::MIN),
                 Bound::Included(Amount::MAX),
             ),
+        };
+        ledger_db.build_tree();
+        ledger_db
+    }
+
+    /// Build the ledger tree from the ledger entries if it was not built yet,
+    /// which is the case of databases created before the ledger tree existed.
+    ///
+    /// The tree is built by chunks of entries and marked as built once complete,
+    /// a partially built tree is cleared and built again.
+    fn build_tree(&self) {
+        let metadata_handle = self.db.cf_handle(METADATA_CF).expect(CF_ERROR);
+        if self
+            .db
+            .get_cf(metadata_handle, TREE_BUILT_KEY)
+            .expect(CRUD_ERROR)
+            .is_some()
+        {
+            return;
+        }
+        let tree_handle = self.db.cf_handle(TREE_CF).expect(CF_ERROR);
+        let mut batch = WriteBatch::default();
+        for (key, _) in self
+            .db
+            .iterator_cf(tree_handle, IteratorMode::Start)
+            .flatten()
+        {
+            batch.delete_cf(tree_handle, key);
+        }
+        self.db.write(batch).expect(CRUD_ERROR);
+
+        let handle = self.db.cf_handle(LEDGER_CF).expect(CF_ERROR);
+        let mut entries = self.db.iterator_cf(handle, IteratorMode::Start).peekable();
+        while entries.peek().is_some() {
+            let updates = entries
+                .by_ref()
+                .take(TREE_BUILD_CHUNK_SIZE)
+                .map(|item| {
+                    let (key, value) = item.expect(CRUD_ERROR);
+                    let entry_hash = ledger_entry_hash(&key, &value);
+                    (key.to_vec(), Some(entry_hash))
+                })
+                .collect();
+            let mut tree_batch = LedgerTreeBatch::new(&self.db, tree_handle);
+            tree_batch.apply_updates(updates);
+            let mut batch = WriteBatch::default();
+            tree_batch.write(&mut batch);
+            self.db.write(batch).expect(CRUD_ERROR);
         }
+        self.db
+            .put_cf(metadata_handle, TREE_BUILT_KEY, b"")
+            .expect(CRUD_ERROR);
     }
 
     /// Loads the initial disk ledger
@@ -175,6 +281,33 @@ impl LedgerDB {
         self.write_batch(batch);
     }
 
+    /// Delete every entry and metadata of the disk ledger.
+    ///
+    /// The archive and its range are kept, the reloaded ledger is archived again in full
+    /// with the next archived changes.
+    pub fn reset(&mut self) {
+        let mut batch = WriteBatch::default();
+        for cf in [LEDGER_CF, METADATA_CF, TREE_CF] {
+            let handle = self.db.cf_handle(cf).expect(CF_ERROR);
+            for (key, _) in self.db.iterator_cf(handle, IteratorMode::Start).flatten() {
+                if cf == METADATA_CF
+                    && [&ARCHIVE_START_KEY[..], &ARCHIVE_END_KEY[..]].contains(&&key[..])
+                {
+                    continue;
+                }
+                batch.delete_cf(handle, key);
+            }
+        }
+        // the empty tree matches the empty ledger
+        let metadata_handle = self.db.cf_handle(METADATA_CF).expect(CF_ERROR);
+        batch.put_cf(metadata_handle, TREE_BUILT_KEY, b"");
+        // the reloaded ledger will need to be archived again
+        if self.get_archive_slot(ARCHIVE_START_KEY).is_some() {
+            batch.put_cf(metadata_handle, ARCHIVE_RESYNC_KEY, b"");
+        }
+        self.db.write(batch).expect(CRUD_ERROR);
+    }
+
     /// Allows applying `LedgerChanges` to the disk ledger
     ///
     /// # Arguments
@@ -182,7 +315,10 @@ impl LedgerDB {
     /// * slot: new slot associated to the final ledger
     pub fn apply_changes(&mut self, changes: LedgerChanges, slot: Slot) {
         // create the batch
-        let mut batch = LedgerBatch::new(self.get_ledger_hash());
+        let mut batch = LedgerBatch::new(self.get_legacy_ledger_hash());
+        if self.archival {
+            self.set_archive_slot(slot, &mut batch);
+        }
         // for all incoming changes
         for (addr, change) in changes.0 {
             match change {
@@ -210,8 +346,152 @@ impl LedgerDB {
         self.write_batch(batch);
     }
 
+    /// Archive the changes of the batch at the given slot.
+    ///
+    /// When the archive is empty, or when the ledger was reset since the last archived changes,
+    /// the whole current ledger is first archived as of its current slot,
+    /// or as of `slot` for a ledger that was never attached to a slot.
+    fn set_archive_slot(&self, slot: Slot, batch: &mut LedgerBatch) {
+        let metadata_handle = self.db.cf_handle(METADATA_CF).expect(CF_ERROR);
+        let archive_start = self.get_archive_slot(ARCHIVE_START_KEY);
+        let resync = self
+            .db
+            .get_cf(metadata_handle, ARCHIVE_RESYNC_KEY)
+            .expect(CRUD_ERROR)
+            .is_some();
+        if archive_start.is_none() || resync {
+            let start_slot = self.get_slot().unwrap_or(slot);
+            let handle = self.db.cf_handle(LEDGER_CF).expect(CF_ERROR);
+            let archive_handle = self.db.cf_handle(ARCHIVE_CF).expect(CF_ERROR);
+            if resync {
+                resync_archive(
+                    &mut batch.write_batch,
+                    &self.db,
+                    archive_handle,
+                    handle,
+                    &start_slot,
+                );
+                batch
+                    .write_batch
+                    .delete_cf(metadata_handle, ARCHIVE_RESYNC_KEY);
+            }
+            for (key, value) in self.db.iterator_cf(handle, IteratorMode::Start).flatten() {
+                put_archived_value(
+                    &mut batch.write_batch,
+                    archive_handle,
+                    &key,
+                    &start_slot,
+                    Some(&value),
+                );
+            }
+            let start_slot = archive_start.map_or(start_slot, |archive_start| {
+                std::cmp::min(archive_start, start_slot)
+            });
+            batch.write_batch.put_cf(
+                metadata_handle,
+                ARCHIVE_START_KEY,
+                start_slot.to_bytes_key(),
+            );
+        }
+        batch
+            .write_batch
+            .put_cf(metadata_handle, ARCHIVE_END_KEY, slot.to_bytes_key());
+        batch.archive_slot = Some(slot);
+    }
+
+    /// Get an archive bound slot from metadata
+    fn get_archive_slot(&self, bound_key: &[u8]) -> Option<Slot> {
+        let handle = self.db.cf_handle(METADATA_CF).expect(CF_ERROR);
+        self.db
+            .get_cf(handle, bound_key)
+            .expect(CRUD_ERROR)
+            .map(|bytes| Slot::from_bytes_key(&bytes.try_into().expect(ARCHIVE_SLOT_ERROR)))
+    }
+
+    /// Get the slot of the disk ledger, if it was attached to one
+    pub fn get_slot(&self) -> Option<Slot> {
+        let handle = self.db.cf_handle(METADATA_CF).expect(CF_ERROR);
+        let slot_deserializer = SlotDeserializer::new(
+            (Bound::Included(0), Bound::Included(u64::MAX)),
+            (Bound::Included(0), Bound::Excluded(self.thread_count)),
+        );
+        self.db
+            .get_cf(handle, SLOT_KEY)
+            .expect(CRUD_ERROR)
+            .map(|bytes| {
+                slot_deserializer
+                    .deserialize::<DeserializeError>(&bytes)
+                    .expect(SLOT_ERROR)
+                    .1
+            })
+    }
+
+    /// Check that the archive covers the given slot
+    fn check_archived_slot(&self, slot: &Slot) -> Result<(), LedgerError> {
+        if !self.archival {
+            return Err(LedgerError::ArchiveError(
+                "the node is not running in archival mode".to_string(),
+            ));
+        }
+        match (
+            self.get_archive_slot(ARCHIVE_START_KEY),
+            self.get_archive_slot(ARCHIVE_END_KEY),
+        ) {
+            (Some(start), Some(end)) if start <= *slot && *slot <= end => Ok(()),
+            (Some(start), Some(end)) => Err(LedgerError::ArchiveError(format!(
+                "slot {} is out of the archived range [{}, {}]",
+                slot, start, end
+            ))),
+            _ => Err(LedgerError::ArchiveError(
+                "the archive is empty".to_string(),
+            )),
+        }
+    }
+
+    /// Get the archived value of a sub entry as of the given slot
+    ///
+    /// # Arguments
+    /// * addr: associated address
+    /// * ty: type of the queried sub entry
+    /// * slot: slot at the output of which the value is queried
+    ///
+    /// # Returns
+    /// The sub entry value, `None` if it did not exist at that slot
+    pub fn get_sub_entry_at(
+        &self,
+        addr: &Address,
+        ty: LedgerSubEntry,
+        slot: &Slot,
+    ) -> Result<Option<Vec<u8>>, LedgerError> {
+        self.check_archived_slot(slot)?;
+        let handle = self.db.cf_handle(ARCHIVE_CF).expect(CF_ERROR);
+        let key = match ty {
+            LedgerSubEntry::Balance => balance_key!(addr),
+            LedgerSubEntry::Bytecode => bytecode_key!(addr),
+            LedgerSubEntry::Datastore(hash) => data_key!(addr, hash),
+        };
+        Ok(get_archived_value(&self.db, handle, &key, slot))
+    }
+
+    /// Get the archived datastore keys of an address as of the given slot
+    pub fn get_datastore_keys_at(
+        &self,
+        addr: &Address,
+        slot: &Slot,
+    ) -> Result<BTreeSet<Vec<u8>>, LedgerError> {
+        self.check_archived_slot(slot)?;
+        let handle = self.db.cf_handle(ARCHIVE_CF).expect(CF_ERROR);
+        Ok(get_archived_datastore_keys(&self.db, handle, addr, slot))
+    }
+
     /// Apply the given operation batch to the disk ledger
     fn write_batch(&self, mut batch: LedgerBatch) {
+        // update the ledger tree, maintained alongside the legacy ledger hash
+        let tree_handle = self.db.cf_handle(TREE_CF).expect(CF_ERROR);
+        let mut tree_batch = LedgerTreeBatch::new(&self.db, tree_handle);
+        tree_batch.apply_updates(std::mem::take(&mut batch.tree_updates));
+        tree_batch.write(&mut batch.write_batch);
+
         let handle = self.db.cf_handle(METADATA_CF).expect(CF_ERROR);
         batch
             .write_batch
@@ -241,8 +521,37 @@ impl LedgerDB {
         batch.ledger_hash ^= Hash::compute_from(&slot_bytes);
     }
 
-    /// Get the current disk ledger hash
+    /// Whether the ledger hash at the given slot is computed from the ledger tree root
+    fn is_tree_hash_active_at(&self, slot: &Slot) -> bool {
+        self.tree_hash_activation_period
+            .map_or(false, |period| slot.period >= period)
+    }
+
+    /// Whether the current disk ledger hash is computed from the ledger tree root,
+    /// in which case the entry proofs can be checked against it
+    pub fn is_tree_hash_active(&self) -> bool {
+        self.get_slot()
+            .map_or(false, |slot| self.is_tree_hash_active_at(&slot))
+    }
+
+    /// Get the current disk ledger hash.
+    ///
+    /// Until the activation of the ledger tree hash, it is the legacy XOR of the entry hashes
+    /// and of the slot hash. From its activation, it is the ledger tree root XORed with the slot hash.
     pub fn get_ledger_hash(&self) -> Hash {
+        let handle = self.db.cf_handle(METADATA_CF).expect(CF_ERROR);
+        match self.db.get_cf(handle, SLOT_KEY).expect(CRUD_ERROR) {
+            Some(slot_bytes) if self.is_tree_hash_active() => {
+                let tree_handle = self.db.cf_handle(TREE_CF).expect(CF_ERROR);
+                get_root_hash(&self.db, tree_handle) ^ Hash::compute_from(&slot_bytes)
+            }
+            _ => self.get_legacy_ledger_hash(),
+        }
+    }
+
+    /// Get the legacy ledger hash, XOR of the entry hashes and of the slot hash,
+    /// which is maintained incrementally in the metadata
+    fn get_legacy_ledger_hash(&self) -> Hash {
         let handle = self.db.cf_handle(METADATA_CF).expect(CF_ERROR);
         if let Some(ledger_hash_bytes) = self.db.get_cf(handle, LEDGER_HASH_KEY).expect(CRUD_ERROR)
         {
@@ -255,7 +564,34 @@ impl LedgerDB {
         }
     }
 
-    /// Internal function to put a key & value and perform the ledger hash XORs
+    /// Compute the disk ledger hash from scratch with a full scan of the ledger,
+    /// regardless of the stored ledger tree and hash
+    pub fn compute_ledger_hash(&self) -> Hash {
+        compute_snapshot_ledger_hash(&self.db, &self.db.snapshot(), self.is_tree_hash_active())
+    }
+
+    /// Start computing the disk ledger hash from scratch on a snapshot of the ledger taken now.
+    ///
+    /// The full scan runs in its own thread, which only holds the snapshot,
+    /// so the ledger can be written during the scan. The returned closure waits for the result.
+    pub fn compute_ledger_hash_on_snapshot(&self) -> Box<dyn FnOnce() -> Hash + Send> {
+        let db = self.db.clone();
+        let tree_hash_active = self.is_tree_hash_active();
+        let (snapshot_tx, snapshot_rx) = std::sync::mpsc::sync_channel(0);
+        let scan_handle = std::thread::Builder::new()
+            .name("ledger_hash_scan".into())
+            .spawn(move || {
+                let snapshot = db.snapshot();
+                // the snapshot is taken: the caller no longer needs to hold the ledger
+                let _ = snapshot_tx.send(());
+                compute_snapshot_ledger_hash(&db, &snapshot, tree_hash_active)
+            })
+            .expect(HASH_SCAN_ERROR);
+        snapshot_rx.recv().expect(HASH_SCAN_ERROR);
+        Box::new(move || scan_handle.join().expect(HASH_SCAN_ERROR))
+    }
+
+    /// Internal function to put a key & value, perform the ledger hash XORs and update the ledger tree
     fn put_entry_value(
         &self,
         handle: &ColumnFamily,
@@ -263,13 +599,51 @@ impl LedgerDB {
         key: &[u8],
         value: &[u8],
     ) {
-        let mut len_bytes = Vec::new();
-        self.len_serializer
-            .serialize(&(key.len() as u64), &mut len_bytes)
-            .expect(KEY_LEN_SER_ERROR);
-        let hash = Hash::compute_from(&[&len_bytes, key, value].concat());
+        let hash = ledger_entry_hash(key, value);
+        batch.ledger_hash ^= hash;
+        batch.aeh_list.insert(key.to_vec(), hash);
+        self.write_entry_value(handle, batch, key, value, hash);
+    }
+
+    /// Internal function to update a key & value, perform the ledger hash XORs and update the ledger tree
+    fn update_key_value(
+        &self,
+        handle: &ColumnFamily,
+        batch: &mut LedgerBatch,
+        key: &[u8],
+        value: &[u8],
+    ) {
+        if let Some(added_hash) = batch.aeh_list.get(key) {
+            batch.ledger_hash ^= *added_hash;
+        } else if let Some(prev_bytes) = self.db.get_cf(handle, key).expect(CRUD_ERROR) {
+            batch.ledger_hash ^= ledger_entry_hash(key, &prev_bytes);
+        }
+        let hash = ledger_entry_hash(key, value);
         batch.ledger_hash ^= hash;
         batch.aeh_list.insert(key.to_vec(), hash);
+        self.write_entry_value(handle, batch, key, value, hash);
+    }
+
+    /// Internal function to write a key & value to the batch along with its tree and archive updates
+    fn write_entry_value(
+        &self,
+        handle: &ColumnFamily,
+        batch: &mut LedgerBatch,
+        key: &[u8],
+        value: &[u8],
+        entry_hash: Hash,
+    ) {
+        batch.tree_updates.insert(key.to_vec(), Some(entry_hash));
+        if let Some(slot) = batch.archive_slot {
+            let archive_handle = self.db.cf_handle(ARCHIVE_CF).expect(CF_ERROR);
+            put_archived_value(
+                &mut batch.write_batch,
+                archive_handle,
+                key,
+                &slot,
+                Some(value),
+            );
+        }
         batch.write_batch.put_cf(handle, key, value);
     }
 
@@ -326,6 +700,22 @@ impl LedgerDB {
         }
     }
 
+    /// Get the value of a ledger key and its proof against the ledger tree root.
+    ///
+    /// # Arguments
+    /// * `key`: serialized ledger key
+    ///
+    /// # Returns
+    /// A tuple containing the value of the key, or `None` if absent, and its proof
+    pub fn get_entry_proof(&self, key: &[u8]) -> (Option<Vec<u8>>, LedgerTreeProof) {
+        let handle = self.db.cf_handle(LEDGER_CF).expect(CF_ERROR);
+        let tree_handle = self.db.cf_handle(TREE_CF).expect(CF_ERROR);
+        (
+            self.db.get_cf(handle, key).expect(CRUD_ERROR),
+            get_proof(&self.db, tree_handle, key),
+        )
+    }
+
     /// Get every key of the datastore for a given address.
     ///
     /// # Returns
@@ -347,27 +737,45 @@ impl LedgerDB {
             .collect()
     }
 
-    /// Internal function to update a key & value and perform the ledger hash XORs
-    fn update_key_value(
+    /// Get a page of the datastore keys of a given address starting with a given prefix.
+    ///
+    /// # Arguments
+    /// * `prefix`: prefix of the returned keys
+    /// * `start_after`: if set, only keys strictly greater than it are returned
+    /// * `limit`: maximum number of returned keys
+    ///
+    /// # Returns
+    /// A `BTreeSet` of the first `limit` matching datastore keys
+    pub fn get_datastore_keys_page(
         &self,
-        handle: &ColumnFamily,
-        batch: &mut LedgerBatch,
-        key: &[u8],
-        value: &[u8],
-    ) {
-        let mut len_bytes = Vec::new();
-        self.len_serializer
-            .serialize(&(key.len() as u64), &mut len_bytes)
-            .expect(KEY_LEN_SER_ERROR);
-        if let Some(added_hash) = batch.aeh_list.get(key) {
-            batch.ledger_hash ^= *added_hash;
-        } else if let Some(prev_bytes) = self.db.get_cf(handle, key).expect(CRUD_ERROR) {
-            batch.ledger_hash ^= Hash::compute_from(&[&len_bytes, key, &prev_bytes].concat());
-        }
-        let hash = Hash::compute_from(&[&len_bytes, key, value].concat());
-        batch.ledger_hash ^= hash;
-        batch.aeh_list.insert(key.to_vec(), hash);
-        batch.write_batch.put_cf(handle, key, value);
+        addr: &Address,
+        prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: usize,
+    ) -> BTreeSet<Vec<u8>> {
+        let handle = self.db.cf_handle(LEDGER_CF).expect(CF_ERROR);
+        let key_prefix = data_key!(addr, prefix);
+
+        let mut opt = ReadOptions::default();
+        opt.set_iterate_upper_bound(end_prefix(&key_prefix).unwrap());
+
+        // seek directly to the cursor when it is past the beginning of the prefix range
+        let start_key = match start_after {
+            Some(after) if after > prefix => data_key!(addr, after),
+            _ => key_prefix,
+        };
+
+        self.db
+            .iterator_cf_opt(
+                handle,
+                opt,
+                IteratorMode::From(&start_key, Direction::Forward),
+            )
+            .flatten()
+            .map(|(key, _)| key.split_at(ADDRESS_SIZE_BYTES + 1).1.to_vec())
+            .filter(|key| Some(key.as_slice()) != start_after)
+            .take(limit)
+            .collect()
     }
 
     /// Update the ledger entry of a given address.
@@ -409,16 +817,17 @@ impl LedgerDB {
         }
     }
 
-    /// Internal function to delete a key and perform the ledger hash XOR
+    /// Internal function to delete a key, perform the ledger hash XOR and update the ledger tree
     fn delete_key(&self, handle: &ColumnFamily, batch: &mut LedgerBatch, key: &[u8]) {
         if let Some(added_hash) = batch.aeh_list.get(key) {
             batch.ledger_hash ^= *added_hash;
         } else if let Some(prev_bytes) = self.db.get_cf(handle, key).expect(CRUD_ERROR) {
-            let mut len_bytes = Vec::new();
-            self.len_serializer
-                .serialize(&(key.len() as u64), &mut len_bytes)
-                .expect(KEY_LEN_SER_ERROR);
-            batch.ledger_hash ^= Hash::compute_from(&[&len_bytes, key, &prev_bytes].concat());
+            batch.ledger_hash ^= ledger_entry_hash(key, &prev_bytes);
+        }
+        batch.tree_updates.insert(key.to_vec(), None);
+        if let Some(slot) = batch.archive_slot {
+            let archive_handle = self.db.cf_handle(ARCHIVE_CF).expect(CF_ERROR);
+            put_archived_value(&mut batch.write_batch, archive_handle, key, &slot, None);
         }
         batch.write_batch.delete_cf(handle, key);
     }
@@ -521,7 +930,7 @@ impl LedgerDB {
             VecU8Deserializer::new(Bound::Included(0), Bound::Excluded(u64::MAX));
         let key_deserializer = KeyDeserializer::new(self.max_datastore_key_length);
         let mut last_key = Rc::new(Vec::new());
-        let mut batch = LedgerBatch::new(self.get_ledger_hash());
+        let mut batch = LedgerBatch::new(self.get_legacy_ledger_hash());
 
         // Since this data is coming from the network, deser to address and ser back to bytes for a security check.
         let (rest, _) = many0(|input: &'a [u8]| {
@@ -625,13 +1034,17 @@ mod tests {
     use super::LedgerDB;
     use crate::ledger_db::{LedgerBatch, LedgerSubEntry, LEDGER_HASH_INITIAL_BYTES};
     use massa_hash::Hash;
-    use massa_ledger_exports::{LedgerEntry, LedgerEntryUpdate, SetOrKeep};
+    use massa_ledger_exports::{
+        data_key, LedgerChanges, LedgerEntry, LedgerEntryUpdate, SetOrKeep, DATASTORE_IDENT,
+        LEDGER_TREE_EMPTY_HASH_BYTES,
+    };
     use massa_models::{
         address::Address,
-        amount::{Amount, AmountDeserializer},
+        amount::{Amount, AmountDeserializer, AmountSerializer},
+        slot::Slot,
         streaming_step::StreamingStep,
     };
-    use massa_serialization::{DeserializeError, Deserializer};
+    use massa_serialization::{DeserializeError, Deserializer, Serializer};
     use massa_signature::KeyPair;
     use std::collections::BTreeMap;
     use std::ops::Bound::Included;
@@ -658,7 +1071,14 @@ mod tests {
 
         // write data
         let temp_dir = TempDir::new().unwrap();
-        let mut db = LedgerDB::new(temp_dir.path().to_path_buf(), 32, 255, 1_000_000);
+        let mut db = LedgerDB::new(
+            temp_dir.path().to_path_buf(),
+            32,
+            255,
+            1_000_000,
+            true,
+            None,
+        );
         let mut batch = LedgerBatch::new(Hash::from_bytes(LEDGER_HASH_INITIAL_BYTES));
         db.put_entry(&addr, entry, &mut batch);
         db.update_entry(&addr, entry_update, &mut batch);
@@ -673,7 +1093,7 @@ mod tests {
     fn test_ledger_db() {
         let addr = Address::from_public_key(&KeyPair::generate().get_public_key());
         let (db, data) = init_test_ledger(addr);
-        let ledger_hash = db.get_ledger_hash();
+        let ledger_hash = db.get_legacy_ledger_hash();
         let amount_deserializer =
             AmountDeserializer::new(Included(Amount::MIN), Included(Amount::MAX));
 
@@ -708,6 +1128,313 @@ mod tests {
         assert!(db.get_entire_datastore(&addr).is_empty());
     }
 
+    #[test]
+    fn test_datastore_keys_page() {
+        let addr = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let (mut db, _) = init_test_ledger(addr);
+        let mut batch = LedgerBatch::new(db.get_legacy_ledger_hash());
+        db.put_entry(
+            &addr,
+            LedgerEntry {
+                datastore: [b"a1", b"a2", b"a3", b"b1"]
+                    .into_iter()
+                    .map(|key| (key.to_vec(), vec![0]))
+                    .collect(),
+                ..Default::default()
+            },
+            &mut batch,
+        );
+        db.write_batch(batch);
+
+        let keys = |prefix: &[u8], start_after: Option<&[u8]>, limit| {
+            db.get_datastore_keys_page(&addr, prefix, start_after, limit)
+                .into_iter()
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(keys(b"a", None, 2), vec![b"a1".to_vec(), b"a2".to_vec()]);
+        assert_eq!(keys(b"a", Some(b"a2"), 2), vec![b"a3".to_vec()]);
+        assert_eq!(
+            keys(b"", Some(b"a25"), 10),
+            vec![b"a3".to_vec(), b"b1".to_vec()]
+        );
+        assert!(keys(b"c", None, 10).is_empty());
+    }
+
+    #[test]
+    fn test_compute_ledger_hash() {
+        let addr = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let (mut db, _) = init_test_ledger(addr);
+        let other_addr = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let mut batch = LedgerBatch::new(db.get_legacy_ledger_hash());
+        db.put_entry(&other_addr, LedgerEntry::default(), &mut batch);
+        db.set_slot(Slot::new(1, 0), &mut batch);
+        db.write_batch(batch);
+        assert_eq!(db.get_ledger_hash(), db.compute_ledger_hash());
+    }
+
+    #[test]
+    fn test_ledger_tree_hash_activation() {
+        let addr = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let mut changes = LedgerChanges::default();
+        changes.set_balance(addr, Amount::from_str("10").unwrap());
+        changes.set_data_entry(addr, b"1".to_vec(), b"a".to_vec());
+
+        let temp_dir = TempDir::new().unwrap();
+        let mut db = LedgerDB::new(
+            temp_dir.path().to_path_buf(),
+            32,
+            255,
+            1_000_000,
+            false,
+            Some(2),
+        );
+        let mut slot_bytes = Vec::new();
+        db.slot_serializer
+            .serialize(&Slot::new(1, 0), &mut slot_bytes)
+            .unwrap();
+        db.apply_changes(changes, Slot::new(1, 0));
+
+        // before the activation, the ledger hash is the XOR of the entry hashes and of the slot hash
+        let handle = db.db.cf_handle(super::LEDGER_CF).unwrap();
+        let legacy_hash = db
+            .db
+            .iterator_cf(handle, rocksdb::IteratorMode::Start)
+            .flatten()
+            .fold(Hash::compute_from(&slot_bytes), |hash, (key, value)| {
+                hash ^ super::ledger_entry_hash(&key, &value)
+            });
+        assert!(!db.is_tree_hash_active());
+        assert_eq!(db.get_ledger_hash(), legacy_hash);
+        assert_eq!(db.compute_ledger_hash(), legacy_hash);
+
+        // from the activation, it is the tree root XORed with the slot hash
+        db.apply_changes(LedgerChanges::default(), Slot::new(2, 0));
+        let mut slot_bytes = Vec::new();
+        db.slot_serializer
+            .serialize(&Slot::new(2, 0), &mut slot_bytes)
+            .unwrap();
+        let root = super::get_root_hash(&db.db, db.db.cf_handle(super::TREE_CF).unwrap());
+        assert!(db.is_tree_hash_active());
+        assert_eq!(db.get_ledger_hash(), root ^ Hash::compute_from(&slot_bytes));
+        assert_eq!(db.compute_ledger_hash(), db.get_ledger_hash());
+    }
+
+    #[test]
+    fn test_ledger_tree_build() {
+        let addr = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let temp_dir = TempDir::new().unwrap();
+        let open = || {
+            LedgerDB::new(
+                temp_dir.path().to_path_buf(),
+                32,
+                255,
+                1_000_000,
+                false,
+                Some(0),
+            )
+        };
+        let ledger_hash = {
+            let mut db = open();
+            let mut changes = LedgerChanges::default();
+            changes.set_balance(addr, Amount::from_str("10").unwrap());
+            changes.set_data_entry(addr, b"1".to_vec(), b"a".to_vec());
+            db.apply_changes(changes, Slot::new(1, 0));
+
+            // drop the tree, as in a database created before it existed
+            let tree_handle = db.db.cf_handle(super::TREE_CF).unwrap();
+            for (key, _) in db
+                .db
+                .iterator_cf(tree_handle, rocksdb::IteratorMode::Start)
+                .flatten()
+            {
+                db.db.delete_cf(tree_handle, key).unwrap();
+            }
+            let metadata_handle = db.db.cf_handle(super::METADATA_CF).unwrap();
+            db.db
+                .delete_cf(metadata_handle, super::TREE_BUILT_KEY)
+                .unwrap();
+            db.compute_ledger_hash()
+        };
+
+        // the tree is built again when the database is opened
+        let db = open();
+        assert_eq!(db.get_ledger_hash(), ledger_hash);
+        let key = data_key!(addr, b"1".to_vec());
+        let (value, proof) = db.get_entry_proof(&key);
+        assert_eq!(
+            proof.compute_root(&key, value.as_deref()),
+            Some(super::get_root_hash(
+                &db.db,
+                db.db.cf_handle(super::TREE_CF).unwrap()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_ledger_archive() {
+        let addr = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let (mut db, _) = init_test_ledger(addr);
+
+        // the archive starts with the first applied changes
+        let mut changes = LedgerChanges::default();
+        changes.set_balance(addr, Amount::from_str("10").unwrap());
+        changes.set_data_entry(addr, b"4".to_vec(), b"d".to_vec());
+        db.apply_changes(changes, Slot::new(1, 0));
+        let mut changes = LedgerChanges::default();
+        changes.set_balance(addr, Amount::from_str("5").unwrap());
+        changes.delete_data_entry(addr, b"1".to_vec());
+        db.apply_changes(changes, Slot::new(2, 0));
+
+        // check the archived values
+        let balance = |slot| {
+            db.get_sub_entry_at(&addr, LedgerSubEntry::Balance, &slot)
+                .unwrap()
+                .map(|bytes| {
+                    AmountDeserializer::new(Included(Amount::MIN), Included(Amount::MAX))
+                        .deserialize::<DeserializeError>(&bytes)
+                        .unwrap()
+                        .1
+                })
+        };
+        assert_eq!(
+            balance(Slot::new(1, 0)),
+            Some(Amount::from_str("10").unwrap())
+        );
+        assert_eq!(
+            balance(Slot::new(1, 5)),
+            Some(Amount::from_str("10").unwrap())
+        );
+        assert_eq!(
+            balance(Slot::new(2, 0)),
+            Some(Amount::from_str("5").unwrap())
+        );
+        assert_eq!(
+            db.get_sub_entry_at(
+                &addr,
+                LedgerSubEntry::Datastore(b"1".to_vec()),
+                &Slot::new(1, 0)
+            )
+            .unwrap(),
+            Some(b"a".to_vec())
+        );
+        assert_eq!(
+            db.get_sub_entry_at(
+                &addr,
+                LedgerSubEntry::Datastore(b"1".to_vec()),
+                &Slot::new(2, 0)
+            )
+            .unwrap(),
+            None
+        );
+        assert_eq!(
+            db.get_datastore_keys_at(&addr, &Slot::new(1, 0)).unwrap(),
+            [b"1".to_vec(), b"2".to_vec(), b"3".to_vec(), b"4".to_vec()]
+                .into_iter()
+                .collect()
+        );
+        assert_eq!(
+            db.get_datastore_keys_at(&addr, &Slot::new(2, 0)).unwrap(),
+            [b"2".to_vec(), b"3".to_vec(), b"4".to_vec()]
+                .into_iter()
+                .collect()
+        );
+
+        // slots outside of the archive are rejected
+        assert!(db
+            .get_sub_entry_at(&addr, LedgerSubEntry::Balance, &Slot::new(3, 0))
+            .is_err());
+    }
+
+    #[test]
+    fn test_ledger_archive_survives_reset() {
+        let addr = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let other_addr = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let (mut db, _) = init_test_ledger(addr);
+        let mut changes = LedgerChanges::default();
+        changes.set_balance(addr, Amount::from_str("10").unwrap());
+        db.apply_changes(changes, Slot::new(1, 0));
+        let mut changes = LedgerChanges::default();
+        changes.set_balance(addr, Amount::from_str("5").unwrap());
+        db.apply_changes(changes, Slot::new(2, 0));
+
+        // the archive is still readable after a reset
+        db.reset();
+        assert!(db.get_sub_entry(&addr, LedgerSubEntry::Balance).is_none());
+        let balance_bytes = |amount| {
+            let mut bytes = Vec::new();
+            AmountSerializer::new()
+                .serialize(&Amount::from_str(amount).unwrap(), &mut bytes)
+                .unwrap();
+            bytes
+        };
+        assert_eq!(
+            db.get_sub_entry_at(&addr, LedgerSubEntry::Balance, &Slot::new(1, 0))
+                .unwrap(),
+            Some(balance_bytes("10"))
+        );
+        assert_eq!(
+            db.get_sub_entry_at(&addr, LedgerSubEntry::Balance, &Slot::new(2, 0))
+                .unwrap(),
+            Some(balance_bytes("5"))
+        );
+
+        // the reloaded ledger is archived from its first changes, entries missing from it are archived as deleted
+        let mut changes = LedgerChanges::default();
+        changes.set_balance(other_addr, Amount::from_str("3").unwrap());
+        db.apply_changes(changes, Slot::new(4, 0));
+        assert_eq!(
+            db.get_sub_entry_at(&addr, LedgerSubEntry::Balance, &Slot::new(2, 0))
+                .unwrap(),
+            Some(balance_bytes("5"))
+        );
+        assert_eq!(
+            db.get_sub_entry_at(&addr, LedgerSubEntry::Balance, &Slot::new(4, 0))
+                .unwrap(),
+            None
+        );
+        assert!(db
+            .get_datastore_keys_at(&addr, &Slot::new(4, 0))
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            db.get_sub_entry_at(&other_addr, LedgerSubEntry::Balance, &Slot::new(4, 0))
+                .unwrap(),
+            Some(balance_bytes("3"))
+        );
+    }
+
+    #[test]
+    fn test_ledger_entry_proofs() {
+        let addr = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let (db, data) = init_test_ledger(addr);
+        let root = super::get_root_hash(&db.db, db.db.cf_handle(super::TREE_CF).unwrap());
+        assert_ne!(Hash::from_bytes(LEDGER_TREE_EMPTY_HASH_BYTES), root);
+
+        // check inclusion proofs of present keys
+        for (datastore_key, value) in data.iter() {
+            let key = data_key!(addr, *datastore_key);
+            let (proved_value, proof) = db.get_entry_proof(&key);
+            assert_eq!(proved_value.as_ref(), Some(value));
+            assert_eq!(proof.compute_root(&key, Some(value.as_slice())), Some(root));
+            assert_eq!(proof.compute_root(&key, Some(&b"wrong"[..])), None);
+        }
+
+        // check absence proof of a missing key
+        let key = data_key!(addr, b"4".to_vec());
+        let (proved_value, proof) = db.get_entry_proof(&key);
+        assert!(proved_value.is_none());
+        assert_eq!(proof.compute_root(&key, None), Some(root));
+
+        // check that the tree is emptied with the ledger
+        let mut batch = LedgerBatch::new(db.get_legacy_ledger_hash());
+        db.delete_entry(&addr, &mut batch);
+        db.write_batch(batch);
+        assert_eq!(
+            Hash::from_bytes(LEDGER_TREE_EMPTY_HASH_BYTES),
+            super::get_root_hash(&db.db, db.db.cf_handle(super::TREE_CF).unwrap())
+        );
+    }
+
     #[test]
     fn test_ledger_parts() {
         let pub_a = KeyPair::generate().get_public_key();
@@ -716,4 +1443,17 @@ mod tests {
         let res = db.get_ledger_part(StreamingStep::Started).unwrap();
         db.set_ledger_part(&res.0[..]).unwrap();
     }
+
+    #[test]
+    fn test_ledger_reset() {
+        let addr = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let (mut db, _) = init_test_ledger(addr);
+        db.reset();
+        assert_eq!(
+            Hash::from_bytes(LEDGER_HASH_INITIAL_BYTES),
+            db.get_ledger_hash()
+        );
+        assert!(db.get_sub_entry(&addr, LedgerSubEntry::Balance).is_none());
+        assert!(db.get_entire_datastore(&addr).is_empty());
+    }
 }
@@ -0,0 +1,296 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Sparse Merkle tree committing to the entries of the disk ledger.
+//! See `massa-ledger-exports/src/proof.rs` for the tree definition.
+//!
+//! Nodes are stored in their own column family of the ledger database,
+//! indexed by their depth and by the path leading to them.
+
+use massa_hash::{Hash, HASH_SIZE_BYTES};
+use massa_ledger_exports::{
+    ledger_tree_internal_hash, ledger_tree_leaf_hash, ledger_tree_path, ledger_tree_path_bit,
+    LedgerTreeProof, LEDGER_TREE_EMPTY_HASH_BYTES,
+};
+use rocksdb::{ColumnFamily, WriteBatch, DB};
+use std::collections::{BTreeMap, HashMap};
+
+const CRUD_ERROR: &str = "critical: rocksdb crud operation failed";
+const NODE_ERROR: &str = "critical: saved ledger tree node is corrupted";
+const LEAF_IDENT: u8 = 0u8;
+const INTERNAL_IDENT: u8 = 1u8;
+
+/// Node of the ledger tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    /// Leaf of a single ledger entry
+    Leaf { path: Hash, entry_hash: Hash },
+    /// Internal node with at least two entries below it
+    Internal { left: Hash, right: Hash },
+}
+
+impl Node {
+    fn hash(&self) -> Hash {
+        match self {
+            Node::Leaf { path, entry_hash } => ledger_tree_leaf_hash(path, entry_hash),
+            Node::Internal { left, right } => ledger_tree_internal_hash(left, right),
+        }
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Node::Leaf { path, entry_hash } => {
+                [&[LEAF_IDENT], path.to_bytes(), entry_hash.to_bytes()].concat()
+            }
+            Node::Internal { left, right } => {
+                [&[INTERNAL_IDENT], left.to_bytes(), right.to_bytes()].concat()
+            }
+        }
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Self {
+        let (ident, hashes) = bytes.split_first().expect(NODE_ERROR);
+        let (first, second) = hashes.split_at(HASH_SIZE_BYTES);
+        let first = Hash::from_bytes(first.try_into().expect(NODE_ERROR));
+        let second = Hash::from_bytes(second.try_into().expect(NODE_ERROR));
+        match *ident {
+            LEAF_IDENT => Node::Leaf {
+                path: first,
+                entry_hash: second,
+            },
+            INTERNAL_IDENT => Node::Internal {
+                left: first,
+                right: second,
+            },
+            _ => panic!("{}", NODE_ERROR),
+        }
+    }
+}
+
+/// Database key of the node at the given depth on `path`
+fn node_key(depth: usize, path: &Hash) -> Vec<u8> {
+    let mut prefix = path.into_bytes();
+    for (index, byte) in prefix.iter_mut().enumerate() {
+        let kept_bits = depth.saturating_sub(index * 8).min(8);
+        *byte &= !(0xffu8.checked_shr(kept_bits as u32).unwrap_or(0));
+    }
+    [&(depth as u16).to_be_bytes()[..], &prefix].concat()
+}
+
+/// Path of the sibling of the node at the given depth on `path`
+fn sibling_path(depth: usize, path: &Hash) -> Hash {
+    let mut bytes = path.into_bytes();
+    bytes[(depth - 1) / 8] ^= 0x80 >> ((depth - 1) % 8);
+    Hash::from_bytes(&bytes)
+}
+
+/// Get the hash of the root of the ledger tree
+pub(crate) fn get_root_hash(db: &DB, handle: &ColumnFamily) -> Hash {
+    let empty = Hash::from_bytes(LEDGER_TREE_EMPTY_HASH_BYTES);
+    get_node(db, handle, 0, &empty).map_or(empty, |root| root.hash())
+}
+
+/// Get the proof of a ledger key against the root of the ledger tree
+pub(crate) fn get_proof(db: &DB, handle: &ColumnFamily, key: &[u8]) -> LedgerTreeProof {
+    let path = ledger_tree_path(key);
+    let mut siblings = Vec::new();
+    let mut depth = 0;
+    loop {
+        match get_node(db, handle, depth, &path) {
+            Some(Node::Internal { left, right }) => {
+                siblings.push(if ledger_tree_path_bit(&path, depth) {
+                    left
+                } else {
+                    right
+                });
+                depth += 1;
+            }
+            Some(Node::Leaf { path, entry_hash }) => {
+                return LedgerTreeProof {
+                    siblings,
+                    leaf: Some((path, entry_hash)),
+                }
+            }
+            None => {
+                return LedgerTreeProof {
+                    siblings,
+                    leaf: None,
+                }
+            }
+        }
+    }
+}
+
+/// Compute the root hash of the ledger tree from scratch out of its leaves
+///
+/// # Arguments
+/// * `leaves`: path and entry hash of every ledger entry
+pub(crate) fn compute_root_hash(mut leaves: Vec<(Hash, Hash)>) -> Hash {
+    leaves.sort_unstable_by(|(a, _), (b, _)| a.to_bytes().cmp(b.to_bytes()));
+    compute_subtree_hash(&leaves, 0)
+}
+
+/// Hash of the subtree at the given depth containing the given sorted leaves
+fn compute_subtree_hash(leaves: &[(Hash, Hash)], depth: usize) -> Hash {
+    match leaves {
+        [] => Hash::from_bytes(LEDGER_TREE_EMPTY_HASH_BYTES),
+        [(path, entry_hash)] => ledger_tree_leaf_hash(path, entry_hash),
+        _ => {
+            let split = leaves.partition_point(|(path, _)| !ledger_tree_path_bit(path, depth));
+            ledger_tree_internal_hash(
+                &compute_subtree_hash(&leaves[..split], depth + 1),
+                &compute_subtree_hash(&leaves[split..], depth + 1),
+            )
+        }
+    }
+}
+
+fn get_node(db: &DB, handle: &ColumnFamily, depth: usize, path: &Hash) -> Option<Node> {
+    db.get_cf(handle, node_key(depth, path))
+        .expect(CRUD_ERROR)
+        .map(|bytes| Node::from_bytes(&bytes))
+}
+
+/// Pending modifications of the ledger tree
+pub(crate) struct LedgerTreeBatch<'a> {
+    db: &'a DB,
+    handle: &'a ColumnFamily,
+    // modified nodes, `None` for deleted ones
+    nodes: HashMap<Vec<u8>, Option<Node>>,
+}
+
+impl<'a> LedgerTreeBatch<'a> {
+    pub(crate) fn new(db: &'a DB, handle: &'a ColumnFamily) -> Self {
+        Self {
+            db,
+            handle,
+            nodes: HashMap::new(),
+        }
+    }
+
+    /// Apply entry updates to the tree, `None` deleting the entry
+    ///
+    /// # Returns
+    /// The new root hash of the tree
+    pub(crate) fn apply_updates(&mut self, updates: BTreeMap<Vec<u8>, Option<Hash>>) -> Hash {
+        for (key, entry_hash) in updates {
+            self.update(0, &ledger_tree_path(&key), entry_hash);
+        }
+        let empty = Hash::from_bytes(LEDGER_TREE_EMPTY_HASH_BYTES);
+        self.get_node(0, &empty).map_or(empty, |root| root.hash())
+    }
+
+    /// Add the node modifications to the given database batch
+    pub(crate) fn write(self, batch: &mut WriteBatch) {
+        for (key, node) in self.nodes {
+            match node {
+                Some(node) => batch.put_cf(self.handle, key, node.to_bytes()),
+                None => batch.delete_cf(self.handle, key),
+            }
+        }
+    }
+
+    fn get_node(&self, depth: usize, path: &Hash) -> Option<Node> {
+        match self.nodes.get(&node_key(depth, path)) {
+            Some(node) => node.clone(),
+            None => get_node(self.db, self.handle, depth, path),
+        }
+    }
+
+    fn set_node(&mut self, depth: usize, path: &Hash, node: Option<Node>) {
+        self.nodes.insert(node_key(depth, path), node);
+    }
+
+    /// Set the entry hash of `path` in the subtree at the given depth
+    ///
+    /// # Returns
+    /// The new node at the root of the subtree
+    fn update(&mut self, depth: usize, path: &Hash, entry_hash: Option<Hash>) -> Option<Node> {
+        let new_node = match (self.get_node(depth, path), entry_hash) {
+            // nothing to delete
+            (None, None) => return None,
+            (None, Some(entry_hash)) => Some(Node::Leaf {
+                path: *path,
+                entry_hash,
+            }),
+            (
+                Some(Node::Leaf {
+                    path: leaf_path, ..
+                }),
+                entry_hash,
+            ) if leaf_path == *path => entry_hash.map(|entry_hash| Node::Leaf {
+                path: *path,
+                entry_hash,
+            }),
+            // nothing to delete
+            (Some(leaf @ Node::Leaf { .. }), None) => return Some(leaf),
+            (
+                Some(Node::Leaf {
+                    path: leaf_path,
+                    entry_hash: leaf_entry_hash,
+                }),
+                Some(entry_hash),
+            ) => {
+                // push the existing leaf one level down and insert the new entry next to it
+                let leaf = Node::Leaf {
+                    path: leaf_path,
+                    entry_hash: leaf_entry_hash,
+                };
+                let leaf_hash = leaf.hash();
+                self.set_node(depth + 1, &leaf_path, Some(leaf));
+                let empty = Hash::from_bytes(LEDGER_TREE_EMPTY_HASH_BYTES);
+                let (left, right) = if ledger_tree_path_bit(&leaf_path, depth) {
+                    (empty, leaf_hash)
+                } else {
+                    (leaf_hash, empty)
+                };
+                self.update_child(depth, path, Some(entry_hash), left, right)
+            }
+            (Some(Node::Internal { left, right }), entry_hash) => {
+                self.update_child(depth, path, entry_hash, left, right)
+            }
+        };
+        self.set_node(depth, path, new_node.clone());
+        new_node
+    }
+
+    /// Update the child on `path` of an internal node, collapsing the node if a single leaf remains below it
+    ///
+    /// # Returns
+    /// The new node replacing the internal node
+    fn update_child(
+        &mut self,
+        depth: usize,
+        path: &Hash,
+        entry_hash: Option<Hash>,
+        left: Hash,
+        right: Hash,
+    ) -> Option<Node> {
+        let empty = Hash::from_bytes(LEDGER_TREE_EMPTY_HASH_BYTES);
+        let child = self.update(depth + 1, path, entry_hash);
+        let child_hash = child.as_ref().map_or(empty, Node::hash);
+        let (left, right, sibling_hash) = if ledger_tree_path_bit(path, depth) {
+            (left, child_hash, left)
+        } else {
+            (child_hash, right, right)
+        };
+        match child {
+            // a subtree containing a single leaf is replaced by that leaf
+            Some(leaf @ Node::Leaf { .. }) if sibling_hash == empty => {
+                self.set_node(depth + 1, path, None);
+                Some(leaf)
+            }
+            None if sibling_hash == empty => None,
+            None => {
+                let sibling_path = sibling_path(depth + 1, path);
+                match self.get_node(depth + 1, &sibling_path) {
+                    Some(leaf @ Node::Leaf { .. }) => {
+                        self.set_node(depth + 1, &sibling_path, None);
+                        Some(leaf)
+                    }
+                    _ => Some(Node::Internal { left, right }),
+                }
+            }
+            _ => Some(Node::Internal { left, right }),
+        }
+    }
+}
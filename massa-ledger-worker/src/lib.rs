@@ -27,6 +27,14 @@
 //! Represents a list of changes to ledger entries that
 //! can be modified, combined or applied to the final ledger.
 //!
+//! ## `ledger_tree.rs`
+//! Maintains the sparse Merkle tree committing to the disk ledger entries,
+//! from which ledger entry proofs are produced.
+//!
+//! ## `ledger_archive.rs`
+//! Keeps every version of the disk ledger entries on archival nodes,
+//! so that the ledger can be queried at past final slots.
+//!
 //! ## `bootstrap.rs`
 //! Provides serializable structures and tools for bootstrapping the final ledger.  
 //!
@@ -39,7 +47,9 @@
 #![warn(unused_crate_dependencies)]
 
 mod ledger;
+mod ledger_archive;
 mod ledger_db;
+mod ledger_tree;
 
 pub use ledger::FinalLedger;
 
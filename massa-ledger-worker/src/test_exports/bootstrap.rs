@@ -19,6 +19,8 @@ pub fn create_final_ledger(
         config.thread_count,
         config.max_key_length,
         config.max_ledger_part_size,
+        config.archival,
+        config.tree_hash_activation_period,
     );
     db.load_initial_ledger(initial_ledger);
     FinalLedger {
@@ -5,7 +5,8 @@ use tempfile::TempDir;
 
 use crate::{ledger_db::LedgerDB, FinalLedger};
 use massa_models::config::{
-    LEDGER_PART_SIZE_MESSAGE_BYTES, MAX_DATASTORE_KEY_LENGTH, THREAD_COUNT,
+    LEDGER_PART_SIZE_MESSAGE_BYTES, LEDGER_TREE_HASH_ACTIVATION_PERIOD, MAX_DATASTORE_KEY_LENGTH,
+    THREAD_COUNT,
 };
 
 /// Default value of `FinalLedger` used for tests
@@ -17,6 +18,8 @@ impl Default for FinalLedger {
             THREAD_COUNT,
             MAX_DATASTORE_KEY_LENGTH,
             LEDGER_PART_SIZE_MESSAGE_BYTES,
+            false,
+            LEDGER_TREE_HASH_ACTIVATION_PERIOD,
         );
         FinalLedger {
             config: Default::default(),
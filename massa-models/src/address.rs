@@ -1,5 +1,6 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use crate::amount::Amount;
 use crate::error::ModelsError;
 use crate::prehash::PreHashed;
 use massa_hash::{Hash, HashDeserializer};
@@ -300,3 +301,18 @@ pub struct ExecutionAddressCycleInfo {
     /// number of active rolls the address had at that cycle (if still available)
     pub active_rolls: Option<u64>,
 }
+
+/// Storage used by an address in the ledger and the coins locked to pay for it
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddressStorageReport {
+    /// size of the bytecode in bytes
+    pub bytecode_size: u64,
+    /// number of datastore entries
+    pub datastore_entry_count: u64,
+    /// total size of the datastore keys in bytes
+    pub datastore_key_bytes: u64,
+    /// total size of the datastore values in bytes
+    pub datastore_value_bytes: u64,
+    /// coins locked by the storage costs of the ledger entry, its bytecode and its datastore
+    pub locked_coins: Amount,
+}
@@ -1,20 +1,27 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
-use crate::address::ExecutionAddressCycleInfo;
+use crate::address::{AddressStorageReport, ExecutionAddressCycleInfo};
 use crate::endorsement::{EndorsementId, WrappedEndorsement};
+use crate::error::ModelsError;
+use crate::execution::AddressStateOverride;
+use crate::graph_export::{GraphExport, GraphExportFormat};
 use crate::ledger_models::LedgerData;
 use crate::node::NodeId;
-use crate::operation::{OperationId, WrappedOperation};
+use crate::operation::{AddressRole, OperationId, WrappedOperation};
+use crate::output_event::EventExecutionContext;
+use crate::receipt::OperationReceipt;
 use crate::stats::{ConsensusStats, ExecutionStats, NetworkStats};
 use crate::{
     address::Address, amount::Amount, block::Block, block::BlockId, config::CompactConfig,
     slot::Slot, version::Version,
 };
+use massa_hash::Hash;
 use massa_signature::{PublicKey, Signature};
 use massa_time::MassaTime;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::net::IpAddr;
+use std::str::FromStr;
 use strum::Display;
 
 /// operation input
@@ -120,6 +127,8 @@ pub struct OperationInfo {
     pub is_final: bool,
     /// the operation itself
     pub operation: WrappedOperation,
+    /// receipt of the execution of the operation, if it was executed
+    pub receipt: Option<OperationReceipt>,
 }
 
 impl std::fmt::Display for OperationInfo {
@@ -136,6 +145,10 @@ impl std::fmt::Display for OperationInfo {
             writeln!(f, "\t- {}", block_id)?;
         }
         writeln!(f, "{}", self.operation)?;
+        if let Some(receipt) = &self.receipt {
+            writeln!(f, "Receipt:")?;
+            writeln!(f, "{}", receipt)?;
+        }
         Ok(())
     }
 }
@@ -246,6 +259,39 @@ pub struct AddressInfo {
     pub cycle_infos: Vec<ExecutionAddressCycleInfo>,
 }
 
+/// Storage used by an address at the latest final and active executed slots
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct AddressStorageInfo {
+    /// the address
+    pub address: Address,
+    /// final storage report
+    pub final_storage: AddressStorageReport,
+    /// candidate storage report
+    pub candidate_storage: AddressStorageReport,
+}
+
+impl std::fmt::Display for AddressStorageInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Address {} storage:", self.address)?;
+        for (name, report) in [
+            ("final", &self.final_storage),
+            ("candidate", &self.candidate_storage),
+        ] {
+            writeln!(
+                f,
+                "\t{}: bytecode of {} bytes, {} datastore entries ({} key bytes, {} value bytes), {} locked coins",
+                name,
+                report.bytecode_size,
+                report.datastore_entry_count,
+                report.datastore_key_bytes,
+                report.datastore_value_bytes,
+                report.locked_coins
+            )?;
+        }
+        Ok(())
+    }
+}
+
 impl std::fmt::Display for AddressInfo {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "Address {} (thread {}):", self.address, self.thread)?;
@@ -511,6 +557,27 @@ pub struct TimeInterval {
     pub end: Option<MassaTime>,
 }
 
+/// Block graph export request
+#[derive(Debug, Deserialize, Clone, Copy, Serialize)]
+pub struct GraphExportInput {
+    /// optional start of the exported time window
+    pub start: Option<MassaTime>,
+    /// optional end of the exported time window
+    pub end: Option<MassaTime>,
+    /// export format, JSON by default
+    #[serde(default)]
+    pub format: GraphExportFormat,
+}
+
+/// Block graph export, in the requested format
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub enum GraphExportOutput {
+    /// Graphviz DOT source
+    Dot(String),
+    /// JSON graph
+    Json(GraphExport),
+}
+
 /// Datastore entry query input structure
 #[derive(Debug, Deserialize, Clone, Serialize)]
 pub struct DatastoreEntryInput {
@@ -520,6 +587,148 @@ pub struct DatastoreEntryInput {
     pub key: Vec<u8>,
 }
 
+/// Query input structure about addresses at the output of a past final slot
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct AddressesAtSlotInput {
+    /// final slot at the output of which the addresses are queried
+    pub slot: Slot,
+    /// queried addresses
+    pub addresses: Vec<Address>,
+}
+
+/// Query input structure about datastore entries at the output of a past final slot
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct DatastoreEntriesAtSlotInput {
+    /// final slot at the output of which the entries are queried
+    pub slot: Slot,
+    /// queried datastore entries
+    pub entries: Vec<DatastoreEntryInput>,
+}
+
+/// Paginated datastore keys query input structure
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct DatastoreKeysInput {
+    /// address whose datastore keys are listed
+    pub address: Address,
+    /// prefix that the keys must start with, empty by default
+    #[serde(default)]
+    pub prefix: Vec<u8>,
+    /// optional continuation cursor
+    ///
+    /// Only the keys strictly greater than the cursor are returned.
+    /// To get the next page, set it to the last returned key.
+    pub start_after: Option<Vec<u8>>,
+    /// optional maximum number of keys returned for each view
+    pub limit: Option<usize>,
+}
+
+/// Paginated datastore keys query output structure
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct DatastoreKeysOutput {
+    /// the address
+    pub address: Address,
+    /// final datastore keys, in ascending order
+    pub final_keys: Vec<Vec<u8>>,
+    /// candidate datastore keys, in ascending order
+    pub candidate_keys: Vec<Vec<u8>>,
+}
+
+/// Paginated address history query input structure
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct AddressHistoryInput {
+    /// address whose final operations are listed, most recent first
+    pub address: Address,
+    /// optional continuation cursor
+    ///
+    /// Only the operations strictly before the cursor are returned.
+    /// To get the next page, set it to the position of the last returned operation.
+    pub cursor: Option<AddressActivityCursor>,
+    /// optional maximum number of returned operations, defaults to the maximum set by the node
+    pub limit: Option<usize>,
+}
+
+/// Final operation involving an address, as recorded by the address activity index
+#[derive(Debug, Deserialize, Clone, Serialize, PartialEq, Eq)]
+pub struct AddressActivity {
+    /// id of the operation
+    pub operation_id: OperationId,
+    /// slot at which the operation was executed
+    pub slot: Slot,
+    /// index of the operation in its block
+    pub index_in_block: u64,
+    /// roles of the address in the operation
+    pub roles: Vec<AddressRole>,
+    /// true if the operation executed successfully
+    pub success: bool,
+}
+
+impl std::fmt::Display for AddressActivity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Operation {} at slot {} (index {} in block){}",
+            self.operation_id,
+            self.slot,
+            self.index_in_block,
+            if self.success { "" } else { ", failed" }
+        )?;
+        writeln!(
+            f,
+            "\tRoles: {}",
+            self.roles
+                .iter()
+                .map(|role| role.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )?;
+        Ok(())
+    }
+}
+
+/// position of an operation in the address activity index, used as a continuation cursor
+#[derive(Debug, Deserialize, Clone, Copy, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct AddressActivityCursor {
+    /// slot at which the operation was executed
+    pub slot: Slot,
+    /// index of the operation in its block
+    pub index_in_block: u64,
+}
+
+impl From<&AddressActivity> for AddressActivityCursor {
+    fn from(activity: &AddressActivity) -> Self {
+        AddressActivityCursor {
+            slot: activity.slot,
+            index_in_block: activity.index_in_block,
+        }
+    }
+}
+
+impl FromStr for AddressActivityCursor {
+    type Err = ModelsError;
+
+    /// parse a cursor formatted as `period,thread,index_in_block`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (slot, index_in_block) = s.rsplit_once(',').ok_or_else(|| {
+            ModelsError::DeserializeError("invalid address activity cursor format".to_string())
+        })?;
+        Ok(AddressActivityCursor {
+            slot: slot.parse()?,
+            index_in_block: index_in_block.parse::<u64>().map_err(|_| {
+                ModelsError::DeserializeError("invalid operation index in block".to_string())
+            })?,
+        })
+    }
+}
+
+/// Ledger entry proof query input structure
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct LedgerEntryProofInput {
+    /// associated address of the entry
+    pub address: Address,
+    /// datastore key, `None` to prove the balance of the address
+    pub datastore_key: Option<Vec<u8>>,
+}
+
 /// Datastore entry query output structure
 #[derive(Debug, Deserialize, Clone, Serialize)]
 pub struct DatastoreEntryOutput {
@@ -562,6 +771,93 @@ pub struct EventFilter {
     /// Some(false) means events coming from a succeeded sc execution
     /// None means both
     pub is_error: Option<bool>,
+    /// optional id of the block in which the events were emitted
+    pub block_id: Option<BlockId>,
+    /// optional prefix that the event data must start with
+    pub data_prefix: Option<String>,
+    /// optional string that the event data must contain
+    pub data_contains: Option<String>,
+    /// order in which the events are returned, ascending by default
+    #[serde(default)]
+    pub order: EventOrder,
+    /// optional continuation cursor
+    ///
+    /// Only the events strictly after the cursor in the requested order are returned.
+    /// To get the next page, set it to the position of the last returned event.
+    pub cursor: Option<EventCursor>,
+    /// optional maximum number of returned events
+    pub limit: Option<usize>,
+}
+
+/// order in which SC output events are returned
+#[derive(Default, Debug, Deserialize, Clone, Copy, Serialize, PartialEq, Eq)]
+pub enum EventOrder {
+    /// oldest events first
+    #[default]
+    Ascending,
+    /// most recent events first
+    Descending,
+}
+
+impl FromStr for EventOrder {
+    type Err = ModelsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "asc" | "ascending" => Ok(EventOrder::Ascending),
+            "desc" | "descending" => Ok(EventOrder::Descending),
+            _ => Err(ModelsError::DeserializeError(
+                "invalid event order".to_string(),
+            )),
+        }
+    }
+}
+
+/// position of a SC output event, used as a continuation cursor when paginating events
+#[derive(Debug, Deserialize, Clone, Copy, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct EventCursor {
+    /// slot at which the event was emitted
+    pub slot: Slot,
+    /// index of the event in the slot
+    pub index_in_slot: u64,
+}
+
+impl From<&EventExecutionContext> for EventCursor {
+    fn from(context: &EventExecutionContext) -> Self {
+        EventCursor {
+            slot: context.slot,
+            index_in_slot: context.index_in_slot,
+        }
+    }
+}
+
+impl FromStr for EventCursor {
+    type Err = ModelsError;
+
+    /// parse a cursor formatted as `period,thread,index_in_slot`
+    ///
+    /// ## Example
+    /// ```rust
+    /// # use massa_models::{api::{EventCursor, EventOrder}, slot::Slot};
+    /// # use std::str::FromStr;
+    /// let cursor = EventCursor::from_str("12,3,4").unwrap();
+    /// assert_eq!(cursor, EventCursor { slot: Slot::new(12, 3), index_in_slot: 4 });
+    /// assert!(EventCursor::from_str("12,3").is_err());
+    /// // cursors are ordered by slot, then by index in the slot
+    /// assert!(cursor < EventCursor::from_str("12,4,0").unwrap());
+    /// assert_eq!(EventOrder::from_str("DESC").unwrap(), EventOrder::Descending);
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (slot, index_in_slot) = s.rsplit_once(',').ok_or_else(|| {
+            ModelsError::DeserializeError("invalid event cursor format".to_string())
+        })?;
+        Ok(EventCursor {
+            slot: slot.parse()?,
+            index_in_slot: index_in_slot.parse::<u64>().map_err(|_| {
+                ModelsError::DeserializeError("invalid event index in slot".to_string())
+            })?,
+        })
+    }
 }
 
 /// read only bytecode execution request
@@ -575,6 +871,9 @@ pub struct ReadOnlyBytecodeExecution {
     pub address: Option<Address>,
     /// Operation datastore, optional
     pub operation_datastore: Option<Vec<u8>>,
+    /// ledger state to assume for some addresses during the execution, optional
+    #[serde(default)]
+    pub state_overrides: Vec<AddressStateOverride>,
 }
 
 /// read SC call request
@@ -590,6 +889,39 @@ pub struct ReadOnlyCall {
     pub parameter: Vec<u8>,
     /// caller's address, optional
     pub caller_address: Option<Address>,
+    /// ledger state to assume for some addresses during the execution, optional
+    #[serde(default)]
+    pub state_overrides: Vec<AddressStateOverride>,
+}
+
+/// gas estimation request
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct GasEstimationInput {
+    /// caller's address, optional
+    pub caller_address: Option<Address>,
+    /// execution whose gas is estimated
+    pub target: GasEstimationTarget,
+}
+
+/// execution whose gas is estimated
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub enum GasEstimationTarget {
+    /// execution of a bytecode, as done by an `ExecuteSC` operation
+    ExecuteSC {
+        /// byte code
+        bytecode: Vec<u8>,
+        /// Operation datastore, optional
+        operation_datastore: Option<Vec<u8>>,
+    },
+    /// call of a smart contract function, as done by a `CallSC` operation
+    CallSC {
+        /// target address
+        target_address: Address,
+        /// target function
+        target_function: String,
+        /// function parameter
+        parameter: Vec<u8>,
+    },
 }
 
 /// SCRUD operations
@@ -617,3 +949,62 @@ pub enum ListType {
     /// contains allowed entry
     Whitelist,
 }
+
+/// Stored and recomputed hashes of a final state component
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct FinalStateComponentHash {
+    /// name of the component
+    pub component: String,
+    /// hash maintained incrementally by the final state
+    pub stored_hash: Hash,
+    /// hash recomputed from scratch
+    pub computed_hash: Hash,
+}
+
+impl FinalStateComponentHash {
+    /// Returns true if the stored hash matches the recomputed one
+    pub fn is_consistent(&self) -> bool {
+        self.stored_hash == self.computed_hash
+    }
+}
+
+impl std::fmt::Display for FinalStateComponentHash {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_consistent() {
+            writeln!(f, "{}: OK ({})", self.component, self.stored_hash)
+        } else {
+            writeln!(f, "{}: MISMATCH", self.component)?;
+            writeln!(f, "\tStored hash: {}", self.stored_hash)?;
+            writeln!(f, "\tComputed hash: {}", self.computed_hash)
+        }
+    }
+}
+
+/// Result of a final state integrity check
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct FinalStateIntegrityReport {
+    /// final slot at the output of which the check was made
+    pub slot: Slot,
+    /// hashes of the final state components, in the order they enter the final state hash,
+    /// followed by the final state hash itself
+    pub components: Vec<FinalStateComponentHash>,
+}
+
+impl FinalStateIntegrityReport {
+    /// Returns true if every stored hash matches its recomputed value
+    pub fn is_consistent(&self) -> bool {
+        self.components
+            .iter()
+            .all(FinalStateComponentHash::is_consistent)
+    }
+}
+
+impl std::fmt::Display for FinalStateIntegrityReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Final state integrity at slot {}:", self.slot)?;
+        for component in &self.components {
+            write!(f, "{}", component)?;
+        }
+        Ok(())
+    }
+}
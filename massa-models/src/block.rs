@@ -201,6 +201,21 @@ pub struct FilledBlock {
     pub operations: Vec<(OperationId, Option<WrappedOperation>)>,
 }
 
+/// block that became final, along with the final state hash obtained once its slot was executed as final
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FinalBlock {
+    /// id of the block
+    pub block_id: BlockId,
+    /// slot of the block
+    pub slot: Slot,
+    /// signed header
+    pub header: WrappedHeader,
+    /// operations of the block
+    pub operations: Vec<WrappedOperation>,
+    /// final state hash after the execution of the block's slot
+    pub final_state_hash: Hash,
+}
+
 /// Wrapped Block
 pub type WrappedBlock = Wrapped<Block, BlockId>;
 
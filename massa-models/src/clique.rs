@@ -19,6 +19,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::block::BlockId;
 use crate::prehash::PreHashSet;
+use crate::slot::Slot;
 use std::ops::Bound::{Excluded, Included};
 
 /// Mutually compatible blocks in the graph
@@ -43,6 +44,24 @@ impl Default for Clique {
     }
 }
 
+/// Switch of the blockclique to a different set of candidate blocks
+///
+/// Blocks leaving the blockclique because they became final are not listed as removed.
+/// Blocks appended to the blockclique after the last executed slot roll nothing back and are not reported as a change.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct BlockcliqueChange {
+    /// candidate blocks that left the blockclique, sorted by slot
+    pub removed_blocks: Vec<BlockId>,
+    /// blocks that joined the blockclique, sorted by slot
+    pub added_blocks: Vec<BlockId>,
+    /// slots whose candidate execution was rolled back, sorted
+    ///
+    /// Candidate execution is cancelled from the first changed slot onwards:
+    /// these are all the slots from the first changed slot up to the last executed slot,
+    /// blockless ones included, as they are all executed again.
+    pub rolled_back_slots: Vec<Slot>,
+}
+
 /// Basic serializer for `Clique`
 #[derive(Default)]
 pub struct CliqueSerializer {
@@ -64,7 +64,7 @@ lazy_static::lazy_static! {
         if cfg!(feature = "sandbox") {
             "SAND.0.0"
         } else {
-            "TEST.18.0"
+            "TEST.18.1"
         }
         .parse()
         .unwrap()
@@ -109,6 +109,19 @@ pub const PERIODS_PER_CYCLE: u64 = 128;
 pub const POS_SAVED_CYCLES: usize = 5;
 /// Maximum size batch of data in a part of the ledger
 pub const LEDGER_PART_SIZE_MESSAGE_BYTES: u64 = 1_000_000;
+/// Period from which the ledger hash is computed from the ledger tree root instead of
+/// the XOR of the entry hashes, `None` while this network upgrade is not scheduled
+pub const LEDGER_TREE_HASH_ACTIVATION_PERIOD: Option<u64> = None;
+/// Period from which asynchronous messages can be sent with triggers other than a ledger change
+/// (balance thresholds, datastore prefixes, bytecode changes and their combinations),
+/// `None` while this network upgrade is not scheduled
+pub const ASYNC_MESSAGE_TRIGGERS_ACTIVATION_PERIOD: Option<u64> = if cfg!(feature = "sandbox") {
+    Some(0)
+} else {
+    Some(64_800) // Monday, January 16, 2023 00:00:01 AM UTC
+};
+/// Maximum length of a trigger serialized in the versioned argument of the `send_message` ABI
+pub const MAX_ASYNC_MESSAGE_TRIGGER_LENGTH: usize = 10_000;
 /// Maximum async messages in a batch of the bootstrap of the async pool
 pub const ASYNC_POOL_BOOTSTRAP_PART_SIZE: u64 = 100;
 /// Maximum proof-of-stake deferred credits in a bootstrap batch
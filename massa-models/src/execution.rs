@@ -1,6 +1,9 @@
 use std::{collections::VecDeque, fmt::Display};
 
-use crate::{output_event::SCOutputEvent, slot::Slot};
+use crate::{
+    address::Address, amount::Amount, operation::OperationId, output_event::SCOutputEvent,
+    receipt::OperationReceipt, slot::Slot,
+};
 use serde::{Deserialize, Serialize};
 
 /// The result of the read-only execution.
@@ -23,6 +26,8 @@ pub struct ExecuteReadOnlyResponse {
     pub output_events: VecDeque<SCOutputEvent>,
     /// The gas cost for the execution
     pub gas_cost: u64,
+    /// The call tree of the execution, if call tracing is enabled on the node
+    pub trace: Option<CallTrace>,
 }
 
 impl Display for ExecuteReadOnlyResponse {
@@ -43,6 +48,274 @@ impl Display for ExecuteReadOnlyResponse {
                 writeln!(f, "{}", event)?; // id already displayed in event
             }
         }
+        if let Some(trace) = &self.trace {
+            writeln!(f, "Call trace:")?;
+            write!(f, "{}", trace)?;
+        }
         Ok(())
     }
 }
+
+/// The result of a gas estimation.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum GasEstimation {
+    /// The execution failed even with the maximal read-only gas.
+    Error(String),
+    /// The gas with which the execution succeeds.
+    Ok {
+        /// The minimal `max_gas` with which the execution succeeds.
+        min_gas: u64,
+        /// The `max_gas` to use, the minimal one plus a safety margin.
+        max_gas: u64,
+    },
+}
+
+impl Display for GasEstimation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GasEstimation::Error(e) => {
+                writeln!(f, "Gas estimation failed: {}", e)
+            }
+            GasEstimation::Ok { min_gas, max_gas } => {
+                writeln!(f, "Minimal gas: {}", min_gas)?;
+                writeln!(f, "Estimated max gas: {}", max_gas)
+            }
+        }
+    }
+}
+
+/// The response to a request for the dry-run of an operation.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DryRunOperationResponse {
+    /// The slot at which the operation was dry-run, `None` if the dry-run failed.
+    pub executed_at: Option<Slot>,
+    /// The error that prevented the operation from being executed
+    /// (invalid validity period, fee that cannot be paid...), if any.
+    pub error: Option<String>,
+    /// The receipt of the execution, `None` if the operation could not be executed.
+    /// Contains the gas used, the fee charged and the execution error if any.
+    pub receipt: Option<OperationReceipt>,
+    /// The output events generated by the execution.
+    pub output_events: VecDeque<SCOutputEvent>,
+    /// The state changes caused by the execution, serialized with the `StateChanges` serializer.
+    pub state_changes: Vec<u8>,
+    /// The call tree of the execution, if call tracing is enabled on the node
+    pub trace: Option<CallTrace>,
+}
+
+impl Display for DryRunOperationResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(slot) = self.executed_at {
+            writeln!(f, "Executed at slot: {}", slot)?;
+        }
+        if let Some(err) = &self.error {
+            writeln!(f, "The operation could not be executed: {}", err)?;
+        }
+        if let Some(receipt) = &self.receipt {
+            write!(f, "{}", receipt)?;
+        }
+        if !self.output_events.is_empty() {
+            writeln!(f, "Generated events:",)?;
+            for event in self.output_events.iter() {
+                writeln!(f, "{}", event)?; // id already displayed in event
+            }
+        }
+        writeln!(f, "State changes size: {} bytes", self.state_changes.len())?;
+        if let Some(trace) = &self.trace {
+            writeln!(f, "Call trace:")?;
+            write!(f, "{}", trace)?;
+        }
+        Ok(())
+    }
+}
+
+/// Value to assume for a datastore entry during a read-only execution
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DatastoreEntryOverride {
+    /// key of the datastore entry
+    pub key: Vec<u8>,
+    /// value of the datastore entry, `None` to consider the entry as deleted
+    pub value: Option<Vec<u8>>,
+}
+
+/// Ledger state to assume for an address during a read-only execution.
+/// The address is created if it does not exist.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AddressStateOverride {
+    /// address whose state is overridden
+    pub address: Address,
+    /// balance of the address, kept unchanged if `None`
+    #[serde(default)]
+    pub balance: Option<Amount>,
+    /// bytecode of the address, kept unchanged if `None`
+    #[serde(default)]
+    pub bytecode: Option<Vec<u8>>,
+    /// datastore entries of the address to set or delete
+    #[serde(default)]
+    pub datastore: Vec<DatastoreEntryOverride>,
+}
+
+/// Access to a datastore entry during a traced call
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct DatastoreAccess {
+    /// address owning the datastore entry
+    pub address: Address,
+    /// key of the datastore entry
+    pub key: Vec<u8>,
+}
+
+/// Coin transfer made during a traced call
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct CoinTransfer {
+    /// address the coins were taken from, `None` if they were created
+    pub from: Option<Address>,
+    /// address the coins were given to, `None` if they were destroyed
+    pub to: Option<Address>,
+    /// amount of coins transferred
+    pub amount: Amount,
+}
+
+/// Node of the call tree of a traced execution
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct CallTrace {
+    /// address the call was made on
+    pub target: Address,
+    /// called function, if known.
+    /// The VM does not report the function name of the calls made by a smart contract.
+    pub function: Option<String>,
+    /// coins sent along with the call
+    pub coins: Amount,
+    /// gas consumed by the call, if known.
+    /// The VM only reports the gas consumed at the root of an execution.
+    pub gas_used: Option<u64>,
+    /// datastore entries read during the call
+    pub datastore_reads: Vec<DatastoreAccess>,
+    /// datastore entries written or deleted during the call
+    pub datastore_writes: Vec<DatastoreAccess>,
+    /// coin transfers made during the call
+    pub transfers: Vec<CoinTransfer>,
+    /// calls made by the called function, in order
+    pub calls: Vec<CallTrace>,
+    /// error that made the execution fail, set on the root of a failed execution
+    pub error: Option<String>,
+}
+
+impl CallTrace {
+    /// Create a new call trace node without any recorded activity
+    pub fn new(target: Address, function: Option<String>, coins: Amount) -> Self {
+        CallTrace {
+            target,
+            function,
+            coins,
+            gas_used: None,
+            datastore_reads: Vec::new(),
+            datastore_writes: Vec::new(),
+            transfers: Vec::new(),
+            calls: Vec::new(),
+            error: None,
+        }
+    }
+
+    fn fmt_indented(&self, f: &mut std::fmt::Formatter<'_>, depth: usize) -> std::fmt::Result {
+        let indent = "\t".repeat(depth);
+        writeln!(
+            f,
+            "{}Call to {} function {} with {} coins",
+            indent,
+            self.target,
+            self.function.as_deref().unwrap_or("<unknown>"),
+            self.coins
+        )?;
+        if let Some(gas_used) = self.gas_used {
+            writeln!(f, "{}  Gas used: {}", indent, gas_used)?;
+        }
+        for access in &self.datastore_reads {
+            writeln!(
+                f,
+                "{}  Read {} key {:?}",
+                indent, access.address, access.key
+            )?;
+        }
+        for access in &self.datastore_writes {
+            writeln!(
+                f,
+                "{}  Write {} key {:?}",
+                indent, access.address, access.key
+            )?;
+        }
+        for transfer in &self.transfers {
+            writeln!(
+                f,
+                "{}  Transfer {} coins from {} to {}",
+                indent,
+                transfer.amount,
+                transfer
+                    .from
+                    .map_or_else(|| "none".to_string(), |a| a.to_string()),
+                transfer
+                    .to
+                    .map_or_else(|| "none".to_string(), |a| a.to_string())
+            )?;
+        }
+        if let Some(err) = &self.error {
+            writeln!(f, "{}  Error: {}", indent, err)?;
+        }
+        for call in &self.calls {
+            call.fmt_indented(f, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for CallTrace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_indented(f, 0)
+    }
+}
+
+/// Origin of a traced execution
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub enum TraceOrigin {
+    /// execution of an operation included in a block
+    Operation(OperationId),
+    /// execution of an asynchronous message
+    AsyncMessage {
+        /// sender of the message
+        sender: Address,
+        /// slot at which the message was emitted
+        emission_slot: Slot,
+        /// index of the message emission in its slot
+        emission_index: u64,
+    },
+}
+
+/// Call tree of an operation or asynchronous message execution
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct ExecutionTrace {
+    /// slot at which the execution happened
+    pub slot: Slot,
+    /// what was executed
+    pub origin: TraceOrigin,
+    /// root of the call tree
+    pub root: CallTrace,
+}
+
+impl Display for ExecutionTrace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.origin {
+            TraceOrigin::Operation(id) => {
+                writeln!(f, "Operation {} executed at slot {}", id, self.slot)?
+            }
+            TraceOrigin::AsyncMessage {
+                sender,
+                emission_slot,
+                emission_index,
+            } => writeln!(
+                f,
+                "Asynchronous message sent by {} at slot {} (index {}) executed at slot {}",
+                sender, emission_slot, emission_index, self.slot
+            )?,
+        }
+        write!(f, "{}", self.root)
+    }
+}
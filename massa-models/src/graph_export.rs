@@ -0,0 +1,205 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use crate::{address::Address, block::BlockId, error::ModelsError, slot::Slot};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::str::FromStr;
+
+/// Format of a block graph export
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GraphExportFormat {
+    /// Graphviz DOT
+    Dot,
+    /// JSON graph
+    #[default]
+    Json,
+}
+
+impl FromStr for GraphExportFormat {
+    type Err = ModelsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "dot" => Ok(GraphExportFormat::Dot),
+            "json" => Ok(GraphExportFormat::Json),
+            _ => Err(ModelsError::DeserializeError(format!(
+                "invalid graph export format {}, expected dot or json",
+                s
+            ))),
+        }
+    }
+}
+
+/// Status of a block in the graph
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GraphBlockStatus {
+    /// valid and not yet final
+    Active,
+    /// final and still kept in the graph
+    Final,
+    /// not part of the graph anymore, with the reason why
+    Discarded(String),
+}
+
+/// Block of an exported graph
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GraphBlock {
+    /// id of the block
+    pub id: BlockId,
+    /// slot of the block
+    pub slot: Slot,
+    /// address of the block creator
+    pub creator: Address,
+    /// status of the block
+    pub status: GraphBlockStatus,
+    /// number of endorsements of the block, unknown for discarded blocks
+    pub endorsement_count: Option<u32>,
+    /// fitness of the block, unknown for discarded blocks
+    pub fitness: Option<u64>,
+    /// indexes of the exported cliques the block belongs to
+    pub cliques: Vec<usize>,
+}
+
+/// Link from a block to its parent in a thread
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GraphEdge {
+    /// child block
+    pub source: BlockId,
+    /// parent block
+    pub target: BlockId,
+    /// thread of the parent block
+    pub thread: u8,
+}
+
+/// Maximal clique of compatible blocks of an exported graph
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GraphClique {
+    /// fitness of the clique
+    pub fitness: u64,
+    /// true if the clique is the blockclique
+    pub is_blockclique: bool,
+    /// number of blocks of the clique, including the ones outside of the exported window
+    pub block_count: usize,
+}
+
+/// Block graph export over a time window
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GraphExport {
+    /// blocks of the window, sorted by slot
+    pub blocks: Vec<GraphBlock>,
+    /// parent links of the blocks of the window, possibly to blocks outside of it
+    pub edges: Vec<GraphEdge>,
+    /// maximal cliques of the graph
+    pub cliques: Vec<GraphClique>,
+}
+
+impl GraphExport {
+    /// Renders the graph in the Graphviz DOT language, with one cluster per thread.
+    ///
+    /// Final blocks are blue, blockclique blocks green, other active blocks orange
+    /// and discarded blocks grey.
+    pub fn to_dot(&self) -> String {
+        let blockclique = self.cliques.iter().position(|c| c.is_blockclique);
+        let mut dot =
+            String::from("digraph massa {\n    rankdir=LR;\n    node [shape=box, style=filled];\n");
+        let thread_count = self.blocks.iter().map(|b| b.slot.thread + 1).max();
+        for thread in 0..thread_count.unwrap_or(0) {
+            let _ = writeln!(dot, "    subgraph cluster_thread_{} {{", thread);
+            let _ = writeln!(dot, "        label=\"thread {}\";", thread);
+            for block in self.blocks.iter().filter(|b| b.slot.thread == thread) {
+                let color = match &block.status {
+                    GraphBlockStatus::Final => "lightblue",
+                    GraphBlockStatus::Active
+                        if blockclique.map_or(false, |i| block.cliques.contains(&i)) =>
+                    {
+                        "palegreen"
+                    }
+                    GraphBlockStatus::Active => "orange",
+                    GraphBlockStatus::Discarded(_) => "lightgrey",
+                };
+                let mut label = format!(
+                    "{}\\nperiod {}\\nfitness {}",
+                    block.id,
+                    block.slot.period,
+                    block
+                        .fitness
+                        .map_or_else(|| "?".to_string(), |f| f.to_string())
+                );
+                if let GraphBlockStatus::Discarded(reason) = &block.status {
+                    let _ = write!(label, "\\ndiscarded: {}", reason.replace('"', "'"));
+                }
+                let _ = writeln!(
+                    dot,
+                    "        \"{}\" [label=\"{}\", fillcolor={}];",
+                    block.id, label, color
+                );
+            }
+            dot.push_str("    }\n");
+        }
+        for edge in &self.edges {
+            let _ = writeln!(
+                dot,
+                "    \"{}\" -> \"{}\" [label=\"t{}\"];",
+                edge.source, edge.target, edge.thread
+            );
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_hash::Hash;
+
+    #[test]
+    fn test_graph_export_to_dot() {
+        let parent = BlockId(Hash::compute_from(b"parent"));
+        let child = BlockId(Hash::compute_from(b"child"));
+        let creator = Address(Hash::compute_from(b"creator"));
+        let export = GraphExport {
+            blocks: vec![
+                GraphBlock {
+                    id: parent,
+                    slot: Slot::new(1, 0),
+                    creator,
+                    status: GraphBlockStatus::Final,
+                    endorsement_count: Some(0),
+                    fitness: Some(1),
+                    cliques: vec![],
+                },
+                GraphBlock {
+                    id: child,
+                    slot: Slot::new(1, 1),
+                    creator,
+                    status: GraphBlockStatus::Active,
+                    endorsement_count: Some(2),
+                    fitness: Some(3),
+                    cliques: vec![0],
+                },
+            ],
+            edges: vec![GraphEdge {
+                source: child,
+                target: parent,
+                thread: 0,
+            }],
+            cliques: vec![GraphClique {
+                fitness: 3,
+                is_blockclique: true,
+                block_count: 1,
+            }],
+        };
+        let dot = export.to_dot();
+        assert!(dot.starts_with("digraph massa {"));
+        assert!(dot.contains("subgraph cluster_thread_0"));
+        assert!(dot.contains("subgraph cluster_thread_1"));
+        assert!(dot.contains(&format!("\"{}\" -> \"{}\" [label=\"t0\"];", child, parent)));
+        assert!(dot.contains("fillcolor=lightblue"));
+        assert!(dot.contains("fillcolor=palegreen"));
+        assert_eq!(
+            "DOT".parse::<GraphExportFormat>().unwrap(),
+            GraphExportFormat::Dot
+        );
+    }
+}
@@ -33,6 +33,8 @@ pub mod endorsement;
 pub mod error;
 /// execution related structures
 pub mod execution;
+/// block graph export for visualization
+pub mod graph_export;
 /// ledger related structures
 pub mod ledger_models;
 /// node related structure
@@ -43,6 +45,8 @@ pub mod operation;
 pub mod output_event;
 /// pre-hashed trait, for hash less hashmap/set
 pub mod prehash;
+/// execution receipts of operations
+pub mod receipt;
 /// rolls
 pub mod rolls;
 /// serialization
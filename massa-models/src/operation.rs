@@ -1,7 +1,7 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use crate::datastore::{Datastore, DatastoreDeserializer, DatastoreSerializer};
-use crate::prehash::{PreHashSet, PreHashed};
+use crate::prehash::{PreHashMap, PreHashSet, PreHashed};
 use crate::wrapped::{Id, Wrapped, WrappedContent, WrappedDeserializer, WrappedSerializer};
 use crate::{
     address::{Address, AddressDeserializer},
@@ -463,6 +463,33 @@ pub enum OperationType {
     },
 }
 
+/// Role of an address in an operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AddressRole {
+    /// the address created the operation
+    Creator,
+    /// the address receives the coins of a transaction
+    TransactionRecipient,
+    /// the address is the smart contract called by the operation
+    CallTarget,
+    /// the address buys rolls
+    RollBuyer,
+    /// the address sells rolls
+    RollSeller,
+}
+
+impl std::fmt::Display for AddressRole {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AddressRole::Creator => write!(f, "creator"),
+            AddressRole::TransactionRecipient => write!(f, "transaction recipient"),
+            AddressRole::CallTarget => write!(f, "call target"),
+            AddressRole::RollBuyer => write!(f, "roll buyer"),
+            AddressRole::RollSeller => write!(f, "roll seller"),
+        }
+    }
+}
+
 impl std::fmt::Display for OperationType {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -812,6 +839,26 @@ impl WrappedOperation {
         }
     }
 
+    /// get the roles of every address involved in this operation
+    pub fn get_address_roles(&self) -> PreHashMap<Address, Vec<AddressRole>> {
+        let mut res = PreHashMap::<Address, Vec<AddressRole>>::default();
+        let creator_address = Address::from_public_key(&self.creator_public_key);
+        res.entry(creator_address)
+            .or_default()
+            .push(AddressRole::Creator);
+        let (address, role) = match &self.content.op {
+            OperationType::Transaction {
+                recipient_address, ..
+            } => (*recipient_address, AddressRole::TransactionRecipient),
+            OperationType::RollBuy { .. } => (creator_address, AddressRole::RollBuyer),
+            OperationType::RollSell { .. } => (creator_address, AddressRole::RollSeller),
+            OperationType::ExecuteSC { .. } => return res,
+            OperationType::CallSC { target_addr, .. } => (*target_addr, AddressRole::CallTarget),
+        };
+        res.entry(address).or_default().push(role);
+        res
+    }
+
     /// get the addresses that are involved in this operation from a ledger point of view
     pub fn get_ledger_involved_addresses(&self) -> PreHashSet<Address> {
         let mut res = PreHashSet::<Address>::default();
@@ -2,7 +2,7 @@ use crate::{address::Address, block::BlockId, operation::OperationId, slot::Slot
 use serde::{Deserialize, Serialize};
 use std::{collections::VecDeque, fmt::Display};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 /// By product of a byte code execution
 pub struct SCOutputEvent {
     /// context generated by the execution context
@@ -19,7 +19,7 @@ impl Display for SCOutputEvent {
 }
 
 /// Context of the event (not generated by the user)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct EventExecutionContext {
     /// when was it generated
     pub slot: Slot,
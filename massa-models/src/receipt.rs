@@ -0,0 +1,69 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use crate::{amount::Amount, block::BlockId, operation::OperationId, slot::Slot};
+use displaydoc::Display;
+use serde::{Deserialize, Serialize};
+
+/// Reason why the execution of an included operation failed
+#[derive(Display, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OperationExecutionError {
+    /// roll buy failed: {0}
+    RollBuy(String),
+    /// roll sell failed: {0}
+    RollSell(String),
+    /// transaction failed: {0}
+    Transaction(String),
+    /// coins of the smart contract call could not be transferred: {0}
+    CallCoins(String),
+    /// smart contract bytecode execution failed: {0}
+    Bytecode(String),
+    /// unexpected execution error: {0}
+    Internal(String),
+}
+
+/// Result of the execution of an operation included in a block
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OperationReceipt {
+    /// id of the executed operation
+    pub operation_id: OperationId,
+    /// slot at which the operation was executed
+    pub slot: Slot,
+    /// block in which the operation was included
+    pub block_id: Option<BlockId>,
+    /// true if the execution of the operation is final
+    pub is_final: bool,
+    /// true if the operation executed successfully
+    pub success: bool,
+    /// error that made the execution fail, if any
+    pub error: Option<OperationExecutionError>,
+    /// gas used by the smart contract execution, as reported by the VM.
+    /// `None` if the VM did not run, or if it failed since it does not report gas on failure
+    pub gas_used: Option<u64>,
+    /// fee paid by the operation sender
+    pub fee: Amount,
+    /// coins transferred or spent by the operation, zero if it failed
+    pub coins: Amount,
+}
+
+impl std::fmt::Display for OperationReceipt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Operation {} executed at slot {}",
+            self.operation_id, self.slot
+        )?;
+        if let Some(id) = self.block_id {
+            writeln!(f, "Block id: {}", id)?;
+        }
+        writeln!(f, "{}", if self.is_final { "Final" } else { "Candidate" })?;
+        match &self.error {
+            None => writeln!(f, "Success")?,
+            Some(err) => writeln!(f, "Failure: {}", err)?,
+        }
+        if let Some(gas_used) = self.gas_used {
+            writeln!(f, "Gas used: {}", gas_used)?;
+        }
+        writeln!(f, "Fee: {}", self.fee)?;
+        writeln!(f, "Coins: {}", self.coins)
+    }
+}
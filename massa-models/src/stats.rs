@@ -18,6 +18,10 @@ pub struct ExecutionStats {
     pub final_executed_operations_count: usize,
     /// active execution cursor slot
     pub active_cursor: Slot,
+    /// number of bytecodes found in the bytecode cache since the node started
+    pub bytecode_cache_hits: u64,
+    /// number of bytecodes not found in the bytecode cache since the node started
+    pub bytecode_cache_misses: u64,
 }
 
 impl std::fmt::Display for ExecutionStats {
@@ -44,6 +48,16 @@ impl std::fmt::Display for ExecutionStats {
             self.final_executed_operations_count
         )?;
         writeln!(f, "\tActive cursor: {}", self.active_cursor)?;
+        writeln!(
+            f,
+            "\tBytecode cache hits since the node started: {}",
+            self.bytecode_cache_hits
+        )?;
+        writeln!(
+            f,
+            "\tBytecode cache misses since the node started: {}",
+            self.bytecode_cache_misses
+        )?;
         Ok(())
     }
 }
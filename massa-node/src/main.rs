@@ -12,24 +12,30 @@ use massa_api::{APIConfig, ApiServer, ApiV2, Private, Public, RpcServer, StopHan
 use massa_async_pool::AsyncPoolConfig;
 use massa_bootstrap::{get_state, start_bootstrap_server, BootstrapConfig, BootstrapManager};
 use massa_consensus_exports::events::ConsensusEvent;
+use massa_consensus_exports::persisted_graph::{
+    PersistedGraph, PersistedGraphDeserializer, RestoredGraph,
+};
 use massa_consensus_exports::{ConsensusChannels, ConsensusConfig, ConsensusManager};
 use massa_consensus_worker::start_consensus_worker;
 use massa_executed_ops::ExecutedOpsConfig;
-use massa_execution_exports::{ExecutionConfig, ExecutionManager, GasCosts, StorageCostsConstants};
-use massa_execution_worker::start_execution_worker;
+use massa_execution_exports::{
+    ExecutionChannels, ExecutionConfig, ExecutionManager, GasCosts, StorageCostsConstants,
+};
+use massa_execution_worker::{replay_final_slots, start_execution_worker};
 use massa_factory_exports::{FactoryChannels, FactoryConfig, FactoryManager};
 use massa_factory_worker::start_factory;
-use massa_final_state::{FinalState, FinalStateConfig};
+use massa_final_state::{FinalState, FinalStateConfig, FinalStateSnapshotDeserializer};
 use massa_ledger_exports::LedgerConfig;
 use massa_ledger_worker::FinalLedger;
 use massa_logging::massa_trace;
 use massa_models::address::Address;
 use massa_models::config::constants::{
-    ASYNC_POOL_BOOTSTRAP_PART_SIZE, BLOCK_REWARD, BOOTSTRAP_RANDOMNESS_SIZE_BYTES, CHANNEL_SIZE,
-    DEFERRED_CREDITS_BOOTSTRAP_PART_SIZE, DELTA_F0, ENDORSEMENT_COUNT, END_TIMESTAMP,
-    EXECUTED_OPS_BOOTSTRAP_PART_SIZE, GENESIS_KEY, GENESIS_TIMESTAMP, INITIAL_DRAW_SEED,
-    LEDGER_COST_PER_BYTE, LEDGER_ENTRY_BASE_SIZE, LEDGER_ENTRY_DATASTORE_BASE_SIZE,
-    LEDGER_PART_SIZE_MESSAGE_BYTES, MAX_ADVERTISE_LENGTH, MAX_ASK_BLOCKS_PER_MESSAGE,
+    ASYNC_MESSAGE_TRIGGERS_ACTIVATION_PERIOD, ASYNC_POOL_BOOTSTRAP_PART_SIZE, BLOCK_REWARD,
+    BOOTSTRAP_RANDOMNESS_SIZE_BYTES, CHANNEL_SIZE, DEFERRED_CREDITS_BOOTSTRAP_PART_SIZE, DELTA_F0,
+    ENDORSEMENT_COUNT, END_TIMESTAMP, EXECUTED_OPS_BOOTSTRAP_PART_SIZE, GENESIS_KEY,
+    GENESIS_TIMESTAMP, INITIAL_DRAW_SEED, LEDGER_COST_PER_BYTE, LEDGER_ENTRY_BASE_SIZE,
+    LEDGER_ENTRY_DATASTORE_BASE_SIZE, LEDGER_PART_SIZE_MESSAGE_BYTES,
+    LEDGER_TREE_HASH_ACTIVATION_PERIOD, MAX_ADVERTISE_LENGTH, MAX_ASK_BLOCKS_PER_MESSAGE,
     MAX_ASYNC_GAS, MAX_ASYNC_MESSAGE_DATA, MAX_ASYNC_POOL_LENGTH, MAX_BLOCK_SIZE,
     MAX_BOOTSTRAP_ASYNC_POOL_CHANGES, MAX_BOOTSTRAP_BLOCKS, MAX_BOOTSTRAP_ERROR_LENGTH,
     MAX_BOOTSTRAP_FINAL_STATE_PARTS_SIZE, MAX_BOOTSTRAP_MESSAGE_SIZE, MAX_BYTECODE_LENGTH,
@@ -45,17 +51,20 @@ use massa_models::config::constants::{
     PROTOCOL_EVENT_CHANNEL_SIZE, ROLL_PRICE, T0, THREAD_COUNT, VERSION,
 };
 use massa_models::config::CONSENSUS_BOOTSTRAP_PART_SIZE;
+use massa_models::slot::Slot;
+use massa_models::timeslots::get_block_slot_timestamp;
 use massa_network_exports::{Establisher, NetworkConfig, NetworkManager};
 use massa_network_worker::start_network_controller;
 use massa_pool_exports::{PoolConfig, PoolManager};
 use massa_pool_worker::start_pool_controller;
-use massa_pos_exports::{PoSConfig, SelectorConfig, SelectorManager};
+use massa_pos_exports::{PoSConfig, SelectorConfig, SelectorController, SelectorManager};
 use massa_pos_worker::start_selector_worker;
 use massa_protocol_exports::{
     ProtocolCommand, ProtocolCommandSender, ProtocolConfig, ProtocolManager, ProtocolReceivers,
     ProtocolSenders,
 };
 use massa_protocol_worker::start_protocol_controller;
+use massa_serialization::{DeserializeError, Deserializer};
 use massa_storage::Storage;
 use massa_time::MassaTime;
 use massa_wallet::Wallet;
@@ -74,6 +83,7 @@ mod settings;
 
 async fn launch(
     node_wallet: Arc<RwLock<Wallet>>,
+    snapshot_path: Option<PathBuf>,
 ) -> (
     Receiver<ConsensusEvent>,
     Option<BootstrapManager>,
@@ -100,71 +110,42 @@ async fn launch(
     let shared_storage: Storage = Storage::create_root();
 
     // init final state
-    let ledger_config = LedgerConfig {
-        thread_count: THREAD_COUNT,
-        initial_ledger_path: SETTINGS.ledger.initial_ledger_path.clone(),
-        disk_ledger_path: SETTINGS.ledger.disk_ledger_path.clone(),
-        max_key_length: MAX_DATASTORE_KEY_LENGTH,
-        max_ledger_part_size: LEDGER_PART_SIZE_MESSAGE_BYTES,
-    };
-    let async_pool_config = AsyncPoolConfig {
-        max_length: MAX_ASYNC_POOL_LENGTH,
-        thread_count: THREAD_COUNT,
-        bootstrap_part_size: ASYNC_POOL_BOOTSTRAP_PART_SIZE,
-        max_async_message_data: MAX_ASYNC_MESSAGE_DATA,
-    };
-    let pos_config = PoSConfig {
-        periods_per_cycle: PERIODS_PER_CYCLE,
-        thread_count: THREAD_COUNT,
-        cycle_history_length: POS_SAVED_CYCLES,
-        credits_bootstrap_part_size: DEFERRED_CREDITS_BOOTSTRAP_PART_SIZE,
-    };
-    let executed_ops_config = ExecutedOpsConfig {
-        thread_count: THREAD_COUNT,
-        bootstrap_part_size: EXECUTED_OPS_BOOTSTRAP_PART_SIZE,
-    };
-    let final_state_config = FinalStateConfig {
-        ledger_config: ledger_config.clone(),
-        async_pool_config,
-        pos_config,
-        executed_ops_config,
-        final_history_length: SETTINGS.ledger.final_history_length,
-        thread_count: THREAD_COUNT,
-        periods_per_cycle: PERIODS_PER_CYCLE,
-        initial_seed_string: INITIAL_DRAW_SEED.into(),
-        initial_rolls_path: SETTINGS.selector.initial_rolls_path.clone(),
-    };
-
-    // Remove current disk ledger if there is one
-    // NOTE: this is temporary, since we cannot currently handle bootstrap from remaining ledger
-    if SETTINGS.ledger.disk_ledger_path.exists() {
-        std::fs::remove_dir_all(SETTINGS.ledger.disk_ledger_path.clone())
-            .expect("disk ledger delete failed");
-    }
-
-    // Create final ledger
-    let ledger = FinalLedger::new(ledger_config.clone());
+    let (final_state, selector_manager, selector_controller) = create_final_state(
+        SETTINGS.ledger.disk_ledger_path.clone(),
+        SETTINGS.ledger.final_state_path.clone(),
+        SETTINGS.ledger.archival,
+    );
 
-    // launch selector worker
-    let (selector_manager, selector_controller) = start_selector_worker(SelectorConfig {
-        max_draw_cache: SETTINGS.selector.max_draw_cache,
-        channel_size: CHANNEL_SIZE,
-        thread_count: THREAD_COUNT,
-        endorsement_count: ENDORSEMENT_COUNT,
-        periods_per_cycle: PERIODS_PER_CYCLE,
-        genesis_address: Address::from_public_key(&GENESIS_KEY.get_public_key()),
-    })
-    .expect("could not start selector worker");
+    // Start from the given snapshot file, otherwise resume from the final state
+    // persisted by a previous run if there is a valid one
+    let final_state_restored = if let Some(path) = snapshot_path {
+        load_final_state_snapshot(&mut final_state.write(), &path);
+        true
+    } else {
+        let mut final_state_guard = final_state.write();
+        match final_state_guard.restore_from_disk() {
+            Ok(true) => true,
+            Ok(false) => {
+                // clear any remaining disk ledger that is not attached to a persisted final state
+                final_state_guard.reset();
+                false
+            }
+            Err(err) => {
+                warn!("could not restore the final state from disk: {}", err);
+                final_state_guard.reset();
+                false
+            }
+        }
+    };
 
-    // Create final state
-    let final_state = Arc::new(parking_lot::RwLock::new(
-        FinalState::new(
-            final_state_config,
-            Box::new(ledger),
-            selector_controller.clone(),
-        )
-        .expect("could not init final state"),
-    ));
+    // Resume from the block graph persisted by a previous run if it matches the restored final state.
+    // The node then only gets the peers from bootstrap and syncs the missing blocks from them.
+    let restored_graph = if final_state_restored && SETTINGS.consensus.persist_graph {
+        let final_slot = final_state.read().slot;
+        load_persisted_graph(final_slot)
+    } else {
+        None
+    };
 
     // interrupt signal listener
     let stop_signal = signal::ctrl_c();
@@ -219,7 +200,10 @@ async fn launch(
         consensus_bootstrap_part_size: CONSENSUS_BOOTSTRAP_PART_SIZE,
     };
 
-    // bootstrap
+    // bootstrap, only checking the clock and version and getting the peers when resuming from the persisted block graph
+    if restored_graph.is_some() {
+        info!("Resuming from the persisted block graph, bootstrapping the peers only");
+    }
     let bootstrap_state = tokio::select! {
         _ = &mut stop_signal => {
             info!("interrupt signal received in bootstrap loop");
@@ -232,12 +216,17 @@ async fn launch(
             *VERSION,
             *GENESIS_TIMESTAMP,
             *END_TIMESTAMP,
+            final_state_restored,
+            restored_graph.is_some(),
         ) => match res {
             Ok(vals) => vals,
             Err(err) => panic!("critical error detected in the bootstrap process: {}", err)
         }
     };
 
+    // persist the bootstrapped final state, later slots are persisted on finalization
+    final_state.write().write_to_disk();
+
     let network_config: NetworkConfig = NetworkConfig {
         bind: SETTINGS.network.bind,
         routable_ip: SETTINGS.network.routable_ip,
@@ -298,48 +287,17 @@ async fn launch(
         .compute_initial_draws()
         .expect("could not compute initial draws"); // TODO: this might just mean a bad bootstrap, no need to panic, just reboot
 
-    // Storage costs constants
-    let storage_costs_constants = StorageCostsConstants {
-        ledger_cost_per_byte: LEDGER_COST_PER_BYTE,
-        ledger_entry_base_cost: LEDGER_COST_PER_BYTE
-            .checked_mul_u64(LEDGER_ENTRY_BASE_SIZE as u64)
-            .expect("Overflow when creating constant ledger_entry_base_cost"),
-        ledger_entry_datastore_base_cost: LEDGER_COST_PER_BYTE
-            .checked_mul_u64(LEDGER_ENTRY_DATASTORE_BASE_SIZE as u64)
-            .expect("Overflow when creating constant ledger_entry_datastore_base_size"),
-    };
     // launch execution module
-    let execution_config = ExecutionConfig {
-        max_final_events: SETTINGS.execution.max_final_events,
-        readonly_queue_length: SETTINGS.execution.readonly_queue_length,
-        cursor_delay: SETTINGS.execution.cursor_delay,
-        max_async_gas: MAX_ASYNC_GAS,
-        max_gas_per_block: MAX_GAS_PER_BLOCK,
-        roll_price: ROLL_PRICE,
-        thread_count: THREAD_COUNT,
-        t0: T0,
-        genesis_timestamp: *GENESIS_TIMESTAMP,
-        block_reward: BLOCK_REWARD,
-        endorsement_count: ENDORSEMENT_COUNT as u64,
-        operation_validity_period: OPERATION_VALIDITY_PERIODS,
-        periods_per_cycle: PERIODS_PER_CYCLE,
-        stats_time_window_duration: SETTINGS.execution.stats_time_window_duration,
-        max_miss_ratio: *POS_MISS_RATE_DEACTIVATION_THRESHOLD,
-        max_datastore_key_length: MAX_DATASTORE_KEY_LENGTH,
-        max_bytecode_size: MAX_BYTECODE_LENGTH,
-        max_datastore_value_size: MAX_DATASTORE_VALUE_LENGTH,
-        storage_costs_constants,
-        max_read_only_gas: SETTINGS.execution.max_read_only_gas,
-        gas_costs: GasCosts::new(
-            SETTINGS.execution.abi_gas_costs_file.clone(),
-            SETTINGS.execution.wasm_gas_costs_file.clone(),
-        )
-        .expect("Failed to load gas costs"),
+    let execution_config = create_execution_config();
+    let execution_channels = ExecutionChannels {
+        sc_event_sender: broadcast::channel(execution_config.broadcast_sc_events_capacity).0,
+        final_block_sender: broadcast::channel(execution_config.broadcast_final_blocks_capacity).0,
     };
     let (execution_manager, execution_controller) = start_execution_worker(
         execution_config,
         final_state.clone(),
         selector_controller.clone(),
+        execution_channels.clone(),
     );
 
     // launch pool controller
@@ -386,6 +344,13 @@ async fn launch(
         broadcast_blocks_headers_capacity: SETTINGS.consensus.broadcast_blocks_headers_capacity,
         broadcast_blocks_capacity: SETTINGS.consensus.broadcast_blocks_capacity,
         broadcast_filled_blocks_capacity: SETTINGS.consensus.broadcast_filled_blocks_capacity,
+        broadcast_blockclique_changes_capacity: SETTINGS
+            .consensus
+            .broadcast_blockclique_changes_capacity,
+        graph_store_path: SETTINGS
+            .consensus
+            .persist_graph
+            .then(|| SETTINGS.consensus.graph_store_path.clone()),
     };
 
     let (consensus_event_sender, consensus_event_receiver) =
@@ -401,15 +366,52 @@ async fn launch(
         block_sender: broadcast::channel(consensus_config.broadcast_blocks_capacity).0,
         filled_block_sender: broadcast::channel(consensus_config.broadcast_filled_blocks_capacity)
             .0,
+        blockclique_change_sender: broadcast::channel(
+            consensus_config.broadcast_blockclique_changes_capacity,
+        )
+        .0,
+    };
+
+    // the storage of the restored operations is kept until the restored blocks claimed them
+    let (init_graph, candidate_blocks, restored_op_storage) = match restored_graph {
+        Some(RestoredGraph {
+            graph,
+            candidate_blocks,
+            operations,
+        }) => {
+            let mut op_storage = shared_storage.clone_without_refs();
+            op_storage.store_operations(operations);
+            (Some(graph), candidate_blocks, op_storage)
+        }
+        None => (
+            bootstrap_state.graph,
+            Vec::new(),
+            shared_storage.clone_without_refs(),
+        ),
     };
 
     let (consensus_controller, consensus_manager) = start_consensus_worker(
         consensus_config,
         consensus_channels.clone(),
-        bootstrap_state.graph,
+        init_graph,
         shared_storage.clone(),
     );
 
+    // process the restored non-final blocks again, the ones with missing operations are synced from peers
+    for block in candidate_blocks {
+        let mut block_storage = restored_op_storage.clone_without_refs();
+        let claimed_ops =
+            block_storage.claim_operation_refs(&block.content.operations.iter().copied().collect());
+        if claimed_ops.len() != block.content.operations.len() {
+            continue;
+        }
+        block_storage.store_endorsements(block.content.header.content.endorsements.clone());
+        let (block_id, slot) = (block.id, block.content.header.content.slot);
+        block_storage.store_block(block);
+        consensus_controller.register_block(block_id, slot, block_storage, false);
+    }
+    drop(restored_op_storage);
+
     // launch protocol controller
     let protocol_config = ProtocolConfig {
         thread_count: THREAD_COUNT,
@@ -525,12 +527,20 @@ async fn launch(
         genesis_timestamp: *GENESIS_TIMESTAMP,
         t0: T0,
         periods_per_cycle: PERIODS_PER_CYCLE,
+        max_read_only_gas: SETTINGS.execution.max_read_only_gas,
+        gas_estimation_margin_percent: SETTINGS.api.gas_estimation_margin_percent,
+        max_gas_estimations_per_request: SETTINGS.api.max_gas_estimations_per_request,
+        max_gas_estimation_iterations: SETTINGS.api.max_gas_estimation_iterations,
+        max_datastore_keys_per_request: SETTINGS.api.max_datastore_keys_per_request,
+        max_events_per_request: SETTINGS.api.max_events_per_request,
+        max_address_history_per_request: SETTINGS.api.max_address_history_per_request,
     };
 
     // spawn Massa API
     let api = API::<ApiV2>::new(
         consensus_channels,
         protocol_senders,
+        execution_channels,
         api_config.clone(),
         *VERSION,
     );
@@ -546,9 +556,11 @@ async fn launch(
     // spawn private API
     let (api_private, api_private_stop_rx) = API::<Private>::new(
         network_command_sender.clone(),
+        consensus_controller.clone(),
         execution_controller.clone(),
         api_config.clone(),
         node_wallet,
+        final_state,
     );
     let api_private_handle = api_private
         .serve(&SETTINGS.api.bind_private, &api_config)
@@ -703,6 +715,275 @@ struct Args {
     /// Wallet password
     #[structopt(short = "p", long = "pwd")]
     password: Option<String>,
+    /// Final state snapshot file to start from
+    #[structopt(long = "snapshot", parse(from_os_str))]
+    snapshot: Option<PathBuf>,
+    /// Final slot records to replay offline from the snapshot, instead of running the node
+    #[structopt(long = "replay", parse(from_os_str))]
+    replay: Option<PathBuf>,
+    /// Last slot to replay, formatted as `period,thread`
+    #[structopt(long = "replay-until")]
+    replay_until: Option<Slot>,
+}
+
+/// Create the final state and the selector worker it feeds the draws to
+fn create_final_state(
+    disk_ledger_path: PathBuf,
+    final_state_path: PathBuf,
+    archival: bool,
+) -> (
+    Arc<RwLock<FinalState>>,
+    Box<dyn SelectorManager>,
+    Box<dyn SelectorController>,
+) {
+    let ledger_config = LedgerConfig {
+        thread_count: THREAD_COUNT,
+        initial_ledger_path: SETTINGS.ledger.initial_ledger_path.clone(),
+        disk_ledger_path,
+        max_key_length: MAX_DATASTORE_KEY_LENGTH,
+        max_ledger_part_size: LEDGER_PART_SIZE_MESSAGE_BYTES,
+        archival,
+        tree_hash_activation_period: LEDGER_TREE_HASH_ACTIVATION_PERIOD,
+    };
+    let async_pool_config = AsyncPoolConfig {
+        max_length: MAX_ASYNC_POOL_LENGTH,
+        thread_count: THREAD_COUNT,
+        bootstrap_part_size: ASYNC_POOL_BOOTSTRAP_PART_SIZE,
+        max_async_message_data: MAX_ASYNC_MESSAGE_DATA,
+    };
+    let pos_config = PoSConfig {
+        periods_per_cycle: PERIODS_PER_CYCLE,
+        thread_count: THREAD_COUNT,
+        cycle_history_length: POS_SAVED_CYCLES,
+        credits_bootstrap_part_size: DEFERRED_CREDITS_BOOTSTRAP_PART_SIZE,
+    };
+    let executed_ops_config = ExecutedOpsConfig {
+        thread_count: THREAD_COUNT,
+        bootstrap_part_size: EXECUTED_OPS_BOOTSTRAP_PART_SIZE,
+    };
+    let final_state_config = FinalStateConfig {
+        ledger_config: ledger_config.clone(),
+        async_pool_config,
+        pos_config,
+        executed_ops_config,
+        final_history_length: SETTINGS.ledger.final_history_length,
+        thread_count: THREAD_COUNT,
+        periods_per_cycle: PERIODS_PER_CYCLE,
+        initial_seed_string: INITIAL_DRAW_SEED.into(),
+        initial_rolls_path: SETTINGS.selector.initial_rolls_path.clone(),
+        final_state_path,
+    };
+
+    // Create final ledger
+    let ledger = FinalLedger::new(ledger_config.clone());
+
+    // launch selector worker
+    let (selector_manager, selector_controller) = start_selector_worker(SelectorConfig {
+        max_draw_cache: SETTINGS.selector.max_draw_cache,
+        channel_size: CHANNEL_SIZE,
+        thread_count: THREAD_COUNT,
+        endorsement_count: ENDORSEMENT_COUNT,
+        periods_per_cycle: PERIODS_PER_CYCLE,
+        genesis_address: Address::from_public_key(&GENESIS_KEY.get_public_key()),
+    })
+    .expect("could not start selector worker");
+
+    // Create final state
+    let final_state = Arc::new(parking_lot::RwLock::new(
+        FinalState::new(
+            final_state_config,
+            Box::new(ledger),
+            selector_controller.clone(),
+        )
+        .expect("could not init final state"),
+    ));
+
+    (final_state, selector_manager, selector_controller)
+}
+
+/// Load the block graph persisted by a previous run if it covers the restored final state.
+/// Peers only keep `force_keep_final_periods` final periods in their graph, so an older graph
+/// is not used because the blocks missing since then could not be synced anymore.
+fn load_persisted_graph(final_slot: Slot) -> Option<RestoredGraph> {
+    let final_timestamp =
+        get_block_slot_timestamp(THREAD_COUNT, T0, *GENESIS_TIMESTAMP, final_slot).ok()?;
+    let max_age = T0
+        .checked_mul(SETTINGS.consensus.force_keep_final_periods)
+        .ok()?;
+    if MassaTime::now().ok()?.saturating_sub(final_timestamp) > max_age {
+        info!("The persisted block graph is too old to resume from");
+        return None;
+    }
+
+    let deserializer = PersistedGraphDeserializer::new(
+        THREAD_COUNT,
+        ENDORSEMENT_COUNT,
+        MAX_OPERATIONS_PER_BLOCK,
+        MAX_DATASTORE_VALUE_LENGTH,
+        MAX_FUNCTION_NAME_LENGTH,
+        MAX_PARAMETERS_SIZE,
+        MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+        MAX_OPERATION_DATASTORE_KEY_LENGTH,
+        MAX_OPERATION_DATASTORE_VALUE_LENGTH,
+    );
+    match PersistedGraph::read_from_disk(&SETTINGS.consensus.graph_store_path, &deserializer) {
+        Ok(Some(persisted_graph)) => {
+            let restored_graph = persisted_graph.restore(final_slot, THREAD_COUNT);
+            if restored_graph.is_none() {
+                info!("The persisted block graph is behind the final state");
+            }
+            restored_graph
+        }
+        Ok(None) => None,
+        Err(err) => {
+            warn!("could not read the persisted block graph: {}", err);
+            None
+        }
+    }
+}
+
+/// Build the execution configuration from the node settings
+fn create_execution_config() -> ExecutionConfig {
+    // Storage costs constants
+    let storage_costs_constants = StorageCostsConstants {
+        ledger_cost_per_byte: LEDGER_COST_PER_BYTE,
+        ledger_entry_base_cost: LEDGER_COST_PER_BYTE
+            .checked_mul_u64(LEDGER_ENTRY_BASE_SIZE as u64)
+            .expect("Overflow when creating constant ledger_entry_base_cost"),
+        ledger_entry_datastore_base_cost: LEDGER_COST_PER_BYTE
+            .checked_mul_u64(LEDGER_ENTRY_DATASTORE_BASE_SIZE as u64)
+            .expect("Overflow when creating constant ledger_entry_datastore_base_size"),
+    };
+    ExecutionConfig {
+        event_store_path: SETTINGS.execution.event_store_path.clone(),
+        event_retention_periods: SETTINGS.execution.event_retention_periods,
+        receipt_store_path: SETTINGS.execution.receipt_store_path.clone(),
+        receipt_retention_periods: SETTINGS.execution.receipt_retention_periods,
+        call_tracing: SETTINGS.execution.call_tracing,
+        call_trace_history_length: SETTINGS.execution.call_trace_history_length,
+        async_message_status_history_length: SETTINGS.execution.async_message_status_history_length,
+        final_slot_record: SETTINGS.execution.final_slot_record,
+        final_slot_record_path: SETTINGS.execution.final_slot_record_path.clone(),
+        final_slot_record_retention_periods: SETTINGS.execution.final_slot_record_retention_periods,
+        address_index: SETTINGS.execution.address_index,
+        address_index_path: SETTINGS.execution.address_index_path.clone(),
+        parallel_execution: SETTINGS.execution.parallel_execution,
+        parallel_execution_threads: SETTINGS.execution.parallel_execution_threads,
+        readonly_queue_length: SETTINGS.execution.readonly_queue_length,
+        cursor_delay: SETTINGS.execution.cursor_delay,
+        max_async_gas: MAX_ASYNC_GAS,
+        async_message_triggers_activation_period: ASYNC_MESSAGE_TRIGGERS_ACTIVATION_PERIOD,
+        max_gas_per_block: MAX_GAS_PER_BLOCK,
+        roll_price: ROLL_PRICE,
+        thread_count: THREAD_COUNT,
+        t0: T0,
+        genesis_timestamp: *GENESIS_TIMESTAMP,
+        block_reward: BLOCK_REWARD,
+        endorsement_count: ENDORSEMENT_COUNT as u64,
+        operation_validity_period: OPERATION_VALIDITY_PERIODS,
+        periods_per_cycle: PERIODS_PER_CYCLE,
+        stats_time_window_duration: SETTINGS.execution.stats_time_window_duration,
+        max_miss_ratio: *POS_MISS_RATE_DEACTIVATION_THRESHOLD,
+        max_datastore_key_length: MAX_DATASTORE_KEY_LENGTH,
+        max_bytecode_size: MAX_BYTECODE_LENGTH,
+        max_datastore_value_size: MAX_DATASTORE_VALUE_LENGTH,
+        storage_costs_constants,
+        max_read_only_gas: SETTINGS.execution.max_read_only_gas,
+        bytecode_cache_size: SETTINGS.execution.bytecode_cache_size,
+        gas_costs: GasCosts::new(
+            SETTINGS.execution.abi_gas_costs_file.clone(),
+            SETTINGS.execution.wasm_gas_costs_file.clone(),
+        )
+        .expect("Failed to load gas costs"),
+        broadcast_enabled: SETTINGS.api.enable_ws,
+        broadcast_sc_events_capacity: SETTINGS.execution.broadcast_sc_events_capacity,
+        broadcast_final_blocks_capacity: SETTINGS.execution.broadcast_final_blocks_capacity,
+    }
+}
+
+/// Execute again the recorded final slots from a final state snapshot, without any network access,
+/// checking after each slot that the final state hash is the recorded one
+fn replay(
+    snapshot_path: &Path,
+    record_path: PathBuf,
+    last_slot: Option<Slot>,
+) -> anyhow::Result<()> {
+    // the replayed state is kept apart from the node state, and wiped at every replay
+    let replay_path = &SETTINGS.execution.replay_path;
+    if replay_path.exists() {
+        std::fs::remove_dir_all(replay_path)?;
+    }
+    let (final_state, mut selector_manager, selector_controller) = create_final_state(
+        replay_path.join("ledger"),
+        replay_path.join("final_state"),
+        false,
+    );
+    load_final_state_snapshot(&mut final_state.write(), snapshot_path);
+    final_state
+        .write()
+        .compute_initial_draws()
+        .expect("could not compute initial draws");
+
+    let execution_config = ExecutionConfig {
+        event_store_path: replay_path.join("events"),
+        receipt_store_path: replay_path.join("receipts"),
+        address_index_path: replay_path.join("address_index"),
+        ..create_execution_config()
+    };
+    let execution_channels = ExecutionChannels {
+        sc_event_sender: broadcast::channel(execution_config.broadcast_sc_events_capacity).0,
+        final_block_sender: broadcast::channel(execution_config.broadcast_final_blocks_capacity).0,
+    };
+    let res = replay_final_slots(
+        execution_config,
+        final_state,
+        selector_controller,
+        execution_channels,
+        record_path,
+        last_slot,
+    );
+    selector_manager.stop();
+    match res {
+        Ok(slot) => {
+            info!(
+                "replayed final state hashes match the records up to slot {}",
+                slot
+            );
+            Ok(())
+        }
+        Err(err) => Err(anyhow::anyhow!("replay failed: {}", err)),
+    }
+}
+
+/// Replace the final state by the content of a snapshot file
+fn load_final_state_snapshot(final_state: &mut FinalState, path: &Path) {
+    info!("loading the final state snapshot {}", path.display());
+    let bytes = std::fs::read(path).expect("could not read the final state snapshot file");
+    let deserializer = FinalStateSnapshotDeserializer::new(
+        THREAD_COUNT,
+        MAX_ASYNC_POOL_LENGTH,
+        MAX_ASYNC_MESSAGE_DATA,
+        // ledger changes of every slot of the final state history
+        MAX_LEDGER_CHANGES_COUNT.saturating_mul(SETTINGS.ledger.final_history_length as u64),
+        MAX_DATASTORE_KEY_LENGTH,
+        MAX_DATASTORE_VALUE_LENGTH,
+        MAX_DATASTORE_ENTRY_COUNT,
+        POS_SAVED_CYCLES as u64,
+        MAX_ROLLS_COUNT_LENGTH,
+        MAX_PRODUCTION_STATS_LENGTH,
+        MAX_DEFERRED_CREDITS_LENGTH,
+        MAX_EXECUTED_OPS_LENGTH,
+        MAX_OPERATIONS_PER_BLOCK as u64,
+    );
+    let (rest, snapshot) = deserializer
+        .deserialize::<DeserializeError>(&bytes)
+        .expect("could not deserialize the final state snapshot");
+    if !rest.is_empty() {
+        panic!("the final state snapshot file has trailing bytes");
+    }
+    final_state
+        .load_snapshot(snapshot)
+        .expect("could not load the final state snapshot");
 }
 
 /// Load wallet, asking for passwords if necessary
@@ -774,9 +1055,20 @@ async fn run(args: Args) -> anyhow::Result<()> {
         std::process::exit(1);
     }));
 
+    // replay the recorded final slots and exit
+    if let Some(record_path) = args.replay {
+        let snapshot_path = args
+            .snapshot
+            .ok_or_else(|| anyhow::anyhow!("a final state snapshot to replay from is required"))?;
+        return replay(&snapshot_path, record_path, args.replay_until);
+    }
+
     // load or create wallet, asking for password if necessary
     let node_wallet = load_wallet(args.password, &SETTINGS.factory.staking_wallet_path)?;
 
+    // the snapshot is only used on the first launch, restarts resume from disk
+    let mut snapshot_path = args.snapshot;
+
     loop {
         let (
             consensus_event_receiver,
@@ -792,7 +1084,7 @@ async fn run(args: Args) -> anyhow::Result<()> {
             api_private_handle,
             api_public_handle,
             api_handle,
-        ) = launch(node_wallet.clone()).await;
+        ) = launch(node_wallet.clone(), snapshot_path.take()).await;
 
         // interrupt signal listener
         let (tx, rx) = crossbeam_channel::bounded(1);
@@ -23,13 +23,30 @@ pub struct LoggingSettings {
 
 #[derive(Clone, Debug, Deserialize)]
 pub struct ExecutionSettings {
-    pub max_final_events: usize,
+    pub event_store_path: PathBuf,
+    pub event_retention_periods: u64,
+    pub receipt_store_path: PathBuf,
+    pub receipt_retention_periods: u64,
+    pub call_tracing: bool,
+    pub call_trace_history_length: usize,
+    pub async_message_status_history_length: usize,
+    pub final_slot_record: bool,
+    pub final_slot_record_path: PathBuf,
+    pub final_slot_record_retention_periods: u64,
+    pub address_index: bool,
+    pub address_index_path: PathBuf,
+    pub replay_path: PathBuf,
+    pub parallel_execution: bool,
+    pub parallel_execution_threads: usize,
     pub readonly_queue_length: usize,
     pub cursor_delay: MassaTime,
     pub stats_time_window_duration: MassaTime,
     pub max_read_only_gas: u64,
+    pub bytecode_cache_size: usize,
     pub abi_gas_costs_file: PathBuf,
     pub wasm_gas_costs_file: PathBuf,
+    pub broadcast_sc_events_capacity: usize,
+    pub broadcast_final_blocks_capacity: usize,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -42,7 +59,9 @@ pub struct SelectionSettings {
 pub struct LedgerSettings {
     pub initial_ledger_path: PathBuf,
     pub disk_ledger_path: PathBuf,
+    pub final_state_path: PathBuf,
     pub final_history_length: usize,
+    pub archival: bool,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -131,6 +150,12 @@ pub struct APISettings {
     pub ping_interval: MassaTime,
     pub enable_http: bool,
     pub enable_ws: bool,
+    pub gas_estimation_margin_percent: u64,
+    pub max_gas_estimations_per_request: u64,
+    pub max_gas_estimation_iterations: u64,
+    pub max_datastore_keys_per_request: u64,
+    pub max_events_per_request: u64,
+    pub max_address_history_per_request: u64,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -176,6 +201,12 @@ pub struct ConsensusSettings {
     pub broadcast_blocks_capacity: usize,
     /// filled blocks sender(channel) capacity
     pub broadcast_filled_blocks_capacity: usize,
+    /// blockclique changes sender(channel) capacity
+    pub broadcast_blockclique_changes_capacity: usize,
+    /// whether to persist the active blocks, to resume from them after a restart
+    pub persist_graph: bool,
+    /// file the active blocks are persisted to
+    pub graph_store_path: PathBuf,
 }
 
 /// Protocol Configuration, read from toml user configuration file
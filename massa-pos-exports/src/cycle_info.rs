@@ -120,39 +120,56 @@ impl CycleInfo {
         rng_seed: BitVec<u8>,
         production_stats: PreHashMap<Address, ProductionStats>,
     ) -> Self {
+        // create the new cycle with placeholder hashes, replaced below
+        let mut cycle_info = CycleInfo {
+            cycle,
+            complete,
+            roll_counts,
+            rng_seed,
+            production_stats,
+            roll_counts_hash: Hash::from_bytes(CYCLE_INFO_HASH_INITIAL_BYTES),
+            production_stats_hash: Hash::from_bytes(CYCLE_INFO_HASH_INITIAL_BYTES),
+            cycle_global_hash: Hash::from_bytes(CYCLE_INFO_HASH_INITIAL_BYTES),
+            final_state_hash_snapshot: None,
+        };
+        (
+            cycle_info.roll_counts_hash,
+            cycle_info.production_stats_hash,
+            cycle_info.cycle_global_hash,
+        ) = cycle_info.compute_hashes();
+        cycle_info
+    }
+
+    /// Compute the hashes of the cycle from scratch, regardless of the accumulated ones
+    ///
+    /// # Returns
+    /// A tuple `(roll_counts_hash, production_stats_hash, cycle_global_hash)`
+    pub fn compute_hashes(&self) -> (Hash, Hash, Hash) {
         let hash_computer = CycleInfoHashComputer::new();
         let mut roll_counts_hash = Hash::from_bytes(CYCLE_INFO_HASH_INITIAL_BYTES);
         let mut production_stats_hash = Hash::from_bytes(CYCLE_INFO_HASH_INITIAL_BYTES);
 
         // compute the cycle hash
         let mut hash_concat: Vec<u8> = Vec::new();
-        hash_concat.extend(hash_computer.compute_cycle_hash(cycle).to_bytes());
-        hash_concat.extend(hash_computer.compute_complete_hash(complete).to_bytes());
-        hash_concat.extend(hash_computer.compute_seed_hash(&rng_seed).to_bytes());
-        for (addr, &count) in &roll_counts {
+        hash_concat.extend(hash_computer.compute_cycle_hash(self.cycle).to_bytes());
+        hash_concat.extend(
+            hash_computer
+                .compute_complete_hash(self.complete)
+                .to_bytes(),
+        );
+        hash_concat.extend(hash_computer.compute_seed_hash(&self.rng_seed).to_bytes());
+        for (addr, &count) in &self.roll_counts {
             roll_counts_hash ^= hash_computer.compute_roll_entry_hash(addr, count);
         }
         hash_concat.extend(roll_counts_hash.to_bytes());
-        for (addr, prod_stats) in &production_stats {
+        for (addr, prod_stats) in &self.production_stats {
             production_stats_hash ^= hash_computer.compute_prod_stats_entry_hash(addr, prod_stats);
         }
         hash_concat.extend(production_stats_hash.to_bytes());
 
         // compute the global hash
         let cycle_global_hash = Hash::compute_from(&hash_concat);
-
-        // create the new cycle
-        CycleInfo {
-            cycle,
-            complete,
-            roll_counts,
-            rng_seed,
-            production_stats,
-            roll_counts_hash,
-            production_stats_hash,
-            cycle_global_hash,
-            final_state_hash_snapshot: None,
-        }
+        (roll_counts_hash, production_stats_hash, cycle_global_hash)
     }
 
     /// Apply every part of a `PoSChanges` to a cycle info, except for `deferred_credits`
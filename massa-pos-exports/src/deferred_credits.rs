@@ -19,7 +19,7 @@ use std::ops::Bound::{Excluded, Included};
 
 const DEFERRED_CREDITS_HASH_INITIAL_BYTES: &[u8; 32] = &[0; HASH_SIZE_BYTES];
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 /// Structure containing all the PoS deferred credits information
 pub struct DeferredCredits {
     /// Deferred credits
@@ -128,6 +128,18 @@ impl DeferredCredits {
         let entry = self.credits.entry(slot).or_default();
         entry.insert(addr, amount);
     }
+
+    /// Compute the hash of the deferred credits from scratch, regardless of the accumulated `hash`
+    pub fn compute_hash(&self) -> Hash {
+        let hash_computer = DeferredCreditsHashComputer::new();
+        let mut hash = Hash::from_bytes(DEFERRED_CREDITS_HASH_INITIAL_BYTES);
+        for (slot, credits) in &self.credits {
+            for (address, amount) in credits {
+                hash ^= hash_computer.compute_credit_hash(slot, address, amount);
+            }
+        }
+        hash
+    }
 }
 
 /// Serializer for `DeferredCredits`
@@ -16,7 +16,7 @@ use nom::{
 };
 
 /// Recap of all PoS changes
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Clone, PartialEq)]
 pub struct PoSChanges {
     /// extra block seed bits added
     pub seed_bits: BitVec<u8>,
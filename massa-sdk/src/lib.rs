@@ -9,23 +9,32 @@ use jsonrpsee::core::client::{CertificateStore, ClientT, IdKind};
 use jsonrpsee::http_client::HttpClient;
 use jsonrpsee::rpc_params;
 use jsonrpsee::ws_client::{HeaderMap, HeaderValue};
+use massa_async_pool::{AsyncMessageFilter, AsyncMessageId, AsyncMessageInfo};
+use massa_ledger_exports::LedgerEntryProof;
 use massa_models::api::{
-    AddressInfo, BlockInfo, BlockSummary, DatastoreEntryInput, DatastoreEntryOutput,
-    EndorsementInfo, EventFilter, NodeStatus, OperationInfo, OperationInput,
-    ReadOnlyBytecodeExecution, ReadOnlyCall, TimeInterval,
+    AddressActivity, AddressHistoryInput, AddressInfo, AddressStorageInfo, AddressesAtSlotInput,
+    BlockInfo, BlockSummary, DatastoreEntriesAtSlotInput, DatastoreEntryInput,
+    DatastoreEntryOutput, DatastoreKeysInput, DatastoreKeysOutput, EndorsementInfo, EventFilter,
+    FinalStateIntegrityReport, GasEstimationInput, GraphExportInput, GraphExportOutput,
+    LedgerEntryProofInput, NodeStatus, OperationInfo, OperationInput, ReadOnlyBytecodeExecution,
+    ReadOnlyCall, TimeInterval,
 };
 use massa_models::clique::Clique;
 use massa_models::composite::PubkeySig;
-use massa_models::execution::ExecuteReadOnlyResponse;
+use massa_models::execution::{
+    DryRunOperationResponse, ExecuteReadOnlyResponse, ExecutionTrace, GasEstimation,
+};
 use massa_models::node::NodeId;
 use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::{PreHashMap, PreHashSet};
 use massa_models::{
-    address::Address, block::BlockId, endorsement::EndorsementId, operation::OperationId,
+    address::Address, amount::Amount, block::BlockId, endorsement::EndorsementId,
+    operation::OperationId, slot::Slot,
 };
 
 use jsonrpsee::{core::Error as JsonRpseeError, core::RpcResult, http_client::HttpClientBuilder};
 use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
 use std::str::FromStr;
 
 mod config;
@@ -243,6 +252,44 @@ impl RpcClient {
             .await
     }
 
+    /// Export a snapshot of the final state to a file of the node.
+    pub async fn node_export_final_state_snapshot(&self, path: PathBuf) -> RpcResult<Slot> {
+        self.http_client
+            .request("node_export_final_state_snapshot", rpc_params![path])
+            .await
+    }
+
+    /// Recompute every final state component hash and compare it to the stored one.
+    pub async fn node_check_final_state_integrity(&self) -> RpcResult<FinalStateIntegrityReport> {
+        self.http_client
+            .request("node_check_final_state_integrity", rpc_params![])
+            .await
+    }
+
+    /// Export a time window of the consensus graph in Graphviz DOT or as a JSON graph.
+    pub async fn node_export_graph(&self, input: GraphExportInput) -> RpcResult<GraphExportOutput> {
+        self.http_client
+            .request("node_export_graph", rpc_params![input])
+            .await
+    }
+
+    /// Get the call trees recorded for the given operations.
+    pub async fn trace_operation(
+        &self,
+        operation_ids: Vec<OperationId>,
+    ) -> RpcResult<Vec<Option<ExecutionTrace>>> {
+        self.http_client
+            .request("trace_operation", rpc_params![operation_ids])
+            .await
+    }
+
+    /// Get the call trees recorded for the operations and asynchronous messages executed at a slot.
+    pub async fn trace_slot(&self, slot: Slot) -> RpcResult<Vec<ExecutionTrace>> {
+        self.http_client
+            .request("trace_slot", rpc_params![slot])
+            .await
+    }
+
     ////////////////
     // public-api //
     ////////////////
@@ -302,6 +349,26 @@ impl RpcClient {
             .await
     }
 
+    /// Get the messages of the final asynchronous pool with various filters
+    pub async fn get_async_messages(
+        &self,
+        filter: AsyncMessageFilter,
+    ) -> RpcResult<Vec<AsyncMessageInfo>> {
+        self.http_client
+            .request("get_async_messages", rpc_params![filter])
+            .await
+    }
+
+    /// Get a message of the final asynchronous pool, or what happened to it
+    pub async fn get_async_message(
+        &self,
+        id: AsyncMessageId,
+    ) -> RpcResult<Option<AsyncMessageInfo>> {
+        self.http_client
+            .request("get_async_message", rpc_params![id])
+            .await
+    }
+
     /// Get the block graph within the specified time interval.
     /// Optional parameters: from `<time_start>` (included) and to `<time_end>` (excluded) millisecond timestamp
     pub(crate) async fn _get_graph_interval(
@@ -320,6 +387,26 @@ impl RpcClient {
             .await
     }
 
+    /// Get the storage used by addresses and the coins locked to pay for it
+    pub async fn get_addresses_storage(
+        &self,
+        addresses: Vec<Address>,
+    ) -> RpcResult<Vec<AddressStorageInfo>> {
+        self.http_client
+            .request("get_addresses_storage", rpc_params![addresses])
+            .await
+    }
+
+    /// Get the final operations involving an address, most recent first
+    pub async fn get_address_history(
+        &self,
+        input: AddressHistoryInput,
+    ) -> RpcResult<Vec<AddressActivity>> {
+        self.http_client
+            .request("get_address_history", rpc_params![input])
+            .await
+    }
+
     /// Get datastore entries
     pub async fn get_datastore_entries(
         &self,
@@ -330,6 +417,56 @@ impl RpcClient {
             .await
     }
 
+    /// Get a page of the final and candidate datastore keys of an address
+    pub async fn get_datastore_keys(
+        &self,
+        input: DatastoreKeysInput,
+    ) -> RpcResult<DatastoreKeysOutput> {
+        self.http_client
+            .request("get_datastore_keys", rpc_params![input])
+            .await
+    }
+
+    /// Get the final balances of addresses at the output of a past final slot (archival nodes only)
+    pub async fn get_balances_at(
+        &self,
+        input: AddressesAtSlotInput,
+    ) -> RpcResult<Vec<Option<Amount>>> {
+        self.http_client
+            .request("get_balances_at", rpc_params![input])
+            .await
+    }
+
+    /// Get final datastore entries at the output of a past final slot (archival nodes only)
+    pub async fn get_datastore_entries_at(
+        &self,
+        input: DatastoreEntriesAtSlotInput,
+    ) -> RpcResult<Vec<Option<Vec<u8>>>> {
+        self.http_client
+            .request("get_datastore_entries_at", rpc_params![input])
+            .await
+    }
+
+    /// Get the final datastore keys of addresses at the output of a past final slot (archival nodes only)
+    pub async fn get_datastore_keys_at(
+        &self,
+        input: AddressesAtSlotInput,
+    ) -> RpcResult<Vec<Option<Vec<Vec<u8>>>>> {
+        self.http_client
+            .request("get_datastore_keys_at", rpc_params![input])
+            .await
+    }
+
+    /// Get proofs of final ledger entries against the final state hash
+    pub async fn get_ledger_entry_proofs(
+        &self,
+        input: Vec<LedgerEntryProofInput>,
+    ) -> RpcResult<Vec<LedgerEntryProof>> {
+        self.http_client
+            .request("get_ledger_entry_proofs", rpc_params![input])
+            .await
+    }
+
     // User (interaction with the node)
 
     /// Adds operations to pool. Returns operations that were ok and sent to pool.
@@ -342,6 +479,26 @@ impl RpcClient {
             .await
     }
 
+    /// Executes operations on top of the candidate state without committing their effects.
+    pub async fn dry_run_operations(
+        &self,
+        operations: Vec<OperationInput>,
+    ) -> RpcResult<Vec<DryRunOperationResponse>> {
+        self.http_client
+            .request("dry_run_operations", rpc_params![operations])
+            .await
+    }
+
+    /// Estimates the gas to set as `max_gas` for `ExecuteSC` and `CallSC` operations.
+    pub async fn estimate_gas(
+        &self,
+        estimations: Vec<GasEstimationInput>,
+    ) -> RpcResult<Vec<GasEstimation>> {
+        self.http_client
+            .request("estimate_gas", rpc_params![estimations])
+            .await
+    }
+
     /// execute read only bytecode
     pub async fn execute_read_only_bytecode(
         &self,